@@ -52,6 +52,9 @@ struct StageProgressEntry {
     /// than actually computed.  When `true`, the UI shows "-" instead
     /// of a timing value.
     cached: bool,
+    /// Fraction of a running stage completed, when the stage reports
+    /// intra-stage progress.
+    fraction: Option<f64>,
 }
 
 /// Cherry blossoms example image bundled at compile time so the app
@@ -129,6 +132,7 @@ fn app() -> Element {
             status: StageStatus::Pending,
             elapsed_ms: 0.0,
             cached: false,
+            fraction: None,
         })
     });
 
@@ -260,6 +264,7 @@ fn app() -> Element {
             status: StageStatus::Pending,
             elapsed_ms: 0.0,
             cached: false,
+            fraction: None,
         }));
         current_stage_start.set(None);
 
@@ -327,6 +332,7 @@ fn app() -> Element {
                         entries[ui_idx].status = StageStatus::Running;
                         entries[ui_idx].elapsed_ms = 0.0;
                         entries[ui_idx].cached = false;
+                        entries[ui_idx].fraction = None;
                         current_stage_start.set(Some(now));
                     }
                 } else {
@@ -353,13 +359,31 @@ fn app() -> Element {
                 stage_progress.set(entries);
             };
 
+            // Fraction callback — reported from checkpoints inside long
+            // stages (edges, canvas, join) for the running stage.
+            let on_fraction = move |fraction: f64| {
+                let mut entries = *stage_progress.peek();
+                for entry in &mut entries {
+                    if entry.status == StageStatus::Running {
+                        entry.fraction = Some(fraction);
+                    }
+                }
+                stage_progress.set(entries);
+            };
+
             // Run the pipeline in the web worker — this .await yields
             // to the browser event loop so animations and cancel clicks
             // keep working. PNG encoding also happens in the worker, so
             // the returned WorkerResult has ready-to-use Blob URLs.
             #[allow(clippy::cast_precision_loss)]
             let outcome = worker
-                .run(&bytes, &cfg, my_generation as f64, Some(on_progress))
+                .run(
+                    &bytes,
+                    &cfg,
+                    my_generation as f64,
+                    Some(on_progress),
+                    Some(on_fraction),
+                )
                 .await;
 
             // If another run was triggered while we were processing,
@@ -370,6 +394,12 @@ fn app() -> Element {
                 return;
             }
 
+            // A cancelled run has nothing to deliver;
+            // the cancel handler already hid the dialog.
+            if matches!(outcome, Err(mujou_pipeline::PipelineError::Cancelled)) {
+                return;
+            }
+
             // Record final elapsed time. The dialog stays visible —
             // either for a 1s delay (auto-close) or until manually
             // dismissed (auto-close off).
@@ -447,7 +477,7 @@ fn app() -> Element {
     });
 
     // --- Cancel / Done handler ---
-    // During processing: terminates the worker and hides the dialog.
+    // During processing: cancels the worker run and hides the dialog.
     // After completion: just dismisses the dialog.
     let worker_for_cancel = Rc::clone(&worker);
     let on_cancel_or_done = move |_| {
@@ -604,7 +634,11 @@ fn app() -> Element {
                                                         },
                                                         match entry.status {
                                                             StageStatus::Completed if entry.cached => "-".to_string(),
-                                                            StageStatus::Running | StageStatus::Completed => format_elapsed(entry.elapsed_ms),
+                                                            StageStatus::Running => entry.fraction.map_or_else(
+                                                                || format_elapsed(entry.elapsed_ms),
+                                                                |f| format!("{:.0}% · {}", f * 100.0, format_elapsed(entry.elapsed_ms)),
+                                                            ),
+                                                            StageStatus::Completed => format_elapsed(entry.elapsed_ms),
                                                             StageStatus::Pending => String::new(),
                                                         }
                                                     }
@@ -670,6 +704,10 @@ fn app() -> Element {
                                     config: live_config(),
                                     on_config_change: on_config_change,
                                     show_descriptions: show_descriptions(),
+                                    frame_count: image_bytes
+                                        .read()
+                                        .as_deref()
+                                        .map_or(1, mujou_pipeline::grayscale::frame_count),
                                 }
                             }
                         }
@@ -769,6 +807,10 @@ fn app() -> Element {
             },
             config_json: serde_json::to_string(&committed_config()).ok(),
             border_margin: committed_config().border_margin,
+            bezier_tolerance: {
+                let curve = committed_config().curve;
+                (curve.kind == mujou_pipeline::CurveKind::Bezier).then_some(curve.tolerance)
+            },
             show: show_export,
         }
     }
@@ -0,0 +1,386 @@
+//! `mujou-bench compare`: check a run against a saved baseline.
+//!
+//! Both inputs are `mujou-bench --json` output: one or more
+//! [`PipelineDiagnostics`] documents (one per `--runs` iteration), or a
+//! JSON array of them.  Per-stage durations are compared by median, and
+//! only count as regressed when the slowdown exceeds the relative
+//! threshold, the absolute floor, *and* the run-to-run noise (a
+//! multiple of the larger standard deviation of the two sample sets).
+//! Quality metrics are deterministic and compared with a relative
+//! threshold only; for all of them, higher is worse.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Args;
+use mujou_pipeline::JoinQualityMetrics;
+use mujou_pipeline::diagnostics::{PipelineDiagnostics, StageMetrics};
+
+/// Arguments for `mujou-bench compare`.
+#[derive(Args)]
+pub struct CompareArgs {
+    /// Baseline diagnostics (`mujou-bench --json` output).
+    baseline: PathBuf,
+
+    /// Diagnostics to check against the baseline.
+    current: PathBuf,
+
+    /// Relative slowdown of a stage's median duration that counts as a
+    /// regression (0.10 = 10%).
+    #[arg(long, default_value_t = 0.10)]
+    time_threshold: f64,
+
+    /// A slowdown must also exceed this many standard deviations of
+    /// run-to-run noise.
+    #[arg(long, default_value_t = 2.0)]
+    noise_factor: f64,
+
+    /// Ignore duration changes smaller than this many milliseconds.
+    #[arg(long, default_value_t = 0.5)]
+    min_ms: f64,
+
+    /// Relative increase of a quality metric that counts as a
+    /// regression (0.01 = 1%).
+    #[arg(long, default_value_t = 0.01)]
+    quality_threshold: f64,
+}
+
+/// Outcome of comparing one metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Unchanged,
+    Improved,
+    Regressed,
+}
+
+impl Verdict {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Unchanged => "ok",
+            Self::Improved => "improved",
+            Self::Regressed => "REGRESSED",
+        }
+    }
+}
+
+/// Function pointer type for extracting a stage duration in
+/// milliseconds.
+type TimingExtractor = fn(&PipelineDiagnostics) -> f64;
+
+/// Function pointer type for extracting a quality metric, `None` when
+/// the run does not report it.
+type QualityExtractor = fn(&PipelineDiagnostics) -> Option<f64>;
+
+/// Run `mujou-bench compare`, exiting non-zero on any regression.
+pub fn run(args: &CompareArgs) -> ExitCode {
+    let (baseline, current) = match (load(&args.baseline), load(&args.current)) {
+        (Ok(b), Ok(c)) => (b, c),
+        (Err(msg), _) | (_, Err(msg)) => {
+            eprintln!("{msg}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (b, c) = (&baseline[0].summary, &current[0].summary);
+    if (b.image_width, b.image_height) != (c.image_width, c.image_height) {
+        eprintln!(
+            "Warning: image sizes differ ({}x{} vs {}x{}); runs may not be comparable",
+            b.image_width, b.image_height, c.image_width, c.image_height,
+        );
+    }
+
+    println!(
+        "Baseline: {} ({} run(s))  Current: {} ({} run(s))",
+        args.baseline.display(),
+        baseline.len(),
+        args.current.display(),
+        current.len(),
+    );
+    println!();
+    println!(
+        "{:<24} {:>14} {:>14} {:>9}  Status",
+        "Metric", "Baseline", "Current", "Change",
+    );
+    println!("{}", "-".repeat(74));
+
+    let mut regressions = 0;
+    for (name, extract) in TIMINGS {
+        let base: Vec<f64> = baseline.iter().map(extract).collect();
+        let cur: Vec<f64> = current.iter().map(extract).collect();
+        let verdict = timing_verdict(&base, &cur, args);
+        print_row(
+            &format!("{name} (ms)"),
+            median(&base),
+            median(&cur),
+            verdict,
+        );
+        regressions += usize::from(verdict == Verdict::Regressed);
+    }
+    for (name, extract) in QUALITY {
+        let base: Option<Vec<f64>> = baseline.iter().map(extract).collect();
+        let cur: Option<Vec<f64>> = current.iter().map(extract).collect();
+        let (Some(base), Some(cur)) = (base, cur) else {
+            println!("{name:<24} {:>14} {:>14} {:>9}  n/a", "-", "-", "-");
+            continue;
+        };
+        let (base, cur) = (median(&base), median(&cur));
+        let verdict = quality_verdict(base, cur, args.quality_threshold);
+        print_row(name, base, cur, verdict);
+        regressions += usize::from(verdict == Verdict::Regressed);
+    }
+
+    println!();
+    if regressions == 0 {
+        println!("No regressions");
+        ExitCode::SUCCESS
+    } else {
+        println!("{regressions} regression(s)");
+        ExitCode::FAILURE
+    }
+}
+
+/// Stage durations compared by [`timing_verdict`].
+const TIMINGS: &[(&str, TimingExtractor)] = &[
+    ("Decode", |d| ms(d.decode.duration)),
+    ("Downsample", |d| ms(d.downsample.duration)),
+    ("Tone", |d| ms(d.tone.duration)),
+    ("Blur", |d| ms(d.blur.duration)),
+    ("Edge Detection", |d| ms(d.edge_detection.duration)),
+    ("Contour Tracing", |d| ms(d.contour_tracing.duration)),
+    ("Simplification", |d| ms(d.simplification.duration)),
+    ("Canvas", |d| ms(d.canvas.duration)),
+    ("Join", |d| ms(d.join.duration)),
+    ("Output", |d| ms(d.output.duration)),
+    ("Total", |d| ms(d.total_duration)),
+];
+
+/// Quality metrics compared by [`quality_verdict`].
+#[allow(clippy::cast_precision_loss)]
+const QUALITY: &[(&str, QualityExtractor)] = &[
+    ("Final points", |d| Some(d.summary.final_point_count as f64)),
+    ("Output points", |d| match d.output.metrics {
+        StageMetrics::Output { points_after, .. } => Some(points_after as f64),
+        _ => None,
+    }),
+    ("MST edge weight", |d| {
+        join_quality(d).map(|q| q.total_mst_edge_weight)
+    }),
+    ("Max MST edge weight", |d| {
+        join_quality(d).map(|q| q.max_mst_edge_weight)
+    }),
+    ("Retrace distance", |d| {
+        join_quality(d).map(|q| q.total_retrace_distance)
+    }),
+    ("Path length", |d| {
+        join_quality(d).map(|q| q.total_path_length)
+    }),
+    ("Chamfer distance", |d| {
+        d.quality.map(|q| q.chamfer_distance)
+    }),
+    ("Edge F1 loss", |d| d.quality.map(|q| 1.0 - q.edge_f1)),
+];
+
+/// Read diagnostics from `path`.
+fn load(path: &Path) -> Result<Vec<PipelineDiagnostics>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+    let runs = parse_runs(&text).map_err(|e| format!("Error parsing {}: {e}", path.display()))?;
+    if runs.is_empty() {
+        return Err(format!("{} contains no diagnostics", path.display()));
+    }
+    Ok(runs)
+}
+
+/// Parse a stream of JSON documents, each a [`PipelineDiagnostics`] or
+/// an array of them.
+fn parse_runs(text: &str) -> Result<Vec<PipelineDiagnostics>, serde_json::Error> {
+    let mut runs = Vec::new();
+    for value in serde_json::Deserializer::from_str(text).into_iter::<serde_json::Value>() {
+        match value? {
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    runs.push(serde_json::from_value(item)?);
+                }
+            }
+            value => runs.push(serde_json::from_value(value)?),
+        }
+    }
+    Ok(runs)
+}
+
+/// Compare duration samples (milliseconds).
+fn timing_verdict(base: &[f64], cur: &[f64], args: &CompareArgs) -> Verdict {
+    let base_median = median(base);
+    let delta = median(cur) - base_median;
+    let noise = args.noise_factor * std_dev(base).max(std_dev(cur));
+    let threshold = (base_median * args.time_threshold)
+        .max(noise)
+        .max(args.min_ms);
+    if delta > threshold {
+        Verdict::Regressed
+    } else if -delta > threshold {
+        Verdict::Improved
+    } else {
+        Verdict::Unchanged
+    }
+}
+
+/// Compare a deterministic metric where higher is worse.
+fn quality_verdict(base: f64, cur: f64, threshold: f64) -> Verdict {
+    let tolerance = base.abs() * threshold;
+    if cur - base > tolerance {
+        Verdict::Regressed
+    } else if base - cur > tolerance {
+        Verdict::Improved
+    } else {
+        Verdict::Unchanged
+    }
+}
+
+/// Print one comparison table row.
+fn print_row(name: &str, base: f64, cur: f64, verdict: Verdict) {
+    let change = if base == 0.0 {
+        "-".to_owned()
+    } else {
+        format!("{:+.1}%", (cur - base) / base * 100.0)
+    };
+    println!(
+        "{name:<24} {base:>14.3} {cur:>14.3} {change:>9}  {}",
+        verdict.label()
+    );
+}
+
+/// The MST join quality metrics, if the MST joiner ran.
+const fn join_quality(d: &PipelineDiagnostics) -> Option<&JoinQualityMetrics> {
+    match d.join.metrics {
+        StageMetrics::Join {
+            quality: Some(ref q),
+            ..
+        } => Some(q),
+        _ => None,
+    }
+}
+
+/// Duration in milliseconds.
+fn ms(d: std::time::Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Median of `values` (0 when empty).
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    match n {
+        0 => 0.0,
+        _ if n % 2 == 1 => sorted[n / 2],
+        _ => f64::midpoint(sorted[n / 2 - 1], sorted[n / 2]),
+    }
+}
+
+/// Sample standard deviation of `values` (0 for fewer than two).
+#[allow(clippy::cast_precision_loss)]
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    variance.sqrt()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::time::Duration;
+
+    use mujou_pipeline::diagnostics::{PipelineSummary, StageDiagnostics};
+
+    use super::*;
+
+    fn args() -> CompareArgs {
+        CompareArgs {
+            baseline: PathBuf::new(),
+            current: PathBuf::new(),
+            time_threshold: 0.10,
+            noise_factor: 2.0,
+            min_ms: 0.5,
+            quality_threshold: 0.01,
+        }
+    }
+
+    #[test]
+    fn median_handles_odd_and_even_lengths() {
+        assert!((median(&[3.0, 1.0, 2.0]) - 2.0).abs() < f64::EPSILON);
+        assert!((median(&[4.0, 1.0, 2.0, 3.0]) - 2.5).abs() < f64::EPSILON);
+        assert!(median(&[]).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn clear_slowdown_is_a_regression() {
+        let verdict = timing_verdict(&[100.0, 101.0, 99.0], &[130.0, 131.0, 129.0], &args());
+        assert_eq!(verdict, Verdict::Regressed);
+    }
+
+    #[test]
+    fn slowdown_within_noise_is_not_a_regression() {
+        // 20% slower by median, but the baseline varies by ±30 ms.
+        let verdict = timing_verdict(&[70.0, 100.0, 130.0], &[90.0, 120.0, 150.0], &args());
+        assert_eq!(verdict, Verdict::Unchanged);
+    }
+
+    #[test]
+    fn tiny_stages_ignore_relative_changes() {
+        let verdict = timing_verdict(&[0.1], &[0.3], &args());
+        assert_eq!(verdict, Verdict::Unchanged);
+    }
+
+    #[test]
+    fn quality_increase_beyond_threshold_regresses() {
+        assert_eq!(quality_verdict(10.0, 10.05, 0.01), Verdict::Unchanged);
+        assert_eq!(quality_verdict(10.0, 10.5, 0.01), Verdict::Regressed);
+        assert_eq!(quality_verdict(10.0, 9.0, 0.01), Verdict::Improved);
+    }
+
+    #[test]
+    fn parses_concatenated_documents_and_arrays() {
+        let stage = StageDiagnostics {
+            duration: Duration::from_millis(1),
+            metrics: StageMetrics::Blur {
+                sigma: 1.0,
+                smoothing: "Gaussian".to_owned(),
+            },
+        };
+        let diagnostics = PipelineDiagnostics {
+            decode: stage.clone(),
+            downsample: stage.clone(),
+            tone: stage.clone(),
+            blur: stage.clone(),
+            edge_detection: stage.clone(),
+            invert: None,
+            contour_tracing: stage.clone(),
+            simplification: stage.clone(),
+            canvas: stage.clone(),
+            join: stage.clone(),
+            output: stage,
+            total_duration: Duration::from_millis(9),
+            summary: PipelineSummary {
+                image_width: 1,
+                image_height: 1,
+                pixel_count: 1,
+                contour_count: 1,
+                final_point_count: 2,
+            },
+            quality: None,
+        };
+        let one = serde_json::to_string_pretty(&diagnostics).unwrap();
+
+        assert_eq!(parse_runs(&format!("{one}\n{one}\n")).unwrap().len(), 2);
+        assert_eq!(
+            parse_runs(&format!("[{one}, {one}, {one}]")).unwrap().len(),
+            3
+        );
+        assert!(parse_runs("{}").is_err());
+    }
+}
@@ -0,0 +1,496 @@
+//! `mujou-bench corpus`: run a directory of reference images through
+//! every joiner strategy and aggregate the results.
+//!
+//! Each image is run once per [`PathJoinerKind`] (and, for the MST
+//! joiner, once per [`ParityStrategy`]) through a shared
+//! [`PipelineCache`](mujou_pipeline::PipelineCache), so only the join
+//! and output stages repeat per strategy.  Join time, path size, the
+//! MST [`JoinQualityMetrics`](mujou_pipeline::JoinQualityMetrics) and
+//! the [`quality`](mujou_pipeline::quality) render scores are then
+//! summarized per strategy as mean, 95th percentile and worst
+//! value across images.  For every metric, higher is worse.
+//!
+//! Without a directory, the built-in [`synthetic`](crate::synthetic)
+//! corpus is used.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Args;
+use mujou_pipeline::diagnostics::{StageMetrics, run_cached_with_diagnostics};
+use mujou_pipeline::quality::{self, DEFAULT_TOLERANCE_PX};
+use mujou_pipeline::{ParityStrategy, PathJoinerKind, PipelineConfig};
+use serde_json::{Value, json};
+
+use crate::{Joiner, synthetic};
+
+/// Every joiner, in report order.
+const JOINERS: [PathJoinerKind; 3] = [
+    PathJoinerKind::StraightLine,
+    PathJoinerKind::Retrace,
+    PathJoinerKind::Mst,
+];
+
+/// Every parity strategy, in report order.
+const PARITIES: [ParityStrategy; 2] = [ParityStrategy::Greedy, ParityStrategy::Optimal];
+
+/// Arguments for `mujou-bench corpus`.
+#[derive(Args)]
+pub struct CorpusArgs {
+    /// Directory of reference images (not recursive).  Defaults to the
+    /// built-in synthetic corpus.
+    dir: Option<PathBuf>,
+
+    /// Also run the built-in synthetic corpus when DIR is given.
+    #[arg(long)]
+    synthetic: bool,
+
+    /// Write the built-in synthetic corpus as PNG files into this
+    /// directory and exit.
+    #[arg(long, value_name = "DIR")]
+    save_synthetic: Option<PathBuf>,
+
+    /// Only run these joiners (repeatable).  Defaults to all of them.
+    ///
+    /// Full-history retrace can grow quadratically on images with many
+    /// scattered contours; leave it out for large photographic corpora.
+    #[arg(long, value_enum)]
+    joiner: Vec<Joiner>,
+
+    /// Base pipeline config as a JSON string; the joiner and parity
+    /// strategy are overridden per run.
+    #[arg(long)]
+    config_json: Option<String>,
+
+    /// Output the report as JSON instead of tables.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Measurements from one image × strategy run.
+struct Sample {
+    join_ms: f64,
+    points: f64,
+    path_length: f64,
+    mst_edge_weight: Option<f64>,
+    max_mst_edge_weight: Option<f64>,
+    retrace_distance: Option<f64>,
+    chamfer_distance: f64,
+    edge_f1_loss: f64,
+}
+
+/// One image × strategy run.
+struct Run {
+    image: usize,
+    strategy: usize,
+    result: Result<Sample, String>,
+}
+
+/// Function pointer type for extracting a metric from a [`Sample`].
+type MetricExtractor = fn(&Sample) -> Option<f64>;
+
+/// Metrics summarized in the report.
+const METRICS: &[(&str, MetricExtractor)] = &[
+    ("Join time (ms)", |s| Some(s.join_ms)),
+    ("Output points", |s| Some(s.points)),
+    ("Path length", |s| Some(s.path_length)),
+    ("MST edge weight", |s| s.mst_edge_weight),
+    ("Max MST edge weight", |s| s.max_mst_edge_weight),
+    ("Retrace distance", |s| s.retrace_distance),
+    ("Chamfer distance (px)", |s| Some(s.chamfer_distance)),
+    ("Edge F1 loss", |s| Some(s.edge_f1_loss)),
+];
+
+/// Mean, 95th percentile and worst value of one metric.
+struct Aggregate {
+    mean: f64,
+    p95: f64,
+    worst: f64,
+    worst_image: usize,
+}
+
+/// Run `mujou-bench corpus`.
+pub fn run(args: &CorpusArgs) -> ExitCode {
+    if let Some(ref dir) = args.save_synthetic {
+        return match save_synthetic(dir) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(msg) => {
+                eprintln!("{msg}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let base = match args.config_json {
+        Some(ref json) => match serde_json::from_str::<PipelineConfig>(json) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error parsing --config-json: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => PipelineConfig::default(),
+    };
+    if let Err(e) = base.validate() {
+        eprintln!("Invalid pipeline config: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let images = match load_images(args.dir.as_deref(), args.synthetic || args.dir.is_none()) {
+        Ok(images) if images.is_empty() => {
+            eprintln!("No images found");
+            return ExitCode::FAILURE;
+        }
+        Ok(images) => images,
+        Err(msg) => {
+            eprintln!("{msg}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let strategies: Vec<_> = strategies()
+        .into_iter()
+        .filter(|&(_, kind, _)| {
+            args.joiner.is_empty() || args.joiner.iter().any(|j| j.kind() == kind)
+        })
+        .collect();
+    let runs = run_corpus(&images, &strategies, &base);
+    let names: Vec<&str> = images.iter().map(|(name, _)| name.as_str()).collect();
+
+    if args.json {
+        match serde_json::to_string_pretty(&report_json(&names, &strategies, &runs)) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("Error serializing report: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print_report(&names, &strategies, &runs);
+    }
+
+    if runs.iter().any(|r| r.result.is_err()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Whether `kind` uses the parity strategy.
+///
+/// Exhaustive so that adding a joiner forces a decision here (and a
+/// reminder to extend [`JOINERS`]).
+const fn uses_parity(kind: PathJoinerKind) -> bool {
+    match kind {
+        PathJoinerKind::StraightLine | PathJoinerKind::Retrace => false,
+        PathJoinerKind::Mst => true,
+    }
+}
+
+/// Joiner/parity combinations to run, with display names.
+fn strategies() -> Vec<(String, PathJoinerKind, ParityStrategy)> {
+    JOINERS
+        .iter()
+        .flat_map(|&joiner| {
+            if uses_parity(joiner) {
+                PARITIES
+                    .iter()
+                    .map(|&parity| (format!("{joiner}/{parity}"), joiner, parity))
+                    .collect()
+            } else {
+                vec![(joiner.to_string(), joiner, ParityStrategy::default())]
+            }
+        })
+        .collect()
+}
+
+/// Write the synthetic corpus into `dir`.
+fn save_synthetic(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Error creating {}: {e}", dir.display()))?;
+    for image in synthetic::corpus()? {
+        let path = dir.join(format!("{}.png", image.name));
+        std::fs::write(&path, &image.png)
+            .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Collect `(name, bytes)` for the images in `dir` (sorted by path) and,
+/// if requested, the synthetic corpus.
+fn load_images(dir: Option<&Path>, synthetic: bool) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut images = Vec::new();
+    if let Some(dir) = dir {
+        let entries =
+            std::fs::read_dir(dir).map_err(|e| format!("Error reading {}: {e}", dir.display()))?;
+        let mut paths: Vec<PathBuf> = entries
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Error reading {}: {e}", dir.display()))?;
+        paths.retain(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(mujou_pipeline::grayscale::is_supported_extension)
+        });
+        paths.sort();
+        for path in paths {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            images.push((name, bytes));
+        }
+    }
+    if synthetic {
+        images.extend(
+            synthetic::corpus()?
+                .into_iter()
+                .map(|image| (format!("synthetic:{}", image.name), image.png)),
+        );
+    }
+    Ok(images)
+}
+
+/// Run every image with every strategy.
+fn run_corpus(
+    images: &[(String, Vec<u8>)],
+    strategies: &[(String, PathJoinerKind, ParityStrategy)],
+    base: &PipelineConfig,
+) -> Vec<Run> {
+    let mut runs = Vec::with_capacity(images.len() * strategies.len());
+    for (image, (name, bytes)) in images.iter().enumerate() {
+        let mut cache = None;
+        for (strategy, (label, joiner, parity)) in strategies.iter().enumerate() {
+            eprintln!("{name} [{label}]");
+            let config = PipelineConfig {
+                path_joiner: *joiner,
+                parity_strategy: *parity,
+                ..base.clone()
+            };
+            let zoom = config.zoom;
+            let result =
+                run_cached_with_diagnostics(&mut cache, bytes.clone(), config, &crate::StdClock)
+                    .map(|(staged, diagnostics)| {
+                        let quality = match diagnostics.join.metrics {
+                            StageMetrics::Join { quality, .. } => quality,
+                            _ => None,
+                        };
+                        let render = quality::measure(&staged, zoom, DEFAULT_TOLERANCE_PX);
+                        Sample {
+                            join_ms: diagnostics.join.duration.as_secs_f64() * 1000.0,
+                            #[allow(clippy::cast_precision_loss)]
+                            points: staged.output.len() as f64,
+                            path_length: staged.joined.length(),
+                            mst_edge_weight: quality.as_ref().map(|q| q.total_mst_edge_weight),
+                            max_mst_edge_weight: quality.as_ref().map(|q| q.max_mst_edge_weight),
+                            retrace_distance: quality.as_ref().map(|q| q.total_retrace_distance),
+                            chamfer_distance: render.chamfer_distance,
+                            edge_f1_loss: 1.0 - render.edge_f1,
+                        }
+                    })
+                    .map_err(|e| e.to_string());
+            if let Err(ref msg) = result {
+                eprintln!("  error: {msg}");
+            }
+            runs.push(Run {
+                image,
+                strategy,
+                result,
+            });
+        }
+    }
+    runs
+}
+
+/// Summarize `metric` over the successful runs of `strategy`, or `None`
+/// if no run reports it.
+#[allow(clippy::cast_precision_loss)]
+fn aggregate(runs: &[Run], strategy: usize, metric: MetricExtractor) -> Option<Aggregate> {
+    let mut values: Vec<(f64, usize)> = runs
+        .iter()
+        .filter(|r| r.strategy == strategy)
+        .filter_map(|r| {
+            r.result
+                .as_ref()
+                .ok()
+                .and_then(metric)
+                .map(|v| (v, r.image))
+        })
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let sorted: Vec<f64> = values.iter().map(|v| v.0).collect();
+    let &(worst, worst_image) = values.last()?;
+    Some(Aggregate {
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p95: percentile(&sorted, 0.95),
+        worst,
+        worst_image,
+    })
+}
+
+/// Nearest-rank percentile of ascending `sorted` values.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Print per-metric tables with one row per strategy.
+fn print_report(
+    names: &[&str],
+    strategies: &[(String, PathJoinerKind, ParityStrategy)],
+    runs: &[Run],
+) {
+    println!();
+    println!(
+        "Corpus report: {} image(s) x {} strategies\n{}",
+        names.len(),
+        strategies.len(),
+        "=".repeat(78),
+    );
+
+    for (metric_name, metric) in METRICS {
+        println!();
+        println!(
+            "{metric_name:<22} {:>12} {:>12} {:>12}  Worst image",
+            "Mean", "P95", "Worst",
+        );
+        println!("{}", "-".repeat(78));
+        for (strategy, (label, _, _)) in strategies.iter().enumerate() {
+            match aggregate(runs, strategy, *metric) {
+                Some(a) => println!(
+                    "{label:<22} {:>12.3} {:>12.3} {:>12.3}  {}",
+                    a.mean, a.p95, a.worst, names[a.worst_image],
+                ),
+                None => println!("{label:<22} {:>12} {:>12} {:>12}", "-", "-", "-"),
+            }
+        }
+    }
+
+    let failures: Vec<&Run> = runs.iter().filter(|r| r.result.is_err()).collect();
+    if !failures.is_empty() {
+        println!();
+        println!("Failures");
+        println!("{}", "-".repeat(78));
+        for run in failures {
+            if let Err(ref msg) = run.result {
+                println!(
+                    "{} [{}]: {msg}",
+                    names[run.image], strategies[run.strategy].0
+                );
+            }
+        }
+    }
+}
+
+/// Build the JSON report: per-strategy aggregates plus every run.
+fn report_json(
+    names: &[&str],
+    strategies: &[(String, PathJoinerKind, ParityStrategy)],
+    runs: &[Run],
+) -> Value {
+    let summary: Vec<Value> = strategies
+        .iter()
+        .enumerate()
+        .map(|(strategy, (label, _, _))| {
+            let metrics: serde_json::Map<String, Value> = METRICS
+                .iter()
+                .map(|(metric_name, metric)| {
+                    let value = aggregate(runs, strategy, *metric).map_or(Value::Null, |a| {
+                        json!({
+                            "mean": a.mean,
+                            "p95": a.p95,
+                            "worst": a.worst,
+                            "worst_image": names[a.worst_image],
+                        })
+                    });
+                    ((*metric_name).to_owned(), value)
+                })
+                .collect();
+            let failures = runs
+                .iter()
+                .filter(|r| r.strategy == strategy && r.result.is_err())
+                .count();
+            json!({ "strategy": label, "failures": failures, "metrics": metrics })
+        })
+        .collect();
+
+    let records: Vec<Value> = runs
+        .iter()
+        .map(|run| {
+            let mut record = json!({
+                "image": names[run.image],
+                "strategy": strategies[run.strategy].0,
+            });
+            match run.result {
+                Ok(ref sample) => {
+                    for (metric_name, metric) in METRICS {
+                        record[*metric_name] = metric(sample).map_or(Value::Null, Value::from);
+                    }
+                }
+                Err(ref msg) => record["error"] = Value::from(msg.as_str()),
+            }
+            record
+        })
+        .collect();
+
+    json!({ "images": names, "summary": summary, "runs": records })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn run(image: usize, join_ms: f64) -> Run {
+        Run {
+            image,
+            strategy: 0,
+            result: Ok(Sample {
+                join_ms,
+                points: 0.0,
+                path_length: 0.0,
+                mst_edge_weight: None,
+                max_mst_edge_weight: None,
+                retrace_distance: None,
+                chamfer_distance: 0.0,
+                edge_f1_loss: 0.0,
+            }),
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let values: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert!((percentile(&values, 0.95) - 19.0).abs() < f64::EPSILON);
+        assert!((percentile(&values, 1.0) - 20.0).abs() < f64::EPSILON);
+        assert!((percentile(&[7.0], 0.95) - 7.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn aggregate_reports_mean_and_worst_image() {
+        let runs = [run(0, 2.0), run(1, 8.0), run(2, 5.0)];
+        let a = aggregate(&runs, 0, |s| Some(s.join_ms)).unwrap();
+        assert!((a.mean - 5.0).abs() < f64::EPSILON);
+        assert!((a.worst - 8.0).abs() < f64::EPSILON);
+        assert_eq!(a.worst_image, 1);
+        assert!(aggregate(&runs, 0, |s| s.retrace_distance).is_none());
+    }
+
+    #[test]
+    fn parity_variants_only_for_mst() {
+        let labels: Vec<String> = strategies().into_iter().map(|s| s.0).collect();
+        assert_eq!(
+            labels,
+            ["StraightLine", "Retrace", "Mst/Greedy", "Mst/Optimal"]
+        );
+    }
+}
@@ -12,15 +12,26 @@
 //!
 //! ```text
 //! cargo run --release --bin mujou-bench -- [OPTIONS] <IMAGE_PATH>
+//! cargo run --release --bin mujou-bench -- compare <BASELINE> <CURRENT>
+//! cargo run --release --bin mujou-bench -- corpus [DIR]
 //! ```
+//!
+//! `compare` checks `--json` output against a saved baseline and exits
+//! non-zero on regressions (see [`compare`]).  `corpus` runs a directory
+//! of images (or the built-in [`synthetic`] corpus) with every joiner
+//! strategy and aggregates the results (see [`corpus`]).
 
 #![allow(clippy::print_stdout, clippy::print_stderr)]
 
-use std::path::PathBuf;
+mod compare;
+mod corpus;
+mod synthetic;
+
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::time::{Duration, Instant};
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use mujou_pipeline::diagnostics::{Clock, PipelineDiagnostics};
 
 /// Pipeline parameter experimentation and diagnostics for mujou.
@@ -28,10 +39,20 @@ use mujou_pipeline::diagnostics::{Clock, PipelineDiagnostics};
 /// Runs the image processing pipeline on a given image with configurable
 /// parameters and prints detailed per-stage timing and count diagnostics.
 #[derive(Parser)]
-#[command(name = "mujou-bench", version)]
+#[command(
+    name = "mujou-bench",
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<BenchCommand>,
+
     /// Path to the input image (PNG, JPEG, BMP, WebP).
-    image_path: PathBuf,
+    #[arg(required = true)]
+    image_path: Option<PathBuf>,
 
     /// Gaussian blur sigma.
     #[arg(long, default_value_t = mujou_pipeline::PipelineConfig::DEFAULT_BLUR_SIGMA)]
@@ -93,10 +114,20 @@ struct Cli {
     #[arg(long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
     runs: usize,
 
-    /// Output diagnostics as JSON instead of human-readable report.
+    /// Output diagnostics as JSON instead of human-readable report
+    /// (one document per run; save as a baseline for `compare`).
     #[arg(long)]
     json: bool,
 
+    /// Score the output against the edge map (recall, precision,
+    /// chamfer distance, SSIM) and include it in the report.
+    #[arg(long)]
+    quality: bool,
+
+    /// Tolerance band for `--quality` recall/precision, in pixels.
+    #[arg(long, default_value_t = mujou_pipeline::quality::DEFAULT_TOLERANCE_PX)]
+    quality_tolerance: f64,
+
     /// Full pipeline config as a JSON string.
     ///
     /// When provided, all other pipeline parameter flags are ignored.
@@ -105,6 +136,17 @@ struct Cli {
     config_json: Option<String>,
 }
 
+/// Subcommands other than the default single-image run.
+#[derive(Subcommand)]
+enum BenchCommand {
+    /// Compare `--json` diagnostics against a baseline; exits non-zero
+    /// on regressions.
+    Compare(compare::CompareArgs),
+    /// Run a corpus of images with every joiner strategy and aggregate
+    /// timing and join quality.
+    Corpus(corpus::CorpusArgs),
+}
+
 /// Path joining strategy selection.
 #[derive(Clone, Copy, ValueEnum)]
 enum Joiner {
@@ -116,6 +158,17 @@ enum Joiner {
     Mst,
 }
 
+impl Joiner {
+    /// The pipeline joiner this selection maps to.
+    const fn kind(self) -> mujou_pipeline::PathJoinerKind {
+        match self {
+            Self::Straight => mujou_pipeline::PathJoinerKind::StraightLine,
+            Self::Retrace => mujou_pipeline::PathJoinerKind::Retrace,
+            Self::Mst => mujou_pipeline::PathJoinerKind::Mst,
+        }
+    }
+}
+
 /// Parity-fixing strategy selection.
 #[derive(Clone, Copy, ValueEnum)]
 enum Parity {
@@ -200,11 +253,7 @@ fn config_from_cli(cli: &Cli) -> Result<mujou_pipeline::PipelineConfig, String>
         canny_low: cli.canny_low,
         canny_high: cli.canny_high,
         simplify_tolerance: cli.simplify_tolerance,
-        path_joiner: match cli.joiner {
-            Joiner::Straight => mujou_pipeline::PathJoinerKind::StraightLine,
-            Joiner::Retrace => mujou_pipeline::PathJoinerKind::Retrace,
-            Joiner::Mst => mujou_pipeline::PathJoinerKind::Mst,
-        },
+        path_joiner: cli.joiner.kind(),
         parity_strategy: match cli.parity_strategy {
             Parity::Greedy => mujou_pipeline::ParityStrategy::Greedy,
             Parity::Optimal => mujou_pipeline::ParityStrategy::Optimal,
@@ -233,7 +282,20 @@ fn config_from_cli(cli: &Cli) -> Result<mujou_pipeline::PipelineConfig, String>
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    let config = match config_from_cli(&cli) {
+    match (&cli.command, &cli.image_path) {
+        (Some(BenchCommand::Compare(args)), _) => compare::run(args),
+        (Some(BenchCommand::Corpus(args)), _) => corpus::run(args),
+        (None, Some(image_path)) => bench(&cli, image_path),
+        (None, None) => {
+            eprintln!("Missing <IMAGE_PATH>");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the pipeline on `image_path` and print diagnostics.
+fn bench(cli: &Cli, image_path: &Path) -> ExitCode {
+    let config = match config_from_cli(cli) {
         Ok(c) => c,
         Err(msg) => {
             eprintln!("{msg}");
@@ -241,17 +303,17 @@ fn main() -> ExitCode {
         }
     };
 
-    let image_bytes = match std::fs::read(&cli.image_path) {
+    let image_bytes = match std::fs::read(image_path) {
         Ok(bytes) => bytes,
         Err(e) => {
-            eprintln!("Error reading {}: {e}", cli.image_path.display());
+            eprintln!("Error reading {}: {e}", image_path.display());
             return ExitCode::FAILURE;
         }
     };
 
     eprintln!(
         "Image: {} ({} bytes)",
-        cli.image_path.display(),
+        image_path.display(),
         image_bytes.len(),
     );
     eprintln!("Config: {config:#?}");
@@ -270,7 +332,14 @@ fn main() -> ExitCode {
             &config,
             &StdClock,
         ) {
-            Ok((staged, diagnostics)) => {
+            Ok((staged, mut diagnostics)) => {
+                if cli.quality {
+                    diagnostics.quality = Some(mujou_pipeline::quality::measure(
+                        &staged,
+                        config.zoom,
+                        cli.quality_tolerance,
+                    ));
+                }
                 if cli.json {
                     match serde_json::to_string_pretty(&diagnostics) {
                         Ok(json) => println!("{json}"),
@@ -287,8 +356,7 @@ fn main() -> ExitCode {
                 if run == 0
                     && let Some(ref svg_path) = cli.svg
                 {
-                    let title = cli
-                        .image_path
+                    let title = image_path
                         .file_stem()
                         .and_then(|s| s.to_str())
                         .unwrap_or("bench");
@@ -335,8 +403,9 @@ fn main() -> ExitCode {
         }
     }
 
-    // Print summary when multiple runs.
-    if cli.runs > 1 {
+    // Print summary when multiple runs.  Skipped for JSON so stdout
+    // stays a stream of diagnostics documents for `compare`.
+    if cli.runs > 1 && !cli.json {
         print_multi_run_summary(&all_diagnostics);
     }
 
@@ -397,6 +466,7 @@ fn print_multi_run_summary(all_diagnostics: &[PipelineDiagnostics]) {
     let stage_extractors: &[(&str, StageExtractor)] = &[
         ("Decode", |d| Some(d.decode.duration)),
         ("Downsample", |d| Some(d.downsample.duration)),
+        ("Tone", |d| Some(d.tone.duration)),
         ("Blur", |d| Some(d.blur.duration)),
         ("Edge Detection", |d| Some(d.edge_detection.duration)),
         ("Invert", |d| d.invert.as_ref().map(|s| s.duration)),
@@ -0,0 +1,357 @@
+//! Built-in synthetic reference corpus for `mujou-bench corpus`.
+//!
+//! Four small procedurally drawn images that stress different parts of
+//! the pipeline: clean line art (few long contours), a stylized portrait
+//! (soft gradients and closed shapes), text (many short parallel
+//! strokes), and a high-noise texture (thousands of tiny contours).
+//! They are generated deterministically, so results are comparable
+//! across machines without shipping binary fixtures.
+
+use std::f32::consts::PI;
+
+use tiny_skia::{
+    Color, FillRule, GradientStop, LineCap, LineJoin, Paint, PathBuilder, Pixmap, Point,
+    RadialGradient, Rect, SpreadMode, Stroke, Transform,
+};
+
+/// Side length of every synthetic image in pixels.
+const SIZE: u32 = 512;
+
+/// A named synthetic image.
+pub struct SyntheticImage {
+    /// Short name used in reports and as the file stem.
+    pub name: &'static str,
+    /// PNG-encoded image bytes.
+    pub png: Vec<u8>,
+}
+
+/// Draws one corpus image into a blank `SIZE`×`SIZE` pixmap.
+type Generator = fn(&mut Pixmap);
+
+/// Generate the synthetic corpus.
+///
+/// # Errors
+///
+/// Returns a message if an image cannot be allocated or encoded.
+pub fn corpus() -> Result<Vec<SyntheticImage>, String> {
+    let generators: [(&'static str, Generator); 4] = [
+        ("line-art", line_art),
+        ("portrait", portrait),
+        ("text", text),
+        ("noise", noise),
+    ];
+    generators
+        .into_iter()
+        .map(|(name, draw)| {
+            let mut pixmap = Pixmap::new(SIZE, SIZE).ok_or("failed to allocate pixmap")?;
+            draw(&mut pixmap);
+            let png = pixmap
+                .encode_png()
+                .map_err(|e| format!("encoding {name} failed: {e}"))?;
+            Ok(SyntheticImage { name, png })
+        })
+        .collect()
+}
+
+/// Solid paint of the given opaque color.
+fn paint(r: u8, g: u8, b: u8) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Round-capped stroke of the given width.
+fn stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    }
+}
+
+/// Path through `points`, optionally closed.
+fn polyline(points: &[(f32, f32)], close: bool) -> Option<tiny_skia::Path> {
+    let (&(x, y), rest) = points.split_first()?;
+    let mut pb = PathBuilder::new();
+    pb.move_to(x, y);
+    for &(x, y) in rest {
+        pb.line_to(x, y);
+    }
+    if close {
+        pb.close();
+    }
+    pb.finish()
+}
+
+/// Ellipse path centered at `(cx, cy)`.
+fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<tiny_skia::Path> {
+    PathBuilder::from_oval(Rect::from_xywh(cx - rx, cy - ry, 2.0 * rx, 2.0 * ry)?)
+}
+
+/// Black strokes on white: geometric shapes, a spiral and a house.
+#[allow(clippy::cast_precision_loss)]
+fn line_art(pixmap: &mut Pixmap) {
+    pixmap.fill(Color::WHITE);
+    let ink = paint(0, 0, 0);
+    let pen = stroke(4.0);
+    let id = Transform::identity();
+
+    let spiral: Vec<(f32, f32)> = (0..400)
+        .map(|i| {
+            let t = i as f32 / 400.0 * 6.0 * PI;
+            let r = t.mul_add(7.0, 6.0);
+            (r.mul_add(t.cos(), 140.0), r.mul_add(t.sin(), 150.0))
+        })
+        .collect();
+    let shapes = [
+        polyline(&spiral, false),
+        ellipse(380.0, 130.0, 80.0, 80.0),
+        polyline(&[(300.0, 440.0), (380.0, 300.0), (460.0, 440.0)], true),
+        // House: walls, roof, door.
+        polyline(
+            &[(60.0, 460.0), (60.0, 360.0), (200.0, 360.0), (200.0, 460.0)],
+            false,
+        ),
+        polyline(&[(40.0, 370.0), (130.0, 290.0), (220.0, 370.0)], false),
+        polyline(
+            &[
+                (115.0, 460.0),
+                (115.0, 410.0),
+                (145.0, 410.0),
+                (145.0, 460.0),
+            ],
+            false,
+        ),
+        polyline(&[(20.0, 480.0), (492.0, 480.0)], false),
+    ];
+    for path in shapes.iter().flatten() {
+        pixmap.stroke_path(path, &ink, &pen, id, None);
+    }
+}
+
+/// Stylized head and shoulders with soft shading.
+fn portrait(pixmap: &mut Pixmap) {
+    pixmap.fill(Color::from_rgba8(196, 204, 214, 255));
+    let id = Transform::identity();
+
+    // Shoulders.
+    if let Some(path) = polyline(
+        &[
+            (56.0, 512.0),
+            (120.0, 420.0),
+            (392.0, 420.0),
+            (456.0, 512.0),
+        ],
+        true,
+    ) {
+        pixmap.fill_path(&path, &paint(52, 60, 88), FillRule::Winding, id, None);
+    }
+    // Neck.
+    if let Some(rect) = Rect::from_xywh(220.0, 340.0, 72.0, 90.0) {
+        pixmap.fill_rect(rect, &paint(206, 160, 130), id, None);
+    }
+    // Head with a radial highlight.
+    if let Some(head) = ellipse(256.0, 240.0, 110.0, 140.0) {
+        let mut skin = paint(0, 0, 0);
+        skin.shader = RadialGradient::new(
+            Point::from_xy(226.0, 200.0),
+            0.0,
+            Point::from_xy(256.0, 240.0),
+            170.0,
+            vec![
+                GradientStop::new(0.0, Color::from_rgba8(240, 200, 170, 255)),
+                GradientStop::new(1.0, Color::from_rgba8(170, 120, 95, 255)),
+            ],
+            SpreadMode::Pad,
+            id,
+        )
+        .unwrap_or_else(|| paint(220, 180, 150).shader);
+        pixmap.fill_path(&head, &skin, FillRule::Winding, id, None);
+    }
+    // Hair.
+    let mut pb = PathBuilder::new();
+    pb.move_to(146.0, 240.0);
+    pb.cubic_to(130.0, 80.0, 380.0, 60.0, 366.0, 240.0);
+    pb.cubic_to(350.0, 150.0, 170.0, 140.0, 146.0, 240.0);
+    if let Some(hair) = pb.finish() {
+        pixmap.fill_path(&hair, &paint(60, 40, 30), FillRule::Winding, id, None);
+    }
+    // Eyes, pupils, brows, nose and mouth.
+    for cx in [212.0, 300.0] {
+        if let Some(eye) = ellipse(cx, 236.0, 22.0, 11.0) {
+            pixmap.fill_path(&eye, &paint(250, 250, 250), FillRule::Winding, id, None);
+        }
+        if let Some(pupil) = ellipse(cx, 236.0, 8.0, 8.0) {
+            pixmap.fill_path(&pupil, &paint(40, 30, 25), FillRule::Winding, id, None);
+        }
+        if let Some(brow) = polyline(
+            &[(cx - 26.0, 212.0), (cx, 204.0), (cx + 26.0, 210.0)],
+            false,
+        ) {
+            pixmap.stroke_path(&brow, &paint(70, 50, 40), &stroke(5.0), id, None);
+        }
+    }
+    let features = [
+        polyline(&[(256.0, 250.0), (244.0, 300.0), (262.0, 304.0)], false),
+        polyline(&[(216.0, 330.0), (256.0, 344.0), (296.0, 330.0)], false),
+    ];
+    for path in features.iter().flatten() {
+        pixmap.stroke_path(path, &paint(120, 60, 55), &stroke(4.0), id, None);
+    }
+}
+
+/// Three lines of block capitals drawn with a stroke font.
+fn text(pixmap: &mut Pixmap) {
+    pixmap.fill(Color::WHITE);
+    let ink = paint(20, 20, 20);
+    let pen = stroke(8.0);
+    let scale = 14.0;
+
+    for (row, line) in [(0.0_f32, "MUJOU"), (1.0, "SAND"), (2.0, "TABLE")] {
+        let top = row.mul_add(6.0f32.mul_add(scale, 48.0), 72.0);
+        for (col, c) in (0_u8..).zip(line.chars()) {
+            let left = (f32::from(col) * 6.0).mul_add(scale, 60.0);
+            for stroke_points in glyph_strokes(c) {
+                let points: Vec<(f32, f32)> = stroke_points
+                    .iter()
+                    .map(|&(x, y)| (x.mul_add(scale, left), y.mul_add(scale, top)))
+                    .collect();
+                if let Some(path) = polyline(&points, false) {
+                    pixmap.stroke_path(&path, &ink, &pen, Transform::identity(), None);
+                }
+            }
+        }
+    }
+}
+
+/// Strokes for `c` on a 4×6 grid (y down); unknown characters are blank.
+const fn glyph_strokes(c: char) -> &'static [&'static [(f32, f32)]] {
+    match c {
+        'A' => &[
+            &[(0.0, 6.0), (2.0, 0.0), (4.0, 6.0)],
+            &[(1.0, 3.0), (3.0, 3.0)],
+        ],
+        'B' => &[
+            &[
+                (0.0, 0.0),
+                (0.0, 6.0),
+                (3.0, 6.0),
+                (4.0, 5.0),
+                (4.0, 4.0),
+                (3.0, 3.0),
+                (0.0, 3.0),
+            ],
+            &[(0.0, 0.0), (3.0, 0.0), (4.0, 1.0), (4.0, 2.0), (3.0, 3.0)],
+        ],
+        'D' => &[&[
+            (0.0, 0.0),
+            (0.0, 6.0),
+            (2.0, 6.0),
+            (4.0, 4.0),
+            (4.0, 2.0),
+            (2.0, 0.0),
+            (0.0, 0.0),
+        ]],
+        'E' => &[
+            &[(4.0, 0.0), (0.0, 0.0), (0.0, 6.0), (4.0, 6.0)],
+            &[(0.0, 3.0), (3.0, 3.0)],
+        ],
+        'J' => &[&[(4.0, 0.0), (4.0, 5.0), (3.0, 6.0), (1.0, 6.0), (0.0, 5.0)]],
+        'L' => &[&[(0.0, 0.0), (0.0, 6.0), (4.0, 6.0)]],
+        'M' => &[&[(0.0, 6.0), (0.0, 0.0), (2.0, 3.0), (4.0, 0.0), (4.0, 6.0)]],
+        'N' => &[&[(0.0, 6.0), (0.0, 0.0), (4.0, 6.0), (4.0, 0.0)]],
+        'O' => &[&[
+            (1.0, 0.0),
+            (3.0, 0.0),
+            (4.0, 1.0),
+            (4.0, 5.0),
+            (3.0, 6.0),
+            (1.0, 6.0),
+            (0.0, 5.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+        ]],
+        'S' => &[&[
+            (4.0, 1.0),
+            (3.0, 0.0),
+            (1.0, 0.0),
+            (0.0, 1.0),
+            (0.0, 2.0),
+            (1.0, 3.0),
+            (3.0, 3.0),
+            (4.0, 4.0),
+            (4.0, 5.0),
+            (3.0, 6.0),
+            (1.0, 6.0),
+            (0.0, 5.0),
+        ]],
+        'T' => &[&[(0.0, 0.0), (4.0, 0.0)], &[(2.0, 0.0), (2.0, 6.0)]],
+        'U' => &[&[
+            (0.0, 0.0),
+            (0.0, 5.0),
+            (1.0, 6.0),
+            (3.0, 6.0),
+            (4.0, 5.0),
+            (4.0, 0.0),
+        ]],
+        _ => &[],
+    }
+}
+
+/// Mid-grey with strong per-pixel noise over a faint disc.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn noise(pixmap: &mut Pixmap) {
+    // xorshift32 with a fixed seed for reproducibility.
+    let mut state: u32 = 0x9E37_79B9;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    let center = SIZE as f32 / 2.0;
+    for (i, px) in pixmap.data_mut().chunks_exact_mut(4).enumerate() {
+        let x = (i as u32 % SIZE) as f32 - center;
+        let y = (i as u32 / SIZE) as f32 - center;
+        let disc = if x.hypot(y) < 150.0 { 60.0 } else { 0.0 };
+        let jitter = (next() % 101) as f32 - 50.0;
+        let v = (110.0 + disc + jitter).clamp(0.0, 255.0) as u8;
+        px.copy_from_slice(&[v, v, v, 255]);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_images_are_distinct_valid_pngs() {
+        let images = corpus().unwrap();
+        let names: Vec<&str> = images.iter().map(|i| i.name).collect();
+        assert_eq!(names, ["line-art", "portrait", "text", "noise"]);
+        for image in &images {
+            let pixmap = Pixmap::decode_png(&image.png).unwrap();
+            assert_eq!((pixmap.width(), pixmap.height()), (SIZE, SIZE));
+            let first = pixmap.pixel(0, 0).unwrap();
+            assert!(
+                pixmap.pixels().iter().any(|p| *p != first),
+                "{} is blank",
+                image.name
+            );
+        }
+    }
+
+    #[test]
+    fn corpus_is_deterministic() {
+        let a = corpus().unwrap();
+        let b = corpus().unwrap();
+        assert!(a.iter().zip(&b).all(|(a, b)| a.png == b.png));
+    }
+}
@@ -0,0 +1,162 @@
+//! Loading a [`PipelineConfig`] from command-line arguments.
+//!
+//! A config can come from a JSON file, an inline JSON string, or a file
+//! previously exported by mujou — SVG and THR exports embed the full
+//! config, so any past export doubles as a preset.
+
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use mujou_pipeline::PipelineConfig;
+
+/// Pipeline configuration source.
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Pipeline config preset: a `PipelineConfig` JSON file, or an SVG
+    /// or THR file exported by mujou (its embedded settings are reused).
+    #[arg(long, value_name = "FILE", conflicts_with = "config_json")]
+    pub config: Option<PathBuf>,
+
+    /// Full pipeline config as a JSON string.
+    #[arg(long, value_name = "JSON")]
+    pub config_json: Option<String>,
+}
+
+impl ConfigArgs {
+    /// Resolve the configured [`PipelineConfig`], falling back to the
+    /// defaults when no source was given.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the file cannot be read, carries no
+    /// embedded config, or the JSON does not describe a valid config.
+    pub fn load(&self) -> Result<PipelineConfig, String> {
+        if let Some(ref path) = self.config {
+            return load_file(path);
+        }
+        let config: PipelineConfig = match self.config_json {
+            Some(ref json) => serde_json::from_str(json)
+                .map_err(|e| format!("Error parsing --config-json: {e}"))?,
+            None => PipelineConfig::default(),
+        };
+        config
+            .validate()
+            .map_err(|e| format!("Invalid pipeline config: {e}"))?;
+        Ok(config)
+    }
+}
+
+/// Load and validate a [`PipelineConfig`] from a JSON file or a mujou
+/// SVG/THR export.
+///
+/// # Errors
+///
+/// Returns a message if the file cannot be read, carries no embedded
+/// config, or the JSON does not describe a valid config.
+pub fn load_file(path: &Path) -> Result<PipelineConfig, String> {
+    let json = read_config_file(path)?;
+    let config: PipelineConfig = serde_json::from_str(&json)
+        .map_err(|e| format!("Error parsing config in {}: {e}", path.display()))?;
+    config
+        .validate()
+        .map_err(|e| format!("Invalid pipeline config in {}: {e}", path.display()))?;
+    Ok(config)
+}
+
+/// Read the config JSON from `path`, extracting it from mujou SVG/THR
+/// exports when the extension says so.
+fn read_config_file(path: &Path) -> Result<String, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let embedded = match extension.as_deref() {
+        Some("svg") => config_from_svg(&text),
+        Some("thr") => config_from_thr(&text),
+        _ => return Ok(text),
+    };
+    embedded.ok_or_else(|| format!("{} has no embedded mujou config", path.display()))
+}
+
+/// Extract the config JSON from the `<mujou:pipeline>` metadata element
+/// written by [`mujou_export::to_svg`].
+fn config_from_svg(svg: &str) -> Option<String> {
+    let open = svg.find("<mujou:pipeline")?;
+    let start = open + svg[open..].find('>')? + 1;
+    let end = start + svg[start..].find("</mujou:pipeline>")?;
+    Some(xml_unescape(&svg[start..end]))
+}
+
+/// Extract the config JSON from the `# Config:` comment line written by
+/// [`mujou_export::to_thr`].
+fn config_from_thr(thr: &str) -> Option<String> {
+    thr.lines()
+        .find_map(|line| line.strip_prefix("# Config: "))
+        .map(str::to_owned)
+}
+
+/// Undo the five predefined XML entity escapes.
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> PipelineConfig {
+        PipelineConfig {
+            blur_sigma: 2.5,
+            ..PipelineConfig::default()
+        }
+    }
+
+    #[test]
+    fn config_round_trips_through_svg_export() {
+        let config = sample_config();
+        let json = serde_json::to_string(&config).unwrap();
+        let shape = mujou_pipeline::MaskShape::Circle {
+            center: mujou_pipeline::Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        let svg = mujou_export::to_svg(
+            &[],
+            &mujou_export::SvgMetadata {
+                config_json: Some(&json),
+                ..mujou_export::SvgMetadata::default()
+            },
+            &mujou_export::document_mapping(&shape, 0.0),
+        );
+        let extracted = config_from_svg(&svg).unwrap();
+        let parsed: PipelineConfig = serde_json::from_str(&extracted).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn config_round_trips_through_thr_export() {
+        let config = sample_config();
+        let json = serde_json::to_string(&config).unwrap();
+        let thr = mujou_export::to_thr(
+            &[],
+            &mujou_export::ThrMetadata {
+                config_json: Some(&json),
+                ..mujou_export::ThrMetadata::default()
+            },
+        );
+        let parsed: PipelineConfig = serde_json::from_str(&config_from_thr(&thr).unwrap()).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn missing_embedded_config_is_none() {
+        assert!(config_from_svg("<svg></svg>").is_none());
+        assert!(config_from_thr("# mujou\n0.0 0.0\n").is_none());
+    }
+}
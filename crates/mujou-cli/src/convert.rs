@@ -0,0 +1,383 @@
+//! `mujou convert`: batch conversion of images to SVG/THR/PNG.
+//!
+//! Inputs may be image files, directories (scanned for images), or glob
+//! patterns.  Each image runs through [`process_staged`] on the rayon
+//! thread pool, and its outputs are written next to the input or into
+//! `--out-dir`.  A summary table is printed at the end; the exit code
+//! is non-zero when any image failed.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use mujou_pipeline::{PipelineConfig, grayscale, process_staged};
+use rayon::prelude::*;
+
+use crate::config::ConfigArgs;
+use crate::output::{Format, RenderArgs, RenderOptions, dedup_formats, output_path, render};
+
+/// Arguments for `mujou convert`.
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Image files, directories, or glob patterns (e.g. "photos/*.jpg").
+    #[arg(required = true, value_name = "INPUTS")]
+    inputs: Vec<String>,
+
+    /// Recurse into subdirectories of directory inputs.
+    #[arg(short, long)]
+    recursive: bool,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Output formats to write.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Format::Svg, Format::Thr])]
+    format: Vec<Format>,
+
+    /// Write outputs into this directory instead of next to each input.
+    #[arg(short, long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Number of images to process concurrently (0 = one per CPU).
+    #[arg(short, long, default_value_t = 0)]
+    jobs: usize,
+}
+
+/// Result of converting a single image.
+struct Outcome {
+    input: PathBuf,
+    elapsed: Duration,
+    result: Result<Converted, String>,
+}
+
+/// Successful conversion details.
+struct Converted {
+    points: usize,
+    outputs: Vec<PathBuf>,
+}
+
+/// Run `mujou convert`.
+pub fn run(args: &ConvertArgs) -> ExitCode {
+    let config = match args.config.load() {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let inputs = match expand_inputs(&args.inputs, args.recursive) {
+        Ok(inputs) if inputs.is_empty() => {
+            eprintln!("No images found");
+            return ExitCode::FAILURE;
+        }
+        Ok(inputs) => inputs,
+        Err(msg) => {
+            eprintln!("{msg}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(ref dir) = args.out_dir
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        eprintln!("Error creating {}: {e}", dir.display());
+        return ExitCode::FAILURE;
+    }
+
+    let formats = dedup_formats(&args.format);
+    let render_options = args.render.options();
+    let plans: Vec<Vec<PathBuf>> = inputs
+        .iter()
+        .map(|input| {
+            formats
+                .iter()
+                .map(|&f| output_path(input, args.out_dir.as_deref(), f))
+                .collect()
+        })
+        .collect();
+    let conflicts = find_conflicts(&inputs, &plans);
+
+    let pool = match rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs)
+        .build()
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Error starting worker threads: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    eprintln!(
+        "Converting {} image(s) with {} thread(s)",
+        inputs.len(),
+        pool.current_num_threads(),
+    );
+
+    let outcomes: Vec<Outcome> = pool.install(|| {
+        inputs
+            .par_iter()
+            .zip(&plans)
+            .enumerate()
+            .map(|(i, (input, outputs))| {
+                let start = Instant::now();
+                let result = conflicts.get(&i).map_or_else(
+                    || convert_one(input, &config, &render_options, &formats, outputs),
+                    |msg| Err(msg.clone()),
+                );
+                Outcome {
+                    input: input.clone(),
+                    elapsed: start.elapsed(),
+                    result,
+                }
+            })
+            .collect()
+    });
+
+    print_summary(&outcomes);
+
+    if outcomes.iter().any(|o| o.result.is_err()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Expand command-line inputs into a deduplicated list of image files.
+///
+/// Existing files are taken as-is.  Directories contribute the images
+/// (by [`is_image`]) they contain, sorted by path.  Anything else is
+/// treated as a glob pattern, which must match at least one path.
+fn expand_inputs(inputs: &[String], recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_file() {
+            files.push(path.to_path_buf());
+        } else if path.is_dir() {
+            scan_dir(path, recursive, &mut files)?;
+        } else {
+            let paths = glob::glob(input).map_err(|e| format!("Invalid pattern {input:?}: {e}"))?;
+            let before = files.len();
+            for entry in paths {
+                let found = entry.map_err(|e| format!("Error reading {input:?}: {e}"))?;
+                if found.is_dir() {
+                    scan_dir(&found, recursive, &mut files)?;
+                } else if is_image(&found) {
+                    files.push(found);
+                }
+            }
+            if files.len() == before {
+                return Err(format!("No images match {input:?}"));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(files)
+}
+
+/// Append the images in `dir` (and its subdirectories if `recursive`)
+/// to `files`, sorted by path.
+fn scan_dir(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Error reading {}: {e}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Error reading {}: {e}", dir.display()))?;
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            if recursive {
+                scan_dir(&path, recursive, files)?;
+            }
+        } else if is_image(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` has an extension the pipeline can decode.
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(grayscale::is_supported_extension)
+}
+
+/// Map each input index whose planned outputs would overwrite an input
+/// image, or clash with an earlier input's outputs, to an error message.
+fn find_conflicts(inputs: &[PathBuf], plans: &[Vec<PathBuf>]) -> HashMap<usize, String> {
+    let input_set: HashSet<&Path> = inputs.iter().map(PathBuf::as_path).collect();
+    let mut owners: HashMap<&Path, usize> = HashMap::new();
+    let mut conflicts = HashMap::new();
+    for (i, outputs) in plans.iter().enumerate() {
+        for output in outputs {
+            if input_set.contains(output.as_path()) {
+                conflicts.insert(
+                    i,
+                    format!("output {} would overwrite an input", output.display()),
+                );
+                continue;
+            }
+            match owners.get(output.as_path()) {
+                Some(&owner) if owner != i => {
+                    conflicts.insert(
+                        i,
+                        format!(
+                            "output name collides with {}; use separate --out-dir runs",
+                            inputs[owner].display(),
+                        ),
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    owners.insert(output, i);
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Convert one image and write its outputs.
+fn convert_one(
+    input: &Path,
+    config: &PipelineConfig,
+    render_options: &RenderOptions,
+    formats: &[Format],
+    outputs: &[PathBuf],
+) -> Result<Converted, String> {
+    let bytes = std::fs::read(input).map_err(|e| format!("read failed: {e}"))?;
+    let staged = process_staged(&bytes, config).map_err(|e| e.to_string())?;
+
+    for (&format, path) in formats.iter().zip(outputs) {
+        let contents = render(format, input, &staged, config, render_options)?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("writing {} failed: {e}", path.display()))?;
+    }
+
+    Ok(Converted {
+        points: staged.output.len(),
+        outputs: outputs.to_vec(),
+    })
+}
+
+/// Print the per-image summary table and totals.
+fn print_summary(outcomes: &[Outcome]) {
+    let names: Vec<String> = outcomes
+        .iter()
+        .map(|o| o.input.display().to_string())
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(5);
+
+    println!();
+    println!(
+        "{:<width$}  {:<6}  {:>8}  {:>10}  Details",
+        "Image", "Status", "Points", "Time (ms)",
+    );
+    println!("{}", "-".repeat(width + 40));
+
+    for (name, outcome) in names.iter().zip(outcomes) {
+        let ms = outcome.elapsed.as_secs_f64() * 1000.0;
+        match &outcome.result {
+            Ok(converted) => {
+                let files: Vec<String> = converted
+                    .outputs
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                println!(
+                    "{name:<width$}  {:<6}  {:>8}  {ms:>10.1}  {}",
+                    "ok",
+                    converted.points,
+                    files.join(", "),
+                );
+            }
+            Err(msg) => {
+                println!(
+                    "{name:<width$}  {:<6}  {:>8}  {ms:>10.1}  {msg}",
+                    "FAILED", "-",
+                );
+            }
+        }
+    }
+
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+    println!();
+    println!("{} converted, {failed} failed", outcomes.len() - failed,);
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Fresh, empty scratch directory under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mujou-cli-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn conflicts_flag_later_inputs_with_same_stem() {
+        let inputs = vec![PathBuf::from("a/cat.png"), PathBuf::from("b/cat.jpg")];
+        let out = Some(Path::new("out"));
+        let plans: Vec<Vec<PathBuf>> = inputs
+            .iter()
+            .map(|i| vec![output_path(i, out, Format::Thr)])
+            .collect();
+        let conflicts = find_conflicts(&inputs, &plans);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[&1].contains("a/cat.png"));
+
+        // Next to the inputs, the same stems do not clash.
+        let plans: Vec<Vec<PathBuf>> = inputs
+            .iter()
+            .map(|i| vec![output_path(i, None, Format::Thr)])
+            .collect();
+        assert!(find_conflicts(&inputs, &plans).is_empty());
+    }
+
+    #[test]
+    fn conflicts_flag_outputs_overwriting_inputs() {
+        let inputs = vec![PathBuf::from("a/cat.png")];
+        let plans = vec![vec![output_path(&inputs[0], None, Format::Png)]];
+        assert!(find_conflicts(&inputs, &plans)[&0].contains("overwrite"));
+    }
+
+    #[test]
+    fn expand_inputs_scans_directories_and_globs() {
+        let dir = scratch_dir("expand");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.png", "a.JPG", "notes.txt", "nested/c.webp"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let flat = expand_inputs(&[dir.display().to_string()], false).unwrap();
+        assert_eq!(flat, vec![dir.join("a.JPG"), dir.join("b.png")]);
+
+        let deep = expand_inputs(&[dir.display().to_string()], true).unwrap();
+        assert_eq!(deep.len(), 3);
+
+        let pattern = dir.join("*.png").display().to_string();
+        let globbed = expand_inputs(&[pattern.clone(), pattern], false).unwrap();
+        assert_eq!(globbed, vec![dir.join("b.png")], "duplicates are removed");
+
+        let missing = dir.join("*.bmp").display().to_string();
+        assert!(expand_inputs(&[missing], false).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+//! mujou: command-line conversion of images to sand table and plotter paths.
+//!
+//! Runs the same pipeline as the web app natively, over many images at
+//! once, writing SVG and/or THR files.
+//!
+//! # Usage
+//!
+//! ```text
+//! mujou convert [OPTIONS] <INPUTS>...
+//! mujou watch --config <FILE> [OPTIONS] <IMAGE>
+//! mujou sweep --vary <FIELD=VALUES>... [OPTIONS] <IMAGE>
+//! ```
+
+#![allow(clippy::print_stdout, clippy::print_stderr)]
+
+mod config;
+mod convert;
+mod output;
+mod sheet;
+mod sweep;
+mod watch;
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+/// Convert raster images to vector paths for sand tables, pen plotters,
+/// and CNC devices.
+#[derive(Parser)]
+#[command(name = "mujou", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert images, directories, or glob patterns to SVG/THR/PNG.
+    Convert(convert::ConvertArgs),
+    /// Re-run the pipeline whenever an image or config file changes.
+    Watch(watch::WatchArgs),
+    /// Run a grid of parameter combinations and write a contact sheet.
+    Sweep(sweep::SweepArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Convert(args) => convert::run(&args),
+        Command::Watch(args) => watch::run(&args),
+        Command::Sweep(args) => sweep::run(&args),
+    }
+}
@@ -0,0 +1,253 @@
+//! Output formats shared by the CLI subcommands.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use mujou_export::animation::AnimationOptions;
+use mujou_export::png::PngOptions;
+use mujou_export::sand::SandOptions;
+use mujou_pipeline::{CurveKind, PipelineConfig, StagedResult};
+
+/// Output file format.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Format {
+    /// Cartesian SVG of the joined path.
+    Svg,
+    /// Theta-Rho polar coordinates of the output path.
+    Thr,
+    /// Rasterized preview of the joined path.
+    Png,
+    /// Simulated sand-table render of the output path the table plays.
+    Sand,
+    /// Animated PNG of the output path being drawn, in drawing order.
+    Animation,
+}
+
+impl Format {
+    /// File extension for this format.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Thr => "thr",
+            Self::Png => "png",
+            Self::Sand => "sand.png",
+            Self::Animation => "anim.png",
+        }
+    }
+}
+
+/// Rendering options for [`Format::Svg`], [`Format::Sand`] and
+/// [`Format::Animation`].
+#[derive(Args)]
+pub struct RenderArgs {
+    /// SVG: group the path into contour, connector, retrace and border
+    /// layers.  Combines with Bezier curve smoothing.
+    #[arg(long)]
+    svg_layers: bool,
+
+    /// Sand render: table diameter (or shorter side), in millimetres.
+    #[arg(long, value_name = "MM", default_value_t = SandOptions::default().table_size_mm)]
+    table_mm: f64,
+
+    /// Sand render: ball diameter, in millimetres.
+    #[arg(long, value_name = "MM", default_value_t = SandOptions::default().ball_diameter_mm)]
+    ball_mm: f64,
+
+    /// Animation: number of frames.
+    #[arg(long, value_name = "N", default_value_t = AnimationOptions::default().frames,
+          value_parser = clap::value_parser!(u32).range(1..))]
+    frames: u32,
+
+    /// Animation: display time of each frame, in milliseconds.
+    #[arg(long, value_name = "MS", default_value_t = AnimationOptions::default().frame_delay_ms)]
+    frame_ms: u16,
+}
+
+/// Options for the rendered output formats.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Emit [`Format::Svg`] with one layer per segment kind.
+    pub svg_layers: bool,
+    /// Options for [`Format::Sand`].
+    pub sand: SandOptions,
+    /// Options for [`Format::Animation`].
+    pub animation: AnimationOptions,
+}
+
+impl RenderArgs {
+    /// Rendering options for these arguments.
+    pub fn options(&self) -> RenderOptions {
+        RenderOptions {
+            svg_layers: self.svg_layers,
+            sand: SandOptions {
+                table_size_mm: self.table_mm,
+                ball_diameter_mm: self.ball_mm,
+                ..SandOptions::default()
+            },
+            animation: AnimationOptions {
+                frames: self.frames,
+                frame_delay_ms: self.frame_ms,
+                ..AnimationOptions::default()
+            },
+        }
+    }
+}
+
+/// Remove duplicate formats, keeping first occurrences.
+pub fn dedup_formats(formats: &[Format]) -> Vec<Format> {
+    let mut seen = HashSet::new();
+    formats
+        .iter()
+        .copied()
+        .filter(|f| seen.insert(*f))
+        .collect()
+}
+
+/// Output path for `input` in `format`: `<stem>.<ext>` next to the
+/// input, or inside `out_dir` when given.
+pub fn output_path(input: &Path, out_dir: Option<&Path>, format: Format) -> PathBuf {
+    let dir = out_dir.map_or_else(|| input.parent().unwrap_or_else(|| Path::new("")), |d| d);
+    let mut name: OsString = input.file_stem().unwrap_or_default().to_owned();
+    name.push(".");
+    name.push(format.extension());
+    dir.join(name)
+}
+
+/// Serialize a pipeline result in `format`.
+///
+/// SVG and THR embed the config so the file can be reused as a
+/// `--config` preset.
+///
+/// # Errors
+///
+/// Returns a message if PNG rendering fails.
+pub fn render(
+    format: Format,
+    input: &Path,
+    staged: &StagedResult,
+    config: &PipelineConfig,
+    options: &RenderOptions,
+) -> Result<Vec<u8>, String> {
+    let title = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("mujou");
+    let description = describe(config);
+    let config_json = serde_json::to_string(config).ok();
+    let mapping = mujou_export::document_mapping(&staged.canvas.shape, config.border_margin);
+
+    match format {
+        Format::Svg => {
+            let metadata = mujou_export::SvgMetadata {
+                title: Some(title),
+                description: Some(&description),
+                config_json: config_json.as_deref(),
+            };
+            // The joined (pre-subsampled) path: subsampling only helps
+            // THR polar conversion.
+            let curve_tolerance =
+                (config.curve.kind == CurveKind::Bezier).then_some(config.curve.tolerance);
+            let svg = if options.svg_layers {
+                mujou_export::to_layered_svg(
+                    &staged.joined,
+                    &staged.joined_segments,
+                    curve_tolerance,
+                    &metadata,
+                    &mapping,
+                )
+            } else {
+                curve_tolerance.map_or_else(
+                    || {
+                        mujou_export::to_svg(
+                            std::slice::from_ref(&staged.joined),
+                            &metadata,
+                            &mapping,
+                        )
+                    },
+                    |tolerance| {
+                        mujou_export::to_bezier_svg(
+                            &staged.joined,
+                            &staged.joined_segments,
+                            tolerance,
+                            &metadata,
+                            &mapping,
+                        )
+                    },
+                )
+            };
+            Ok(svg.into_bytes())
+        }
+        Format::Thr => {
+            let metadata = mujou_export::ThrMetadata {
+                title: Some(title),
+                description: Some(&description),
+                timestamp: None,
+                config_json: config_json.as_deref(),
+            };
+            let thr = mujou_export::to_thr(std::slice::from_ref(&staged.output), &metadata);
+            Ok(thr.into_bytes())
+        }
+        Format::Png => mujou_export::png::to_png(
+            std::slice::from_ref(&staged.joined),
+            &mapping,
+            &PngOptions::default(),
+        )
+        .map_err(|e| e.to_string()),
+        Format::Sand => mujou_export::sand::to_png(
+            std::slice::from_ref(&staged.output),
+            &staged.canvas.shape,
+            &options.sand,
+        )
+        .map_err(|e| e.to_string()),
+        Format::Animation => {
+            mujou_export::animation::to_apng(&staged.output, &mapping, &options.animation)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// One-line human-readable summary of the main pipeline parameters.
+pub fn describe(config: &PipelineConfig) -> String {
+    format!(
+        "blur={}, canny={}/{}, simplify={}, tracer={:?}, joiner={:?}, canvas={:?} zoom={:.2}, res={}",
+        config.blur_sigma,
+        config.canny_low,
+        config.canny_high,
+        config.simplify_tolerance,
+        config.contour_tracer,
+        config.path_joiner,
+        config.shape,
+        config.zoom,
+        config.working_resolution,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_path_keeps_dotted_stems() {
+        let input = Path::new("photos/cat.v2.png");
+        assert_eq!(
+            output_path(input, None, Format::Svg),
+            Path::new("photos/cat.v2.svg"),
+        );
+        assert_eq!(
+            output_path(input, Some(Path::new("out")), Format::Thr),
+            Path::new("out/cat.v2.thr"),
+        );
+        assert_eq!(
+            output_path(input, None, Format::Sand),
+            Path::new("photos/cat.v2.sand.png"),
+        );
+    }
+
+    #[test]
+    fn dedup_formats_keeps_first_occurrence() {
+        let formats = [Format::Thr, Format::Svg, Format::Thr];
+        assert!(dedup_formats(&formats) == [Format::Thr, Format::Svg]);
+    }
+}
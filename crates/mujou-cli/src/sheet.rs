@@ -0,0 +1,256 @@
+//! Labeled contact sheets: a grid of rendered results in one PNG.
+//!
+//! Cells are rendered with [`mujou_export::png::render_pixmap`] and
+//! captioned with a built-in 3×5 pixel font, so no font files are
+//! needed.  Letters are drawn in a single (capital) case.
+
+use mujou_export::png::{PngError, PngOptions, render_pixmap};
+use mujou_pipeline::{MaskShape, Polyline};
+use tiny_skia::{Color, Paint, Pixmap, PixmapPaint, Rect, Transform};
+
+/// Glyph width in font pixels.
+const GLYPH_WIDTH: u32 = 3;
+/// Glyph height in font pixels.
+const GLYPH_HEIGHT: u32 = 5;
+/// Font pixel size in image pixels.
+const FONT_SCALE: u32 = 2;
+/// Horizontal advance per character in image pixels.
+const ADVANCE: u32 = (GLYPH_WIDTH + 1) * FONT_SCALE;
+/// Height of one caption line in image pixels.
+const LINE_HEIGHT: u32 = (GLYPH_HEIGHT + 2) * FONT_SCALE;
+/// Gap between cells and around the sheet in image pixels.
+const GAP: u32 = 8;
+
+/// One cell of a contact sheet.
+pub struct Cell<'a> {
+    /// Caption lines drawn above the rendering.
+    pub caption: Vec<String>,
+    /// Rendered path and the canvas shape it was laid out on, or `None`
+    /// for a cell whose run failed (drawn blank).
+    pub drawing: Option<(&'a Polyline, &'a MaskShape)>,
+}
+
+/// Render `cells` row-major into a grid with `columns` columns, each
+/// rendering `options.width_px` wide, and encode it as PNG.
+///
+/// # Errors
+///
+/// Returns [`PngError`] if a cell or the sheet cannot be allocated or
+/// encoded.
+pub fn contact_sheet(
+    cells: &[Cell<'_>],
+    columns: usize,
+    border_margin: f64,
+    options: &PngOptions,
+) -> Result<Vec<u8>, PngError> {
+    let columns = columns.max(1);
+    let caption_lines = cells.iter().map(|c| c.caption.len()).max().unwrap_or(0);
+    let caption_height = u32::try_from(caption_lines).unwrap_or(0) * LINE_HEIGHT;
+
+    let renders = cells
+        .iter()
+        .map(|cell| {
+            cell.drawing
+                .map(|(path, shape)| {
+                    let mapping = mujou_export::document_mapping(shape, border_margin);
+                    render_pixmap(std::slice::from_ref(path), &mapping, options)
+                })
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Rows are as tall as their tallest rendering; failed cells are
+    // square.
+    let cell_width = options.width_px;
+    let row_heights: Vec<u32> = renders
+        .chunks(columns)
+        .map(|row| {
+            row.iter()
+                .map(|r| r.as_ref().map_or(cell_width, Pixmap::height))
+                .max()
+                .unwrap_or(cell_width)
+        })
+        .collect();
+
+    let column_count = u32::try_from(columns.min(cells.len().max(1))).unwrap_or(1);
+    let width = column_count * (cell_width + GAP) + GAP;
+    let height = row_heights
+        .iter()
+        .map(|h| h + caption_height + GAP)
+        .sum::<u32>()
+        + GAP;
+    let mut sheet = Pixmap::new(width, height).ok_or(PngError::InvalidSize { width, height })?;
+    sheet.fill(Color::from_rgba8(232, 232, 232, 255));
+
+    let mut y = GAP;
+    for ((row_cells, row_renders), row_height) in cells
+        .chunks(columns)
+        .zip(renders.chunks(columns))
+        .zip(&row_heights)
+    {
+        let mut x = GAP;
+        for (cell, render) in row_cells.iter().zip(row_renders) {
+            for (line_index, line) in (0_u32..).zip(&cell.caption) {
+                draw_text(
+                    &mut sheet,
+                    line,
+                    x,
+                    y + line_index * LINE_HEIGHT,
+                    cell_width,
+                );
+            }
+            let top = y + caption_height;
+            if let Some(render) = render {
+                sheet.draw_pixmap(
+                    i32::try_from(x).unwrap_or(i32::MAX),
+                    i32::try_from(top).unwrap_or(i32::MAX),
+                    render.as_ref(),
+                    &PixmapPaint::default(),
+                    Transform::identity(),
+                    None,
+                );
+            }
+            x += cell_width + GAP;
+        }
+        y += row_height + caption_height + GAP;
+    }
+
+    sheet
+        .encode_png()
+        .map_err(|e| PngError::Encode(e.to_string()))
+}
+
+/// Draw `text` with its top-left corner at `(x, y)`, clipped to
+/// `max_width` pixels.
+#[allow(clippy::cast_precision_loss)]
+fn draw_text(pixmap: &mut Pixmap, text: &str, x: u32, y: u32, max_width: u32) {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(40, 40, 40, 255);
+    let max_chars = (max_width / ADVANCE) as usize;
+
+    for (i, c) in (0_u32..).zip(text.chars().take(max_chars)) {
+        let rows = glyph(c);
+        let left = x + i * ADVANCE;
+        for (row, bits) in (0_u32..).zip(rows) {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+                let px = left + col * FONT_SCALE;
+                let py = y + row * FONT_SCALE;
+                if let Some(rect) =
+                    Rect::from_xywh(px as f32, py as f32, FONT_SCALE as f32, FONT_SCALE as f32)
+                {
+                    pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+                }
+            }
+        }
+    }
+}
+
+/// 3×5 bitmap for `c`, one byte per row with the leftmost pixel in bit
+/// 2.  Lowercase letters share the capital glyphs; characters without a
+/// glyph render as `?`.
+const fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '[' => [0b011, 0b010, 0b010, 0b010, 0b011],
+        ']' => [0b110, 0b010, 0b010, 0b010, 0b110],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use mujou_pipeline::Point;
+
+    use super::*;
+
+    fn decode_size(png: &[u8]) -> (u32, u32) {
+        let pixmap = Pixmap::decode_png(png).unwrap();
+        (pixmap.width(), pixmap.height())
+    }
+
+    #[test]
+    fn sheet_size_follows_grid_and_captions() {
+        let shape = MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        let path = Polyline::new(vec![Point::new(-0.5, 0.0), Point::new(0.5, 0.0)]);
+        let cells: Vec<Cell<'_>> = (0..5)
+            .map(|i| Cell {
+                caption: vec![format!("blur_sigma={i}"), "canny_low=20".to_owned()],
+                drawing: (i != 3).then_some((&path, &shape)),
+            })
+            .collect();
+        let options = PngOptions {
+            width_px: 50,
+            ..PngOptions::default()
+        };
+        let png = contact_sheet(&cells, 3, 0.0, &options).unwrap();
+        let caption = 2 * LINE_HEIGHT;
+        assert_eq!(
+            decode_size(&png),
+            (3 * (50 + GAP) + GAP, 2 * (50 + caption + GAP) + GAP)
+        );
+    }
+
+    #[test]
+    fn text_is_drawn_and_clipped() {
+        let mut pixmap = Pixmap::new(40, 20).unwrap();
+        draw_text(&mut pixmap, "1111111111", 0, 0, 2 * ADVANCE);
+        let inked = |x: u32| (0..20).any(|y| pixmap.pixel(x, y).unwrap().alpha() > 0);
+        assert!(inked(ADVANCE + 2), "second glyph drawn");
+        assert!(!inked(2 * ADVANCE + 2), "third glyph clipped");
+    }
+}
@@ -0,0 +1,558 @@
+//! `mujou sweep`: run a grid of parameter combinations over one image.
+//!
+//! Each `--vary FIELD=VALUES` adds an axis over a [`PipelineConfig`]
+//! field (by its JSON name), given either as a comma-separated list
+//! (`path_joiner=Mst,Retrace`) or an inclusive numeric range
+//! `start:stop:step` (`blur_sigma=0.5:2.5:0.5`).  Every combination
+//! runs through one [`PipelineCache`](mujou_pipeline::PipelineCache);
+//! cells are visited so that axes affecting earlier stages change least
+//! often, letting later-stage sweeps reuse the upstream work.
+//!
+//! Writes to `--out-dir`:
+//!
+//! - `sheet.png`: labeled contact sheet, one column per value of the
+//!   last axis.
+//! - `cells.csv`: per-cell stage durations, summary counts and
+//!   [`JoinQualityMetrics`].  Stages served from the cache report 0 ms.
+//! - `cells.json`: per-cell overrides and full [`PipelineDiagnostics`]
+//!   (without per-MST-edge details).
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use mujou_export::png::PngOptions;
+use mujou_pipeline::diagnostics::{
+    Clock, PipelineDiagnostics, StageMetrics, run_cached_with_diagnostics,
+};
+use mujou_pipeline::pipeline::{STAGE_COUNT, STAGE_NAMES};
+use mujou_pipeline::{JoinQualityMetrics, MaskShape, PipelineConfig, Polyline};
+use serde_json::{Map, Value};
+
+use crate::config::ConfigArgs;
+use crate::sheet::{Cell, contact_sheet};
+
+/// Maximum number of values a single range may expand to.
+const MAX_RANGE_VALUES: usize = 1000;
+
+/// Arguments for `mujou sweep`.
+#[derive(Args)]
+pub struct SweepArgs {
+    /// Input image.
+    image: PathBuf,
+
+    /// Axis to sweep: `FIELD=V1,V2,...` or `FIELD=START:STOP:STEP`
+    /// (repeatable).  FIELD is a `PipelineConfig` JSON field name.
+    #[arg(long = "vary", value_name = "FIELD=VALUES", required = true, value_parser = parse_axis)]
+    axes: Vec<Axis>,
+
+    /// Base config that the swept fields override.
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// Directory for `sheet.png`, `cells.csv` and `cells.json`.
+    #[arg(short, long, value_name = "DIR", default_value = "sweep")]
+    out_dir: PathBuf,
+
+    /// Width of each contact sheet cell in pixels.
+    #[arg(long, default_value_t = 320, value_parser = clap::builder::RangedU64ValueParser::<u32>::new().range(16..))]
+    cell_px: u32,
+}
+
+/// One swept config field and its values.
+#[derive(Clone, Debug, PartialEq)]
+struct Axis {
+    field: String,
+    values: Vec<Value>,
+}
+
+/// Outcome of one grid cell.
+struct CellRun {
+    diagnostics: PipelineDiagnostics,
+    joined: Polyline,
+    shape: MaskShape,
+    elapsed: Duration,
+}
+
+/// Run `mujou sweep`.
+pub fn run(args: &SweepArgs) -> ExitCode {
+    match sweep(args) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(failed) => {
+            eprintln!("{failed} cell(s) failed");
+            ExitCode::FAILURE
+        }
+        Err(msg) => {
+            eprintln!("{msg}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the grid and write the outputs, returning the number of failed
+/// cells.
+fn sweep(args: &SweepArgs) -> Result<usize, String> {
+    let base = args.config.load()?;
+    let base_json = serde_json::to_value(&base).map_err(|e| e.to_string())?;
+    for axis in &args.axes {
+        if base_json.get(&axis.field).is_none() {
+            return Err(format!("Unknown config field {:?}", axis.field));
+        }
+    }
+    let bytes = std::fs::read(&args.image)
+        .map_err(|e| format!("Error reading {}: {e}", args.image.display()))?;
+    std::fs::create_dir_all(&args.out_dir)
+        .map_err(|e| format!("Error creating {}: {e}", args.out_dir.display()))?;
+
+    let cells = grid(&args.axes);
+    let order = run_order(&base, &base_json, &args.axes, &cells);
+    eprintln!(
+        "Sweeping {} cell(s) over {}",
+        cells.len(),
+        args.image.display()
+    );
+
+    let mut results: Vec<Option<Result<CellRun, String>>> = cells.iter().map(|_| None).collect();
+    let mut cache = None;
+    for (step, &i) in order.iter().enumerate() {
+        let start = Instant::now();
+        let result = cell_config(&base_json, &args.axes, &cells[i]).and_then(|config| {
+            run_cached_with_diagnostics(&mut cache, bytes.clone(), config, &StdClock)
+                .map_err(|e| e.to_string())
+        });
+        let caption = caption(&args.axes, &cells[i]).join(" ");
+        let elapsed = start.elapsed();
+        let run = result.map(|(staged, diagnostics)| CellRun {
+            diagnostics,
+            joined: staged.joined.clone(),
+            shape: staged.canvas.shape,
+            elapsed,
+        });
+        match run {
+            Ok(ref r) => eprintln!(
+                "[{}/{}] {caption}  {} points  {:.1} ms",
+                step + 1,
+                cells.len(),
+                r.diagnostics.summary.final_point_count,
+                r.elapsed.as_secs_f64() * 1000.0,
+            ),
+            Err(ref msg) => eprintln!("[{}/{}] {caption}  error: {msg}", step + 1, cells.len()),
+        }
+        results[i] = Some(run);
+    }
+    let results: Vec<Result<CellRun, String>> = results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err("not run".to_owned())))
+        .collect();
+
+    write_outputs(args, &base, &cells, &results)?;
+    Ok(results.iter().filter(|r| r.is_err()).count())
+}
+
+/// Write the contact sheet, CSV and JSON reports.
+fn write_outputs(
+    args: &SweepArgs,
+    base: &PipelineConfig,
+    cells: &[Vec<usize>],
+    results: &[Result<CellRun, String>],
+) -> Result<(), String> {
+    let sheet_cells: Vec<Cell<'_>> = cells
+        .iter()
+        .zip(results)
+        .map(|(cell, result)| Cell {
+            caption: caption(&args.axes, cell),
+            drawing: result.as_ref().ok().map(|r| (&r.joined, &r.shape)),
+        })
+        .collect();
+    let columns = args.axes.last().map_or(1, |a| a.values.len());
+    let options = PngOptions {
+        width_px: args.cell_px,
+        ..PngOptions::default()
+    };
+    let png = contact_sheet(&sheet_cells, columns, base.border_margin, &options)
+        .map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(&cells_json(&args.axes, cells, results))
+        .map_err(|e| e.to_string())?;
+    let files = [
+        ("sheet.png", png),
+        (
+            "cells.csv",
+            cells_csv(&args.axes, cells, results).into_bytes(),
+        ),
+        ("cells.json", json.into_bytes()),
+    ];
+    for (name, contents) in files {
+        let path = args.out_dir.join(name);
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Parse a `--vary` argument.
+fn parse_axis(spec: &str) -> Result<Axis, String> {
+    let (field, values) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("expected FIELD=VALUES, got {spec:?}"))?;
+    let field = field.trim();
+    if field.is_empty() {
+        return Err(format!("missing field name in {spec:?}"));
+    }
+    let values = parse_values(values)?;
+    if values.is_empty() {
+        return Err(format!("no values for {field}"));
+    }
+    Ok(Axis {
+        field: field.to_owned(),
+        values,
+    })
+}
+
+/// Parse `START:STOP:STEP` as an inclusive numeric range, or otherwise
+/// a comma-separated list.  List items that are not valid JSON (e.g.
+/// enum variant names) are taken as strings.
+fn parse_values(spec: &str) -> Result<Vec<Value>, String> {
+    let parts: Vec<&str> = spec.split(':').map(str::trim).collect();
+    if let [start, stop, step] = parts[..] {
+        let number = |s: &str| {
+            s.parse::<f64>()
+                .map_err(|e| format!("invalid range bound {s:?}: {e}"))
+        };
+        return range(number(start)?, number(stop)?, number(step)?);
+    }
+    Ok(spec
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_owned())))
+        .collect())
+}
+
+/// Values `first, first + step, ...` up to and including `last`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn range(first: f64, last: f64, step: f64) -> Result<Vec<Value>, String> {
+    if !(step > 0.0 && first.is_finite() && last.is_finite() && first <= last) {
+        return Err(format!(
+            "invalid range {first}:{last}:{step} (need start <= stop and step > 0)"
+        ));
+    }
+    // Small epsilon so that e.g. 0.1:0.3:0.1 includes 0.3.
+    let count = ((last - first) / step + 1e-9).floor() as usize + 1;
+    if count > MAX_RANGE_VALUES {
+        return Err(format!(
+            "range {first}:{last}:{step} has more than {MAX_RANGE_VALUES} values"
+        ));
+    }
+    Ok((0..count)
+        .map(|i| {
+            // Round away float noise (0.30000000000000004 → 0.3).
+            let v = (i as f64).mul_add(step, first);
+            let v = (v * 1e9).round() / 1e9;
+            serde_json::Number::from_f64(v).map_or(Value::Null, Value::Number)
+        })
+        .collect())
+}
+
+/// Every combination of axis value indices, with the last axis varying
+/// fastest.
+fn grid(axes: &[Axis]) -> Vec<Vec<usize>> {
+    axes.iter().fold(vec![Vec::new()], |cells, axis| {
+        cells
+            .iter()
+            .flat_map(|prefix| {
+                (0..axis.values.len()).map(move |v| {
+                    let mut cell = prefix.clone();
+                    cell.push(v);
+                    cell
+                })
+            })
+            .collect()
+    })
+}
+
+/// Order in which to run `cells` so that axes affecting earlier
+/// pipeline stages change least often.
+fn run_order(
+    base: &PipelineConfig,
+    base_json: &Value,
+    axes: &[Axis],
+    cells: &[Vec<usize>],
+) -> Vec<usize> {
+    let stage_of = |axis: &Axis| {
+        (0..axis.values.len())
+            .filter_map(|v| cell_config(base_json, std::slice::from_ref(axis), &[v]).ok())
+            .map(|config| base.earliest_changed_stage(&config))
+            .min()
+            .unwrap_or(STAGE_COUNT)
+    };
+    let mut axis_order: Vec<usize> = (0..axes.len()).collect();
+    axis_order.sort_by_key(|&a| stage_of(&axes[a]));
+
+    let mut order: Vec<usize> = (0..cells.len()).collect();
+    order.sort_by_key(|&i| axis_order.iter().map(|&a| cells[i][a]).collect::<Vec<_>>());
+    order
+}
+
+/// Build and validate the config for one cell.
+fn cell_config(base_json: &Value, axes: &[Axis], cell: &[usize]) -> Result<PipelineConfig, String> {
+    let mut json = base_json.clone();
+    for (axis, &v) in axes.iter().zip(cell) {
+        if let Some(slot) = json.get_mut(&axis.field) {
+            *slot = coerce(slot, &axis.values[v]);
+        }
+    }
+    let config: PipelineConfig =
+        serde_json::from_value(json).map_err(|e| format!("invalid config: {e}"))?;
+    config
+        .validate()
+        .map_err(|e| format!("invalid config: {e}"))?;
+    Ok(config)
+}
+
+/// Convert integral floats to integers where the field is an integer,
+/// so ranges like `100:300:100` work for integer fields.
+#[allow(clippy::cast_possible_truncation)]
+fn coerce(current: &Value, value: &Value) -> Value {
+    match value.as_f64() {
+        Some(f) if (current.is_i64() || current.is_u64()) && f.fract() == 0.0 => {
+            Value::from(f as i64)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Caption lines (`field=value`) for one cell.
+fn caption(axes: &[Axis], cell: &[usize]) -> Vec<String> {
+    axes.iter()
+        .zip(cell)
+        .map(|(axis, &v)| format!("{}={}", axis.field, display_value(&axis.values[v])))
+        .collect()
+}
+
+/// Display a value without JSON string quotes.
+fn display_value(value: &Value) -> String {
+    value
+        .as_str()
+        .map_or_else(|| value.to_string(), str::to_owned)
+}
+
+/// Per-stage durations in diagnostics order, aligned with
+/// `STAGE_NAMES[1..]`.
+const fn stage_durations(d: &PipelineDiagnostics) -> [Duration; STAGE_COUNT - 1] {
+    [
+        d.decode.duration,
+        d.downsample.duration,
+        d.tone.duration,
+        d.blur.duration,
+        d.edge_detection.duration,
+        d.contour_tracing.duration,
+        d.simplification.duration,
+        d.canvas.duration,
+        d.join.duration,
+        d.output.duration,
+    ]
+}
+
+/// The MST join quality metrics, if the MST joiner ran.
+const fn join_quality(d: &PipelineDiagnostics) -> Option<&JoinQualityMetrics> {
+    match d.join.metrics {
+        StageMetrics::Join {
+            quality: Some(ref q),
+            ..
+        } => Some(q),
+        _ => None,
+    }
+}
+
+/// Render the CSV report.
+fn cells_csv(axes: &[Axis], cells: &[Vec<usize>], results: &[Result<CellRun, String>]) -> String {
+    let mut header: Vec<String> = vec!["cell".to_owned()];
+    header.extend(axes.iter().map(|a| a.field.clone()));
+    header.extend(["status".to_owned(), "total_ms".to_owned()]);
+    header.extend(STAGE_NAMES[1..].iter().map(|n| format!("{n}_ms")));
+    header.extend(
+        [
+            "contour_count",
+            "final_point_count",
+            "mst_edge_count",
+            "total_mst_edge_weight",
+            "max_mst_edge_weight",
+            "odd_vertices_before_fix",
+            "odd_vertices_after_fix",
+            "total_retrace_distance",
+            "total_path_length",
+        ]
+        .map(str::to_owned),
+    );
+
+    let mut csv = csv_row(&header);
+    for (i, (cell, result)) in cells.iter().zip(results).enumerate() {
+        let mut row = vec![i.to_string()];
+        row.extend(
+            axes.iter()
+                .zip(cell)
+                .map(|(axis, &v)| display_value(&axis.values[v])),
+        );
+        match result {
+            Ok(run) => {
+                let d = &run.diagnostics;
+                let ms = |t: Duration| format!("{:.3}", t.as_secs_f64() * 1000.0);
+                row.push("ok".to_owned());
+                row.push(ms(d.total_duration));
+                row.extend(stage_durations(d).map(ms));
+                row.push(d.summary.contour_count.to_string());
+                row.push(d.summary.final_point_count.to_string());
+                if let Some(q) = join_quality(d) {
+                    row.extend([
+                        q.mst_edge_count.to_string(),
+                        format!("{:.6}", q.total_mst_edge_weight),
+                        format!("{:.6}", q.max_mst_edge_weight),
+                        q.odd_vertices_before_fix.to_string(),
+                        q.odd_vertices_after_fix.to_string(),
+                        format!("{:.6}", q.total_retrace_distance),
+                        format!("{:.6}", q.total_path_length),
+                    ]);
+                }
+            }
+            Err(msg) => row.push(format!("error: {msg}")),
+        }
+        row.resize(header.len(), String::new());
+        csv.push_str(&csv_row(&row));
+    }
+    csv
+}
+
+/// Format one CSV line, quoting fields that need it.
+fn csv_row(fields: &[String]) -> String {
+    let mut line = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        if field.contains([',', '"', '\n']) {
+            let _ = write!(line, "\"{}\"", field.replace('"', "\"\""));
+        } else {
+            line.push_str(field);
+        }
+    }
+    line.push('\n');
+    line
+}
+
+/// Build the JSON report.
+fn cells_json(axes: &[Axis], cells: &[Vec<usize>], results: &[Result<CellRun, String>]) -> Value {
+    cells
+        .iter()
+        .zip(results)
+        .enumerate()
+        .map(|(i, (cell, result))| {
+            let overrides: Map<String, Value> = axes
+                .iter()
+                .zip(cell)
+                .map(|(axis, &v)| (axis.field.clone(), axis.values[v].clone()))
+                .collect();
+            let mut record = Map::new();
+            record.insert("cell".to_owned(), Value::from(i));
+            record.insert("overrides".to_owned(), Value::Object(overrides));
+            match result {
+                Ok(run) => {
+                    let mut diagnostics = run.diagnostics.clone();
+                    if let StageMetrics::Join {
+                        quality: Some(ref mut q),
+                        ..
+                    } = diagnostics.join.metrics
+                    {
+                        q.mst_edge_details.clear();
+                    }
+                    record.insert(
+                        "diagnostics".to_owned(),
+                        serde_json::to_value(diagnostics).unwrap_or(Value::Null),
+                    );
+                }
+                Err(msg) => {
+                    record.insert("error".to_owned(), Value::from(msg.as_str()));
+                }
+            }
+            Value::Object(record)
+        })
+        .collect()
+}
+
+/// [`Clock`] implementation backed by [`std::time::Instant`].
+struct StdClock;
+
+impl Clock for StdClock {
+    type Instant = Instant;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn elapsed(&self, since: &Instant) -> Duration {
+        since.elapsed()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lists_and_ranges() {
+        let axis = parse_axis("path_joiner=Mst, Retrace").unwrap();
+        assert_eq!(axis.field, "path_joiner");
+        assert_eq!(
+            axis.values,
+            vec![Value::from("Mst"), Value::from("Retrace")]
+        );
+
+        let axis = parse_axis("blur_sigma=0.1:0.3:0.1").unwrap();
+        assert_eq!(
+            axis.values,
+            vec![Value::from(0.1), Value::from(0.2), Value::from(0.3)]
+        );
+
+        assert!(parse_axis("blur_sigma").is_err());
+        assert!(parse_axis("blur_sigma=3:1:1").is_err());
+        assert!(parse_axis("blur_sigma=0:1:0").is_err());
+    }
+
+    #[test]
+    fn integer_fields_accept_ranges() {
+        let base = serde_json::to_value(PipelineConfig::default()).unwrap();
+        let axis = parse_axis("working_resolution=200:400:200").unwrap();
+        let config = cell_config(&base, std::slice::from_ref(&axis), &[1]).unwrap();
+        assert_eq!(config.working_resolution, 400);
+    }
+
+    #[test]
+    fn earlier_stage_axes_vary_slowest() {
+        let base = PipelineConfig::default();
+        let base_json = serde_json::to_value(&base).unwrap();
+        // Listed simplify-first, but blur affects an earlier stage.
+        let axes = [
+            parse_axis("simplify_tolerance=1,2").unwrap(),
+            parse_axis("blur_sigma=1,2").unwrap(),
+        ];
+        let cells = grid(&axes);
+        assert_eq!(cells, vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]]);
+        let order = run_order(&base, &base_json, &axes, &cells);
+        let blur: Vec<usize> = order.iter().map(|&i| cells[i][1]).collect();
+        assert_eq!(blur, vec![0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_commas() {
+        let row = csv_row(&["a".to_owned(), "b,c".to_owned(), "d\"e".to_owned()]);
+        assert_eq!(row, "a,\"b,c\",\"d\"\"e\"\n");
+    }
+}
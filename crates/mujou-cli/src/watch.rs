@@ -0,0 +1,321 @@
+//! `mujou watch`: re-run the pipeline whenever the input image or config
+//! file changes.
+//!
+//! Both files are polled, so no platform file-watching support is
+//! needed.  Runs go through [`PipelineCache`], so editing e.g.
+//! `simplify_tolerance` recomputes only the simplify stage and its
+//! dependents.  Each run prints which stages were served from the cache.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
+
+use clap::Args;
+use mujou_pipeline::pipeline::STAGE_NAMES;
+use mujou_pipeline::{PipelineCache, PipelineConfig, Progress};
+
+use crate::output::{Format, RenderArgs, RenderOptions, dedup_formats, output_path, render};
+
+/// Arguments for `mujou watch`.
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Input image to watch.
+    image: PathBuf,
+
+    /// `PipelineConfig` JSON file to watch (created with the defaults
+    /// if it does not exist).
+    #[arg(long, value_name = "FILE")]
+    config: PathBuf,
+
+    /// Output formats to rewrite after each run.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Format::Svg, Format::Png])]
+    format: Vec<Format>,
+
+    /// Write outputs into this directory instead of next to the image.
+    #[arg(short, long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Polling interval in milliseconds.
+    #[arg(long, default_value_t = 250, value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    interval_ms: u64,
+}
+
+/// Modification stamp used to detect file changes; `None` when the file
+/// is missing.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Per-stage record of one rebuild: `(stage_index, cached, elapsed)`.
+struct Rebuild {
+    stages: Vec<(usize, bool, Duration)>,
+    outputs: Vec<PathBuf>,
+    points: usize,
+}
+
+/// Run `mujou watch` until interrupted.
+pub fn run(args: &WatchArgs) -> ExitCode {
+    let formats = dedup_formats(&args.format);
+    let render_options = args.render.options();
+    let outputs: Vec<PathBuf> = formats
+        .iter()
+        .map(|&f| output_path(&args.image, args.out_dir.as_deref(), f))
+        .collect();
+    if let Some(clash) = outputs.iter().find(|o| **o == args.image) {
+        eprintln!("Output {} would overwrite the input", clash.display());
+        return ExitCode::FAILURE;
+    }
+    if let Some(ref dir) = args.out_dir
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        eprintln!("Error creating {}: {e}", dir.display());
+        return ExitCode::FAILURE;
+    }
+    if !args.config.exists() {
+        if let Err(msg) = write_default_config(&args.config) {
+            eprintln!("{msg}");
+            return ExitCode::FAILURE;
+        }
+        eprintln!("Wrote default config to {}", args.config.display());
+    }
+
+    eprintln!(
+        "Watching {} and {} (Ctrl-C to stop)",
+        args.image.display(),
+        args.config.display(),
+    );
+
+    let interval = Duration::from_millis(args.interval_ms);
+    let mut cache: Option<PipelineCache> = None;
+    let mut seen: Option<(Stamp, Stamp)> = None;
+    let mut run_count = 0_usize;
+
+    loop {
+        let stamps = (stamp(&args.image), stamp(&args.config));
+        if seen != Some(stamps) {
+            seen = Some(stamps);
+            run_count += 1;
+            eprintln!();
+            eprintln!("Run {run_count}");
+            let start = Instant::now();
+            match rebuild(
+                &args.image,
+                &args.config,
+                &formats,
+                &outputs,
+                &render_options,
+                &mut cache,
+            ) {
+                Ok(report) => print_rebuild(&report, start.elapsed()),
+                Err(msg) => eprintln!("  error: {msg}"),
+            }
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Current [`Stamp`] of `path`.
+fn stamp(path: &Path) -> Stamp {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Write the default config as pretty-printed JSON to `path`.
+fn write_default_config(path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&PipelineConfig::default())
+        .map_err(|e| format!("Error serializing default config: {e}"))?;
+    std::fs::write(path, json + "\n").map_err(|e| format!("Error writing {}: {e}", path.display()))
+}
+
+/// Reload the config and image, run the pipeline through `cache`, and
+/// rewrite `outputs` (one per entry of `formats`).
+///
+/// On failure the previous cache is kept for the next attempt.
+fn rebuild(
+    image: &Path,
+    config_path: &Path,
+    formats: &[Format],
+    outputs: &[PathBuf],
+    render_options: &RenderOptions,
+    cache: &mut Option<PipelineCache>,
+) -> Result<Rebuild, String> {
+    let config = crate::config::load_file(config_path)?;
+    let bytes =
+        std::fs::read(image).map_err(|e| format!("Error reading {}: {e}", image.display()))?;
+
+    let records = RefCell::new(Vec::new());
+    let last = RefCell::new(Instant::now());
+    let on_stage = |index: usize, cached: bool| {
+        let now = Instant::now();
+        let elapsed = now - last.replace(now);
+        records.borrow_mut().push((index, cached, elapsed));
+    };
+    let staged =
+        PipelineCache::run_with_progress(cache, bytes, config.clone(), &on_stage, &Progress::NONE)
+            .map_err(|e| e.to_string())?;
+
+    for (&format, path) in formats.iter().zip(outputs) {
+        let contents = render(format, image, &staged, &config, render_options)?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+    }
+
+    Ok(Rebuild {
+        stages: records.into_inner(),
+        outputs: outputs.to_vec(),
+        points: staged.output.len(),
+    })
+}
+
+/// Print per-stage cache hits and timings for one rebuild.
+fn print_rebuild(report: &Rebuild, total: Duration) {
+    for &(index, cached, elapsed) in &report.stages {
+        let name = STAGE_NAMES.get(index).copied().unwrap_or("?");
+        if cached {
+            eprintln!("  {name:<12} cached");
+        } else {
+            eprintln!(
+                "  {name:<12} computed {:>9.1} ms",
+                elapsed.as_secs_f64() * 1000.0,
+            );
+        }
+    }
+    let cached = report.stages.iter().filter(|s| s.1).count();
+    eprintln!(
+        "  {cached}/{} stages cached, {} points, {:.1} ms total",
+        report.stages.len(),
+        report.points,
+        total.as_secs_f64() * 1000.0,
+    );
+    for path in &report.outputs {
+        eprintln!("  wrote {}", path.display());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use mujou_pipeline::{MaskShape, Point, Polyline};
+
+    use super::*;
+
+    /// Scratch directory holding a small line-art PNG and a config file.
+    fn fixture(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("mujou-watch-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let shape = MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        let square = Polyline::new(vec![
+            Point::new(-0.5, -0.5),
+            Point::new(0.5, -0.5),
+            Point::new(0.5, 0.5),
+            Point::new(-0.5, 0.5),
+            Point::new(-0.5, -0.5),
+        ]);
+        let options = mujou_export::png::PngOptions {
+            width_px: 120,
+            line_width_mm: 4.0,
+            ..mujou_export::png::PngOptions::default()
+        };
+        let png = mujou_export::png::to_png(
+            &[square],
+            &mujou_export::document_mapping(&shape, 0.0),
+            &options,
+        )
+        .unwrap();
+        let image = dir.join("square.png");
+        std::fs::write(&image, png).unwrap();
+
+        let config = dir.join("config.json");
+        write_default_config(&config).unwrap();
+        (dir, image, config)
+    }
+
+    #[test]
+    fn config_edit_reuses_upstream_stages() {
+        let (dir, image, config_path) = fixture("reuse");
+        let formats = [Format::Svg, Format::Png];
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let outputs: Vec<PathBuf> = formats
+            .iter()
+            .map(|&f| output_path(&image, Some(&out_dir), f))
+            .collect();
+        let mut cache = None;
+
+        let first = rebuild(
+            &image,
+            &config_path,
+            &formats,
+            &outputs,
+            &RenderOptions::default(),
+            &mut cache,
+        )
+        .unwrap();
+        assert!(first.stages.iter().all(|s| !s.1), "first run computes all");
+        assert!(outputs.iter().all(|o| o.exists()));
+
+        let config = PipelineConfig {
+            simplify_tolerance: PipelineConfig::DEFAULT_SIMPLIFY_TOLERANCE * 2.0,
+            ..PipelineConfig::default()
+        };
+        std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let second = rebuild(
+            &image,
+            &config_path,
+            &formats,
+            &outputs,
+            &RenderOptions::default(),
+            &mut cache,
+        )
+        .unwrap();
+        let cached: Vec<bool> = second.stages.iter().map(|s| s.1).collect();
+        assert!(
+            cached[..STAGE_NAMES.iter().position(|&n| n == "simplify").unwrap()]
+                .iter()
+                .all(|&c| c)
+        );
+        assert!(!cached.last().unwrap(), "output stage is recomputed");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bad_config_keeps_cache() {
+        let (dir, image, config_path) = fixture("bad-config");
+        let formats = [Format::Svg];
+        let outputs = vec![output_path(&image, None, Format::Svg)];
+        let mut cache = None;
+        rebuild(
+            &image,
+            &config_path,
+            &formats,
+            &outputs,
+            &RenderOptions::default(),
+            &mut cache,
+        )
+        .unwrap();
+
+        std::fs::write(&config_path, "{ not json").unwrap();
+        assert!(
+            rebuild(
+                &image,
+                &config_path,
+                &formats,
+                &outputs,
+                &RenderOptions::default(),
+                &mut cache
+            )
+            .is_err()
+        );
+        assert!(cache.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
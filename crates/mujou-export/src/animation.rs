@@ -0,0 +1,270 @@
+//! Animated drawing-order preview (APNG).
+//!
+//! Renders the path progressively, one frame per equal step of arc
+//! length, with a ball marker at the current position and, optionally,
+//! the not-yet-drawn remainder as a faint ghost.  Reviewing the
+//! animation shows the drawing order and makes long connectors easy to
+//! spot; APNG plays in every current browser, so the file can be shared
+//! as-is.
+//!
+//! Frames use the same document layout as [`to_svg`] and
+//! [`png::to_png`](crate::png::to_png).
+//!
+//! Available with the `png` cargo feature.
+//!
+//! [`to_svg`]: crate::svg::to_svg
+
+use mujou_pipeline::Polyline;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Transform};
+
+use crate::png::{PngError, PngOptions, draw_polylines, to_pixel};
+use crate::svg::DocumentMapping;
+
+/// Animation options for [`to_apng`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationOptions {
+    /// Size and colors of each frame.
+    pub frame: PngOptions,
+    /// Number of frames; the path grows by `1 / frames` of its length
+    /// per frame, and the last frame shows it complete.
+    pub frames: u32,
+    /// Display time of each frame, in milliseconds.
+    pub frame_delay_ms: u16,
+    /// Extra display time of the final frame, in milliseconds.
+    pub hold_ms: u16,
+    /// Ball marker diameter in document millimetres (0 hides it).
+    pub ball_diameter_mm: f64,
+    /// Ball marker color (opaque RGB).
+    pub ball_color: [u8; 3],
+    /// Color of the not-yet-drawn remainder, or `None` to hide it.
+    pub ghost_color: Option<[u8; 3]>,
+    /// Number of times to play the animation (0 loops forever).
+    pub plays: u32,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            frame: PngOptions {
+                width_px: 480,
+                ..PngOptions::default()
+            },
+            frames: 90,
+            frame_delay_ms: 40,
+            hold_ms: 2000,
+            ball_diameter_mm: 4.0,
+            ball_color: [214, 48, 49],
+            ghost_color: Some([225, 225, 225]),
+            plays: 0,
+        }
+    }
+}
+
+/// Render `path` being drawn as an animated PNG.
+///
+/// # Errors
+///
+/// Returns [`PngError::InvalidSize`] if a frame cannot be allocated, or
+/// [`PngError::Encode`] if `options.frames` is zero or encoding fails.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline};
+/// use mujou_export::animation::{AnimationOptions, to_apng};
+/// use mujou_export::document_mapping;
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// let path = Polyline::new(vec![Point::new(-0.5, 0.0), Point::new(0.5, 0.0)]);
+/// let options = AnimationOptions { frames: 4, ..AnimationOptions::default() };
+/// let bytes = to_apng(&path, &document_mapping(&shape, 0.0), &options).unwrap();
+/// assert_eq!(&bytes[1..4], b"PNG");
+/// ```
+pub fn to_apng(
+    path: &Polyline,
+    mapping: &DocumentMapping,
+    options: &AnimationOptions,
+) -> Result<Vec<u8>, PngError> {
+    let encode_err = |e: ::png::EncodingError| PngError::Encode(e.to_string());
+
+    // Rendering the first frame also validates the size.
+    let mut frame = render_frame(path, mapping, options, 1)?;
+    let mut bytes = Vec::new();
+    let mut encoder = ::png::Encoder::new(&mut bytes, frame.width(), frame.height());
+    encoder.set_color(::png::ColorType::Rgba);
+    encoder.set_depth(::png::BitDepth::Eight);
+    encoder
+        .set_animated(options.frames, options.plays)
+        .map_err(encode_err)?;
+    let mut writer = encoder.write_header().map_err(encode_err)?;
+
+    for index in 1..=options.frames {
+        if index > 1 {
+            frame = render_frame(path, mapping, options, index)?;
+        }
+        let delay = if index == options.frames {
+            options.frame_delay_ms.saturating_add(options.hold_ms)
+        } else {
+            options.frame_delay_ms
+        };
+        writer.set_frame_delay(delay, 1000).map_err(encode_err)?;
+        // Frames are fully opaque, so premultiplied data is plain RGBA.
+        writer.write_image_data(frame.data()).map_err(encode_err)?;
+    }
+    writer.finish().map_err(encode_err)?;
+    Ok(bytes)
+}
+
+/// Render frame `index` (1-based) of the animation.
+fn render_frame(
+    path: &Polyline,
+    mapping: &DocumentMapping,
+    options: &AnimationOptions,
+    index: u32,
+) -> Result<Pixmap, PngError> {
+    let frame = &options.frame;
+    let ghost = options.ghost_color.map(|color| PngOptions {
+        foreground: color,
+        ..*frame
+    });
+    let mut pixmap = crate::png::render_pixmap(
+        ghost
+            .as_ref()
+            .map_or(&[][..], |_| std::slice::from_ref(path)),
+        mapping,
+        ghost.as_ref().unwrap_or(frame),
+    )?;
+
+    let drawn = path.prefix(f64::from(index) / f64::from(options.frames.max(1)));
+    draw_polylines(
+        &mut pixmap,
+        std::slice::from_ref(&drawn),
+        mapping,
+        frame.line_width_mm,
+        frame.foreground,
+    );
+
+    if let Some(&ball) = drawn.last() {
+        draw_ball(&mut pixmap, ball, mapping, options);
+    }
+    Ok(pixmap)
+}
+
+/// Fill the ball marker centred on `at`.
+#[allow(clippy::cast_possible_truncation)]
+fn draw_ball(
+    pixmap: &mut Pixmap,
+    at: mujou_pipeline::Point,
+    mapping: &DocumentMapping,
+    options: &AnimationOptions,
+) {
+    let px_per_mm = f64::from(pixmap.width()) / mapping.width_mm;
+    let radius = (options.ball_diameter_mm / 2.0 * px_per_mm) as f32;
+    let (x, y) = to_pixel(at, mapping, pixmap.width());
+    let Some(circle) = PathBuilder::from_circle(x, y, radius) else {
+        return;
+    };
+    let [red, green, blue] = options.ball_color;
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba8(red, green, blue, 255));
+    paint.anti_alias = true;
+    pixmap.fill_path(
+        &circle,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use mujou_pipeline::{MaskShape, Point};
+
+    use super::*;
+    use crate::svg::document_mapping;
+
+    fn mapping() -> DocumentMapping {
+        let shape = MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        document_mapping(&shape, 0.0)
+    }
+
+    fn options(frames: u32) -> AnimationOptions {
+        AnimationOptions {
+            frame: PngOptions {
+                width_px: 200,
+                line_width_mm: 4.0,
+                ..PngOptions::default()
+            },
+            frames,
+            ..AnimationOptions::default()
+        }
+    }
+
+    fn red(pixmap: &Pixmap, x: u32, y: u32) -> u8 {
+        pixmap.pixel(x, y).unwrap().red()
+    }
+
+    #[test]
+    fn frames_reveal_the_path_in_order() {
+        // Left to right across the middle, 1 px per mm.
+        let path = Polyline::new(vec![Point::new(-0.8, 0.0), Point::new(0.8, 0.0)]);
+        let opts = AnimationOptions {
+            ghost_color: None,
+            ball_diameter_mm: 0.0,
+            ..options(4)
+        };
+
+        let first = render_frame(&path, &mapping(), &opts, 1).unwrap();
+        assert!(red(&first, 30, 100) < 128, "start drawn in frame 1");
+        assert_eq!(red(&first, 170, 100), 255, "end not yet drawn in frame 1");
+
+        let last = render_frame(&path, &mapping(), &opts, 4).unwrap();
+        assert!(red(&last, 170, 100) < 128, "end drawn in the last frame");
+    }
+
+    #[test]
+    fn ghost_and_ball_are_drawn() {
+        let path = Polyline::new(vec![Point::new(-0.8, 0.0), Point::new(0.8, 0.0)]);
+        let opts = AnimationOptions {
+            ball_diameter_mm: 10.0,
+            ..options(2)
+        };
+        let frame = render_frame(&path, &mapping(), &opts, 1).unwrap();
+
+        // Halfway: the ball sits at the centre.
+        let ball = frame.pixel(100, 100).unwrap();
+        assert_eq!([ball.red(), ball.green(), ball.blue()], opts.ball_color);
+        // The remainder is shown in the ghost color.
+        let ghost = frame.pixel(170, 100).unwrap();
+        assert_eq!(
+            [ghost.red(), ghost.green(), ghost.blue()],
+            opts.ghost_color.unwrap()
+        );
+    }
+
+    #[test]
+    fn apng_declares_frame_count() {
+        let path = Polyline::new(vec![Point::new(-0.5, 0.0), Point::new(0.5, 0.5)]);
+        let bytes = to_apng(&path, &mapping(), &options(3)).unwrap();
+
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(bytes));
+        let reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!(control.num_frames, 3);
+        assert_eq!(control.num_plays, 0);
+    }
+
+    #[test]
+    fn zero_frames_is_an_error() {
+        let path = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(0.5, 0.0)]);
+        assert!(matches!(
+            to_apng(&path, &mapping(), &options(0)),
+            Err(PngError::Encode(_))
+        ));
+    }
+}
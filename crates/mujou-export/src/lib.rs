@@ -1,13 +1,21 @@
 //! mujou-export: Pure format serializers (sans-IO)
 //!
-//! Converts polylines into output formats. Currently supports SVG and THR.
-//! Future formats: G-code, DXF, PNG.
+//! Converts polylines into output formats. Currently supports SVG and THR,
+//! plus PNG previews, drawing-order animations and simulated sand renders
+//! with the `png` feature.
+//! Future formats: G-code, DXF.
 
+#[cfg(feature = "png")]
+pub mod animation;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "png")]
+pub mod sand;
 pub mod svg;
 pub mod thr;
 
 pub use svg::{
-    DocumentMapping, SvgMetadata, build_path_data, document_mapping, to_diagnostic_svg,
-    to_segment_diagnostic_svg, to_svg,
+    DocumentMapping, SvgMetadata, build_path_data, document_mapping, to_bezier_svg,
+    to_diagnostic_svg, to_layered_svg, to_segment_diagnostic_svg, to_svg,
 };
 pub use thr::{ThrMetadata, to_thr};
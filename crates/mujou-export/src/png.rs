@@ -0,0 +1,230 @@
+//! Rasterized PNG preview of polylines.
+//!
+//! Renders paths with anti-aliased round-capped strokes using
+//! `tiny-skia`, laid out on the same document as [`to_svg`] via a
+//! [`DocumentMapping`], so a PNG and an SVG of the same result line up.
+//!
+//! Available with the `png` cargo feature.
+//!
+//! [`to_svg`]: crate::svg::to_svg
+
+use mujou_pipeline::{Point, Polyline};
+use tiny_skia::{Color, LineCap, LineJoin, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::svg::DocumentMapping;
+
+/// Rendering options for [`to_png`] and [`render_pixmap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PngOptions {
+    /// Image width in pixels; the height follows the document aspect
+    /// ratio.
+    pub width_px: u32,
+    /// Stroke width in document millimetres.
+    pub line_width_mm: f64,
+    /// Background color (opaque RGB).
+    pub background: [u8; 3],
+    /// Stroke color (opaque RGB).
+    pub foreground: [u8; 3],
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            width_px: 800,
+            line_width_mm: 0.5,
+            background: [255, 255, 255],
+            foreground: [26, 26, 26],
+        }
+    }
+}
+
+/// Errors from PNG rendering.
+#[derive(Debug, thiserror::Error)]
+pub enum PngError {
+    /// The requested image size is zero or too large to allocate.
+    #[error("invalid image size {width}x{height}")]
+    InvalidSize {
+        /// Requested width in pixels.
+        width: u32,
+        /// Requested height in pixels.
+        height: u32,
+    },
+    /// PNG encoding failed.
+    #[error("PNG encoding failed: {0}")]
+    Encode(String),
+}
+
+/// Render `polylines` into a new pixmap.
+///
+/// Coordinates are mapped from normalized space exactly as in the SVG
+/// export, then scaled from millimetres to pixels.
+///
+/// # Errors
+///
+/// Returns [`PngError::InvalidSize`] if the pixmap cannot be allocated.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn render_pixmap(
+    polylines: &[Polyline],
+    mapping: &DocumentMapping,
+    options: &PngOptions,
+) -> Result<Pixmap, PngError> {
+    let width = options.width_px;
+    let px_per_mm = f64::from(width) / mapping.width_mm;
+    let height = (mapping.height_mm * px_per_mm).round().max(1.0) as u32;
+    let mut pixmap = Pixmap::new(width, height).ok_or(PngError::InvalidSize { width, height })?;
+
+    let [br, bg, bb] = options.background;
+    pixmap.fill(Color::from_rgba8(br, bg, bb, 255));
+    draw_polylines(
+        &mut pixmap,
+        polylines,
+        mapping,
+        options.line_width_mm,
+        options.foreground,
+    );
+
+    Ok(pixmap)
+}
+
+/// Pixel position of normalized point `p` on a pixmap `width_px` wide
+/// that spans the whole document of `mapping`.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn to_pixel(p: Point, mapping: &DocumentMapping, width_px: u32) -> (f32, f32) {
+    let px_per_mm = f64::from(width_px) / mapping.width_mm;
+    (
+        (p.x.mul_add(mapping.scale_factor, mapping.offset_x) * px_per_mm) as f32,
+        ((-p.y).mul_add(mapping.scale_factor, mapping.offset_y) * px_per_mm) as f32,
+    )
+}
+
+/// Stroke `polylines` onto `pixmap`, which spans the whole document of
+/// `mapping`.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn draw_polylines(
+    pixmap: &mut Pixmap,
+    polylines: &[Polyline],
+    mapping: &DocumentMapping,
+    line_width_mm: f64,
+    color: [u8; 3],
+) {
+    let width_px = pixmap.width();
+    let px_per_mm = f64::from(width_px) / mapping.width_mm;
+    let tx = |p: &Point| to_pixel(*p, mapping, width_px);
+
+    let mut pb = PathBuilder::new();
+    for polyline in polylines {
+        let points = polyline.points();
+        if points.len() < 2 {
+            continue;
+        }
+        let (x, y) = tx(&points[0]);
+        pb.move_to(x, y);
+        for p in &points[1..] {
+            let (x, y) = tx(p);
+            pb.line_to(x, y);
+        }
+    }
+
+    if let Some(path) = pb.finish() {
+        let stroke = Stroke {
+            width: (line_width_mm * px_per_mm) as f32,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            ..Stroke::default()
+        };
+        let mut paint = Paint::default();
+        let [r, g, b] = color;
+        paint.set_color_rgba8(r, g, b, 255);
+        paint.anti_alias = true;
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+}
+
+/// Render `polylines` and encode the result as PNG bytes.
+///
+/// # Errors
+///
+/// Returns [`PngError`] if the pixmap cannot be allocated or encoded.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline};
+/// use mujou_export::{document_mapping, png::{PngOptions, to_png}};
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// let line = Polyline::new(vec![Point::new(-0.5, 0.0), Point::new(0.5, 0.0)]);
+/// let bytes = to_png(&[line], &document_mapping(&shape, 0.0), &PngOptions::default()).unwrap();
+/// assert_eq!(&bytes[1..4], b"PNG");
+/// ```
+pub fn to_png(
+    polylines: &[Polyline],
+    mapping: &DocumentMapping,
+    options: &PngOptions,
+) -> Result<Vec<u8>, PngError> {
+    render_pixmap(polylines, mapping, options)?
+        .encode_png()
+        .map_err(|e| PngError::Encode(e.to_string()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use mujou_pipeline::{MaskShape, Point};
+
+    use super::*;
+    use crate::svg::document_mapping;
+
+    fn unit_circle_mapping() -> DocumentMapping {
+        let shape = MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        };
+        document_mapping(&shape, 0.0)
+    }
+
+    #[test]
+    fn stroke_lands_at_mapped_position() {
+        let line = Polyline::new(vec![Point::new(-0.5, 0.5), Point::new(0.5, 0.5)]);
+        // Thick enough to fully cover the pixel rows either side of the
+        // line at 1 px/mm.
+        let options = PngOptions {
+            width_px: 200,
+            line_width_mm: 4.0,
+            ..PngOptions::default()
+        };
+        let pixmap = render_pixmap(&[line], &unit_circle_mapping(), &options).unwrap();
+        // y = +0.5 (up) maps to a quarter of the way down the image.
+        let on = pixmap.pixel(100, 50).unwrap();
+        let off = pixmap.pixel(100, 150).unwrap();
+        assert!(on.red() < 128, "expected stroke at (100, 50)");
+        assert_eq!(off.red(), 255, "expected background at (100, 150)");
+    }
+
+    #[test]
+    fn rectangle_height_follows_aspect_ratio() {
+        let shape = MaskShape::Rectangle {
+            center: Point::new(0.0, 0.0),
+            half_width: 2.0,
+            half_height: 1.0,
+        };
+        let options = PngOptions {
+            width_px: 400,
+            ..PngOptions::default()
+        };
+        let pixmap = render_pixmap(&[], &document_mapping(&shape, 0.0), &options).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (400, 200));
+    }
+
+    #[test]
+    fn zero_width_is_rejected() {
+        let options = PngOptions {
+            width_px: 0,
+            ..PngOptions::default()
+        };
+        assert!(matches!(
+            to_png(&[], &unit_circle_mapping(), &options),
+            Err(PngError::InvalidSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,530 @@
+//! Simulated sand-table preview.
+//!
+//! A 1-px stroke says little about how a drawing looks in sand: the ball
+//! digs a trough as wide as its contact patch, the displaced sand piles
+//! up in ridges on either side, and every extra pass over the same
+//! segment deepens the groove.  This module renders polylines into a
+//! heightmap built from those three effects, then shades it with a
+//! single directional light, so connectors, border runs and retraced
+//! overdraw show up the way they will on the table.
+//!
+//! The heightmap is assembled from anti-aliased `tiny-skia` stroke
+//! coverage masks: a stack of nested strokes approximates the spherical
+//! trough profile, a wider stack the ridge ramp.
+//!
+//! Available with the `png` cargo feature.
+
+use std::collections::HashMap;
+
+use mujou_pipeline::{MaskShape, Point, Polyline};
+use tiny_skia::{
+    FillRule, LineCap, LineJoin, Mask, Path, PathBuilder, Pixmap, Rect, Stroke, Transform,
+};
+
+use crate::png::PngError;
+
+/// Trough depth as a fraction of the ball radius.
+const DEPTH_RATIO: f64 = 0.35;
+
+/// Extra depth per additional pass, as a fraction of the first pass.
+const RETRACE_DEEPENING: f64 = 0.5;
+
+/// Deepest groove, as a fraction of the ball radius.
+const MAX_DEPTH_RATIO: f64 = 0.8;
+
+/// Passes beyond this count no longer deepen the groove.
+const MAX_PASSES: u32 = 4;
+
+/// Ridge height as a fraction of the first-pass trough depth.
+const RIDGE_RATIO: f64 = 0.45;
+
+/// Ridge width beyond the trough edge, as a fraction of the ball radius.
+const RIDGE_WIDTH_RATIO: f64 = 0.9;
+
+/// Coverage layers used to approximate the trough profile.
+const TROUGH_LAYERS: u32 = 8;
+
+/// Coverage layers used to approximate the ridge ramp.
+const RIDGE_LAYERS: u32 = 6;
+
+/// Sand grain amplitude as a fraction of the trough depth.
+const GRAIN_RATIO: f64 = 0.04;
+
+/// Fraction of the sand color kept in full shadow.
+const AMBIENT: f64 = 0.35;
+
+/// Rendering options for [`to_png`] and [`render_pixmap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SandOptions {
+    /// Image width in pixels; the height follows the table aspect ratio.
+    pub width_px: u32,
+    /// Physical size of the table across the canvas shape's shorter
+    /// extent (the diameter of a round table), in millimetres.
+    pub table_size_mm: f64,
+    /// Diameter of the ball, in millimetres.
+    pub ball_diameter_mm: f64,
+    /// Direction the light comes from, in degrees counter-clockwise
+    /// from the right edge of the image (135 = top left).
+    pub light_azimuth_deg: f64,
+    /// Light elevation above the sand surface, in degrees.
+    pub light_elevation_deg: f64,
+    /// Color of flat, lit sand (opaque RGB).
+    pub sand_color: [u8; 3],
+    /// Color outside the table (opaque RGB).
+    pub frame_color: [u8; 3],
+}
+
+impl Default for SandOptions {
+    fn default() -> Self {
+        Self {
+            width_px: 1000,
+            table_size_mm: 600.0,
+            ball_diameter_mm: 12.0,
+            light_azimuth_deg: 135.0,
+            light_elevation_deg: 30.0,
+            sand_color: [226, 205, 165],
+            frame_color: [58, 44, 34],
+        }
+    }
+}
+
+/// Pixel-space layout of the table: the image covers the shape's
+/// bounding box exactly.
+struct Layout {
+    width: u32,
+    height: u32,
+    left: f64,
+    top: f64,
+    px_per_unit: f64,
+}
+
+impl Layout {
+    #[allow(clippy::cast_possible_truncation)]
+    fn to_px(&self, p: Point) -> (f32, f32) {
+        (
+            ((p.x - self.left) * self.px_per_unit) as f32,
+            ((self.top - p.y) * self.px_per_unit) as f32,
+        )
+    }
+}
+
+/// Render `polylines` as grooves in sand on a table of `shape`.
+///
+/// Coordinates are normalized, as produced by the pipeline; the image
+/// spans the bounding box of `shape`, and everything outside the shape
+/// is drawn in the frame color.
+///
+/// # Errors
+///
+/// Returns [`PngError::InvalidSize`] if the pixmap cannot be allocated.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn render_pixmap(
+    polylines: &[Polyline],
+    shape: &MaskShape,
+    options: &SandOptions,
+) -> Result<Pixmap, PngError> {
+    let (center, half_width, half_height) = match *shape {
+        MaskShape::Circle { center, radius } => (center, radius, radius),
+        MaskShape::Rectangle {
+            center,
+            half_width,
+            half_height,
+        } => (center, half_width, half_height),
+    };
+    let width = options.width_px;
+    let px_per_unit = f64::from(width) / (2.0 * half_width);
+    let height = (2.0 * half_height * px_per_unit).round().max(1.0) as u32;
+    let invalid = || PngError::InvalidSize { width, height };
+    let layout = Layout {
+        width,
+        height,
+        left: center.x - half_width,
+        top: center.y + half_height,
+        px_per_unit,
+    };
+
+    let mm_per_unit = options.table_size_mm / (2.0 * half_width.min(half_height));
+    let ball_radius_px = options.ball_diameter_mm / 2.0 / mm_per_unit * px_per_unit;
+
+    let heights = heightmap(polylines, &layout, ball_radius_px).ok_or_else(invalid)?;
+    let table = table_mask(shape, &layout).ok_or_else(invalid)?;
+    let mut pixmap = Pixmap::new(width, height).ok_or_else(invalid)?;
+    shade(&mut pixmap, &heights, &table, options);
+    Ok(pixmap)
+}
+
+/// Render `polylines` as sand and encode the result as PNG bytes.
+///
+/// # Errors
+///
+/// Returns [`PngError`] if the pixmap cannot be allocated or encoded.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline};
+/// use mujou_export::sand::{SandOptions, to_png};
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// let line = Polyline::new(vec![Point::new(-0.5, 0.0), Point::new(0.5, 0.0)]);
+/// let options = SandOptions { width_px: 200, ..SandOptions::default() };
+/// let bytes = to_png(&[line], &shape, &options).unwrap();
+/// assert_eq!(&bytes[1..4], b"PNG");
+/// ```
+pub fn to_png(
+    polylines: &[Polyline],
+    shape: &MaskShape,
+    options: &SandOptions,
+) -> Result<Vec<u8>, PngError> {
+    render_pixmap(polylines, shape, options)?
+        .encode_png()
+        .map_err(|e| PngError::Encode(e.to_string()))
+}
+
+/// Build the sand surface height (in pixels, 0 = undisturbed) for the
+/// given ball radius.
+///
+/// Returns `None` if a coverage mask cannot be allocated.
+fn heightmap(polylines: &[Polyline], layout: &Layout, ball_radius_px: f64) -> Option<Vec<f32>> {
+    let r = ball_radius_px.max(0.5);
+    let depth = r * DEPTH_RATIO;
+    let groove_edge = groove_half_width(r, depth, 0.0);
+    let ridge_height = depth * RIDGE_RATIO;
+    let ridge_width = r * RIDGE_WIDTH_RATIO;
+
+    let passes = pass_counts(polylines);
+    let mut mask = Mask::new(layout.width, layout.height)?;
+    let len = mask.data().len();
+    let mut dug = vec![0.0_f32; len];
+    let mut groove = vec![0.0_f32; len];
+    let mut ridge = vec![0.0_f32; len];
+
+    for level in 1..=passes.values().copied().max().unwrap_or(0).min(MAX_PASSES) {
+        let Some(path) = level_path(polylines, &passes, level, layout) else {
+            continue;
+        };
+        let level_depth =
+            (depth * RETRACE_DEEPENING.mul_add(f64::from(level - 1), 1.0)).min(r * MAX_DEPTH_RATIO);
+
+        // Trough: nested strokes, each adding one slice of the depth
+        // where the sphere reaches below that slice.
+        let mut level_dug = vec![0.0_f32; len];
+        for j in 0..TROUGH_LAYERS {
+            let t = level_depth * (f64::from(j) + 0.5) / f64::from(TROUGH_LAYERS);
+            let half = groove_half_width(r, level_depth, t);
+            coverage(&mut mask, &path, half)?;
+            accumulate(
+                &mut level_dug,
+                &mask,
+                level_depth / f64::from(TROUGH_LAYERS),
+            );
+            if level == 1 && j == 0 {
+                accumulate(&mut groove, &mask, 1.0);
+            }
+        }
+        for (d, l) in dug.iter_mut().zip(&level_dug) {
+            *d = d.max(*l);
+        }
+
+        // Ridges: displaced sand ramps down from the groove edge.
+        if level == 1 {
+            for j in 0..RIDGE_LAYERS {
+                let half =
+                    groove_edge + ridge_width * (f64::from(j) + 0.5) / f64::from(RIDGE_LAYERS);
+                coverage(&mut mask, &path, half)?;
+                accumulate(&mut ridge, &mask, ridge_height / f64::from(RIDGE_LAYERS));
+            }
+        }
+    }
+
+    let mut heights: Vec<f32> = ridge
+        .iter()
+        .zip(&groove)
+        .zip(&dug)
+        .map(|((ridge, groove), dug)| ridge * (1.0 - groove.min(1.0)) - dug)
+        .collect();
+    add_grain(&mut heights, depth * GRAIN_RATIO);
+    let (w, h) = (layout.width as usize, layout.height as usize);
+    box_blur(&mut heights, w, h);
+    box_blur(&mut heights, w, h);
+    Some(heights)
+}
+
+/// Half-width at which a ball of radius `r` sunk `depth` into the sand
+/// reaches at least `t` below the surface.
+fn groove_half_width(r: f64, depth: f64, t: f64) -> f64 {
+    let z = t + r - depth;
+    r.mul_add(r, -(z * z)).max(0.0).sqrt()
+}
+
+/// Segment key independent of direction, quantized so retraced copies
+/// of a segment compare equal.
+#[allow(clippy::cast_possible_truncation)]
+fn segment_key(a: Point, b: Point) -> [i64; 4] {
+    let q = |v: f64| (v * 1e7).round() as i64;
+    let (a, b) = ((q(a.x), q(a.y)), (q(b.x), q(b.y)));
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    [lo.0, lo.1, hi.0, hi.1]
+}
+
+/// How many times each segment is traversed across all polylines.
+fn pass_counts(polylines: &[Polyline]) -> HashMap<[i64; 4], u32> {
+    let mut counts = HashMap::new();
+    for polyline in polylines {
+        for w in polyline.points().windows(2) {
+            *counts.entry(segment_key(w[0], w[1])).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Path through every segment traversed at least `level` times, with
+/// consecutive qualifying segments chained into one subpath.
+fn level_path(
+    polylines: &[Polyline],
+    passes: &HashMap<[i64; 4], u32>,
+    level: u32,
+    layout: &Layout,
+) -> Option<Path> {
+    let mut pb = PathBuilder::new();
+    for polyline in polylines {
+        let mut open = false;
+        for w in polyline.points().windows(2) {
+            let count = passes.get(&segment_key(w[0], w[1])).copied().unwrap_or(0);
+            if count < level {
+                open = false;
+                continue;
+            }
+            if !open {
+                let (x, y) = layout.to_px(w[0]);
+                pb.move_to(x, y);
+                open = true;
+            }
+            let (x, y) = layout.to_px(w[1]);
+            pb.line_to(x, y);
+        }
+    }
+    pb.finish()
+}
+
+/// Replace `mask` with the anti-aliased coverage of `path` stroked with
+/// round caps to the given half-width.
+#[allow(clippy::cast_possible_truncation)]
+fn coverage(mask: &mut Mask, path: &Path, half_width: f64) -> Option<()> {
+    mask.clear();
+    if half_width <= 0.0 {
+        return Some(());
+    }
+    let stroke = Stroke {
+        width: (2.0 * half_width) as f32,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    };
+    let outline = path.stroke(&stroke, 1.0)?;
+    mask.fill_path(&outline, FillRule::Winding, true, Transform::identity());
+    Some(())
+}
+
+/// Add `weight` times the coverage of `mask` to `values`.
+#[allow(clippy::cast_possible_truncation)]
+fn accumulate(values: &mut [f32], mask: &Mask, weight: f64) {
+    let weight = (weight / 255.0) as f32;
+    for (v, &m) in values.iter_mut().zip(mask.data()) {
+        *v += f32::from(m) * weight;
+    }
+}
+
+/// Deterministic fine noise so flat sand is not perfectly smooth.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn add_grain(heights: &mut [f32], amplitude: f64) {
+    let mut state: u32 = 0x2545_F491;
+    let scale = (amplitude / f64::from(u16::MAX)) as f32;
+    for h in heights {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *h += (f32::from((state >> 16) as u16) - 32767.5) * scale;
+    }
+}
+
+/// In-place 3×3 box blur with edge clamping, to soften layer terracing.
+#[allow(clippy::cast_precision_loss)]
+fn box_blur(values: &mut [f32], width: usize, height: usize) {
+    let src = values.to_vec();
+    for y in 0..height {
+        let rows = [y.saturating_sub(1), y, (y + 1).min(height - 1)];
+        for x in 0..width {
+            let cols = [x.saturating_sub(1), x, (x + 1).min(width - 1)];
+            let src = &src;
+            let sum: f32 = rows
+                .iter()
+                .flat_map(|&r| cols.iter().map(move |&c| src[r * width + c]))
+                .sum();
+            values[y * width + x] = sum / 9.0;
+        }
+    }
+}
+
+/// Anti-aliased coverage of the table surface.
+fn table_mask(shape: &MaskShape, layout: &Layout) -> Option<Mask> {
+    let mut mask = Mask::new(layout.width, layout.height)?;
+    let path = match *shape {
+        MaskShape::Circle { center, radius } => {
+            let (cx, cy) = layout.to_px(center);
+            #[allow(clippy::cast_possible_truncation)]
+            let r = (radius * layout.px_per_unit) as f32;
+            PathBuilder::from_circle(cx, cy, r)
+        }
+        MaskShape::Rectangle { .. } => {
+            #[allow(clippy::cast_precision_loss)]
+            let rect = Rect::from_xywh(0.0, 0.0, layout.width as f32, layout.height as f32)?;
+            Some(PathBuilder::from_rect(rect))
+        }
+    }?;
+    mask.fill_path(&path, FillRule::Winding, true, Transform::identity());
+    Some(mask)
+}
+
+/// Lambert-shade the heightmap into `pixmap`, blending to the frame
+/// color outside the table.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn shade(pixmap: &mut Pixmap, heights: &[f32], table: &Mask, options: &SandOptions) {
+    let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+    let azimuth = options.light_azimuth_deg.to_radians();
+    let elevation = options.light_elevation_deg.clamp(1.0, 90.0).to_radians();
+    // Image y grows downward, so "up" in the image is -y.
+    let light = [
+        elevation.cos() * azimuth.cos(),
+        -elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+    ];
+    let h = |x: usize, y: usize| f64::from(heights[y * width + x]);
+
+    for (i, px) in pixmap.data_mut().chunks_exact_mut(4).enumerate() {
+        let (x, y) = (i % width, i / width);
+        let dx = (h((x + 1).min(width - 1), y) - h(x.saturating_sub(1), y)) / 2.0;
+        let dy = (h(x, (y + 1).min(height - 1)) - h(x, y.saturating_sub(1))) / 2.0;
+        let norm = dx.mul_add(dx, dy.mul_add(dy, 1.0)).sqrt();
+        let lambert = (-dx)
+            .mul_add(light[0], (-dy).mul_add(light[1], light[2]))
+            .max(0.0)
+            / norm;
+        // Flat sand is lit exactly to `sand_color`.
+        let brightness = (1.0 - AMBIENT).mul_add(lambert / light[2], AMBIENT);
+        let cover = f64::from(table.data()[i]) / 255.0;
+        for ((out, &sand), &frame) in px
+            .iter_mut()
+            .zip(&options.sand_color)
+            .zip(&options.frame_color)
+        {
+            let sand = (f64::from(sand) * brightness).min(255.0);
+            let frame = f64::from(frame);
+            *out = cover.mul_add(sand - frame, frame).round() as u8;
+        }
+        px[3] = 255;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn circle() -> MaskShape {
+        MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        }
+    }
+
+    fn options() -> SandOptions {
+        SandOptions {
+            width_px: 200,
+            ..SandOptions::default()
+        }
+    }
+
+    fn luma(pixmap: &Pixmap, x: u32, y: u32) -> f64 {
+        let p = pixmap.pixel(x, y).unwrap();
+        0.2126f64.mul_add(
+            f64::from(p.red()),
+            0.7152f64.mul_add(f64::from(p.green()), 0.0722 * f64::from(p.blue())),
+        )
+    }
+
+    #[test]
+    fn flat_sand_matches_sand_color_and_frame_outside() {
+        let pixmap = render_pixmap(&[], &circle(), &options()).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (200, 200));
+        let centre = pixmap.pixel(100, 100).unwrap();
+        for (got, want) in [centre.red(), centre.green(), centre.blue()]
+            .into_iter()
+            .zip(options().sand_color)
+        {
+            assert!(got.abs_diff(want) <= 3, "flat sand {got} vs {want}");
+        }
+        let corner = pixmap.pixel(0, 0).unwrap();
+        assert_eq!(
+            [corner.red(), corner.green(), corner.blue()],
+            options().frame_color
+        );
+    }
+
+    #[test]
+    fn groove_walls_face_toward_and_away_from_light() {
+        // Horizontal groove; light from the top of the image.
+        let line = Polyline::new(vec![Point::new(-0.6, 0.0), Point::new(0.6, 0.0)]);
+        let opts = SandOptions {
+            light_azimuth_deg: 90.0,
+            ..options()
+        };
+        let pixmap = render_pixmap(&[line], &circle(), &opts).unwrap();
+        // Ball radius is 2 px at this scale (12 mm on 600 mm over 200 px).
+        let upper_wall = luma(&pixmap, 100, 99);
+        let lower_wall = luma(&pixmap, 100, 101);
+        assert!(
+            lower_wall > upper_wall,
+            "the wall facing the light should be brighter ({lower_wall} vs {upper_wall})"
+        );
+    }
+
+    #[test]
+    fn retraced_segments_dig_deeper() {
+        let once = Polyline::new(vec![Point::new(-0.6, 0.3), Point::new(0.6, 0.3)]);
+        let thrice = Polyline::new(vec![
+            Point::new(-0.6, -0.3),
+            Point::new(0.6, -0.3),
+            Point::new(-0.6, -0.3),
+            Point::new(0.6, -0.3),
+        ]);
+        let layout = Layout {
+            width: 200,
+            height: 200,
+            left: -1.0,
+            top: 1.0,
+            px_per_unit: 100.0,
+        };
+        let heights = heightmap(&[once, thrice], &layout, 4.0).unwrap();
+        let at = |x: usize, y: usize| heights[y * 200 + x];
+        assert!(at(100, 70) < 0.0, "single pass should dig a groove");
+        assert!(
+            at(100, 130) < at(100, 70),
+            "retraced groove ({}) should be deeper than single pass ({})",
+            at(100, 130),
+            at(100, 70),
+        );
+        assert!(at(100, 76) > 0.0, "displaced sand should form a ridge");
+    }
+
+    #[test]
+    fn rectangle_sets_aspect_ratio() {
+        let shape = MaskShape::Rectangle {
+            center: Point::new(0.0, 0.0),
+            half_width: 1.5,
+            half_height: 1.0,
+        };
+        let pixmap = render_pixmap(&[], &shape, &options()).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (200, 133));
+    }
+}
@@ -5,7 +5,8 @@
 //! data formatting.
 //!
 //! Each polyline becomes a separate `<path>` element using `M` (move to)
-//! and `L` (line to) commands.
+//! and `L` (line to) commands, or `C` (cubic curve to) commands when
+//! exported with [`to_bezier_svg`].
 //!
 //! Optional [`SvgMetadata`] embeds `<title>` and `<desc>` elements for
 //! accessibility and to help file managers identify exported files.
@@ -16,11 +17,13 @@ use std::fmt::Write;
 
 use svg::Document;
 use svg::node::element::path::Data;
-use svg::node::element::{Description, Element, Path, Title};
+use svg::node::element::{Description, Element, Group, Path, Title};
 use svg::node::{Node, Text, Value};
 
-use mujou_pipeline::segment_analysis::{SEGMENT_COLORS, find_top_segments};
-use mujou_pipeline::{MaskShape, MstEdgeInfo, Polyline};
+use mujou_pipeline::segment_analysis::{
+    SEGMENT_COLORS, SegmentRun, find_top_segments, segment_runs,
+};
+use mujou_pipeline::{MaskShape, MstEdgeInfo, Point, Polyline, SegmentKind, fit_cubic_beziers};
 
 // TODO: review these constants for different table models / sizes.
 /// SVG document width and height in millimetres (square canvas).
@@ -232,21 +235,73 @@ fn build_path_data_transformed(polyline: &Polyline, mapping: &DocumentMapping) -
         return String::new();
     }
 
-    let tx = |p: &mujou_pipeline::Point| {
-        (
-            p.x.mul_add(mapping.scale_factor, mapping.offset_x),
-            (-p.y).mul_add(mapping.scale_factor, mapping.offset_y),
-        )
-    };
-
-    let first = tx(&points[0]);
+    let first = to_mm(points[0], mapping);
     let mut data = Data::new().move_to(first);
-    for p in &points[1..] {
-        data = data.line_to(tx(p));
+    for &p in &points[1..] {
+        data = data.line_to(to_mm(p, mapping));
     }
     String::from(Value::from(data))
 }
 
+/// Like [`build_path_data_transformed`] for one run of a joined path
+/// (see [`append_run`]).
+fn build_run_data_transformed(
+    run: &SegmentRun,
+    curve_tolerance: Option<f64>,
+    mapping: &DocumentMapping,
+) -> String {
+    let points = run.polyline.points();
+    if points.len() < 2 {
+        return String::new();
+    }
+
+    let data = Data::new().move_to(to_mm(points[0], mapping));
+    String::from(Value::from(append_run(data, run, curve_tolerance, mapping)))
+}
+
+/// Continue `data`, which ends at the run's first point, through the
+/// rest of `run`.
+///
+/// With a `curve_tolerance` (normalized units) the run is fitted with
+/// cubic Bezier segments and emitted as `C` commands; otherwise, and
+/// for connectors, which are straight by construction, as `L`
+/// commands.  Each run is fitted on its own so curves never span a
+/// join.  Bezier curves are invariant under the affine normalized→mm
+/// transform, so fitting happens before it.
+fn append_run(
+    mut data: Data,
+    run: &SegmentRun,
+    curve_tolerance: Option<f64>,
+    mapping: &DocumentMapping,
+) -> Data {
+    let points = run.polyline.points();
+    match curve_tolerance {
+        Some(tolerance) if run.kind != SegmentKind::Connector => {
+            for curve in fit_cubic_beziers(points, tolerance) {
+                let (x1, y1) = to_mm(curve.p1, mapping);
+                let (x2, y2) = to_mm(curve.p2, mapping);
+                let (x, y) = to_mm(curve.p3, mapping);
+                data = data.cubic_curve_to((x1, y1, x2, y2, x, y));
+            }
+        }
+        _ => {
+            for &p in points.iter().skip(1) {
+                data = data.line_to(to_mm(p, mapping));
+            }
+        }
+    }
+    data
+}
+
+/// Map a normalized point into the mm-based `viewBox` (see
+/// [`DocumentMapping`]).
+fn to_mm(p: Point, mapping: &DocumentMapping) -> (f64, f64) {
+    (
+        p.x.mul_add(mapping.scale_factor, mapping.offset_x),
+        (-p.y).mul_add(mapping.scale_factor, mapping.offset_y),
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers for diagnostic SVG functions (manual string formatting)
 // ---------------------------------------------------------------------------
@@ -407,6 +462,176 @@ pub fn to_svg(
     metadata: &SvgMetadata<'_>,
     mapping: &DocumentMapping,
 ) -> String {
+    let mut doc = document_header(metadata, mapping);
+
+    // One <path> per polyline (skip polylines with fewer than 2 points).
+    // Coordinates are mapped from normalized space into the mm-based viewBox.
+    for polyline in polylines {
+        let d = build_path_data_transformed(polyline, mapping);
+        if d.is_empty() {
+            continue;
+        }
+
+        let path = Path::new()
+            .set("d", d)
+            .set("fill", "none")
+            .set("stroke", "black")
+            .set("stroke-width", 1);
+        doc = doc.add(path);
+    }
+
+    // The svg crate omits the XML declaration, so we prepend it.
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{doc}\n")
+}
+
+/// Serialize a joined path into an SVG document using cubic Bezier
+/// curves.
+///
+/// Like [`to_svg`] for a single path, but the runs of `path` between
+/// changes of [`SegmentKind`] (see [`to_layered_svg`]) are each fitted
+/// with cubic Bezier segments (see [`fit_cubic_beziers`]) so that every
+/// point lies within `tolerance` normalized units of the curve, and
+/// emitted as `C` commands instead of flattened `L` segments.
+/// Connectors stay straight `L` segments.  Intended for output of
+/// [`CurveKind::Bezier`] smoothing, where the fit recovers the smoothed
+/// curves; pass the same
+/// [`CurveSettings::tolerance`](mujou_pipeline::CurveSettings::tolerance).
+///
+/// [`CurveKind::Bezier`]: mujou_pipeline::CurveKind::Bezier
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline, SegmentKind};
+/// use mujou_export::{SvgMetadata, document_mapping, to_bezier_svg};
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// let path = Polyline::new(vec![
+///     Point::new(0.0, 0.0),
+///     Point::new(0.3, 0.1),
+///     Point::new(0.5, 0.3),
+///     Point::new(0.5, 0.6),
+/// ]);
+/// let segments = [SegmentKind::Contour, SegmentKind::Contour, SegmentKind::Connector];
+/// let mapping = document_mapping(&shape, 0.0);
+/// let svg = to_bezier_svg(&path, &segments, 0.002, &SvgMetadata::default(), &mapping);
+/// assert!(svg.contains('C'));
+/// assert!(svg.contains('L'));
+/// ```
+#[must_use]
+pub fn to_bezier_svg(
+    path: &Polyline,
+    segments: &[SegmentKind],
+    tolerance: f64,
+    metadata: &SvgMetadata<'_>,
+    mapping: &DocumentMapping,
+) -> String {
+    let mut doc = document_header(metadata, mapping);
+
+    if let Some(&first) = path.first()
+        && path.len() >= 2
+    {
+        let mut data = Data::new().move_to(to_mm(first, mapping));
+        for run in &segment_runs(path, segments) {
+            data = append_run(data, run, Some(tolerance), mapping);
+        }
+        let path = Path::new()
+            .set("d", data)
+            .set("fill", "none")
+            .set("stroke", "black")
+            .set("stroke-width", 1);
+        doc = doc.add(path);
+    }
+
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{doc}\n")
+}
+
+/// Serialize a joined path into an SVG document with one layer per
+/// [`SegmentKind`].
+///
+/// `segments[i]` labels the segment from point `i` to point `i + 1` of
+/// `path` (see [`StagedResult::joined_segments`]).  Consecutive
+/// segments of the same kind become one `<path>` inside a
+/// `<g id="contour|connector|retrace|border">` group, so the layers can
+/// be toggled or restyled in an editor; empty layers are omitted.
+/// With a `curve_tolerance` the runs are emitted as cubic curves, as
+/// in [`to_bezier_svg`].  Document layout and metadata match
+/// [`to_svg`].
+///
+/// [`StagedResult::joined_segments`]: mujou_pipeline::StagedResult::joined_segments
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline, SegmentKind};
+/// use mujou_export::{SvgMetadata, document_mapping, to_layered_svg};
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// let path = Polyline::new(vec![
+///     Point::new(0.0, 0.0),
+///     Point::new(0.5, 0.0),
+///     Point::new(0.5, 0.5),
+/// ]);
+/// let segments = [SegmentKind::Contour, SegmentKind::Connector];
+/// let mapping = document_mapping(&shape, 0.0);
+/// let svg = to_layered_svg(&path, &segments, None, &SvgMetadata::default(), &mapping);
+/// assert!(svg.contains(r#"id="contour""#));
+/// assert!(svg.contains(r#"id="connector""#));
+/// assert!(!svg.contains(r#"id="retrace""#));
+/// ```
+#[must_use]
+pub fn to_layered_svg(
+    path: &Polyline,
+    segments: &[SegmentKind],
+    curve_tolerance: Option<f64>,
+    metadata: &SvgMetadata<'_>,
+    mapping: &DocumentMapping,
+) -> String {
+    let mut doc = document_header(metadata, mapping);
+    let runs = segment_runs(path, segments);
+
+    for kind in [
+        SegmentKind::Contour,
+        SegmentKind::Border,
+        SegmentKind::Retrace,
+        SegmentKind::Connector,
+    ] {
+        let (id, stroke) = layer_style(kind);
+        let mut group = Group::new()
+            .set("id", id)
+            .set("fill", "none")
+            .set("stroke", stroke)
+            .set("stroke-width", 1);
+        let mut empty = true;
+        for run in runs.iter().filter(|run| run.kind == kind) {
+            let d = build_run_data_transformed(run, curve_tolerance, mapping);
+            if !d.is_empty() {
+                group = group.add(Path::new().set("d", d));
+                empty = false;
+            }
+        }
+        if !empty {
+            doc = doc.add(group);
+        }
+    }
+
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{doc}\n")
+}
+
+/// Group `id` and stroke color of the [`to_layered_svg`] layer for `kind`.
+const fn layer_style(kind: SegmentKind) -> (&'static str, &'static str) {
+    match kind {
+        SegmentKind::Contour => ("contour", "black"),
+        SegmentKind::Connector => ("connector", "#d63031"),
+        SegmentKind::Retrace => ("retrace", "#2266cc"),
+        SegmentKind::Border => ("border", "#888888"),
+    }
+}
+
+/// Create the `<svg>` document shared by [`to_svg`], [`to_bezier_svg`]
+/// and [`to_layered_svg`]: mm dimensions and `viewBox` from `mapping`, plus
+/// the optional `<title>`, `<desc>`, and `<metadata>` elements.
+fn document_header(metadata: &SvgMetadata<'_>, mapping: &DocumentMapping) -> Document {
     let w = mapping.width_mm;
     let h = mapping.height_mm;
 
@@ -436,24 +661,7 @@ pub fn to_svg(
         doc = doc.add(metadata_el);
     }
 
-    // One <path> per polyline (skip polylines with fewer than 2 points).
-    // Coordinates are mapped from normalized space into the mm-based viewBox.
-    for polyline in polylines {
-        let d = build_path_data_transformed(polyline, mapping);
-        if d.is_empty() {
-            continue;
-        }
-
-        let path = Path::new()
-            .set("d", d)
-            .set("fill", "none")
-            .set("stroke", "black")
-            .set("stroke-width", 1);
-        doc = doc.add(path);
-    }
-
-    // The svg crate omits the XML declaration, so we prepend it.
-    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{doc}\n")
+    doc
 }
 
 // ---------------------------------------------------------------------------
@@ -912,6 +1120,46 @@ mod tests {
         assert!(svg.contains(r#"stroke-width="1""#));
     }
 
+    #[test]
+    fn layered_svg_groups_runs_by_kind() {
+        let path = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.5, 0.0),
+            Point::new(0.5, 0.5),
+            Point::new(0.0, 0.5),
+            Point::new(0.0, 0.0),
+        ]);
+        let segments = [
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+            SegmentKind::Contour,
+            SegmentKind::Border,
+        ];
+        let svg = to_layered_svg(&path, &segments, None, &no_meta(), &test_mapping());
+
+        // Contours are drawn first, connectors last (on top).
+        let contour = svg.find(r#"id="contour""#).unwrap();
+        let border = svg.find(r#"id="border""#).unwrap();
+        let connector = svg.find(r#"id="connector""#).unwrap();
+        assert!(contour < border && border < connector);
+        assert!(!svg.contains(r#"id="retrace""#));
+
+        // test_mapping: sf=100, ox=100, oy=100, Y flipped.
+        assert!(svg.contains("M100,100 L150,100"), "first contour run");
+        assert!(svg.contains("M150,100 L150,50"), "connector run");
+        assert!(svg.contains("M150,50 L100,50"), "second contour run");
+        assert!(svg.contains("M100,50 L100,100"), "border run");
+        assert_eq!(svg.matches("<path").count(), 4);
+    }
+
+    #[test]
+    fn layered_svg_without_labels_is_all_contour() {
+        let path = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(0.5, 0.0)]);
+        let svg = to_layered_svg(&path, &[], None, &no_meta(), &test_mapping());
+        assert!(svg.contains(r#"id="contour""#));
+        assert_eq!(svg.matches("<g").count(), 1);
+    }
+
     #[test]
     fn single_polyline_with_three_points() {
         let polylines = vec![Polyline::new(vec![
@@ -927,6 +1175,73 @@ mod tests {
         assert_eq!(svg.matches("<path").count(), 1);
     }
 
+    /// Points on a circle of radius 0.5 from `start` radians in steps
+    /// of 0.1.
+    fn arc(start: f64) -> impl Iterator<Item = Point> {
+        (0..20).map(move |i| {
+            let angle = f64::from(i).mul_add(0.1, start);
+            Point::new(0.5 * angle.cos(), 0.5 * angle.sin())
+        })
+    }
+
+    /// The `d` attribute of the first `<path>` in `svg`.
+    fn first_path_data(svg: &str) -> &str {
+        svg.split(r#"d=""#)
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap()
+    }
+
+    #[test]
+    fn bezier_svg_emits_cubic_commands() {
+        let path = Polyline::new(arc(0.0).collect());
+        let svg = to_bezier_svg(&path, &[], 0.001, &no_meta(), &test_mapping());
+        let d = first_path_data(&svg);
+        assert!(d.starts_with('M'), "d = {d}");
+        assert!(d.contains('C'), "d = {d}");
+        assert!(!d.contains('L'), "d = {d}");
+        // A smooth arc needs far fewer curves than input segments.
+        assert!(d.matches('C').count() < 19, "d = {d}");
+    }
+
+    #[test]
+    fn bezier_svg_fits_each_run_and_keeps_connectors_straight() {
+        // Two arcs joined by a straight connector.
+        let path = Polyline::new(arc(0.0).chain(arc(3.0)).collect());
+        let mut segments = vec![SegmentKind::Contour; 39];
+        segments[19] = SegmentKind::Connector;
+        let svg = to_bezier_svg(&path, &segments, 0.001, &no_meta(), &test_mapping());
+        assert_eq!(svg.matches("<path").count(), 1);
+        let d = first_path_data(&svg);
+        assert_eq!(d.matches('M').count(), 1, "d = {d}");
+        assert_eq!(d.matches('L').count(), 1, "d = {d}");
+        // Curves on both sides of the connector: no fit spans it.
+        let connector = d.find('L').unwrap();
+        assert!(d[..connector].contains('C'), "d = {d}");
+        assert!(d[connector..].contains('C'), "d = {d}");
+    }
+
+    #[test]
+    fn bezier_svg_skips_degenerate_paths() {
+        let path = Polyline::new(vec![Point::new(0.1, 0.1)]);
+        let svg = to_bezier_svg(&path, &[], 0.001, &no_meta(), &test_mapping());
+        assert_eq!(svg.matches("<path").count(), 0);
+    }
+
+    #[test]
+    fn layered_svg_with_curve_tolerance_fits_runs() {
+        let path = Polyline::new(arc(0.0).chain(arc(3.0)).collect());
+        let mut segments = vec![SegmentKind::Contour; 39];
+        segments[19] = SegmentKind::Connector;
+        let svg = to_layered_svg(&path, &segments, Some(0.001), &no_meta(), &test_mapping());
+        let contour = svg.find(r#"id="contour""#).unwrap();
+        let connector = svg.find(r#"id="connector""#).unwrap();
+        assert!(svg[contour..connector].contains('C'));
+        assert!(!svg[contour..connector].contains('L'));
+        assert!(svg[connector..].contains('L'));
+        assert!(!svg[connector..].contains('C'));
+    }
+
     #[test]
     fn multiple_polylines_produce_multiple_paths() {
         let polylines = vec![
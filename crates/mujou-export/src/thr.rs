@@ -26,6 +26,10 @@
 //! rho is naturally in [0, 1].  For rectangular masks or no mask, rho is
 //! clamped to [0, 1].
 //!
+//! Chords that pass close to the origin make theta swing sharply.  The
+//! pipeline can reroute them around the center beforehand (see
+//! `mujou_pipeline::center_avoid`).
+//!
 //! This is a pure function with no I/O — it returns a `String`.
 
 use std::f64::consts::PI;
@@ -27,6 +27,13 @@ pub struct ExportPanelProps {
     /// Passed through to [`mujou_export::document_mapping`] so the SVG
     /// drawing area is inset from the document edges.
     border_margin: f64,
+    /// Bezier fitting tolerance (normalized units) when
+    /// [`CurveKind::Bezier`](mujou_pipeline::CurveKind::Bezier) smoothing
+    /// is selected.
+    ///
+    /// When `Some`, the SVG is exported with cubic `C` commands via
+    /// [`mujou_export::to_bezier_svg`] instead of flattened `L` segments.
+    bezier_tolerance: Option<f64>,
     /// Controls visibility of the export popup.
     show: Signal<bool>,
 }
@@ -43,6 +50,7 @@ impl PartialEq for ExportPanelProps {
             && self.config_description == other.config_description
             && self.config_json == other.config_json
             && self.border_margin == other.border_margin
+            && self.bezier_tolerance == other.bezier_tolerance
             && self.show == other.show
     }
 }
@@ -96,6 +104,7 @@ pub fn ExportPanel(props: ExportPanelProps) -> Element {
         let config_description = props.config_description;
         let config_json = props.config_json;
         let border_margin = props.border_margin;
+        let bezier_tolerance = props.bezier_tolerance;
         move |_| {
             if let Some(ref res) = result {
                 let timestamp = now_timestamp();
@@ -113,8 +122,24 @@ pub fn ExportPanel(props: ExportPanelProps) -> Element {
                     // ~3x, causing compatibility issues with grounded.so.
                     let polyline = &res.joined;
                     let mapping = mujou_export::document_mapping(&res.canvas.shape, border_margin);
-                    let svg =
-                        mujou_export::to_svg(std::slice::from_ref(polyline), &metadata, &mapping);
+                    let svg = bezier_tolerance.map_or_else(
+                        || {
+                            mujou_export::to_svg(
+                                std::slice::from_ref(polyline),
+                                &metadata,
+                                &mapping,
+                            )
+                        },
+                        |tolerance| {
+                            mujou_export::to_bezier_svg(
+                                polyline,
+                                &res.joined_segments,
+                                tolerance,
+                                &metadata,
+                                &mapping,
+                            )
+                        },
+                    );
                     let download_name = format!("{filename}_{timestamp}.svg");
                     if let Err(e) =
                         download::trigger_download(&svg, &download_name, "image/svg+xml")
@@ -134,6 +134,7 @@ fn render_thumbnail(result: &WorkerResult, stage: StageId, is_dark: bool) -> Ele
         StageId::Downsampled => {
             render_img_thumb(result.downsampled_url.url(), "Downsampled thumbnail")
         }
+        StageId::Tone => render_img_thumb(result.tone_image_url(), "Tone thumbnail"),
         StageId::Blur => render_img_thumb(result.blur_url.url(), "Blur thumbnail"),
 
         StageId::Edges => {
@@ -7,8 +7,10 @@
 
 use dioxus::prelude::*;
 use mujou_pipeline::{
-    BorderPathMode, CanvasShape, ContourTracerKind, DownsampleFilter, ParityStrategy,
-    PathJoinerKind, PipelineConfig, StartPointStrategy, max_gradient_magnitude,
+    AlphaMode, BorderPathMode, CannyThresholds, CanvasShape, ContourTracerKind, CurveKind,
+    CurveSettings, DownsampleFilter, EdgeChannel, EdgeChannels, EdgeFusion, ParityStrategy,
+    PathJoinerKind, PipelineConfig, SimplifierKind, SmoothingKind, StartPointStrategy,
+    SubsampleMode, ToneKind, ToneSettings, max_gradient_magnitude,
 };
 
 use crate::stage::StageId;
@@ -24,13 +26,16 @@ pub struct StageControlsProps {
     on_config_change: EventHandler<PipelineConfig>,
     /// Whether to display description text below each parameter control.
     show_descriptions: bool,
+    /// Number of frames in the input image (see
+    /// [`frame_count`](mujou_pipeline::grayscale::frame_count)).
+    frame_count: u32,
 }
 
 /// Renders parameter controls for the currently selected pipeline stage.
 ///
 /// Each stage shows only its relevant controls:
-/// - **Original**: no controls
-/// - **Blur**: blur sigma slider
+/// - **Original**: input frame slider (multi-frame inputs only)
+/// - **Blur**: smoothing strategy select, sigma and range sliders
 /// - **Edges**: Canny low/high sliders, invert toggle
 /// - **Contours**: contour tracer select
 /// - **Simplified**: simplify tolerance slider
@@ -49,9 +54,31 @@ pub fn StageControls(props: StageControlsProps) -> Element {
 
     match props.stage {
         StageId::Original => {
+            if props.frame_count <= 1 {
+                return rsx! {};
+            }
+            let last_frame = props.frame_count - 1;
+            let value = config.input_frame.min(last_frame);
+            let config = config.clone();
             rsx! {
-                p { class: "text-sm text-[var(--text-secondary)] italic",
-                    "No adjustable parameters for this stage."
+                div { class: "space-y-2",
+                    {render_slider(
+                        "input_frame",
+                        "Input Frame",
+                        desc("Frame of the animated GIF to process."),
+                        f64::from(value),
+                        0.0,
+                        f64::from(last_frame),
+                        1.0,
+                        0,
+                        1.0, "",
+                        move |v: f64| {
+                            let mut c = config.clone();
+                            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                            { c.input_frame = v as u32; }
+                            on_change.call(c);
+                        },
+                    )}
                 }
             }
         }
@@ -60,6 +87,8 @@ pub fn StageControls(props: StageControlsProps) -> Element {
             let value = config.working_resolution;
             let config_slider = config.clone();
             let config_filter = config.clone();
+            let config_alpha = config.clone();
+            let config_background = config.clone();
             rsx! {
                 div { class: "space-y-2",
                     {render_slider(
@@ -112,22 +141,185 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             on_change.call(c);
                         },
                     )}
+                    {render_select(
+                        "alpha_mode",
+                        "Transparency",
+                        desc("Ignore alpha, flatten over a background color, or drop contours outside opaque areas."),
+                        &[
+                            ("Ignore", "Ignore"),
+                            ("Composite", "Composite over Background"),
+                            ("Mask", "Mask Contours"),
+                        ],
+                        match config_alpha.alpha_mode {
+                            AlphaMode::Ignore => "Ignore",
+                            AlphaMode::Composite => "Composite",
+                            AlphaMode::Mask => "Mask",
+                        },
+                        move |v: String| {
+                            let mut c = config_alpha.clone();
+                            c.alpha_mode = match v.as_str() {
+                                "Composite" => AlphaMode::Composite,
+                                "Mask" => AlphaMode::Mask,
+                                _ => AlphaMode::Ignore,
+                            };
+                            on_change.call(c);
+                        },
+                    )}
+                    if config.alpha_mode == AlphaMode::Composite {
+                        {render_color(
+                            "alpha_background",
+                            "Background",
+                            desc("Color transparent pixels are flattened onto."),
+                            config.alpha_background,
+                            move |rgb: [u8; 3]| {
+                                let mut c = config_background.clone();
+                                c.alpha_background = rgb;
+                                on_change.call(c);
+                            },
+                        )}
+                    }
+                }
+            }
+        }
+
+        StageId::Tone => {
+            let tone = config.tone.clone();
+            let config_kind = config.clone();
+            let config_tile = config.clone();
+            let config_clip = config.clone();
+            let config_gamma = config.clone();
+            rsx! {
+                div { class: "space-y-2",
+                    {render_select(
+                        "tone_kind",
+                        "Contrast",
+                        desc("Stretch contrast before blur so dark or flat photos yield edges. CLAHE adapts to local regions; gamma lifts or deepens shadows."),
+                        &[
+                            ("Off", "Off"),
+                            ("Equalize", "Histogram Equalization"),
+                            ("Clahe", "CLAHE (Adaptive)"),
+                            ("Gamma", "Gamma"),
+                        ],
+                        match tone.kind {
+                            ToneKind::Off => "Off",
+                            ToneKind::Equalize => "Equalize",
+                            ToneKind::Clahe => "Clahe",
+                            ToneKind::Gamma => "Gamma",
+                        },
+                        move |v: String| {
+                            let mut c = config_kind.clone();
+                            c.tone.kind = match v.as_str() {
+                                "Equalize" => ToneKind::Equalize,
+                                "Clahe" => ToneKind::Clahe,
+                                "Gamma" => ToneKind::Gamma,
+                                _ => ToneKind::Off,
+                            };
+                            on_change.call(c);
+                        },
+                    )}
+                    if tone.kind == ToneKind::Clahe {
+                        {render_slider(
+                            "tone_tile_size",
+                            "Tile Size",
+                            desc("Size of the regions equalized independently. Smaller tiles bring out finer local detail."),
+                            f64::from(tone.tile_size),
+                            f64::from(ToneSettings::MIN_TILE_SIZE),
+                            512.0,
+                            8.0,
+                            0,
+                            1.0, " px",
+                            move |v: f64| {
+                                let mut c = config_tile.clone();
+                                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                                { c.tone.tile_size = v as u32; }
+                                on_change.call(c);
+                            },
+                        )}
+                        {render_slider(
+                            "tone_clip_limit",
+                            "Clip Limit",
+                            desc("Maximum contrast gain. Higher values reveal more detail but amplify noise."),
+                            f64::from(tone.clip_limit),
+                            1.0,
+                            10.0,
+                            0.1,
+                            1,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_clip.clone();
+                                #[allow(clippy::cast_possible_truncation)]
+                                { c.tone.clip_limit = v as f32; }
+                                on_change.call(c);
+                            },
+                        )}
+                    }
+                    if tone.kind == ToneKind::Gamma {
+                        {render_slider(
+                            "tone_gamma",
+                            "Gamma",
+                            desc("Below 1 brightens shadows, above 1 darkens them."),
+                            f64::from(tone.gamma),
+                            0.1,
+                            3.0,
+                            0.05,
+                            2,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_gamma.clone();
+                                #[allow(clippy::cast_possible_truncation)]
+                                { c.tone.gamma = v as f32; }
+                                on_change.call(c);
+                            },
+                        )}
+                    }
                 }
             }
         }
 
         StageId::Blur => {
             let value = config.blur_sigma;
+            let smoothing = config.smoothing;
+            let range = config.smoothing_range;
+            let config_smoothing = config.clone();
+            let config_range = config.clone();
             let config = config.clone();
             rsx! {
                 div { class: "space-y-2",
+                    {render_select(
+                        "smoothing",
+                        "Smoothing",
+                        desc("Gaussian blur softens edges along with noise. Bilateral, median and Perona-Malik diffusion smooth noise while keeping edges sharp, at extra cost."),
+                        &[
+                            ("Gaussian", "Gaussian"),
+                            ("Bilateral", "Bilateral"),
+                            ("Median", "Median"),
+                            ("PeronaMalik", "Perona-Malik Diffusion"),
+                        ],
+                        match smoothing {
+                            SmoothingKind::Gaussian => "Gaussian",
+                            SmoothingKind::Bilateral => "Bilateral",
+                            SmoothingKind::Median => "Median",
+                            SmoothingKind::PeronaMalik => "PeronaMalik",
+                        },
+                        move |v: String| {
+                            let mut c = config_smoothing.clone();
+                            c.smoothing = match v.as_str() {
+                                "Bilateral" => SmoothingKind::Bilateral,
+                                "Median" => SmoothingKind::Median,
+                                "PeronaMalik" => SmoothingKind::PeronaMalik,
+                                _ => SmoothingKind::Gaussian,
+                            };
+                            c.blur_sigma = c.blur_sigma.min(c.smoothing.max_sigma());
+                            on_change.call(c);
+                        },
+                    )}
                     {render_slider(
                         "blur_sigma",
                         "Blur Sigma",
-                        desc("Gaussian blur strength. Smooths noise but softens edges."),
+                        desc("Smoothing strength. Smooths noise but, with Gaussian blur, softens edges."),
                         f64::from(value),
                         0.0,
-                        10.0,
+                        f64::from(smoothing.max_sigma()),
                         0.1,
                         1,
                         1.0, "",
@@ -138,6 +330,25 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             on_change.call(c);
                         },
                     )}
+                    if smoothing.uses_range() {
+                        {render_slider(
+                            "smoothing_range",
+                            "Edge Range",
+                            desc("Brightness difference treated as an edge. Lower values preserve fainter edges."),
+                            f64::from(range),
+                            1.0,
+                            100.0,
+                            1.0,
+                            0,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_range.clone();
+                                #[allow(clippy::cast_possible_truncation)]
+                                { c.smoothing_range = v as f32; }
+                                on_change.call(c);
+                            },
+                        )}
+                    }
                 }
             }
         }
@@ -152,11 +363,7 @@ pub fn StageControls(props: StageControlsProps) -> Element {
             let config_high = config.clone();
             let config_max = config.clone();
             let config_invert = config.clone();
-            let config_lum = config.clone();
-            let config_red = config.clone();
-            let config_green = config.clone();
-            let config_blue = config.clone();
-            let config_sat = config.clone();
+            let config_channels = config.clone();
             let theoretical_max = f64::from(max_gradient_magnitude());
             rsx! {
                 div { class: "space-y-2",
@@ -214,8 +421,13 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             let v = v as f32;
                             // Slider range starts at 0 so the full
                             // scale is visible, but clamp so canny_max
-                            // never drops below canny_high.
-                            c.canny_max = v.max(c.canny_high);
+                            // never drops below canny_high or any
+                            // per-channel high threshold.
+                            c.canny_max = c
+                                .edge_channels
+                                .thresholds
+                                .values()
+                                .fold(v.max(c.canny_high), |max, t| max.max(t.high));
                             on_change.call(c);
                         },
                     )}
@@ -237,7 +449,7 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             "Edge Channels"
                         }
                         {
-                            let ch_desc = desc("Select which image channels contribute to edge detection. Edges from all enabled channels are combined.");
+                            let ch_desc = desc("Select which image channels contribute to edge detection. Edges from all enabled channels are combined; noisy channels can use their own thresholds.");
                             let ch_desc_id = if ch_desc.is_empty() { "" } else { "edge-ch-desc" };
                             rsx! {
                                 if !ch_desc.is_empty() {
@@ -247,66 +459,145 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                                         "{ch_desc}"
                                     }
                                 }
-                                {render_channel_toggle(
-                                    "ch_luminance",
-                                    "Luminance",
-                                    channels.luminance,
-                                    channels.count() <= 1 && channels.luminance,
-                                    move |v: bool| {
-                                        let mut c = config_lum.clone();
-                                        c.edge_channels.luminance = v;
-                                        if c.edge_channels.any_enabled() { on_change.call(c); }
-                                    },
-                                    ch_desc_id,
-                                )}
-                                {render_channel_toggle(
-                                    "ch_red",
-                                    "Red",
-                                    channels.red,
-                                    channels.count() <= 1 && channels.red,
-                                    move |v: bool| {
-                                        let mut c = config_red.clone();
-                                        c.edge_channels.red = v;
-                                        if c.edge_channels.any_enabled() { on_change.call(c); }
-                                    },
-                                    ch_desc_id,
-                                )}
-                                {render_channel_toggle(
-                                    "ch_green",
-                                    "Green",
-                                    channels.green,
-                                    channels.count() <= 1 && channels.green,
-                                    move |v: bool| {
-                                        let mut c = config_green.clone();
-                                        c.edge_channels.green = v;
-                                        if c.edge_channels.any_enabled() { on_change.call(c); }
-                                    },
-                                    ch_desc_id,
-                                )}
-                                {render_channel_toggle(
-                                    "ch_blue",
-                                    "Blue",
-                                    channels.blue,
-                                    channels.count() <= 1 && channels.blue,
-                                    move |v: bool| {
-                                        let mut c = config_blue.clone();
-                                        c.edge_channels.blue = v;
-                                        if c.edge_channels.any_enabled() { on_change.call(c); }
-                                    },
-                                    ch_desc_id,
-                                )}
-                                {render_channel_toggle(
-                                    "ch_saturation",
-                                    "Saturation",
-                                    channels.saturation,
-                                    channels.count() <= 1 && channels.saturation,
-                                    move |v: bool| {
-                                        let mut c = config_sat.clone();
-                                        c.edge_channels.saturation = v;
-                                        if c.edge_channels.any_enabled() { on_change.call(c); }
-                                    },
-                                    ch_desc_id,
-                                )}
+                                {
+                                    let config_fusion = config_channels.clone();
+                                    render_select(
+                                        "edge_fusion",
+                                        "Channel Fusion",
+                                        desc("Max keeps edges found in any channel. Weighted Sum averages the channels' gradients and thresholds once, suppressing noise that appears in only one channel."),
+                                        &[("Max", "Max"), ("WeightedSum", "Weighted Sum")],
+                                        match channels.fusion {
+                                            EdgeFusion::Max => "Max",
+                                            EdgeFusion::WeightedSum => "WeightedSum",
+                                        },
+                                        move |v: String| {
+                                            let mut c = config_fusion.clone();
+                                            c.edge_channels.fusion = match v.as_str() {
+                                                "WeightedSum" => EdgeFusion::WeightedSum,
+                                                _ => EdgeFusion::Max,
+                                            };
+                                            on_change.call(c);
+                                        },
+                                    )
+                                }
+                                for channel in EdgeChannel::ALL {
+                                    {
+                                        let enabled = channels.is_enabled(channel);
+                                        // Per-channel thresholds only apply to max fusion.
+                                        let show_overrides = enabled && channels.fusion == EdgeFusion::Max;
+                                        let overrides = channels.thresholds.get(&channel).copied();
+                                        let config_toggle = config_channels.clone();
+                                        let config_weight = config_channels.clone();
+                                        let config_override = config_channels.clone();
+                                        let config_override_low = config_channels.clone();
+                                        let config_override_high = config_channels.clone();
+                                        rsx! {
+                                            {render_channel_toggle(
+                                                &format!("ch_{}", channel_key(channel)),
+                                                channel_label(channel),
+                                                enabled,
+                                                channels.count() <= 1 && enabled,
+                                                move |v: bool| {
+                                                    let mut c = config_toggle.clone();
+                                                    c.edge_channels.set_enabled(channel, v);
+                                                    if c.edge_channels.any_enabled() { on_change.call(c); }
+                                                },
+                                                ch_desc_id,
+                                            )}
+                                            if enabled {
+                                                {render_slider(
+                                                    &format!("weight_{}", channel_key(channel)),
+                                                    &format!("{} Weight", channel_label(channel)),
+                                                    "",
+                                                    f64::from(channels.weight(channel)),
+                                                    0.25,
+                                                    f64::from(EdgeChannels::MAX_WEIGHT),
+                                                    0.05,
+                                                    2,
+                                                    1.0, "×",
+                                                    move |v: f64| {
+                                                        let mut c = config_weight.clone();
+                                                        #[allow(clippy::cast_possible_truncation)]
+                                                        let v = v as f32;
+                                                        // Keep the map sparse: 1.0 is the default.
+                                                        if (v - 1.0).abs() < f32::EPSILON {
+                                                            c.edge_channels.weights.remove(&channel);
+                                                        } else {
+                                                            c.edge_channels.weights.insert(channel, v);
+                                                        }
+                                                        on_change.call(c);
+                                                    },
+                                                )}
+                                            }
+                                            if show_overrides {
+                                                {render_toggle(
+                                                    &format!("thresholds_{}", channel_key(channel)),
+                                                    &format!("{} Own Thresholds", channel_label(channel)),
+                                                    "",
+                                                    overrides.is_some(),
+                                                    move |v: bool| {
+                                                        let mut c = config_override.clone();
+                                                        if v {
+                                                            c.edge_channels.thresholds.insert(
+                                                                channel,
+                                                                CannyThresholds {
+                                                                    low: c.canny_low,
+                                                                    high: c.canny_high,
+                                                                },
+                                                            );
+                                                        } else {
+                                                            c.edge_channels.thresholds.remove(&channel);
+                                                        }
+                                                        on_change.call(c);
+                                                    },
+                                                )}
+                                            }
+                                            if let Some(t) = overrides.filter(|_| show_overrides) {
+                                                {render_slider(
+                                                    &format!("low_{}", channel_key(channel)),
+                                                    &format!("{} Low", channel_label(channel)),
+                                                    "",
+                                                    f64::from(t.low),
+                                                    1.0,
+                                                    f64::from(canny_max),
+                                                    1.0,
+                                                    0,
+                                                    1.0, "",
+                                                    move |v: f64| {
+                                                        let mut c = config_override_low.clone();
+                                                        #[allow(clippy::cast_possible_truncation)]
+                                                        let v = v as f32;
+                                                        if let Some(t) = c.edge_channels.thresholds.get_mut(&channel) {
+                                                            t.low = v.min(t.high);
+                                                        }
+                                                        on_change.call(c);
+                                                    },
+                                                )}
+                                                {render_slider(
+                                                    &format!("high_{}", channel_key(channel)),
+                                                    &format!("{} High", channel_label(channel)),
+                                                    "",
+                                                    f64::from(t.high),
+                                                    1.0,
+                                                    f64::from(canny_max),
+                                                    1.0,
+                                                    0,
+                                                    1.0, "",
+                                                    move |v: f64| {
+                                                        let mut c = config_override_high.clone();
+                                                        #[allow(clippy::cast_possible_truncation)]
+                                                        let v = v as f32;
+                                                        let max = c.canny_max;
+                                                        if let Some(t) = c.edge_channels.thresholds.get_mut(&channel) {
+                                                            t.high = v.max(t.low).min(max);
+                                                        }
+                                                        on_change.call(c);
+                                                    },
+                                                )}
+                                            }
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -339,10 +630,45 @@ pub fn StageControls(props: StageControlsProps) -> Element {
         }
 
         StageId::Simplified => {
+            let simplifier = config.simplifier;
+            let config_simplifier = config.clone();
             let value = config.simplify_tolerance;
+            let filter = config.contour_filter.clone();
+            let config_length = config.clone();
+            let config_area = config.clone();
+            let config_near = config.clone();
+            let curve = config.curve.clone();
+            let config_curve = config.clone();
+            let config_iterations = config.clone();
+            let config_spacing = config.clone();
+            let config_curve_tolerance = config.clone();
             let config = config.clone();
             rsx! {
                 div { class: "space-y-2",
+                    {render_select(
+                        "simplifier",
+                        "Simplifier",
+                        desc("RDP keeps sharp spikes; Visvalingam-Whyatt drops narrow spikes and keeps gentle curves. Topology-preserving never lets a path cross itself."),
+                        &[
+                            ("Rdp", "Ramer-Douglas-Peucker"),
+                            ("VisvalingamWhyatt", "Visvalingam-Whyatt"),
+                            ("TopologyPreserving", "Topology-Preserving"),
+                        ],
+                        match simplifier {
+                            SimplifierKind::Rdp => "Rdp",
+                            SimplifierKind::VisvalingamWhyatt => "VisvalingamWhyatt",
+                            SimplifierKind::TopologyPreserving => "TopologyPreserving",
+                        },
+                        move |v: String| {
+                            let mut c = config_simplifier.clone();
+                            c.simplifier = match v.as_str() {
+                                "VisvalingamWhyatt" => SimplifierKind::VisvalingamWhyatt,
+                                "TopologyPreserving" => SimplifierKind::TopologyPreserving,
+                                _ => SimplifierKind::Rdp,
+                            };
+                            on_change.call(c);
+                        },
+                    )}
                     {render_slider(
                         "simplify_tolerance",
                         "Simplify Tolerance",
@@ -359,6 +685,138 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             on_change.call(c);
                         },
                     )}
+                    {render_slider(
+                        "contour_min_length",
+                        "Min Contour Length",
+                        desc("Drop contours shorter than this, in normalized units. 0 keeps all."),
+                        filter.min_length,
+                        0.0,
+                        0.1,
+                        0.001,
+                        3,
+                        1.0, "",
+                        move |v: f64| {
+                            let mut c = config_length.clone();
+                            c.contour_filter.min_length = v;
+                            on_change.call(c);
+                        },
+                    )}
+                    {render_slider(
+                        "contour_min_area",
+                        "Min Contour Area",
+                        desc("Drop contours smaller than this: the area of a square spanning the same bounding-box diagonal, in normalized units squared. Long straight lines are kept. 0 keeps all."),
+                        filter.min_area,
+                        0.0,
+                        0.01,
+                        0.0001,
+                        4,
+                        1.0, "",
+                        move |v: f64| {
+                            let mut c = config_area.clone();
+                            c.contour_filter.min_area = v;
+                            on_change.call(c);
+                        },
+                    )}
+                    if filter.is_active() {
+                        {render_slider(
+                            "contour_keep_near",
+                            "Keep If Near",
+                            desc("Keep small contours within this distance of a larger one. 0 disables."),
+                            filter.keep_near,
+                            0.0,
+                            0.05,
+                            0.001,
+                            3,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_near.clone();
+                                c.contour_filter.keep_near = v;
+                                on_change.call(c);
+                            },
+                        )}
+                    }
+                    {render_select(
+                        "curve_kind",
+                        "Curve Smoothing",
+                        desc("Round off the straight facets left by simplification. Bezier also exports true curves to SVG."),
+                        &[
+                            ("Off", "Off"),
+                            ("Chaikin", "Chaikin"),
+                            ("CatmullRom", "Catmull-Rom"),
+                            ("Bezier", "Bezier"),
+                        ],
+                        match curve.kind {
+                            CurveKind::Off => "Off",
+                            CurveKind::Chaikin => "Chaikin",
+                            CurveKind::CatmullRom => "CatmullRom",
+                            CurveKind::Bezier => "Bezier",
+                        },
+                        move |v: String| {
+                            let mut c = config_curve.clone();
+                            c.curve.kind = match v.as_str() {
+                                "Chaikin" => CurveKind::Chaikin,
+                                "CatmullRom" => CurveKind::CatmullRom,
+                                "Bezier" => CurveKind::Bezier,
+                                _ => CurveKind::Off,
+                            };
+                            on_change.call(c);
+                        },
+                    )}
+                    if curve.kind == CurveKind::Chaikin {
+                        {render_slider(
+                            "curve_iterations",
+                            "Passes",
+                            desc("Corner-cutting passes. Each pass rounds more and doubles the point count."),
+                            f64::from(curve.iterations),
+                            1.0,
+                            f64::from(CurveSettings::MAX_ITERATIONS),
+                            1.0,
+                            0,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_iterations.clone();
+                                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                                { c.curve.iterations = v as u32; }
+                                on_change.call(c);
+                            },
+                        )}
+                    }
+                    if curve.kind == CurveKind::CatmullRom {
+                        {render_slider(
+                            "curve_spacing",
+                            "Sample Spacing",
+                            desc("Distance between points sampled along the spline, in normalized units. Smaller is smoother."),
+                            curve.spacing,
+                            0.001,
+                            0.05,
+                            0.001,
+                            3,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_spacing.clone();
+                                c.curve.spacing = v;
+                                on_change.call(c);
+                            },
+                        )}
+                    }
+                    if curve.kind == CurveKind::Bezier {
+                        {render_slider(
+                            "curve_tolerance",
+                            "Fit Tolerance",
+                            desc("Maximum distance between the simplified points and the fitted curves, in normalized units."),
+                            curve.tolerance,
+                            0.0005,
+                            0.02,
+                            0.0005,
+                            4,
+                            1.0, "",
+                            move |v: f64| {
+                                let mut c = config_curve_tolerance.clone();
+                                c.curve.tolerance = v;
+                                on_change.call(c);
+                            },
+                        )}
+                    }
                 }
             }
         }
@@ -493,7 +951,11 @@ pub fn StageControls(props: StageControlsProps) -> Element {
             let config_start = config.clone();
             let config_slider = config.clone();
             let config_parity = config.clone();
+            let config_perimeter = config.clone();
+            let config_epsilon = config.clone();
             let is_mst = matches!(config.path_joiner, PathJoinerKind::Mst);
+            let is_circle = matches!(config.shape, CanvasShape::Circle);
+            let perimeter_connectors = config.perimeter_connectors;
             rsx! {
                 div { class: "space-y-2",
                     {render_select(
@@ -536,6 +998,38 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                         },
                     )}
 
+                    if is_circle {
+                        {render_toggle(
+                            "perimeter_connectors",
+                            "Perimeter Connectors",
+                            desc("Route connectors between two near-edge points along the rim instead of cutting straight across. Travel along the rim is nearly invisible on real tables."),
+                            perimeter_connectors,
+                            move |v: bool| {
+                                let mut c = config_perimeter.clone();
+                                c.perimeter_connectors = v;
+                                on_change.call(c);
+                            },
+                        )}
+                        if perimeter_connectors {
+                            {render_slider(
+                                "perimeter_epsilon",
+                                "Rim Distance",
+                                desc("How close to the edge (normalized units) both connector endpoints must be to be routed along the rim."),
+                                config_epsilon.perimeter_epsilon,
+                                0.0,
+                                0.2,
+                                0.005,
+                                3,
+                                1.0, "",
+                                move |v: f64| {
+                                    let mut c = config_epsilon.clone();
+                                    c.perimeter_epsilon = v;
+                                    on_change.call(c);
+                                },
+                            )}
+                        }
+                    }
+
                     if is_mst {
                         {render_slider(
                             "mst_neighbours",
@@ -580,9 +1074,30 @@ pub fn StageControls(props: StageControlsProps) -> Element {
 
         StageId::Output => {
             let value = config.subsample_max_length;
+            let center_value = config.center_avoid_radius;
+            let config_mode = config.clone();
+            let config_center = config.clone();
             let config = config.clone();
             rsx! {
                 div { class: "space-y-2",
+                    {render_select(
+                        "subsample_mode",
+                        "Subsample Mode",
+                        desc("Cartesian limits XY segment length. Polar limits radial and arc step length, sampling densely near the center and dropping redundant points — best for polar (THR) tables."),
+                        &[("Cartesian", "Cartesian"), ("Polar", "Polar")],
+                        match config_mode.subsample_mode {
+                            SubsampleMode::Cartesian => "Cartesian",
+                            SubsampleMode::Polar => "Polar",
+                        },
+                        move |v: String| {
+                            let mut c = config_mode.clone();
+                            c.subsample_mode = match v.as_str() {
+                                "Polar" => SubsampleMode::Polar,
+                                _ => SubsampleMode::Cartesian,
+                            };
+                            on_change.call(c);
+                        },
+                    )}
                     {render_slider(
                         "subsample_max_length",
                         "Max Segment Length",
@@ -599,6 +1114,22 @@ pub fn StageControls(props: StageControlsProps) -> Element {
                             on_change.call(c);
                         },
                     )}
+                    {render_slider(
+                        "center_avoid_radius",
+                        "Center Avoid Radius",
+                        desc("Reroute segments passing this close to the center around it with a small arc, so polar (THR) tables don't spin through the center. 0 disables."),
+                        center_value,
+                        0.0,
+                        0.1,
+                        0.005,
+                        3,
+                        1.0, "",
+                        move |v: f64| {
+                            let mut c = config_center.clone();
+                            c.center_avoid_radius = v;
+                            on_change.call(c);
+                        },
+                    )}
                 }
             }
         }
@@ -729,6 +1260,96 @@ fn render_toggle(
     }
 }
 
+/// Stable id fragment for an edge channel's controls.
+const fn channel_key(channel: EdgeChannel) -> &'static str {
+    match channel {
+        EdgeChannel::Luminance => "luminance",
+        EdgeChannel::Red => "red",
+        EdgeChannel::Green => "green",
+        EdgeChannel::Blue => "blue",
+        EdgeChannel::Saturation => "saturation",
+        EdgeChannel::Alpha => "alpha",
+        EdgeChannel::Hue => "hue",
+        EdgeChannel::Value => "value",
+        EdgeChannel::Lightness => "lightness",
+        EdgeChannel::LabA => "lab_a",
+        EdgeChannel::LabB => "lab_b",
+    }
+}
+
+/// UI label for an edge channel.
+const fn channel_label(channel: EdgeChannel) -> &'static str {
+    match channel {
+        EdgeChannel::Luminance => "Luminance",
+        EdgeChannel::Red => "Red",
+        EdgeChannel::Green => "Green",
+        EdgeChannel::Blue => "Blue",
+        EdgeChannel::Saturation => "Saturation",
+        EdgeChannel::Alpha => "Alpha",
+        EdgeChannel::Hue => "Hue",
+        EdgeChannel::Value => "Value",
+        EdgeChannel::Lightness => "Lightness (L*)",
+        EdgeChannel::LabA => "Green–Red (a*)",
+        EdgeChannel::LabB => "Blue–Yellow (b*)",
+    }
+}
+
+/// Render a labeled color picker for an RGB value.
+fn render_color(
+    id: &str,
+    label: &str,
+    description: &str,
+    value: [u8; 3],
+    on_change: impl Fn([u8; 3]) + 'static,
+) -> Element {
+    let id = id.to_string();
+    let label = label.to_string();
+    let description = description.to_string();
+    let desc_id = description_element_id(&id, &description);
+    let hex = format!("#{:02x}{:02x}{:02x}", value[0], value[1], value[2]);
+    let described_by = (!desc_id.is_empty()).then(|| desc_id.clone());
+
+    rsx! {
+        div { class: "flex flex-col gap-1",
+            div { class: "flex items-center justify-between",
+                label { r#for: "{id}",
+                    class: "text-sm text-[var(--text-heading)] font-medium",
+                    "{label}"
+                }
+                input {
+                    r#type: "color",
+                    id: "{id}",
+                    value: "{hex}",
+                    "aria-describedby": described_by,
+                    class: "w-10 h-6 cursor-pointer",
+                    oninput: move |e| {
+                        if let Some(rgb) = parse_hex_color(&e.value()) {
+                            on_change(rgb);
+                        }
+                    },
+                }
+            }
+            if !description.is_empty() {
+                p {
+                    id: "{desc_id}",
+                    class: "text-xs text-[var(--text-secondary)]",
+                    "{description}"
+                }
+            }
+        }
+    }
+}
+
+/// Parse a `#rrggbb` color, as produced by `<input type="color">`.
+fn parse_hex_color(hex: &str) -> Option<[u8; 3]> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
 /// Render a compact channel toggle checkbox.
 ///
 /// When `disabled` is `true` the checkbox is shown as disabled (used to
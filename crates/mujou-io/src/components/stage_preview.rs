@@ -15,6 +15,10 @@
 //! context signal), the Join stage preview renders additional layers:
 //! - Top-N longest segments highlighted in distinct colors
 //! - MST connecting edges shown as red lines
+//!
+//! The Output stage has a drawing-order timeline: a slider reveals the
+//! path up to a fraction of its length, with the ball position marked
+//! and connector and retrace segments colored apart from contours.
 
 use std::rc::Rc;
 
@@ -22,8 +26,8 @@ use dioxus::prelude::*;
 
 use crate::stage::StageId;
 use crate::worker::WorkerResult;
-use mujou_pipeline::segment_analysis::find_top_segments;
-use mujou_pipeline::{MaskShape, MstEdgeInfo};
+use mujou_pipeline::segment_analysis::{find_top_segments, segment_runs};
+use mujou_pipeline::{MaskShape, MstEdgeInfo, Polyline, SegmentKind};
 
 /// Props for the [`StagePreview`] component.
 #[derive(Props, Clone)]
@@ -64,6 +68,11 @@ impl PartialEq for StagePreviewProps {
 /// the preview area styling is rendered.
 #[component]
 pub fn StagePreview(props: StagePreviewProps) -> Element {
+    // Drawing-order timeline position for the Output stage, as a
+    // fraction of the path length.  Kept across re-renders so the
+    // scrubber stays put while parameters are tweaked.
+    let timeline = use_signal(|| 1.0_f64);
+
     let Some(ref result) = props.result else {
         // No result yet — show placeholder matching the preview area
         return rsx! {
@@ -87,11 +96,12 @@ pub fn StagePreview(props: StagePreviewProps) -> Element {
         // raster stages are hidden with `display: none`.
         {render_raster_img(result.original_url.url(), "Original", selected == StageId::Original)}
         {render_raster_img(result.downsampled_url.url(), "Downsampled", selected == StageId::Downsampled)}
+        {render_raster_img(result.tone_image_url(), "Tone", selected == StageId::Tone)}
         {render_raster_img(result.blur_url.url(), "Blur", selected == StageId::Blur)}
         {render_raster_edges(result, selected == StageId::Edges, is_dark)}
 
         // Vector stages — conditionally rendered (SVG is instant).
-        {render_vector_preview(result, selected, show_diagnostics())}
+        {render_vector_preview(result, selected, show_diagnostics(), timeline)}
     }
 }
 
@@ -124,13 +134,14 @@ fn render_raster_edges(result: &WorkerResult, visible: bool, is_dark: bool) -> E
     }
 }
 
-/// Render the vector (SVG) preview for Contours, Simplified, Join, or
-/// Masked stages. Returns empty for raster stages.
+/// Render the vector (SVG) preview for Contours, Simplified, Canvas,
+/// Join, or Output stages. Returns empty for raster stages.
 #[allow(clippy::too_many_lines)]
 fn render_vector_preview(
     result: &WorkerResult,
     selected: StageId,
     show_diagnostics: bool,
+    timeline: Signal<f64>,
 ) -> Element {
     match selected {
         StageId::Contours | StageId::Simplified => {
@@ -190,32 +201,7 @@ fn render_vector_preview(
             }
         }
 
-        StageId::Output => {
-            let polyline = &result.output;
-            let view_box = canvas_view_box(&result.canvas.shape);
-            let d = build_path_data_normalized(polyline);
-
-            rsx! {
-                svg {
-                    xmlns: "http://www.w3.org/2000/svg",
-                    view_box: "{view_box}",
-                    class: "w-full h-full bg-[var(--preview-bg)] rounded",
-                    "preserveAspectRatio": "xMidYMid meet",
-                    role: "img",
-                    "aria-label": "Output stage preview",
-
-                    if !d.is_empty() {
-                        path {
-                            d: "{d}",
-                            fill: "none",
-                            stroke: "var(--preview-stroke)",
-                            stroke_width: "1",
-                            "vector-effect": "non-scaling-stroke",
-                        }
-                    }
-                }
-            }
-        }
+        StageId::Output => render_output_timeline(result, timeline),
 
         StageId::Join => {
             let polyline = &result.joined;
@@ -270,6 +256,153 @@ fn render_vector_preview(
     }
 }
 
+/// Render the Output stage with its drawing-order timeline.
+///
+/// While scrubbing, the timeline walks the joined path, whose segments
+/// carry provenance labels from the join stage, so each segment is
+/// colored by its label and the not-yet-drawn remainder stays visible
+/// as a faint ghost.  The joined path lacks the output stage's
+/// center-avoid detours and polar resampling, so at 100% the final
+/// output path is drawn instead.
+fn render_output_timeline(result: &WorkerResult, mut timeline: Signal<f64>) -> Element {
+    let polyline = &result.joined;
+    let view_box = canvas_view_box(&result.canvas.shape);
+    let fraction = timeline();
+    let scrubbing = fraction < 1.0;
+
+    let drawn = polyline.prefix(fraction);
+    let (runs, ghost, finished) = if scrubbing {
+        (
+            segment_runs(&drawn, &result.joined_segments),
+            build_path_data_normalized(polyline),
+            String::new(),
+        )
+    } else {
+        (
+            Vec::new(),
+            String::new(),
+            build_path_data_normalized(&result.output),
+        )
+    };
+    let percent = format!("{:.0}%", fraction * 100.0);
+
+    rsx! {
+        div {
+            class: "w-full h-full flex flex-col gap-2",
+            svg {
+                xmlns: "http://www.w3.org/2000/svg",
+                view_box: "{view_box}",
+                class: "w-full flex-1 min-h-0 bg-[var(--preview-bg)] rounded",
+                "preserveAspectRatio": "xMidYMid meet",
+                role: "img",
+                "aria-label": "Output stage preview",
+
+                if !ghost.is_empty() {
+                    path {
+                        d: "{ghost}",
+                        fill: "none",
+                        stroke: "var(--preview-stroke)",
+                        stroke_width: "1",
+                        opacity: "0.15",
+                        "vector-effect": "non-scaling-stroke",
+                    }
+                }
+
+                if !finished.is_empty() {
+                    path {
+                        d: "{finished}",
+                        fill: "none",
+                        stroke: "var(--preview-stroke)",
+                        stroke_width: "1",
+                        "vector-effect": "non-scaling-stroke",
+                    }
+                }
+
+                for (i, run) in runs.iter().enumerate() {
+                    {
+                        let d = build_path_data_normalized(&run.polyline);
+                        let stroke = segment_kind_stroke(run.kind);
+                        rsx! {
+                            path {
+                                key: "run-{i}",
+                                d: "{d}",
+                                fill: "none",
+                                stroke: "{stroke}",
+                                stroke_width: "1",
+                                "vector-effect": "non-scaling-stroke",
+                            }
+                        }
+                    }
+                }
+
+                {render_ball(&drawn, scrubbing)}
+            }
+            label {
+                class: "flex items-center gap-2 text-sm text-[var(--text-secondary)]",
+                "Drawing order"
+                input {
+                    r#type: "range",
+                    min: "0",
+                    max: "1",
+                    step: "0.001",
+                    value: "{fraction}",
+                    "aria-valuetext": "{percent}",
+                    class: "flex-1 accent-[var(--btn-primary)]",
+                    oninput: move |e| {
+                        match e.value().parse::<f64>() {
+                            Ok(v) => timeline.set(v.clamp(0.0, 1.0)),
+                            Err(err) => {
+                                web_sys::console::warn_1(
+                                    &format!("timeline parse failure: {err:?} from {:?}", e.value())
+                                        .into(),
+                                );
+                            }
+                        }
+                    },
+                }
+                span { class: "w-10 text-right tabular-nums", "{percent}" }
+            }
+        }
+    }
+}
+
+/// Stroke color for a timeline segment run.
+const fn segment_kind_stroke(kind: SegmentKind) -> &'static str {
+    match kind {
+        SegmentKind::Contour => "var(--preview-stroke)",
+        SegmentKind::Connector => "var(--diag-mst)",
+        SegmentKind::Retrace => "var(--timeline-retrace)",
+        SegmentKind::Border => "var(--timeline-border)",
+    }
+}
+
+/// Render the ball marker at the end of the drawn part of the path.
+///
+/// Hidden once the whole path is drawn, so the finished preview looks
+/// like the plain output.
+fn render_ball(drawn: &Polyline, show: bool) -> Element {
+    if !show {
+        return rsx! {};
+    }
+    let Some(ball) = drawn.last() else {
+        return rsx! {};
+    };
+
+    // Same fixed normalized radius as the start indicator.
+    let r = 0.035;
+
+    let cy = -ball.y;
+    rsx! {
+        circle {
+            cx: "{ball.x}",
+            cy: "{cy}",
+            r: "{r}",
+            fill: "var(--timeline-ball)",
+            "data-layer": "timeline-ball",
+        }
+    }
+}
+
 /// Build an SVG path `d` attribute string for a normalized-space polyline,
 /// negating Y for SVG (+Y down).
 ///
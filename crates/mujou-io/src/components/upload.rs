@@ -7,16 +7,19 @@ use dioxus_free_icons::icons::ld_icons::LdUpload;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::closure::Closure;
 
-/// Allowed file extensions for image uploads.
-const ALLOWED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp"];
-
-/// Check whether a filename has an allowed image extension.
+/// Check whether a filename has an extension the pipeline can decode.
 fn has_allowed_extension(name: &str) -> bool {
-    name.rsplit_once('.').is_some_and(|(_, ext)| {
-        ALLOWED_EXTENSIONS
-            .iter()
-            .any(|a| a.eq_ignore_ascii_case(ext))
-    })
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| mujou_pipeline::grayscale::is_supported_extension(ext))
+}
+
+/// The file input's `accept` list, e.g. `".png,.jpg,.jpeg"`.
+fn accept_attribute() -> String {
+    mujou_pipeline::grayscale::supported_extensions()
+        .iter()
+        .map(|ext| format!(".{ext}"))
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 /// Props for the [`FileUpload`] component.
@@ -130,7 +133,7 @@ pub fn FileUpload(props: FileUploadProps) -> Element {
                 "aria-label": "Upload image",
                 input {
                     r#type: "file",
-                    accept: ".png,.jpg,.jpeg,.bmp,.webp",
+                    accept: accept_attribute(),
                     class: "hidden",
                     onchange: handle_files,
                 }
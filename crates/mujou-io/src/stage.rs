@@ -14,13 +14,15 @@ use std::fmt;
 ///   visually indistinguishable from simplification
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StageId {
-    /// Stage 0: original source image (RGBA, pre-processing).
+    /// Stages 0+1: original source image (RGBA, pre-processing).
     Original,
-    /// Stage 1: downsampled to working resolution.
+    /// Stage 2: downsampled to working resolution.
     Downsampled,
-    /// Stage 3: Gaussian blur.
+    /// Stage 3: contrast normalization.
+    Tone,
+    /// Stage 4: Gaussian blur.
     Blur,
-    /// Stages 4+5: Canny edge detection + optional inversion.
+    /// Stage 5: Canny edge detection + optional inversion.
     Edges,
     /// Stage 6: contour tracing.
     Contours,
@@ -36,9 +38,10 @@ pub enum StageId {
 
 impl StageId {
     /// All stages in pipeline order, for iterating the filmstrip.
-    pub const ALL: [Self; 9] = [
+    pub const ALL: [Self; 10] = [
         Self::Original,
         Self::Downsampled,
+        Self::Tone,
         Self::Blur,
         Self::Edges,
         Self::Contours,
@@ -54,6 +57,7 @@ impl StageId {
         match self {
             Self::Original => "Original",
             Self::Downsampled => "Downsampled",
+            Self::Tone => "Tone",
             Self::Blur => "Blur",
             Self::Edges => "Edges",
             Self::Contours => "Contours",
@@ -66,8 +70,8 @@ impl StageId {
 
     /// Map a pipeline-internal stage index to the corresponding UI stage.
     ///
-    /// The pipeline has 10 internal stages (indices 0–9) while the UI
-    /// presents 9 stages. Backend stages 0 (`Pending`/source) and 1
+    /// The pipeline has 11 internal stages (indices 0–10) while the UI
+    /// presents 10 stages. Backend stages 0 (`Pending`/source) and 1
     /// (`Decoded`/decode) both map to [`StageId::Original`] because
     /// decode is the operation that produces the original preview image.
     /// Returns `None` for out-of-range indices.
@@ -78,13 +82,14 @@ impl StageId {
         match index {
             0 | 1 => Some(Self::Original), // Pending + Decoded
             2 => Some(Self::Downsampled),  // Downsampled
-            3 => Some(Self::Blur),         // Blurred
-            4 => Some(Self::Edges),        // EdgesDetected
-            5 => Some(Self::Contours),     // ContoursTraced
-            6 => Some(Self::Simplified),   // Simplified
-            7 => Some(Self::Canvas),       // Canvas
-            8 => Some(Self::Join),         // Joined
-            9 => Some(Self::Output),       // Output
+            3 => Some(Self::Tone),         // Toned
+            4 => Some(Self::Blur),         // Blurred
+            5 => Some(Self::Edges),        // EdgesDetected
+            6 => Some(Self::Contours),     // ContoursTraced
+            7 => Some(Self::Simplified),   // Simplified
+            8 => Some(Self::Canvas),       // Canvas
+            9 => Some(Self::Join),         // Joined
+            10 => Some(Self::Output),      // Output
             _ => None,
         }
     }
@@ -95,6 +100,7 @@ impl StageId {
         match self {
             Self::Original => "Orig",
             Self::Downsampled => "Down",
+            Self::Tone => "Tone",
             Self::Blur => "Blur",
             Self::Edges => "Edge",
             Self::Contours => "Cont",
@@ -121,7 +127,7 @@ mod tests {
         // If you add a variant to StageId, update ALL and this count.
         assert_eq!(
             StageId::ALL.len(),
-            9,
+            10,
             "StageId::ALL length must match variant count"
         );
         // Verify no duplicates.
@@ -133,15 +139,15 @@ mod tests {
 
     #[test]
     fn from_pipeline_index_maps_all_backend_stages() {
-        // Backend indices 0-9 should all map to a valid StageId.
-        for i in 0..=9 {
+        // Backend indices 0-10 should all map to a valid StageId.
+        for i in 0..=10 {
             assert!(
                 StageId::from_pipeline_index(i).is_some(),
                 "pipeline index {i} should map to a StageId"
             );
         }
         // Out-of-range returns None.
-        assert_eq!(StageId::from_pipeline_index(10), None);
+        assert_eq!(StageId::from_pipeline_index(11), None);
         assert_eq!(StageId::from_pipeline_index(usize::MAX), None);
     }
 
@@ -172,7 +178,7 @@ mod tests {
     fn from_pipeline_index_covers_all_ui_stages() {
         // Every StageId variant should be reachable from some pipeline index.
         let mut reachable = std::collections::HashSet::new();
-        for i in 0..=9 {
+        for i in 0..=10 {
             if let Some(stage) = StageId::from_pipeline_index(i) {
                 reachable.insert(stage);
             }
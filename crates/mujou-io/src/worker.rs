@@ -10,11 +10,11 @@
 //! operation that doesn't block the UI.
 
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use mujou_pipeline::{
-    Dimensions, MaskResult, MstEdgeInfo, PipelineConfig, PipelineError, Polyline,
+    Dimensions, MaskResult, MstEdgeInfo, PipelineConfig, PipelineError, Polyline, SegmentKind,
 };
 
 use crate::stage::StageId;
@@ -34,6 +34,8 @@ struct VectorResult {
     joined: Polyline,
     output: Polyline,
     #[serde(default)]
+    joined_segments: Vec<SegmentKind>,
+    #[serde(default)]
     mst_edge_details: Vec<MstEdgeInfo>,
     dimensions: Dimensions,
 }
@@ -48,6 +50,10 @@ pub struct WorkerResult {
     pub original_url: raster::CachedBlobUrl,
     /// Blob URL for the downsampled RGBA image (working resolution).
     pub downsampled_url: raster::CachedBlobUrl,
+    /// Blob URL for the tone-mapped image, `None` when tone mapping is
+    /// off and the image is [`downsampled_url`](Self::downsampled_url).
+    /// Use [`tone_image_url`](Self::tone_image_url) to display it.
+    pub tone_url: Option<raster::CachedBlobUrl>,
     /// Blob URL for the blurred image.
     pub blur_url: raster::CachedBlobUrl,
     /// Blob URL for edges in light theme.
@@ -68,6 +74,8 @@ pub struct WorkerResult {
     /// exceeds `config.subsample_max_length` normalized units. This
     /// prevents angular artifacts in polar (THR) conversion.
     pub output: Polyline,
+    /// Provenance of each segment of [`joined`](Self::joined).
+    pub joined_segments: Vec<SegmentKind>,
     /// Per-MST-edge diagnostic details from the join stage.
     ///
     /// Present only when the MST joiner is used. Enables diagnostic
@@ -78,6 +86,16 @@ pub struct WorkerResult {
 }
 
 impl WorkerResult {
+    /// URL of the tone stage's image, falling back to the downsampled
+    /// image when tone mapping is off.
+    #[must_use]
+    pub fn tone_image_url(&self) -> &str {
+        self.tone_url
+            .as_ref()
+            .unwrap_or(&self.downsampled_url)
+            .url()
+    }
+
     /// The final output polyline — the subsampled (THR-optimized) path.
     ///
     /// The subsampled path is the joined path with long segments
@@ -108,6 +126,7 @@ impl WorkerResult {
             StageId::Simplified
             | StageId::Original
             | StageId::Downsampled
+            | StageId::Tone
             | StageId::Blur
             | StageId::Edges
             | StageId::Join
@@ -116,11 +135,16 @@ impl WorkerResult {
     }
 }
 
+/// Resolves a pending [`PipelineWorker::run`] as cancelled.
+type CancelRun = Box<dyn FnOnce()>;
+
 /// A pipeline worker that runs `process_staged` in a dedicated web worker.
 ///
 /// Create one at app startup and reuse it for all pipeline runs.
-/// Call [`cancel`](Self::cancel) to terminate an in-progress run —
-/// this kills the worker and spawns a fresh one.
+/// Call [`cancel`](Self::cancel) to abort an in-progress run.  When the
+/// page is cross-origin isolated the run is cancelled cooperatively
+/// through a shared flag and the worker keeps its stage cache;
+/// otherwise the worker is killed and a fresh one spawned.
 pub struct PipelineWorker {
     /// The embedded JS glue for the worker (from `include_str!` in the
     /// app crate's build.rs).
@@ -128,8 +152,17 @@ pub struct PipelineWorker {
     /// The embedded WASM binary for the worker (from `include_bytes!`
     /// in the app crate's build.rs).
     worker_wasm: &'static [u8],
-    /// The current worker instance. Replaced on cancel.
+    /// The current worker instance. Replaced on cancel when there is
+    /// no shared cancel flag.
     inner: RefCell<web_sys::Worker>,
+    /// Shared flag holding the highest cancelled generation, present
+    /// only when `SharedArrayBuffer` is available.
+    cancel_flag: Option<js_sys::Int32Array>,
+    /// Generation of the most recent [`run`](Self::run).
+    last_generation: Cell<f64>,
+    /// Resolves the pending [`run`](Self::run) with
+    /// [`PipelineError::Cancelled`], tagged with its generation.
+    pending_cancel: RefCell<Option<(f64, CancelRun)>>,
 }
 
 impl PipelineWorker {
@@ -141,11 +174,13 @@ impl PipelineWorker {
     /// environment).
     #[must_use]
     pub fn new(worker_js: &'static str, worker_wasm: &'static [u8]) -> Self {
-        let worker = create_worker(worker_js, worker_wasm);
         Self {
             worker_js,
             worker_wasm,
-            inner: RefCell::new(worker),
+            inner: RefCell::new(create_worker(worker_js, worker_wasm)),
+            cancel_flag: create_cancel_flag(),
+            last_generation: Cell::new(-1.0),
+            pending_cancel: RefCell::new(None),
         }
     }
 
@@ -165,13 +200,19 @@ impl PipelineWorker {
     /// this to a [`StageId`](crate::StageId) via
     /// [`StageId::from_pipeline_index`](crate::StageId::from_pipeline_index).
     ///
+    /// The optional `on_fraction` callback receives the fraction (0–1)
+    /// of the running stage completed, reported from checkpoints inside
+    /// long stages.
+    ///
     /// # Errors
     ///
     /// Returns a `PipelineError` if:
     /// - The config cannot be serialized
-    /// - The worker fails to respond (e.g. was terminated)
+    /// - The worker fails to respond
     /// - The result cannot be deserialized
     ///
+    /// Returns [`PipelineError::Cancelled`] after [`cancel`](Self::cancel).
+    ///
     /// # Concurrent calls
     ///
     /// Only one `run()` should be in-flight at a time. A second call
@@ -186,6 +227,7 @@ impl PipelineWorker {
         config: &PipelineConfig,
         generation: f64,
         on_progress: Option<impl FnMut(usize, bool) + 'static>,
+        on_fraction: Option<impl FnMut(f64) + 'static>,
     ) -> Result<WorkerResult, PipelineError> {
         let config_json = serde_json::to_string(config).map_err(|e| {
             PipelineError::InvalidConfig(format!("failed to serialize config: {e}"))
@@ -215,6 +257,11 @@ impl PipelineWorker {
             &JsValue::from_f64(generation),
         )
         .map_err(|_| PipelineError::InvalidConfig("failed to set generation".into()))?;
+        if let Some(flag) = &self.cancel_flag {
+            js_sys::Reflect::set(&message, &JsValue::from_str("cancelFlag"), flag)
+                .map_err(|_| PipelineError::InvalidConfig("failed to set cancelFlag".into()))?;
+        }
+        self.last_generation.set(generation);
 
         // Theme colors as hex strings (no # prefix).
         set_rgb(&message, "lightBg", colors.light.bg)?;
@@ -233,6 +280,7 @@ impl PipelineWorker {
         // final result messages (with "ok" field) resolve the promise.
         let resolve_clone = resolve.clone();
         let mut on_progress = on_progress;
+        let mut on_fraction = on_fraction;
         let onmessage = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(
             move |event: web_sys::MessageEvent| {
                 let data = event.data();
@@ -273,6 +321,16 @@ impl PipelineWorker {
                     return;
                 }
 
+                if msg_type.as_deref() == Some("fraction") {
+                    let fraction = js_sys::Reflect::get(&data, &JsValue::from_str("fraction"))
+                        .ok()
+                        .and_then(|v| v.as_f64());
+                    if let (Some(cb), Some(fraction)) = (on_fraction.as_mut(), fraction) {
+                        cb(fraction);
+                    }
+                    return;
+                }
+
                 // Final result — resolve the promise.
                 let outcome = decode_response(&data);
                 *result_clone.borrow_mut() = Some(outcome);
@@ -297,6 +355,18 @@ impl PipelineWorker {
                 .map_err(|_| PipelineError::InvalidConfig("failed to postMessage".into()))?;
         }
 
+        // Let `cancel()` resolve this run without waiting for the worker.
+        let cancel_result = Rc::clone(&result);
+        *self.pending_cancel.borrow_mut() = Some((
+            generation,
+            Box::new(move || {
+                cancel_result
+                    .borrow_mut()
+                    .get_or_insert(Err(PipelineError::Cancelled));
+                resolve.call0(&JsValue::NULL).ok();
+            }),
+        ));
+
         // Prevent closures from being dropped while we await.
         let _onmessage_guard = onmessage;
         let _onerror_guard = onerror;
@@ -304,11 +374,12 @@ impl PipelineWorker {
         // Await the promise — this yields to the browser event loop.
         let await_result = wasm_bindgen_futures::JsFuture::from(promise).await;
 
-        // Clean up the handlers.
-        {
+        // Clean up, unless a newer run has already taken over the worker.
+        if (self.last_generation.get() - generation).abs() <= f64::EPSILON {
             let worker = self.inner.borrow();
             worker.set_onmessage(None);
             worker.set_onerror(None);
+            self.pending_cancel.borrow_mut().take();
         }
 
         match await_result {
@@ -327,15 +398,30 @@ impl PipelineWorker {
         }
     }
 
-    /// Cancel any in-progress pipeline run by terminating the worker
-    /// and creating a fresh one.
+    /// Cancel any in-progress pipeline run.
     ///
-    /// This is instant — the worker is killed immediately regardless
-    /// of what stage the pipeline is in.
+    /// The pending [`run`](Self::run) resolves with
+    /// [`PipelineError::Cancelled`] immediately.  With a shared cancel
+    /// flag the worker stops at its next checkpoint and keeps its
+    /// stage cache.  Without one the worker is terminated and a fresh
+    /// one spawned, so the next run starts at once but from an empty
+    /// cache.
+    #[allow(clippy::cast_possible_truncation)]
     pub fn cancel(&self) {
-        self.inner.borrow().terminate();
-        let new_worker = create_worker(self.worker_js, self.worker_wasm);
-        *self.inner.borrow_mut() = new_worker;
+        let generation = self.last_generation.get();
+        if let Some(flag) = &self.cancel_flag {
+            let _ = js_sys::Atomics::store(flag, 0, generation as i32);
+        } else {
+            self.inner.borrow().terminate();
+            let new_worker = create_worker(self.worker_js, self.worker_wasm);
+            *self.inner.borrow_mut() = new_worker;
+        }
+        let pending = self.pending_cancel.borrow_mut().take();
+        if let Some((pending_generation, resolve)) = pending
+            && (pending_generation - generation).abs() <= f64::EPSILON
+        {
+            resolve();
+        }
     }
 }
 
@@ -387,6 +473,12 @@ fn decode_response(data: &JsValue) -> Result<WorkerResult, PipelineError> {
     // Create Blob URLs from pre-encoded PNG bytes (near-instant).
     let original_url = png_to_blob_url(data, "originalPng")?;
     let downsampled_url = png_to_blob_url(data, "downsampledPng")?;
+    let has_tone = js_sys::Reflect::has(data, &JsValue::from_str("tonedPng")).unwrap_or(false);
+    let tone_url = if has_tone {
+        Some(png_to_blob_url(data, "tonedPng")?)
+    } else {
+        None
+    };
     let blur_url = png_to_blob_url(data, "blurredPng")?;
     let edges_light_url = png_to_blob_url(data, "edgesLightPng")?;
     let edges_dark_url = png_to_blob_url(data, "edgesDarkPng")?;
@@ -394,6 +486,7 @@ fn decode_response(data: &JsValue) -> Result<WorkerResult, PipelineError> {
     Ok(WorkerResult {
         original_url,
         downsampled_url,
+        tone_url,
         blur_url,
         edges_light_url,
         edges_dark_url,
@@ -402,6 +495,7 @@ fn decode_response(data: &JsValue) -> Result<WorkerResult, PipelineError> {
         canvas: vector.canvas,
         joined: vector.joined,
         output: vector.output,
+        joined_segments: vector.joined_segments,
         mst_edge_details: vector.mst_edge_details,
         dimensions: vector.dimensions,
     })
@@ -429,6 +523,24 @@ fn png_to_blob_url(data: &JsValue, key: &str) -> Result<raster::CachedBlobUrl, P
     Ok(raster::CachedBlobUrl::new(url))
 }
 
+/// Create the shared cancel flag, if the page may use
+/// `SharedArrayBuffer` (i.e. it is cross-origin isolated).
+fn create_cancel_flag() -> Option<js_sys::Int32Array> {
+    let global = js_sys::global();
+    let isolated = js_sys::Reflect::get(&global, &JsValue::from_str("crossOriginIsolated"))
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !isolated {
+        return None;
+    }
+    let buffer = js_sys::SharedArrayBuffer::new(4);
+    let flag = js_sys::Int32Array::new(&buffer);
+    // No generation is cancelled yet.
+    flag.set_index(0, -1);
+    Some(flag)
+}
+
 /// Create a web worker from embedded JS glue and WASM binary.
 ///
 /// 1. Creates a Blob URL for the WASM binary
@@ -0,0 +1,213 @@
+//! Transparency handling for sources with an alpha channel.
+//!
+//! By default the pipeline ignores alpha: transparent pixels keep
+//! whatever RGB they were stored with (often black), so a logo on a
+//! transparent background traces poorly.  [`AlphaMode`] selects an
+//! alternative:
+//!
+//! - [`Composite`](AlphaMode::Composite) flattens the image over a
+//!   solid background color right after downsampling, so every later
+//!   stage sees what a viewer would see.
+//! - [`Mask`](AlphaMode::Mask) keeps the pixels as-is but discards
+//!   traced contours outside the opaque region, before simplification
+//!   and joining.
+//!
+//! Independently, the alpha channel can feed edge detection directly
+//! via [`EdgeChannels::alpha`](crate::EdgeChannels::alpha), which traces
+//! the silhouette of the opaque region.
+
+use std::fmt;
+
+use image::GrayImage;
+use imageproc::distance_transform::Norm;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Luma32FImage, Point, Polyline, RgbaImage};
+
+/// Alpha value at or above which a pixel counts as opaque for
+/// [`AlphaMode::Mask`].
+pub const OPAQUE_THRESHOLD: u8 = 128;
+
+/// How far (pixels, Chebyshev distance) the opaque region is grown
+/// before clipping contours in [`AlphaMode::Mask`].
+///
+/// Canny places the silhouette edge on the alpha transition, which can
+/// land on the transparent side of [`OPAQUE_THRESHOLD`]; the margin
+/// keeps that outline.
+pub const MASK_MARGIN_PX: u8 = 2;
+
+/// How the source image's alpha channel is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AlphaMode {
+    /// Ignore alpha; transparent pixels keep their stored RGB.
+    #[default]
+    Ignore,
+    /// Composite the image over
+    /// [`alpha_background`](crate::PipelineConfig::alpha_background).
+    Composite,
+    /// Discard contours outside the opaque region.
+    Mask,
+}
+
+impl fmt::Display for AlphaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ignore => f.write_str("Ignore"),
+            Self::Composite => f.write_str("Composite"),
+            Self::Mask => f.write_str("Mask"),
+        }
+    }
+}
+
+/// Composite `rgba` over an opaque `background` color.
+///
+/// The result is fully opaque.  Uses straight (non-premultiplied)
+/// alpha, as decoded by the `image` crate.
+#[must_use]
+pub fn composite(rgba: &RgbaImage, background: [u8; 3]) -> RgbaImage {
+    RgbaImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let mix = |fg: u8, bg: u8| {
+            let a = u16::from(a);
+            let value = (u16::from(fg) * a + u16::from(bg) * (255 - a) + 127) / 255;
+            #[allow(clippy::cast_possible_truncation)]
+            let value = value as u8;
+            value
+        };
+        image::Rgba([
+            mix(r, background[0]),
+            mix(g, background[1]),
+            mix(b, background[2]),
+            255,
+        ])
+    })
+}
+
+/// Composite a full-precision luminance over `background`, weighting by
+/// the alpha channel of `rgba` (same dimensions).
+///
+/// Companion to [`composite`] for high bit-depth sources (see
+/// [`crate::grayscale::to_precise_luma`]).
+#[must_use]
+pub fn composite_luma(luma: &Luma32FImage, rgba: &RgbaImage, background: [u8; 3]) -> Luma32FImage {
+    let [r, g, b] = background.map(f32::from);
+    let background_luma = 0.0722_f32.mul_add(b, 0.2126_f32.mul_add(r, 0.7152 * g));
+    Luma32FImage::from_fn(luma.width(), luma.height(), |x, y| {
+        let alpha = f32::from(rgba.get_pixel(x, y).0[3]) / 255.0;
+        let value = luma.get_pixel(x, y).0[0];
+        image::Luma([(value - background_luma).mul_add(alpha, background_luma)])
+    })
+}
+
+/// Binary mask of the opaque region of `rgba`: 255 where alpha is at
+/// least [`OPAQUE_THRESHOLD`], grown by [`MASK_MARGIN_PX`].
+#[must_use]
+pub fn opaque_region(rgba: &RgbaImage) -> GrayImage {
+    let mask = GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        image::Luma([if rgba.get_pixel(x, y).0[3] >= OPAQUE_THRESHOLD {
+            255
+        } else {
+            0
+        }])
+    });
+    imageproc::morphology::dilate(&mask, Norm::LInf, MASK_MARGIN_PX)
+}
+
+/// Clip pixel-space `contours` to the nonzero pixels of `region`.
+///
+/// Each contour is split into the runs of consecutive points inside the
+/// region; runs of fewer than two points are dropped.  Points outside
+/// the image count as outside the region.
+#[must_use]
+pub fn clip_to_region(contours: &[Polyline], region: &GrayImage) -> Vec<Polyline> {
+    let inside = |p: &Point| {
+        let (x, y) = (p.x.round(), p.y.round());
+        if x < 0.0 || y < 0.0 {
+            return false;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let (x, y) = (x as u32, y as u32);
+        region.get_pixel_checked(x, y).is_some_and(|p| p.0[0] > 0)
+    };
+
+    let mut clipped = Vec::new();
+    for contour in contours {
+        let mut run: Vec<Point> = Vec::new();
+        for point in contour.points() {
+            if inside(point) {
+                run.push(*point);
+            } else if run.len() >= 2 {
+                clipped.push(Polyline::new(std::mem::take(&mut run)));
+            } else {
+                run.clear();
+            }
+        }
+        if run.len() >= 2 {
+            clipped.push(Polyline::new(run));
+        }
+    }
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20x20 image, opaque red in the left half, transparent black in
+    /// the right half.
+    fn half_transparent() -> RgbaImage {
+        RgbaImage::from_fn(20, 20, |x, _| {
+            if x < 10 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([0, 0, 0, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn composite_fills_transparent_pixels_with_background() {
+        let flat = composite(&half_transparent(), [255, 255, 255]);
+        assert_eq!(flat.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(flat.get_pixel(19, 0).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn composite_blends_partial_alpha() {
+        let rgba = RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 128]));
+        let flat = composite(&rgba, [255, 255, 255]);
+        assert_eq!(flat.get_pixel(0, 0).0, [127, 127, 127, 255]);
+    }
+
+    #[test]
+    fn composite_luma_uses_background_luminance() {
+        let rgba = half_transparent();
+        let luma = Luma32FImage::from_pixel(20, 20, image::Luma([10.0]));
+        let flat = composite_luma(&luma, &rgba, [255, 255, 255]);
+        assert!((flat.get_pixel(0, 0).0[0] - 10.0).abs() < 1e-3);
+        assert!((flat.get_pixel(19, 0).0[0] - 255.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn opaque_region_includes_margin() {
+        let region = opaque_region(&half_transparent());
+        assert_eq!(region.get_pixel(9 + u32::from(MASK_MARGIN_PX), 5).0[0], 255);
+        assert_eq!(region.get_pixel(10 + u32::from(MASK_MARGIN_PX), 5).0[0], 0);
+    }
+
+    #[test]
+    fn clip_splits_contours_at_region_boundary() {
+        let region = GrayImage::from_fn(20, 20, |x, _| image::Luma([if x < 10 { 255 } else { 0 }]));
+        let line = |xs: &[f64]| Polyline::new(xs.iter().map(|&x| Point::new(x, 5.0)).collect());
+        let contours = vec![
+            // In, out, back in: two runs.
+            line(&[1.0, 2.0, 3.0, 15.0, 16.0, 4.0, 5.0]),
+            // Entirely outside: dropped.
+            line(&[12.0, 13.0, 14.0]),
+            // A single inside point: dropped.
+            line(&[15.0, 6.0, 15.0]),
+        ];
+        let clipped = clip_to_region(&contours, &region);
+        assert_eq!(clipped, vec![line(&[1.0, 2.0, 3.0]), line(&[4.0, 5.0])],);
+    }
+}
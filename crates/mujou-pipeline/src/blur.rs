@@ -1,4 +1,4 @@
-//! Gaussian blur for noise reduction before edge detection.
+//! Smoothing for noise reduction before edge detection.
 //!
 //! Wraps [`imageproc::filter::gaussian_blur_f32`] to smooth images,
 //! reducing high-frequency noise that would produce spurious edges
@@ -8,10 +8,115 @@
 //! [`gaussian_blur_rgba`] applies the same blur independently to each
 //! R/G/B/A channel of a color image, preserving color information for
 //! the UI preview while preparing all channels for edge detection.
+//! [`gaussian_blur_luma32f`] blurs the full-precision luminance kept for
+//! high bit-depth sources.
+//!
+//! # Strategy pattern
+//!
+//! Gaussian blur softens the edges we want to keep as much as the noise.
+//! The [`Smoother`] trait and [`SmoothingKind`] enum let the user pick an
+//! edge-preserving alternative — [`bilateral`], [`median`], or
+//! [`perona_malik`] diffusion — at runtime.  Every strategy takes its
+//! spatial scale from the same sigma, so switching filters keeps the
+//! amount of smoothing roughly comparable.
+
+use std::fmt;
 
 use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Luma32FImage, RgbaImage};
+
+/// Selects which smoothing filter runs before edge detection.
+///
+/// All variants take their spatial scale from
+/// [`blur_sigma`](crate::PipelineConfig::blur_sigma).  The
+/// edge-preserving ones also take the intensity difference they treat
+/// as an edge from
+/// [`smoothing_range`](crate::PipelineConfig::smoothing_range).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SmoothingKind {
+    /// Isotropic Gaussian blur.  Fast, but blurs edges as much as noise.
+    #[default]
+    Gaussian,
+    /// Bilateral filter: Gaussian spatial weights multiplied by Gaussian
+    /// weights on intensity difference, so pixels across an edge barely
+    /// mix.  Cost grows with sigma squared.
+    Bilateral,
+    /// Median over a square window of radius `max(1, round(sigma))`.
+    /// Removes speckle and keeps step edges sharp, but rounds corners.
+    Median,
+    /// Perona-Malik anisotropic diffusion: heat diffusion whose
+    /// conductance falls off with gradient magnitude, so flat regions
+    /// smooth like a Gaussian of the same sigma while edges stay put.
+    PeronaMalik,
+}
+
+impl SmoothingKind {
+    /// Whether this strategy reads the `range` parameter.
+    #[must_use]
+    pub const fn uses_range(self) -> bool {
+        matches!(self, Self::Bilateral | Self::PeronaMalik)
+    }
+
+    /// Largest `sigma` this strategy accepts, keeping its cost bounded:
+    /// the bilateral window is `(4σ+1)²` pixels, the median window
+    /// `(2σ+1)²`, and Perona-Malik runs `ceil(2σ²)` diffusion passes.
+    #[must_use]
+    pub const fn max_sigma(self) -> f32 {
+        match self {
+            Self::Gaussian => 20.0,
+            Self::Median => 10.0,
+            Self::Bilateral => 6.0,
+            Self::PeronaMalik => 8.0,
+        }
+    }
+}
+
+impl fmt::Display for SmoothingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gaussian => f.write_str("Gaussian"),
+            Self::Bilateral => f.write_str("Bilateral"),
+            Self::Median => f.write_str("Median"),
+            Self::PeronaMalik => f.write_str("Perona-Malik"),
+        }
+    }
+}
+
+/// Trait for smoothing strategies.
+///
+/// `sigma` is the spatial scale in pixels and `range` the intensity
+/// difference (0–255 levels) treated as an edge; strategies that are
+/// not edge-aware ignore `range`.  Non-positive `sigma` returns the
+/// image unchanged.
+pub trait Smoother {
+    /// Smooth each R/G/B/A channel of a color image independently.
+    fn smooth_rgba(&self, image: &RgbaImage, sigma: f32, range: f32) -> RgbaImage;
 
-use crate::types::RgbaImage;
+    /// Smooth a full-precision luminance image.
+    fn smooth_luma32f(&self, image: &Luma32FImage, sigma: f32, range: f32) -> Luma32FImage;
+}
+
+impl Smoother for SmoothingKind {
+    fn smooth_rgba(&self, image: &RgbaImage, sigma: f32, range: f32) -> RgbaImage {
+        match *self {
+            Self::Gaussian => gaussian_blur_rgba(image, sigma),
+            Self::Bilateral | Self::Median | Self::PeronaMalik => {
+                map_rgba_planes(image, |plane| self.smooth_luma32f(plane, sigma, range))
+            }
+        }
+    }
+
+    fn smooth_luma32f(&self, image: &Luma32FImage, sigma: f32, range: f32) -> Luma32FImage {
+        match *self {
+            Self::Gaussian => gaussian_blur_luma32f(image, sigma),
+            Self::Bilateral => bilateral(image, sigma, range),
+            Self::Median => median(image, sigma),
+            Self::PeronaMalik => perona_malik(image, sigma, range),
+        }
+    }
+}
 
 /// Apply Gaussian blur to a grayscale image.
 ///
@@ -70,6 +175,169 @@ pub fn gaussian_blur_rgba(image: &RgbaImage, sigma: f32) -> RgbaImage {
     })
 }
 
+/// Apply Gaussian blur to a full-precision luminance image.
+///
+/// Same kernel as [`gaussian_blur_rgba`], but without rounding to 8 bits
+/// so smooth gradients from 16-bit sources survive into edge detection.
+/// Non-positive sigma values return the image unchanged.
+#[must_use = "returns the blurred image"]
+pub fn gaussian_blur_luma32f(image: &Luma32FImage, sigma: f32) -> Luma32FImage {
+    if sigma <= 0.0 {
+        return image.clone();
+    }
+
+    imageproc::filter::gaussian_blur_f32(image, sigma)
+}
+
+/// Bilateral filter over a window of radius `ceil(2 * sigma)`.
+///
+/// Each neighbour is weighted by a Gaussian of its distance (`sigma`)
+/// times a Gaussian of its intensity difference from the center pixel
+/// (`range`), so smoothing stops at edges stronger than about `range`.
+/// Non-positive `sigma` returns the image unchanged.
+#[must_use = "returns the smoothed image"]
+pub fn bilateral(image: &Luma32FImage, sigma: f32, range: f32) -> Luma32FImage {
+    if sigma <= 0.0 {
+        return image.clone();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let radius = (2.0 * sigma).ceil() as i32;
+    let spatial: Vec<f32> = (-radius..=radius)
+        .map(|d| {
+            #[allow(clippy::cast_precision_loss)]
+            let d = d as f32;
+            (-(d * d) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let range_coeff = -1.0 / (2.0 * range * range);
+
+    let (w, h) = image.dimensions();
+    Luma32FImage::from_fn(w, h, |x, y| {
+        let center = image.get_pixel(x, y).0[0];
+        let mut sum = 0.0_f32;
+        let mut total = 0.0_f32;
+        for (dy, wy) in (-radius..=radius).zip(&spatial) {
+            let Some(ny) = neighbour(y, dy, h) else {
+                continue;
+            };
+            for (dx, wx) in (-radius..=radius).zip(&spatial) {
+                let Some(nx) = neighbour(x, dx, w) else {
+                    continue;
+                };
+                let value = image.get_pixel(nx, ny).0[0];
+                let diff = value - center;
+                let weight = wy * wx * (diff * diff * range_coeff).exp();
+                sum = weight.mul_add(value, sum);
+                total += weight;
+            }
+        }
+        image::Luma([sum / total])
+    })
+}
+
+/// Median filter over a square window of radius `max(1, round(sigma))`.
+///
+/// Windows are truncated at the image border.  Non-positive `sigma`
+/// returns the image unchanged.
+#[must_use = "returns the smoothed image"]
+pub fn median(image: &Luma32FImage, sigma: f32) -> Luma32FImage {
+    if sigma <= 0.0 {
+        return image.clone();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let radius = sigma.round().max(1.0) as i32;
+    let (w, h) = image.dimensions();
+    let mut window = Vec::new();
+    Luma32FImage::from_fn(w, h, |x, y| {
+        window.clear();
+        for dy in -radius..=radius {
+            let Some(ny) = neighbour(y, dy, h) else {
+                continue;
+            };
+            for dx in -radius..=radius {
+                if let Some(nx) = neighbour(x, dx, w) {
+                    window.push(image.get_pixel(nx, ny).0[0]);
+                }
+            }
+        }
+        let mid = window.len() / 2;
+        let (_, value, _) = window.select_nth_unstable_by(mid, f32::total_cmp);
+        image::Luma([*value])
+    })
+}
+
+/// Time step of [`perona_malik`]; 0.25 is the stability limit for the
+/// explicit 4-neighbour scheme.
+const DIFFUSION_STEP: f32 = 0.25;
+
+/// Perona-Malik anisotropic diffusion.
+///
+/// Runs `ceil(sigma² / 2 / DIFFUSION_STEP)` explicit steps, a total
+/// diffusion time of `sigma² / 2` — where the conductance is 1 this
+/// matches a Gaussian blur of `sigma`.  Conductance is
+/// `exp(-(|∇I| / range)²)`, so flux across differences much larger than
+/// `range` stops.  Borders are insulating.  Non-positive `sigma`
+/// returns the image unchanged.
+#[must_use = "returns the smoothed image"]
+pub fn perona_malik(image: &Luma32FImage, sigma: f32, range: f32) -> Luma32FImage {
+    if sigma <= 0.0 {
+        return image.clone();
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let steps = (sigma * sigma / 2.0 / DIFFUSION_STEP).ceil() as u32;
+    let inv_range_sq = 1.0 / (range * range);
+    let (w, h) = image.dimensions();
+
+    let mut current = image.clone();
+    for _ in 0..steps {
+        let previous = current;
+        current = Luma32FImage::from_fn(w, h, |x, y| {
+            let center = previous.get_pixel(x, y).0[0];
+            let mut flux = 0.0_f32;
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                if let (Some(nx), Some(ny)) = (neighbour(x, dx, w), neighbour(y, dy, h)) {
+                    let diff = previous.get_pixel(nx, ny).0[0] - center;
+                    flux = diff.mul_add((-(diff * diff) * inv_range_sq).exp(), flux);
+                }
+            }
+            image::Luma([DIFFUSION_STEP.mul_add(flux, center)])
+        });
+    }
+    current
+}
+
+/// `coord + delta` if it lies in `0..len`.
+fn neighbour(coord: u32, delta: i32, len: u32) -> Option<u32> {
+    coord.checked_add_signed(delta).filter(|&c| c < len)
+}
+
+/// Apply a full-precision single-channel filter to each R/G/B/A
+/// channel of a color image, rounding the results back to 8 bits.
+fn map_rgba_planes(
+    image: &RgbaImage,
+    filter: impl Fn(&Luma32FImage) -> Luma32FImage + Sync + Send,
+) -> RgbaImage {
+    let (w, h) = image.dimensions();
+    let planes: Vec<Luma32FImage> = (0..4)
+        .map(|c| {
+            Luma32FImage::from_fn(w, h, |x, y| {
+                image::Luma([f32::from(image.get_pixel(x, y).0[c])])
+            })
+        })
+        .collect();
+    let smoothed = crate::par::map(&planes, filter);
+    RgbaImage::from_fn(w, h, |x, y| {
+        image::Rgba(std::array::from_fn(|c| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let value = smoothed[c].get_pixel(x, y).0[0].round().clamp(0.0, 255.0) as u8;
+            value
+        }))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,4 +511,100 @@ mod tests {
             }
         }
     }
+
+    // ─────── Edge-preserving smoothing tests ─────────────────────
+
+    /// 20x20 full-precision image with a step from 50 to 200 at x = 10
+    /// and a single faint speck (70) at (4, 4).
+    fn noisy_step() -> Luma32FImage {
+        Luma32FImage::from_fn(20, 20, |x, y| {
+            image::Luma([match (x, y) {
+                (4, 4) => 70.0,
+                (x, _) if x < 10 => 50.0,
+                _ => 200.0,
+            }])
+        })
+    }
+
+    /// Absolute step across the boundary at x = 10 on row `y`.
+    fn step_height(image: &Luma32FImage, y: u32) -> f32 {
+        (image.get_pixel(10, y).0[0] - image.get_pixel(9, y).0[0]).abs()
+    }
+
+    #[test]
+    fn smoothing_kind_default_is_gaussian() {
+        assert_eq!(SmoothingKind::default(), SmoothingKind::Gaussian);
+    }
+
+    #[test]
+    fn gaussian_kind_matches_gaussian_blur() {
+        let img = RgbaImage::from_fn(10, 10, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            let v = (x * 20 + y * 5) as u8;
+            image::Rgba([v, 255 - v, v / 2, 255])
+        });
+        assert_eq!(
+            SmoothingKind::Gaussian.smooth_rgba(&img, 1.4, 25.0),
+            gaussian_blur_rgba(&img, 1.4),
+        );
+    }
+
+    #[test]
+    fn edge_preserving_kinds_keep_step_sharper_than_gaussian() {
+        let img = noisy_step();
+        let gaussian = step_height(&gaussian_blur_luma32f(&img, 2.0), 15);
+        for kind in [
+            SmoothingKind::Bilateral,
+            SmoothingKind::Median,
+            SmoothingKind::PeronaMalik,
+        ] {
+            let smoothed = kind.smooth_luma32f(&img, 2.0, 25.0);
+            let step = step_height(&smoothed, 15);
+            assert!(
+                step > gaussian,
+                "{kind}: step {step} should exceed Gaussian's {gaussian}",
+            );
+        }
+    }
+
+    #[test]
+    fn median_removes_isolated_speck() {
+        let smoothed = median(&noisy_step(), 1.0);
+        assert!((smoothed.get_pixel(4, 4).0[0] - 50.0).abs() < f32::EPSILON);
+        // The step itself survives untouched.
+        assert!((step_height(&smoothed, 15) - 150.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn perona_malik_flattens_small_differences() {
+        // The 20-level speck is under `range` and diffuses away; the
+        // 150-level step is far above it and stays.
+        let smoothed = perona_malik(&noisy_step(), 2.0, 25.0);
+        assert!(smoothed.get_pixel(4, 4).0[0] < 55.0);
+        assert!(step_height(&smoothed, 15) > 140.0);
+    }
+
+    #[test]
+    fn edge_preserving_kinds_leave_uniform_image_unchanged() {
+        let img = RgbaImage::from_pixel(8, 8, image::Rgba([100, 150, 200, 250]));
+        for kind in [
+            SmoothingKind::Bilateral,
+            SmoothingKind::Median,
+            SmoothingKind::PeronaMalik,
+        ] {
+            assert_eq!(kind.smooth_rgba(&img, 1.5, 25.0), img, "{kind}");
+        }
+    }
+
+    #[test]
+    fn edge_preserving_kinds_zero_sigma_returns_identical_image() {
+        let img = noisy_step();
+        for kind in [
+            SmoothingKind::Bilateral,
+            SmoothingKind::Median,
+            SmoothingKind::PeronaMalik,
+        ] {
+            assert_eq!(kind.smooth_luma32f(&img, 0.0, 25.0), img, "{kind}");
+        }
+    }
 }
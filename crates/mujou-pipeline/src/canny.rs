@@ -17,6 +17,15 @@
 //! - Issue: <https://github.com/image-rs/imageproc/issues/705>
 //! - Fix PR (not yet merged): <https://github.com/image-rs/imageproc/pull/746>
 //!
+//! [`canny_f32`] is a mujou addition (not upstream): the same algorithm
+//! on an `f32` image, with Sobel gradients computed in `f32`, so 16-bit
+//! sources keep their precision.  It is split into [`gradient_f32`] and
+//! [`canny_from_gradient`] so callers can combine the gradients of
+//! several images (see [`vector_gradient`] and [`weighted_gradient`])
+//! before thinning.
+//! `non_maximum_suppression` is generic over the gradient type to serve
+//! both entry points.
+//!
 //! Remove this module once the upstream fix is released. Tracked by:
 //! <https://github.com/altendky/mujou/issues/69>
 
@@ -51,8 +60,8 @@ mod inner {
         let blurred = gaussian_blur_f32(image, SIGMA);
 
         // 2. Intensity of gradients.
-        let gx = filter_clamped(&blurred, kernel::SOBEL_HORIZONTAL_3X3);
-        let gy = filter_clamped(&blurred, kernel::SOBEL_VERTICAL_3X3);
+        let gx = filter_clamped::<_, i32, i16>(&blurred, kernel::SOBEL_HORIZONTAL_3X3);
+        let gy = filter_clamped::<_, i32, i16>(&blurred, kernel::SOBEL_VERTICAL_3X3);
         let g: Vec<f32> = gx
             .iter()
             .zip(gy.iter())
@@ -68,18 +77,154 @@ mod inner {
         hysteresis(&thinned, low_threshold, high_threshold)
     }
 
+    /// Sobel gradient of an image: per-pixel components and magnitude.
+    ///
+    /// Not upstream.  `gx`/`gy` give the direction used for non-maximum
+    /// suppression; `magnitude` is what the thresholds apply to.
+    #[derive(Debug, Clone)]
+    pub struct Gradient {
+        pub gx: Image<Luma<f32>>,
+        pub gy: Image<Luma<f32>>,
+        pub magnitude: Image<Luma<f32>>,
+    }
+
+    /// Runs the canny edge detection algorithm on an `f32` image.
+    ///
+    /// Not upstream: mirrors [`canny`] step for step, with the Sobel
+    /// gradients computed in `f32` instead of `i16`.
+    pub fn canny_f32(
+        image: &Image<Luma<f32>>,
+        low_threshold: f32,
+        high_threshold: f32,
+    ) -> GrayImage {
+        canny_from_gradient(&gradient_f32(image), low_threshold, high_threshold)
+    }
+
+    /// Steps 1–2 of [`canny_f32`]: Gaussian blur, then Sobel gradients.
+    pub fn gradient_f32(image: &Image<Luma<f32>>) -> Gradient {
+        const SIGMA: f32 = 1.4;
+        let blurred = gaussian_blur_f32(image, SIGMA);
+
+        let gx = sobel_f32(&blurred, [-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0]);
+        let gy = sobel_f32(&blurred, [-1.0, -2.0, -1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0]);
+        let g: Vec<f32> = gx
+            .iter()
+            .zip(gy.iter())
+            .map(|(h, v)| h.hypot(*v))
+            .collect::<Vec<f32>>();
+
+        let magnitude = Image::from_raw(image.width(), image.height(), g).unwrap();
+        Gradient { gx, gy, magnitude }
+    }
+
+    /// Gradient of a multi-component (vector-valued) image, e.g. the
+    /// cosine and sine of an angle.
+    ///
+    /// The magnitude is the Euclidean norm over every component's `gx`
+    /// and `gy` (the Frobenius norm of the Jacobian); the direction is
+    /// taken from whichever component changes fastest at each pixel.
+    /// All gradients must have the same dimensions.
+    pub fn vector_gradient(components: &[Gradient]) -> Gradient {
+        let first = &components[0];
+        let (w, h) = first.magnitude.dimensions();
+        let mut gx = first.gx.clone();
+        let mut gy = first.gy.clone();
+        let mut magnitude = Image::from_pixel(w, h, Luma([0.0_f32]));
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = 0.0_f32;
+                let mut strongest = 0.0_f32;
+                for c in components {
+                    let m = c.magnitude.get_pixel(x, y)[0];
+                    sum += m * m;
+                    if m > strongest {
+                        strongest = m;
+                        gx.put_pixel(x, y, *c.gx.get_pixel(x, y));
+                        gy.put_pixel(x, y, *c.gy.get_pixel(x, y));
+                    }
+                }
+                magnitude.put_pixel(x, y, Luma([sum.sqrt()]));
+            }
+        }
+        Gradient { gx, gy, magnitude }
+    }
+
+    /// Weighted average of several gradients of the same dimensions.
+    ///
+    /// The magnitude is `Σ wᵢ·|gᵢ| / Σ wᵢ`, so thresholds keep the
+    /// meaning they have for a single gradient; the direction is taken
+    /// from whichever weighted gradient is strongest at each pixel.
+    pub fn weighted_gradient(components: &[(Gradient, f32)]) -> Gradient {
+        let (first, _) = &components[0];
+        let (w, h) = first.magnitude.dimensions();
+        let total: f32 = components.iter().map(|(_, weight)| weight).sum();
+        let mut gx = first.gx.clone();
+        let mut gy = first.gy.clone();
+        let mut magnitude = Image::from_pixel(w, h, Luma([0.0_f32]));
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = 0.0_f32;
+                let mut strongest = 0.0_f32;
+                for (c, weight) in components {
+                    let m = weight * c.magnitude.get_pixel(x, y)[0];
+                    sum += m;
+                    if m > strongest {
+                        strongest = m;
+                        gx.put_pixel(x, y, *c.gx.get_pixel(x, y));
+                        gy.put_pixel(x, y, *c.gy.get_pixel(x, y));
+                    }
+                }
+                magnitude.put_pixel(x, y, Luma([sum / total]));
+            }
+        }
+        Gradient { gx, gy, magnitude }
+    }
+
+    /// Steps 3–4 of [`canny_f32`]: non-maximum suppression and
+    /// hysteresis on a precomputed gradient.
+    pub fn canny_from_gradient(
+        gradient: &Gradient,
+        low_threshold: f32,
+        high_threshold: f32,
+    ) -> GrayImage {
+        assert!(high_threshold >= low_threshold);
+        let thinned = non_maximum_suppression(&gradient.magnitude, &gradient.gx, &gradient.gy);
+        hysteresis(&thinned, low_threshold, high_threshold)
+    }
+
+    /// Correlate `image` with a row-major 3×3 `kernel`, replicating edge
+    /// pixels (the border handling of `imageproc::filter::filter_clamped`).
+    #[allow(clippy::cast_possible_wrap)]
+    fn sobel_f32(image: &Image<Luma<f32>>, kernel: [f32; 9]) -> Image<Luma<f32>> {
+        let (w, h) = (image.width() as i64, image.height() as i64);
+        Image::from_fn(image.width(), image.height(), |x, y| {
+            let mut sum = 0.0;
+            for (k, weight) in kernel.iter().enumerate() {
+                let dx = (k % 3) as i64 - 1;
+                let dy = (k / 3) as i64 - 1;
+                let sx = (x as i64 + dx).clamp(0, w - 1) as u32;
+                let sy = (y as i64 + dy).clamp(0, h - 1) as u32;
+                sum += weight * image.get_pixel(sx, sy)[0];
+            }
+            Luma([sum])
+        })
+    }
+
     /// Finds local maxima to make the edges thinner.
-    fn non_maximum_suppression(
+    ///
+    /// Generic over the gradient type (upstream takes `i16` only) so
+    /// [`canny_f32`] can share it.
+    fn non_maximum_suppression<T: image::Primitive + Into<f32>>(
         g: &Image<Luma<f32>>,
-        gx: &Image<Luma<i16>>,
-        gy: &Image<Luma<i16>>,
+        gx: &Image<Luma<T>>,
+        gy: &Image<Luma<T>>,
     ) -> Image<Luma<f32>> {
         const RADIANS_TO_DEGREES: f32 = 180f32 / f32::consts::PI;
         let mut out = Image::from_pixel(g.width(), g.height(), Luma([0.0]));
         for y in 1..g.height() - 1 {
             for x in 1..g.width() - 1 {
-                let x_gradient = gx[(x, y)][0] as f32;
-                let y_gradient = gy[(x, y)][0] as f32;
+                let x_gradient: f32 = gx[(x, y)][0].into();
+                let y_gradient: f32 = gy[(x, y)][0].into();
                 let mut angle = (y_gradient).atan2(x_gradient) * RADIANS_TO_DEGREES;
                 if angle < 0.0 {
                     angle += 180.0
@@ -190,7 +335,10 @@ mod inner {
     }
 }
 
-pub use inner::canny;
+pub use inner::{
+    Gradient, canny, canny_f32, canny_from_gradient, gradient_f32, vector_gradient,
+    weighted_gradient,
+};
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
@@ -235,4 +383,64 @@ mod tests {
         let edge_count: u32 = edges.pixels().map(|p| u32::from(p.0[0] > 0)).sum();
         assert!(edge_count > 0, "expected edges at sharp boundary");
     }
+
+    /// The `f32` variant finds the same edges as the `u8` one on
+    /// 8-bit-representable input.
+    #[test]
+    fn canny_f32_matches_canny_on_integer_input() {
+        let img = GrayImage::from_fn(20, 20, |x, y| Luma([if x + y / 2 < 12 { 40 } else { 220 }]));
+        let img_f32 = imageproc::definitions::Image::<Luma<f32>>::from_fn(20, 20, |x, y| {
+            Luma([f32::from(img.get_pixel(x, y)[0])])
+        });
+        assert_eq!(
+            canny_f32(&img_f32, 20.0, 60.0).into_raw(),
+            canny(&img, 20.0, 60.0).into_raw()
+        );
+    }
+
+    /// A vector image whose components each change by `d` has gradient
+    /// magnitude √2 times that of one component.
+    #[test]
+    fn vector_gradient_is_euclidean_norm() {
+        let step = imageproc::definitions::Image::<Luma<f32>>::from_fn(20, 20, |x, _| {
+            Luma([if x < 10 { 0.0 } else { 100.0 }])
+        });
+        let single = gradient_f32(&step);
+        let vector = vector_gradient(&[single.clone(), single.clone()]);
+        let (a, b) = (single.magnitude[(10, 5)][0], vector.magnitude[(10, 5)][0]);
+        assert!(a > 0.0);
+        assert!(a.mul_add(-std::f32::consts::SQRT_2, b).abs() < 1e-3);
+        assert_eq!(
+            canny_from_gradient(&single, 20.0, 60.0),
+            canny_f32(&step, 20.0, 60.0)
+        );
+    }
+
+    /// Averaging a gradient with itself, at any weights, changes
+    /// nothing.
+    #[test]
+    fn weighted_gradient_of_identical_inputs_is_unchanged() {
+        let step = imageproc::definitions::Image::<Luma<f32>>::from_fn(20, 20, |x, _| {
+            Luma([if x < 10 { 0.0 } else { 100.0 }])
+        });
+        let single = gradient_f32(&step);
+        let averaged = weighted_gradient(&[(single.clone(), 0.5), (single.clone(), 3.0)]);
+        for (a, b) in single.magnitude.iter().zip(averaged.magnitude.iter()) {
+            assert!((a - b).abs() < 1e-3);
+        }
+    }
+
+    /// A gradient too shallow for 8-bit input is still detected at
+    /// full precision.
+    #[test]
+    fn canny_f32_sees_sub_level_steps() {
+        // A 0.9-level step vanishes when rounded to 8 bits.
+        let img_f32 = imageproc::definitions::Image::<Luma<f32>>::from_fn(20, 20, |x, _| {
+            Luma([if x < 10 { 100.0 } else { 100.9 }])
+        });
+        let img = GrayImage::from_fn(20, 20, |_, _| Luma([100]));
+        let count = |edges: GrayImage| edges.pixels().filter(|p| p[0] > 0).count();
+        assert_eq!(count(canny(&img, 0.5, 1.0)), 0);
+        assert!(count(canny_f32(&img_f32, 0.5, 1.0)) > 0);
+    }
 }
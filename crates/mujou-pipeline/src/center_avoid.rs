@@ -0,0 +1,301 @@
+//! Polar center avoidance: reroute segments that pass near the origin.
+//!
+//! Applied in the output stage (step 9), after path joining and before
+//! subsampling.
+//!
+//! On polar sand tables a straight chord that crosses (or nearly
+//! crosses) rho = 0 forces the theta axis to spin through up to π
+//! radians over a very short distance.  At the exact origin the THR
+//! exporter already reuses the previous theta, but chords that merely
+//! pass *close* to the center still produce violent theta swings and
+//! a visible blob where the ball lingers.
+//!
+//! [`avoid_center`] pushes vertices that lie inside a configurable
+//! radius out onto it, then detects segments whose closest approach to
+//! the origin falls inside the radius and replaces the inner part of
+//! the chord with a short arc along it, so the ball sweeps around the
+//! center instead of through it.
+
+use std::f64::consts::PI;
+
+use crate::types::{Point, Polyline};
+
+/// Number of arc segments used for a half-turn (π radians) detour.
+///
+/// Shorter detours use proportionally fewer segments (minimum 1).
+/// Subsampling later subdivides the arc further if needed.
+const ARC_SEGMENTS_PER_HALF_TURN: f64 = 16.0;
+
+/// Reroute segments that pass within `radius` of the polar origin.
+///
+/// Vertices strictly inside `radius` are first projected radially onto
+/// the circle (a vertex exactly at the origin takes the direction of
+/// the preceding point, or the following one at the start of the
+/// path).  A segment `a → b` whose closest approach to the origin then
+/// still lies strictly inside is replaced by
+///
+/// 1. the straight run from `a` to where it enters the circle,
+/// 2. an arc of the given `radius` around the origin, on the same side
+///    as the chord, and
+/// 3. the straight run from where the chord exits the circle to `b`.
+///
+/// Returns the rerouted polyline together with the number of segments
+/// that were rerouted (given an arc or a moved endpoint).  A
+/// non-positive `radius` disables rerouting and returns the polyline
+/// unchanged (by clone) with a count of zero.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{Point, Polyline};
+/// use mujou_pipeline::center_avoid::avoid_center;
+///
+/// let chord = Polyline::new(vec![Point::new(-0.5, 0.01), Point::new(0.5, 0.01)]);
+/// let (rerouted, count) = avoid_center(&chord, 0.05);
+/// assert_eq!(count, 1);
+/// assert!(rerouted.points().iter().all(|p| p.x.hypot(p.y) >= 0.05 - 1e-9));
+/// ```
+#[must_use]
+pub fn avoid_center(polyline: &Polyline, radius: f64) -> (Polyline, usize) {
+    let points = polyline.points();
+    if points.len() < 2 || radius <= 0.0 {
+        return (polyline.clone(), 0);
+    }
+
+    let (projected, moved) = project_out(points, radius);
+
+    let mut result = Vec::with_capacity(points.len());
+    result.push(projected[0]);
+    let mut reroutes = 0;
+
+    for (i, window) in projected.windows(2).enumerate() {
+        let a = window[0];
+        let b = window[1];
+        let crossing = circle_crossing(a, b, radius);
+        if let Some((entry, exit)) = crossing {
+            result.push(entry);
+            push_arc(entry, exit, radius, &mut result);
+            result.push(exit);
+        }
+        if crossing.is_some() || moved[i] || moved[i + 1] {
+            reroutes += 1;
+        }
+        result.push(b);
+    }
+    result.dedup();
+
+    (Polyline::new(result), reroutes)
+}
+
+/// Project the points strictly inside `radius` radially onto the
+/// circle.
+///
+/// Returns the projected points and, for each, whether it was moved.
+fn project_out(points: &[Point], radius: f64) -> (Vec<Point>, Vec<bool>) {
+    let origin = Point::new(0.0, 0.0);
+    let mut projected: Vec<Point> = Vec::with_capacity(points.len());
+    let mut moved = Vec::with_capacity(points.len());
+    for (i, &p) in points.iter().enumerate() {
+        let rho = p.distance(origin);
+        if rho >= radius {
+            projected.push(p);
+            moved.push(false);
+            continue;
+        }
+        let angle = if rho > 0.0 {
+            p.y.atan2(p.x)
+        } else {
+            // Exactly at the origin: head for the neighbouring point.
+            projected
+                .last()
+                .or_else(|| points[i + 1..].iter().find(|q| **q != origin))
+                .map_or(0.0, |q| q.y.atan2(q.x))
+        };
+        projected.push(Point::new(radius * angle.cos(), radius * angle.sin()));
+        moved.push(true);
+    }
+    (projected, moved)
+}
+
+/// If segment `a → b` should be rerouted, return the points where it
+/// enters and exits the circle of `radius` around the origin.
+///
+/// Endpoints are expected at or outside `radius`; an endpoint that
+/// sits on the circle (after [`project_out`]) is its own entry or exit
+/// point.
+fn circle_crossing(a: Point, b: Point, radius: f64) -> Option<(Point, Point)> {
+    let radius_sq = radius * radius;
+    let origin = Point::new(0.0, 0.0);
+
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len_sq = dx.mul_add(dx, dy * dy);
+    if len_sq == 0.0 {
+        return None;
+    }
+
+    // Closest approach: project the origin onto the segment.
+    let t_closest = -a.x.mul_add(dx, a.y * dy) / len_sq;
+    if t_closest <= 0.0 || t_closest >= 1.0 {
+        return None;
+    }
+    let closest = Point::new(dx.mul_add(t_closest, a.x), dy.mul_add(t_closest, a.y));
+    let closest_sq = closest.distance_squared(origin);
+    if closest_sq >= radius_sq {
+        return None;
+    }
+
+    // Half-chord length inside the circle, in parameter units.
+    let half = ((radius_sq - closest_sq) / len_sq).sqrt();
+    let t_entry = (t_closest - half).max(0.0);
+    let t_exit = (t_closest + half).min(1.0);
+    Some((
+        Point::new(dx.mul_add(t_entry, a.x), dy.mul_add(t_entry, a.y)),
+        Point::new(dx.mul_add(t_exit, a.x), dy.mul_add(t_exit, a.y)),
+    ))
+}
+
+/// Push the interior points of the minor arc from `entry` to `exit`
+/// (both on the circle of `radius`) onto `out`.
+///
+/// Because the chord does not pass through the origin (except in the
+/// degenerate exact-crossing case), the minor arc lies on the same side
+/// of the origin as the chord.  An exact crossing sweeps a half turn.
+fn push_arc(entry: Point, exit: Point, radius: f64, out: &mut Vec<Point>) {
+    let start = entry.y.atan2(entry.x);
+    let end = exit.y.atan2(exit.x);
+    let mut sweep = (end - start) % (2.0 * PI);
+    if sweep > PI {
+        sweep -= 2.0 * PI;
+    } else if sweep <= -PI {
+        sweep += 2.0 * PI;
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    let n = ((sweep.abs() / PI * ARC_SEGMENTS_PER_HALF_TURN).ceil() as usize).max(1);
+    #[allow(clippy::cast_precision_loss)]
+    let n_f = n as f64;
+    for i in 1..n {
+        #[allow(clippy::cast_precision_loss)]
+        let angle = sweep.mul_add(i as f64 / n_f, start);
+        out.push(Point::new(radius * angle.cos(), radius * angle.sin()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: build a simple polyline from (x, y) pairs.
+    fn poly(coords: &[(f64, f64)]) -> Polyline {
+        Polyline::new(coords.iter().map(|&(x, y)| Point::new(x, y)).collect())
+    }
+
+    fn min_rho(polyline: &Polyline) -> f64 {
+        polyline
+            .points()
+            .iter()
+            .map(|p| p.x.hypot(p.y))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn zero_radius_is_noop() {
+        let p = poly(&[(-0.5, 0.0), (0.5, 0.0)]);
+        let (result, count) = avoid_center(&p, 0.0);
+        assert_eq!(result, p);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn far_segment_is_untouched() {
+        let p = poly(&[(-0.5, 0.5), (0.5, 0.5)]);
+        let (result, count) = avoid_center(&p, 0.1);
+        assert_eq!(result, p);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn near_chord_is_rerouted_around_center() {
+        let p = poly(&[(-0.5, 0.02), (0.5, 0.02)]);
+        let radius = 0.1;
+        let (result, count) = avoid_center(&p, radius);
+        assert_eq!(count, 1);
+        assert!(result.len() > p.len());
+        assert!(
+            min_rho(&result) >= radius - 1e-9,
+            "rerouted path dips to rho {}",
+            min_rho(&result),
+        );
+        // Endpoints are preserved.
+        assert_eq!(result.first(), p.first());
+        assert_eq!(result.last(), p.last());
+    }
+
+    #[test]
+    fn detour_stays_on_chord_side() {
+        // The chord lies above the origin, so the detour arc must too.
+        let p = poly(&[(-0.5, 0.02), (0.5, 0.02)]);
+        let (result, _) = avoid_center(&p, 0.1);
+        assert!(result.points().iter().all(|pt| pt.y > 0.0));
+    }
+
+    #[test]
+    fn exact_crossing_is_rerouted() {
+        let p = poly(&[(-0.5, 0.0), (0.5, 0.0)]);
+        let radius = 0.05;
+        let (result, count) = avoid_center(&p, radius);
+        assert_eq!(count, 1);
+        assert!(min_rho(&result) >= radius - 1e-9);
+    }
+
+    #[test]
+    fn vertex_inside_radius_is_projected_out() {
+        let p = poly(&[(-0.5, 0.01), (0.02, 0.03), (0.5, -0.3)]);
+        let radius = 0.1;
+        let (result, count) = avoid_center(&p, radius);
+        assert_eq!(count, 2);
+        assert!(
+            min_rho(&result) >= radius - 1e-9,
+            "rerouted path dips to rho {}",
+            min_rho(&result),
+        );
+        assert_eq!(result.first(), p.first());
+        assert_eq!(result.last(), p.last());
+    }
+
+    #[test]
+    fn vertex_at_origin_is_projected_out() {
+        let p = poly(&[(-0.5, 0.0), (0.0, 0.0), (0.5, 0.3)]);
+        let radius = 0.1;
+        let (result, count) = avoid_center(&p, radius);
+        assert_eq!(count, 2);
+        assert!(min_rho(&result) >= radius - 1e-9);
+        assert!(
+            result
+                .points()
+                .iter()
+                .all(|pt| pt.x.is_finite() && pt.y.is_finite())
+        );
+    }
+
+    #[test]
+    fn path_starting_inside_radius_is_projected_out() {
+        let p = poly(&[(0.0, 0.0), (0.0, 0.05), (0.5, 0.5)]);
+        let radius = 0.1;
+        let (result, _) = avoid_center(&p, radius);
+        assert!(min_rho(&result) >= radius - 1e-9);
+        assert_eq!(result.last(), p.last());
+    }
+
+    #[test]
+    fn counts_each_rerouted_segment() {
+        let p = poly(&[(-0.5, 0.01), (0.5, 0.01), (0.0, -0.5), (0.01, 0.5)]);
+        let (_, count) = avoid_center(&p, 0.05);
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,221 @@
+//! Drop small contours before canvas clipping and joining.
+//!
+//! Canny noise leaves hundreds of tiny contours — specks and short
+//! fragments — that the joiner must connect, each adding a connector
+//! and often a retrace.  [`ContourFilter`] removes polylines whose arc
+//! length or extent falls below a threshold.
+//!
+//! Small contours near a surviving larger one are often real detail
+//! (an eyelash beside an eye, a broken-off stroke) and are cheap to
+//! join.  A positive [`keep_near`](ContourFilter::keep_near) distance
+//! keeps those.
+//!
+//! Applied in the simplification stage, after RDP, so all thresholds
+//! are in normalized units.  When it drops every contour the run fails
+//! with [`PipelineError::NoContours`], as tracing does.
+
+use geo::Line;
+use rstar::{PointDistance, RTree};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{PipelineError, Point, Polyline};
+
+/// Minimum contour size thresholds.
+///
+/// All thresholds are in normalized units (mask edge = 1.0) and 0.0
+/// disables them; the default keeps every contour.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContourFilter {
+    /// Drop polylines whose arc length is below this.
+    pub min_length: f64,
+    /// Drop polylines whose extent is below this.  The extent is the
+    /// area of a square with the same diagonal as the polyline's
+    /// axis-aligned bounding box, so a long straight line (a horizon, a
+    /// building edge) counts as large even though its box has no area.
+    pub min_area: f64,
+    /// Keep a polyline that fails the size thresholds anyway when one
+    /// of its points lies within this distance of a polyline that
+    /// passes them.  0.0 disables the rule.
+    pub keep_near: f64,
+}
+
+impl ContourFilter {
+    /// Whether any threshold is enabled.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.min_length > 0.0 || self.min_area > 0.0
+    }
+
+    /// Check that every threshold is finite and non-negative.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::InvalidConfig`] naming the first
+    /// offending threshold.
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        for (name, value) in [
+            ("min_length", self.min_length),
+            ("min_area", self.min_area),
+            ("keep_near", self.keep_near),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "contour_filter.{name} must be finite and non-negative, got {value}",
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `polyline` meets both size thresholds on its own.
+    fn is_large_enough(&self, polyline: &Polyline) -> bool {
+        if self.min_length > 0.0 && polyline.length() < self.min_length {
+            return false;
+        }
+        self.min_area <= 0.0 || extent(polyline) >= self.min_area
+    }
+
+    /// Drop the polylines that fail the thresholds, preserving the
+    /// order of the survivors.
+    #[must_use = "returns the filtered polylines"]
+    pub fn apply(&self, polylines: Vec<Polyline>) -> Vec<Polyline> {
+        if !self.is_active() {
+            return polylines;
+        }
+        let large: Vec<bool> = polylines.iter().map(|p| self.is_large_enough(p)).collect();
+
+        let near = (self.keep_near > 0.0).then(|| {
+            let segments: Vec<Line<f64>> = polylines
+                .iter()
+                .zip(&large)
+                .filter(|&(_, &is_large)| is_large)
+                .flat_map(|(p, _)| segments(p))
+                .collect();
+            RTree::bulk_load(segments)
+        });
+        let max_dist_sq = self.keep_near * self.keep_near;
+        let is_near = |polyline: &Polyline| {
+            near.as_ref().is_some_and(|tree| {
+                polyline.points().iter().any(|p| {
+                    let query = geo::Point::new(p.x, p.y);
+                    tree.nearest_neighbor(&query)
+                        .is_some_and(|line| line.distance_2(&query) <= max_dist_sq)
+                })
+            })
+        };
+
+        polylines
+            .into_iter()
+            .zip(large)
+            .filter(|(p, is_large)| *is_large || is_near(p))
+            .map(|(p, _)| p)
+            .collect()
+    }
+}
+
+/// Area of the square whose diagonal is the diagonal of the axis-aligned
+/// bounding box of `polyline`'s points; never less than the box's area.
+fn extent(polyline: &Polyline) -> f64 {
+    let (min_x, min_y, max_x, max_y) = crate::types::polyline_bounding_box(&[polyline]);
+    if min_x > max_x {
+        return 0.0;
+    }
+    (max_x - min_x).hypot(max_y - min_y).powi(2) / 2.0
+}
+
+/// The segments of `polyline`, or a single degenerate segment for a
+/// one-point polyline.
+fn segments(polyline: &Polyline) -> Vec<Line<f64>> {
+    let coord = |p: &Point| geo::coord! { x: p.x, y: p.y };
+    match polyline.points() {
+        [] => Vec::new(),
+        [p] => vec![Line::new(coord(p), coord(p))],
+        points => points
+            .windows(2)
+            .map(|w| Line::new(coord(&w[0]), coord(&w[1])))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x0: f64, y0: f64, x1: f64, y1: f64) -> Polyline {
+        Polyline::new(vec![Point::new(x0, y0), Point::new(x1, y1)])
+    }
+
+    #[test]
+    fn default_keeps_everything() {
+        let polylines = vec![line(0.0, 0.0, 0.001, 0.0), line(0.0, 0.0, 0.5, 0.5)];
+        assert!(!ContourFilter::default().is_active());
+        assert_eq!(ContourFilter::default().apply(polylines.clone()), polylines);
+    }
+
+    #[test]
+    fn min_length_drops_short_polylines() {
+        let short = line(0.0, 0.0, 0.01, 0.0);
+        let long = line(0.0, 0.0, 0.5, 0.0);
+        let filter = ContourFilter {
+            min_length: 0.1,
+            ..ContourFilter::default()
+        };
+        assert_eq!(filter.apply(vec![short, long.clone()]), vec![long]);
+    }
+
+    #[test]
+    fn min_area_drops_small_polylines_and_keeps_long_straight_lines() {
+        // A long straight line has no bounding-box area but a large
+        // extent.
+        let straight = line(0.0, 0.0, 0.5, 0.0);
+        let diagonal = line(0.0, 0.0, 0.2, 0.2);
+        let short = line(0.0, 0.0, 0.1, 0.0);
+        let speck = line(0.0, 0.0, 0.05, 0.05);
+        let filter = ContourFilter {
+            min_area: 0.01,
+            ..ContourFilter::default()
+        };
+        assert_eq!(
+            filter.apply(vec![straight.clone(), short, diagonal.clone(), speck]),
+            vec![straight, diagonal]
+        );
+    }
+
+    #[test]
+    fn keep_near_rescues_small_polylines_beside_large_ones() {
+        let large = line(0.0, 0.0, 0.5, 0.0);
+        let beside = line(0.25, 0.02, 0.26, 0.02);
+        let far = line(0.25, 0.5, 0.26, 0.5);
+        let filter = ContourFilter {
+            min_length: 0.1,
+            keep_near: 0.05,
+            ..ContourFilter::default()
+        };
+        assert_eq!(
+            filter.apply(vec![large.clone(), beside.clone(), far]),
+            vec![large, beside],
+        );
+    }
+
+    #[test]
+    fn validate_rejects_negative_and_non_finite_thresholds() {
+        for filter in [
+            ContourFilter {
+                min_length: -0.1,
+                ..ContourFilter::default()
+            },
+            ContourFilter {
+                min_area: f64::NAN,
+                ..ContourFilter::default()
+            },
+            ContourFilter {
+                keep_near: f64::INFINITY,
+                ..ContourFilter::default()
+            },
+        ] {
+            assert!(filter.validate().is_err(), "{filter:?}");
+        }
+        assert!(ContourFilter::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,721 @@
+//! Curve smoothing after path simplification.
+//!
+//! RDP leaves straight facets between the points it keeps, which show
+//! as visible corners on large tables.  [`CurveKind`] selects how the
+//! simplified polylines are rounded again:
+//!
+//! - [`Chaikin`](CurveKind::Chaikin) — corner cutting.  Each pass
+//!   replaces every segment by points at ¼ and ¾ of its length.
+//! - [`CatmullRom`](CurveKind::CatmullRom) — a centripetal Catmull-Rom
+//!   spline through the simplified points, resampled at a fixed
+//!   spacing.  Passes through every input point.
+//! - [`Bezier`](CurveKind::Bezier) — least-squares cubic Bezier fitting
+//!   (Schneider's algorithm) within an error bound, flattened back into
+//!   points.  SVG export can emit the fitted curves directly as `C`
+//!   commands via [`fit_cubic_beziers`].
+//!
+//! Applied in the simplification stage, after RDP and the
+//! [`contour filter`](crate::contour_filter), so all distances are in
+//! normalized units.  Polylines whose first and last points coincide
+//! are smoothed as closed loops.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{PipelineError, Point, Polyline};
+
+/// Selects the curve smoothing applied after simplification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CurveKind {
+    /// Keep the simplified polylines as they are.
+    #[default]
+    Off,
+    /// Chaikin corner cutting.  Rounds every corner and pulls the path
+    /// slightly inside the original points.
+    Chaikin,
+    /// Centripetal Catmull-Rom spline through the simplified points.
+    CatmullRom,
+    /// Least-squares cubic Bezier fitting within an error bound.
+    Bezier,
+}
+
+impl fmt::Display for CurveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => f.write_str("Off"),
+            Self::Chaikin => f.write_str("Chaikin"),
+            Self::CatmullRom => f.write_str("Catmull-Rom"),
+            Self::Bezier => f.write_str("Bezier"),
+        }
+    }
+}
+
+/// Curve smoothing settings.
+///
+/// Only the parameters of the selected [`kind`](Self::kind) are used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurveSettings {
+    /// Which smoothing to apply.
+    pub kind: CurveKind,
+    /// Number of Chaikin passes.  Each pass doubles the point count.
+    /// Must be between 1 and [`MAX_ITERATIONS`](Self::MAX_ITERATIONS).
+    pub iterations: u32,
+    /// Catmull-Rom sample spacing in normalized units.  Must be
+    /// positive.
+    pub spacing: f64,
+    /// Maximum distance between a simplified point and the fitted
+    /// Bezier curve, in normalized units.  Must be positive.
+    pub tolerance: f64,
+}
+
+impl CurveSettings {
+    /// Largest allowed [`iterations`](Self::iterations).
+    pub const MAX_ITERATIONS: u32 = 6;
+    /// Default [`iterations`](Self::iterations).
+    pub const DEFAULT_ITERATIONS: u32 = 2;
+    /// Default [`spacing`](Self::spacing).
+    pub const DEFAULT_SPACING: f64 = 0.005;
+    /// Default [`tolerance`](Self::tolerance).
+    pub const DEFAULT_TOLERANCE: f64 = 0.002;
+
+    /// Check the parameter bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::InvalidConfig`] if `iterations` is
+    /// outside `1..=MAX_ITERATIONS`, or `spacing` or `tolerance` is not
+    /// finite and positive.
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        if !(1..=Self::MAX_ITERATIONS).contains(&self.iterations) {
+            return Err(PipelineError::InvalidConfig(format!(
+                "curve iterations must be between 1 and {}, got {}",
+                Self::MAX_ITERATIONS,
+                self.iterations,
+            )));
+        }
+        for (name, value) in [("spacing", self.spacing), ("tolerance", self.tolerance)] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "curve {name} must be finite and positive, got {value}",
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `self` and `other` produce the same curves, ignoring
+    /// parameters the selected [`kind`](Self::kind) does not read.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn output_eq(&self, other: &Self) -> bool {
+        if self.kind != other.kind {
+            return false;
+        }
+        match self.kind {
+            CurveKind::Off => true,
+            CurveKind::Chaikin => self.iterations == other.iterations,
+            CurveKind::CatmullRom => self.spacing == other.spacing,
+            CurveKind::Bezier => self.tolerance == other.tolerance,
+        }
+    }
+
+    /// Smooth a single polyline.
+    ///
+    /// Consecutive duplicate points are removed first.  Polylines with
+    /// fewer than 3 distinct points have no corners and are returned
+    /// without them.
+    #[must_use = "returns the smoothed polyline"]
+    pub fn smooth(&self, polyline: &Polyline) -> Polyline {
+        let points = dedup(polyline.points());
+        if self.kind == CurveKind::Off || points.len() < 3 {
+            return Polyline::new(points);
+        }
+        let smoothed = match self.kind {
+            CurveKind::Off => unreachable!("handled above"),
+            CurveKind::Chaikin => (0..self.iterations).fold(points, |p, _| chaikin(&p)),
+            CurveKind::CatmullRom => catmull_rom(&points, self.spacing),
+            CurveKind::Bezier => {
+                let curves = fit_cubic_beziers(&points, self.tolerance);
+                let mut out = vec![points[0]];
+                for curve in &curves {
+                    curve.flatten_into(self.tolerance, &mut out);
+                }
+                out
+            }
+        };
+        Polyline::new(smoothed)
+    }
+
+    /// Smooth every polyline, preserving order.
+    #[must_use = "returns the smoothed polylines"]
+    pub fn apply(&self, polylines: Vec<Polyline>) -> Vec<Polyline> {
+        if self.kind == CurveKind::Off {
+            return polylines;
+        }
+        crate::par::map(&polylines, |p| self.smooth(p))
+    }
+}
+
+impl Default for CurveSettings {
+    fn default() -> Self {
+        Self {
+            kind: CurveKind::Off,
+            iterations: Self::DEFAULT_ITERATIONS,
+            spacing: Self::DEFAULT_SPACING,
+            tolerance: Self::DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+/// A cubic Bezier segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    /// Start point.
+    pub p0: Point,
+    /// First control point.
+    pub p1: Point,
+    /// Second control point.
+    pub p2: Point,
+    /// End point.
+    pub p3: Point,
+}
+
+impl CubicBezier {
+    /// The point at parameter `t` in `[0, 1]`.
+    #[must_use]
+    pub fn eval(&self, t: f64) -> Point {
+        // De Casteljau: numerically stable and cheap for one point.
+        let a = lerp(self.p0, self.p1, t);
+        let b = lerp(self.p1, self.p2, t);
+        let c = lerp(self.p2, self.p3, t);
+        lerp(lerp(a, b, t), lerp(b, c, t), t)
+    }
+
+    /// First derivative at `t`.
+    fn derivative(&self, t: f64) -> Point {
+        let a = sub(self.p1, self.p0);
+        let b = sub(self.p2, self.p1);
+        let c = sub(self.p3, self.p2);
+        scale(lerp(lerp(a, b, t), lerp(b, c, t), t), 3.0)
+    }
+
+    /// Second derivative at `t`.
+    fn second_derivative(&self, t: f64) -> Point {
+        let a = sub(sub(self.p2, self.p1), sub(self.p1, self.p0));
+        let b = sub(sub(self.p3, self.p2), sub(self.p2, self.p1));
+        scale(lerp(a, b, t), 6.0)
+    }
+
+    /// Append points approximating the curve to `out`, excluding `p0`,
+    /// so that no point of the curve is farther than about `tolerance`
+    /// from the resulting segments.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn flatten_into(&self, tolerance: f64, out: &mut Vec<Point>) {
+        // Wang's bound on the segment count for a flatness tolerance.
+        let d = length(add(sub(self.p0, scale(self.p1, 2.0)), self.p2))
+            .max(length(add(sub(self.p1, scale(self.p2, 2.0)), self.p3)));
+        let n = (0.75 * d / tolerance).sqrt().ceil().clamp(1.0, 256.0) as usize;
+        out.extend((1..n).map(|i| self.eval(i as f64 / n as f64)));
+        out.push(self.p3);
+    }
+}
+
+/// Fit a chain of cubic Bezier segments through `points`.
+///
+/// Every point lies within `tolerance` of the returned curve.  Turns
+/// sharper than [`CORNER_ANGLE`] are kept as corners; elsewhere
+/// consecutive segments share a tangent.  Consecutive duplicate points
+/// are ignored.  Returns an empty chain for fewer than 2 distinct
+/// points.
+#[must_use]
+pub fn fit_cubic_beziers(points: &[Point], tolerance: f64) -> Vec<CubicBezier> {
+    let points = dedup(points);
+    let mut curves = Vec::new();
+    let mut start = 0;
+    for end in 1..points.len() {
+        if end + 1 == points.len() || is_corner(points[end - 1], points[end], points[end + 1]) {
+            let run = &points[start..=end];
+            let first_tangent = normalize(sub(run[1], run[0]));
+            let last_tangent = normalize(sub(run[run.len() - 2], run[run.len() - 1]));
+            fit_run(run, first_tangent, last_tangent, tolerance, &mut curves);
+            start = end;
+        }
+    }
+    curves
+}
+
+/// Turning angle (radians) above which [`fit_cubic_beziers`] keeps a
+/// sharp corner instead of fitting a smooth curve through it.
+pub const CORNER_ANGLE: f64 = std::f64::consts::FRAC_PI_3;
+
+/// Newton reparameterization passes tried before splitting a run.
+const MAX_REPARAMETERIZATIONS: usize = 4;
+
+/// Fit `points` (at least 2) with tangent directions fixed at both
+/// ends, splitting at the worst point until every piece is within
+/// `tolerance`.
+///
+/// `first_tangent` points forward from the first point and
+/// `last_tangent` backward from the last, as in Schneider's "An
+/// Algorithm for Automatically Fitting Digitized Curves" (Graphics
+/// Gems, 1990).
+fn fit_run(
+    points: &[Point],
+    first_tangent: Point,
+    last_tangent: Point,
+    tolerance: f64,
+    out: &mut Vec<CubicBezier>,
+) {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    if points.len() == 2 {
+        let handle = first.distance(last) / 3.0;
+        out.push(CubicBezier {
+            p0: first,
+            p1: add(first, scale(first_tangent, handle)),
+            p2: add(last, scale(last_tangent, handle)),
+            p3: last,
+        });
+        return;
+    }
+
+    let mut params = chord_length_parameters(points);
+    let mut curve = least_squares_bezier(points, &params, first_tangent, last_tangent);
+    let (mut max_error, mut split) = max_error_of(points, &params, &curve);
+    let tolerance_sq = tolerance * tolerance;
+    if max_error > tolerance_sq && max_error <= 4.0 * tolerance_sq {
+        for _ in 0..MAX_REPARAMETERIZATIONS {
+            params = reparameterize(points, &params, &curve);
+            curve = least_squares_bezier(points, &params, first_tangent, last_tangent);
+            (max_error, split) = max_error_of(points, &params, &curve);
+            if max_error <= tolerance_sq {
+                break;
+            }
+        }
+    }
+    if max_error <= tolerance_sq {
+        out.push(curve);
+        return;
+    }
+
+    let mut center = normalize(sub(points[split - 1], points[split + 1]));
+    if center == Point::new(0.0, 0.0) {
+        center = normalize(sub(points[split - 1], points[split]));
+    }
+    fit_run(&points[..=split], first_tangent, center, tolerance, out);
+    fit_run(
+        &points[split..],
+        scale(center, -1.0),
+        last_tangent,
+        tolerance,
+        out,
+    );
+}
+
+/// Parameters in `[0, 1]` proportional to cumulative chord length.
+fn chord_length_parameters(points: &[Point]) -> Vec<f64> {
+    let mut params = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    params.push(0.0);
+    for w in points.windows(2) {
+        total += w[0].distance(w[1]);
+        params.push(total);
+    }
+    for u in &mut params {
+        *u /= total;
+    }
+    params
+}
+
+/// Least-squares control points for fixed end points and tangents.
+///
+/// Falls back to handles of a third of the chord when the solve is
+/// degenerate or yields non-positive handle lengths.
+fn least_squares_bezier(
+    points: &[Point],
+    params: &[f64],
+    first_tangent: Point,
+    last_tangent: Point,
+) -> CubicBezier {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&p, &u) in points.iter().zip(params) {
+        let v = 1.0 - u;
+        let b0 = v * v * v;
+        let b1 = 3.0 * u * v * v;
+        let b2 = 3.0 * u * u * v;
+        let b3 = u * u * u;
+        let a0 = scale(first_tangent, b1);
+        let a1 = scale(last_tangent, b2);
+        let base = add(scale(first, b0 + b1), scale(last, b2 + b3));
+        let residual = sub(p, base);
+        c00 += dot(a0, a0);
+        c01 += dot(a0, a1);
+        c11 += dot(a1, a1);
+        x0 += dot(a0, residual);
+        x1 += dot(a1, residual);
+    }
+
+    let chord = first.distance(last);
+    let det = c00.mul_add(c11, -(c01 * c01));
+    let (mut alpha_first, mut alpha_last) = if det.abs() > f64::EPSILON {
+        (
+            x0.mul_add(c11, -(x1 * c01)) / det,
+            c00.mul_add(x1, -(c01 * x0)) / det,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+    let min_alpha = 1e-6 * chord;
+    if alpha_first < min_alpha || alpha_last < min_alpha {
+        alpha_first = chord / 3.0;
+        alpha_last = chord / 3.0;
+    }
+    CubicBezier {
+        p0: first,
+        p1: add(first, scale(first_tangent, alpha_first)),
+        p2: add(last, scale(last_tangent, alpha_last)),
+        p3: last,
+    }
+}
+
+/// Largest squared distance between an interior point and its curve
+/// point, and that point's index.
+fn max_error_of(points: &[Point], params: &[f64], curve: &CubicBezier) -> (f64, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for i in 1..points.len() - 1 {
+        let error = curve.eval(params[i]).distance_squared(points[i]);
+        if error >= worst.0 {
+            worst = (error, i);
+        }
+    }
+    worst
+}
+
+/// One Newton-Raphson step moving each parameter toward the curve
+/// point closest to its input point.
+fn reparameterize(points: &[Point], params: &[f64], curve: &CubicBezier) -> Vec<f64> {
+    points
+        .iter()
+        .zip(params)
+        .map(|(&p, &u)| {
+            let diff = sub(curve.eval(u), p);
+            let d1 = curve.derivative(u);
+            let d2 = curve.second_derivative(u);
+            let numerator = dot(diff, d1);
+            let denominator = dot(d1, d1) + dot(diff, d2);
+            if denominator.abs() <= f64::EPSILON {
+                u
+            } else {
+                (u - numerator / denominator).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+/// Whether the path turns by more than [`CORNER_ANGLE`] at `b`.
+fn is_corner(a: Point, b: Point, c: Point) -> bool {
+    let incoming = normalize(sub(b, a));
+    let outgoing = normalize(sub(c, b));
+    dot(incoming, outgoing) < CORNER_ANGLE.cos()
+}
+
+/// One Chaikin pass: every segment contributes its ¼ and ¾ points.
+/// Open polylines keep their end points; closed ones stay closed.
+fn chaikin(points: &[Point]) -> Vec<Point> {
+    let closed = is_closed(points);
+    let mut out = Vec::with_capacity(points.len() * 2);
+    if !closed {
+        out.push(points[0]);
+    }
+    for w in points.windows(2) {
+        out.push(lerp(w[0], w[1], 0.25));
+        out.push(lerp(w[0], w[1], 0.75));
+    }
+    if closed {
+        out.push(out[0]);
+    } else {
+        out.push(points[points.len() - 1]);
+    }
+    out
+}
+
+/// Sample a centripetal Catmull-Rom spline through `points` (at least
+/// 3, no consecutive duplicates) about every `spacing` units.
+///
+/// Open ends use a mirrored phantom point so the spline starts and
+/// ends along the first and last segments.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn catmull_rom(points: &[Point], spacing: f64) -> Vec<Point> {
+    let closed = is_closed(points);
+    let n = points.len();
+    let neighbour = |i: usize, offset: isize| -> Point {
+        let j = i.checked_add_signed(offset);
+        match j {
+            Some(j) if j < n => points[j],
+            // `points[0] == points[n - 1]` on a closed loop, so step
+            // over the duplicate when wrapping.
+            _ if closed && offset < 0 => points[n - 2],
+            _ if closed => points[1],
+            _ if offset < 0 => sub(scale(points[0], 2.0), points[1]),
+            _ => sub(scale(points[n - 1], 2.0), points[n - 2]),
+        }
+    };
+
+    let mut out = vec![points[0]];
+    for i in 0..n - 1 {
+        let p0 = neighbour(i, -1);
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        let p3 = neighbour(i, 2);
+        let t1 = p0.distance(p1).sqrt();
+        let t2 = t1 + p1.distance(p2).sqrt();
+        let t3 = t2 + p2.distance(p3).sqrt();
+        let samples = (p1.distance(p2) / spacing).ceil().max(1.0) as usize;
+        for k in 1..samples {
+            let t = (t2 - t1).mul_add(k as f64 / samples as f64, t1);
+            let a1 = lerp_at(p0, p1, 0.0, t1, t);
+            let a2 = lerp_at(p1, p2, t1, t2, t);
+            let a3 = lerp_at(p2, p3, t2, t3, t);
+            let b1 = lerp_at(a1, a2, 0.0, t2, t);
+            let b2 = lerp_at(a2, a3, t1, t3, t);
+            out.push(lerp_at(b1, b2, t1, t2, t));
+        }
+        out.push(p2);
+    }
+    out
+}
+
+/// `points` without consecutive duplicates.
+fn dedup(points: &[Point]) -> Vec<Point> {
+    let mut out = points.to_vec();
+    out.dedup();
+    out
+}
+
+/// Whether `points` forms a closed loop (at least a triangle whose
+/// last point repeats the first).
+fn is_closed(points: &[Point]) -> bool {
+    points.len() >= 4 && points.first() == points.last()
+}
+
+fn lerp(a: Point, b: Point, t: f64) -> Point {
+    Point::new((b.x - a.x).mul_add(t, a.x), (b.y - a.y).mul_add(t, a.y))
+}
+
+/// Interpolate between `a` at knot `ta` and `b` at knot `tb`.
+fn lerp_at(a: Point, b: Point, ta: f64, tb: f64, t: f64) -> Point {
+    lerp(a, b, (t - ta) / (tb - ta))
+}
+
+fn add(a: Point, b: Point) -> Point {
+    Point::new(a.x + b.x, a.y + b.y)
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    Point::new(a.x - b.x, a.y - b.y)
+}
+
+fn scale(a: Point, s: f64) -> Point {
+    Point::new(a.x * s, a.y * s)
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.x.mul_add(b.x, a.y * b.y)
+}
+
+fn length(a: Point) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// `a` scaled to unit length, or zero if `a` is zero.
+fn normalize(a: Point) -> Point {
+    let len = length(a);
+    if len > 0.0 { scale(a, 1.0 / len) } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(kind: CurveKind) -> CurveSettings {
+        CurveSettings {
+            kind,
+            ..CurveSettings::default()
+        }
+    }
+
+    /// A right-angle corner.
+    fn corner() -> Polyline {
+        Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.1, 0.0),
+            Point::new(0.1, 0.1),
+        ])
+    }
+
+    /// Points on a unit-radius arc, as RDP would leave them.
+    fn arc(count: usize) -> Vec<Point> {
+        (0..count)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss)]
+                let angle = std::f64::consts::FRAC_PI_2 * i as f64 / (count - 1) as f64;
+                Point::new(angle.cos(), angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn off_returns_input() {
+        let polyline = corner();
+        assert_eq!(settings(CurveKind::Off).smooth(&polyline), polyline);
+    }
+
+    #[test]
+    fn chaikin_cuts_corners_and_keeps_ends() {
+        let smoothed = settings(CurveKind::Chaikin).smooth(&corner());
+        let points = smoothed.points();
+        assert_eq!(points.first(), corner().first());
+        assert_eq!(points.last(), corner().last());
+        assert!(
+            !points.contains(&Point::new(0.1, 0.0)),
+            "the corner point should be cut: {points:?}",
+        );
+        // Each pass turns n segments into 2n interior points: 2 → 4 → 10.
+        assert_eq!(points.len(), 2 + 10);
+    }
+
+    #[test]
+    fn chaikin_keeps_closed_loops_closed() {
+        let square = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+            Point::new(0.0, 0.0),
+        ]);
+        let smoothed = settings(CurveKind::Chaikin).smooth(&square);
+        assert_eq!(smoothed.first(), smoothed.last());
+        assert!(!smoothed.points().contains(&Point::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_inputs_at_spacing() {
+        let polyline = corner();
+        let smoothed = settings(CurveKind::CatmullRom).smooth(&polyline);
+        for p in polyline.points() {
+            assert!(smoothed.points().contains(p), "missing {p:?}");
+        }
+        let max_step = smoothed
+            .points()
+            .windows(2)
+            .map(|w| w[0].distance(w[1]))
+            .fold(0.0, f64::max);
+        assert!(
+            max_step <= 1.5 * CurveSettings::DEFAULT_SPACING,
+            "max_step = {max_step}",
+        );
+    }
+
+    #[test]
+    fn bezier_fit_is_within_tolerance() {
+        let points = arc(50);
+        let tolerance = 1e-3;
+        let curves = fit_cubic_beziers(&points, tolerance);
+        assert!(
+            curves.len() < 5,
+            "a quarter circle needs few cubics, got {}",
+            curves.len()
+        );
+        assert_eq!(curves[0].p0, points[0]);
+        assert_eq!(curves[curves.len() - 1].p3, points[points.len() - 1]);
+        for w in curves.windows(2) {
+            assert_eq!(w[0].p3, w[1].p0, "segments should be contiguous");
+        }
+        // Every input point is close to some densely sampled curve point.
+        let samples: Vec<Point> = curves
+            .iter()
+            .flat_map(|c| (0..=2000).map(move |i| c.eval(f64::from(i) / 2000.0)))
+            .collect();
+        for p in &points {
+            let nearest = samples
+                .iter()
+                .map(|s| s.distance(*p))
+                .fold(f64::INFINITY, f64::min);
+            assert!(nearest <= 2.0 * tolerance, "{p:?} is {nearest} away");
+        }
+    }
+
+    #[test]
+    fn bezier_fit_keeps_sharp_corners() {
+        let curves = fit_cubic_beziers(corner().points(), 1e-3);
+        assert!(
+            curves.iter().any(|c| c.p3 == Point::new(0.1, 0.0)),
+            "the corner should be a segment end point: {curves:?}",
+        );
+    }
+
+    #[test]
+    fn bezier_smoothing_ends_at_input_ends() {
+        let polyline = Polyline::new(arc(10));
+        let smoothed = settings(CurveKind::Bezier).smooth(&polyline);
+        assert_eq!(smoothed.first(), polyline.first());
+        assert_eq!(smoothed.last(), polyline.last());
+    }
+
+    #[test]
+    fn short_polylines_are_unchanged() {
+        let segment = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]);
+        for kind in [CurveKind::Chaikin, CurveKind::CatmullRom, CurveKind::Bezier] {
+            assert_eq!(settings(kind).smooth(&segment), segment, "{kind}");
+        }
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_parameters() {
+        for bad in [
+            CurveSettings {
+                iterations: 0,
+                ..CurveSettings::default()
+            },
+            CurveSettings {
+                iterations: CurveSettings::MAX_ITERATIONS + 1,
+                ..CurveSettings::default()
+            },
+            CurveSettings {
+                spacing: 0.0,
+                ..CurveSettings::default()
+            },
+            CurveSettings {
+                tolerance: f64::NAN,
+                ..CurveSettings::default()
+            },
+        ] {
+            assert!(bad.validate().is_err(), "{bad:?}");
+        }
+        assert!(CurveSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn output_eq_ignores_unused_parameters() {
+        let a = settings(CurveKind::Chaikin);
+        let b = CurveSettings {
+            tolerance: 0.5,
+            ..a
+        };
+        assert!(a.output_eq(&b));
+        let c = CurveSettings { iterations: 3, ..a };
+        assert!(!a.output_eq(&c));
+    }
+}
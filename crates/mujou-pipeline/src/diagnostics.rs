@@ -5,18 +5,23 @@
 //! metrics via [`PipelineStage::metrics()`](crate::pipeline::PipelineStage::metrics).
 //!
 //! This crate is sans-IO and does not read the system clock.
-//! [`process_staged_with_diagnostics`] is generic over a [`Clock`]
+//! [`process_staged_with_diagnostics`] and
+//! [`run_cached_with_diagnostics`] are generic over a [`Clock`]
 //! trait so callers can supply any instant type — `std::time::Instant`
 //! on native, `web_time::Instant` on WASM, or a fake clock in tests.
 //!
 //! Duration fields use [`std::time::Duration`] and are serialized as
 //! fractional seconds (`f64`) for JSON compatibility.
 
+use std::cell::RefCell;
+use std::sync::Arc;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::join::SegmentLengths;
 use crate::mst_join::JoinQualityMetrics;
+use crate::pipeline::PipelineCache;
 
 /// Serde support for `std::time::Duration` as fractional seconds.
 mod duration_serde {
@@ -51,32 +56,43 @@ pub struct PipelineDiagnostics {
     pub decode: StageDiagnostics,
     /// Stage 1: downsampling to working resolution.
     pub downsample: StageDiagnostics,
-    /// Stage 2: Gaussian blur (RGBA, preserves color for UI preview).
+    /// Stage 2: contrast normalization.  Absent from diagnostics
+    /// recorded before the tone stage existed; those read back as a
+    /// zero-duration `Off` entry.
+    #[serde(default = "StageDiagnostics::tone_off")]
+    pub tone: StageDiagnostics,
+    /// Stage 3: Gaussian blur (RGBA, preserves color for UI preview).
     pub blur: StageDiagnostics,
-    /// Stage 3: Canny edge detection.
+    /// Stage 4: Canny edge detection.
     pub edge_detection: StageDiagnostics,
-    /// Stage 4: edge map inversion (only when `config.invert == true`).
+    /// Stage 5: edge map inversion (only when `config.invert == true`).
     ///
     /// **Note:** The invert operation runs inside the edge-detection stage
     /// transition, so its `duration` is always `Duration::ZERO`. The
     /// actual inversion cost is included in `edge_detection.duration`.
     /// This entry exists to report the post-inversion edge pixel count.
     pub invert: Option<StageDiagnostics>,
-    /// Stage 5: contour tracing.
+    /// Stage 6: contour tracing.
     pub contour_tracing: StageDiagnostics,
-    /// Stage 6: RDP path simplification.
+    /// Stage 7: RDP path simplification.
     pub simplification: StageDiagnostics,
-    /// Stage 7: canvas clipping and border generation.
+    /// Stage 8: canvas clipping and border generation.
     pub canvas: StageDiagnostics,
-    /// Stage 8: path ordering + joining.
+    /// Stage 9: path ordering + joining.
     pub join: StageDiagnostics,
-    /// Stage 9: segment subsampling.
+    /// Stage 10: segment subsampling.
     pub output: StageDiagnostics,
     /// Total wall-clock duration of the entire pipeline (seconds).
     #[serde(with = "duration_serde")]
     pub total_duration: Duration,
     /// Summary counts across all stages.
     pub summary: PipelineSummary,
+    /// Render-similarity scores of the output against the edge map.
+    ///
+    /// Not computed by the diagnostics runners; callers that want it
+    /// fill it in with [`crate::quality::measure`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<crate::QualityMetrics>,
 }
 
 /// Diagnostics for a single pipeline stage.
@@ -89,6 +105,26 @@ pub struct StageDiagnostics {
     pub metrics: StageMetrics,
 }
 
+impl StageDiagnostics {
+    /// Zero-duration tone diagnostics for a run without tone mapping.
+    fn tone_off() -> Self {
+        Self {
+            duration: Duration::ZERO,
+            metrics: StageMetrics::Tone {
+                kind: crate::ToneKind::Off.to_string(),
+                mean_before: 0.0,
+                mean_after: 0.0,
+            },
+        }
+    }
+}
+
+/// EXIF orientation of an upright image, for reports that predate the
+/// decode `orientation` field.
+const fn exif_upright() -> u8 {
+    1
+}
+
 /// Stage-specific metrics that vary by pipeline stage.
 ///
 /// Each variant captures the counts and sizes meaningful for that
@@ -99,6 +135,10 @@ pub enum StageMetrics {
     Decode {
         /// Size of the input image bytes.
         input_bytes: usize,
+        /// EXIF orientation tag value (1–8) applied to make the image
+        /// upright (1 when the file carries none).
+        #[serde(default = "exif_upright")]
+        orientation: u8,
         /// Decoded image width in pixels.
         width: u32,
         /// Decoded image height in pixels.
@@ -123,10 +163,22 @@ pub enum StageMetrics {
         /// Whether downsampling was actually applied.
         applied: bool,
     },
-    /// Gaussian blur metrics.
+    /// Contrast normalization metrics.
+    Tone {
+        /// Tone mapping used.
+        kind: String,
+        /// Mean luminance (0–255) before tone mapping.
+        mean_before: f64,
+        /// Mean luminance (0–255) after tone mapping.
+        mean_after: f64,
+    },
+    /// Smoothing (blur) metrics.
     Blur {
         /// Sigma value used for the blur kernel.
         sigma: f32,
+        /// Smoothing strategy used.
+        #[serde(default)]
+        smoothing: String,
     },
     /// Canny edge detection metrics.
     EdgeDetection {
@@ -165,6 +217,9 @@ pub enum StageMetrics {
         tolerance: f64,
         /// Number of polylines after simplification.
         polyline_count: usize,
+        /// Polylines dropped by the minimum contour size filter.
+        #[serde(default)]
+        dropped_count: usize,
         /// Total points before simplification.
         points_before: usize,
         /// Total points after simplification.
@@ -200,6 +255,15 @@ pub enum StageMetrics {
         output_point_count: usize,
         /// Ratio of output to input points (> 1.0 means retrace added points).
         expansion_ratio: f64,
+        /// Connectors rerouted along the canvas border (0 when
+        /// `perimeter_connectors` is disabled).
+        #[serde(default)]
+        perimeter_reroutes: usize,
+        /// Joined path length by segment provenance (normalized units).
+        /// `segment_lengths.connector` is the visible new line the join
+        /// added.
+        #[serde(default)]
+        segment_lengths: SegmentLengths,
         /// Quality metrics from the MST joiner (issue #89 evaluation criteria).
         ///
         /// `None` for non-MST joiners.
@@ -207,12 +271,19 @@ pub enum StageMetrics {
     },
     /// Segment subsampling metrics.
     Output {
+        /// Subsample mode used (`Cartesian` or `Polar`).
+        #[serde(default)]
+        mode: String,
         /// Maximum segment length in normalized units.
         max_length: f64,
         /// Points in the joined path before subsampling.
         points_before: usize,
         /// Points after subsampling.
         points_after: usize,
+        /// Segments rerouted around the polar center (0 when
+        /// `center_avoid_radius` is disabled).
+        #[serde(default)]
+        center_reroutes: usize,
     },
 }
 
@@ -261,6 +332,7 @@ impl PipelineDiagnostics {
             let mut s = vec![
                 ("Decode", &self.decode),
                 ("Downsample", &self.downsample),
+                ("Tone", &self.tone),
                 ("Blur", &self.blur),
                 ("Edge Detection", &self.edge_detection),
             ];
@@ -291,6 +363,12 @@ impl PipelineDiagnostics {
             "Contours: {}  |  Final path points: {}",
             self.summary.contour_count, self.summary.final_point_count,
         ));
+        if let Some(ref q) = self.quality {
+            lines.push(format!(
+                "Quality (tol={:.1}px): recall={:.3} precision={:.3} f1={:.3} chamfer={:.2}px ssim={:.3}",
+                q.tolerance_px, q.edge_recall, q.edge_precision, q.edge_f1, q.chamfer_distance, q.ssim,
+            ));
+        }
 
         lines.join("\n")
     }
@@ -307,11 +385,16 @@ fn format_metrics(metrics: &StageMetrics) -> String {
     match metrics {
         StageMetrics::Decode {
             input_bytes,
+            orientation,
             width,
             height,
             ..
         } => {
-            format!("{input_bytes} bytes -> {width}x{height}")
+            if *orientation <= 1 {
+                format!("{input_bytes} bytes -> {width}x{height}")
+            } else {
+                format!("{input_bytes} bytes -> {width}x{height}, EXIF orientation {orientation}")
+            }
         }
         StageMetrics::Downsample {
             original_width,
@@ -330,7 +413,12 @@ fn format_metrics(metrics: &StageMetrics) -> String {
                 format!("{original_width}x{original_height} (no change, <= {max_dimension})",)
             }
         }
-        StageMetrics::Blur { sigma } => format!("sigma={sigma:.2}"),
+        StageMetrics::Tone {
+            kind,
+            mean_before,
+            mean_after,
+        } => format!("{kind} mean_luma={mean_before:.1}->{mean_after:.1}"),
+        StageMetrics::Blur { sigma, smoothing } => format!("{smoothing} sigma={sigma:.2}"),
         StageMetrics::EdgeDetection {
             low_threshold,
             high_threshold,
@@ -364,13 +452,14 @@ fn format_metrics(metrics: &StageMetrics) -> String {
         }
         StageMetrics::Simplification {
             tolerance,
+            dropped_count,
             points_before,
             points_after,
             reduction_ratio,
             ..
         } => {
             format!(
-                "tol={tolerance:.2} {points_before}->{points_after} pts ({:.1}% reduction)",
+                "tol={tolerance:.2} {points_before}->{points_after} pts ({:.1}% reduction) dropped={dropped_count}",
                 reduction_ratio * 100.0,
             )
         }
@@ -391,10 +480,13 @@ fn format_metrics(metrics: &StageMetrics) -> String {
             input_point_count,
             output_point_count,
             expansion_ratio,
+            perimeter_reroutes,
+            segment_lengths: lengths,
             quality,
         } => {
             let base = format!(
-                "{strategy} {input_polyline_count} polys, {input_point_count}->{output_point_count} pts (x{expansion_ratio:.2})",
+                "{strategy} {input_polyline_count} polys, {input_point_count}->{output_point_count} pts (x{expansion_ratio:.2}) perimeter_reroutes={perimeter_reroutes} new_line={:.4} (contour={:.4} retrace={:.4} border={:.4})",
+                lengths.connector, lengths.contour, lengths.retrace, lengths.border,
             );
             if let Some(q) = quality {
                 let longest_info = q
@@ -427,11 +519,15 @@ fn format_metrics(metrics: &StageMetrics) -> String {
             }
         }
         StageMetrics::Output {
+            mode,
             max_length,
             points_before,
             points_after,
+            center_reroutes,
         } => {
-            format!("max_len={max_length:.4} {points_before}->{points_after} pts")
+            format!(
+                "{mode} max_len={max_length:.4} {points_before}->{points_after} pts center_reroutes={center_reroutes}",
+            )
         }
     }
 }
@@ -441,6 +537,20 @@ pub(crate) fn count_edge_pixels(image: &image::GrayImage) -> u64 {
     image.pixels().filter(|p| p.0[0] == 255).count() as u64
 }
 
+/// Mean Rec. 709 luminance (0–255) of an RGBA image, ignoring alpha.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn mean_luma(image: &image::RgbaImage) -> f64 {
+    let pixels = u64::from(image.width()) * u64::from(image.height());
+    if pixels == 0 {
+        return 0.0;
+    }
+    let sum: u64 = crate::edge::rgba_to_luma(image)
+        .pixels()
+        .map(|p| u64::from(p.0[0]))
+        .sum();
+    sum as f64 / pixels as f64
+}
+
 /// Statistics for a set of contour polylines.
 pub(crate) struct ContourStats {
     /// Total number of points across all contours.
@@ -520,10 +630,7 @@ pub fn process_staged_with_diagnostics<C: Clock>(
     config: &crate::PipelineConfig,
     clock: &C,
 ) -> Result<(crate::StagedResult, PipelineDiagnostics), crate::PipelineError> {
-    use crate::pipeline::{
-        Advance, Blurred, Canvas, ContoursTraced, Decoded, Downsampled, EdgesDetected, Joined,
-        Output, PipelineStage as _, STAGE_COUNT, Simplified, Stage,
-    };
+    use crate::pipeline::{Advance, STAGE_COUNT, Stage};
 
     let pipeline_start = clock.now();
     let mut stage: Stage = crate::Pipeline::new(image_bytes.to_vec(), config.clone()).into();
@@ -553,60 +660,144 @@ pub fn process_staged_with_diagnostics<C: Clock>(
             Advance::Complete(done) => {
                 let total_duration = clock.elapsed(&pipeline_start);
                 let result = done.complete()?;
-
-                let summary = PipelineSummary {
-                    image_width: result.dimensions.width,
-                    image_height: result.dimensions.height,
-                    pixel_count: u64::from(result.dimensions.width)
-                        * u64::from(result.dimensions.height),
-                    contour_count: result.contours.len(),
-                    final_point_count: result.joined.len(),
-                };
-
-                let diag_missing = |name: &str| {
-                    crate::PipelineError::InvalidConfig(format!(
-                        "diagnostics bug: {name} diagnostics missing"
-                    ))
-                };
-                let pipeline_diagnostics = PipelineDiagnostics {
-                    decode: stage_diags[Decoded::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Decoded::NAME))?,
-                    downsample: stage_diags[Downsampled::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Downsampled::NAME))?,
-                    blur: stage_diags[Blurred::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Blurred::NAME))?,
-                    edge_detection: stage_diags[EdgesDetected::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(EdgesDetected::NAME))?,
-                    invert: invert_diag,
-                    contour_tracing: stage_diags[ContoursTraced::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(ContoursTraced::NAME))?,
-                    simplification: stage_diags[Simplified::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Simplified::NAME))?,
-                    canvas: stage_diags[Canvas::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Canvas::NAME))?,
-                    join: stage_diags[Joined::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Joined::NAME))?,
-                    output: stage_diags[Output::INDEX]
-                        .take()
-                        .ok_or_else(|| diag_missing(Output::NAME))?,
-                    total_duration,
-                    summary,
-                };
-
+                let pipeline_diagnostics =
+                    assemble(stage_diags, invert_diag, total_duration, &result)?;
                 break Ok((result, pipeline_diagnostics));
             }
         }
     }
 }
 
+/// Run the pipeline through a [`PipelineCache`], with per-stage timing
+/// instrumentation.
+///
+/// Behaves like [`PipelineCache::run_with_progress`]: only stages whose
+/// config changed since the cached run are recomputed, and the cache is
+/// left untouched on error.  Computed stages are timed with the
+/// supplied [`Clock`].  Stages served from the cache report
+/// [`Duration::ZERO`] together with the metrics recorded when they were
+/// last computed, so `total_duration` reflects only the work done by
+/// this call.
+///
+/// # Errors
+///
+/// Returns [`PipelineError`](crate::PipelineError) if any pipeline
+/// stage fails.
+pub fn run_cached_with_diagnostics<C: Clock>(
+    cache: &mut Option<PipelineCache>,
+    image_bytes: Vec<u8>,
+    config: crate::PipelineConfig,
+    clock: &C,
+) -> Result<(Arc<crate::StagedResult>, PipelineDiagnostics), crate::PipelineError> {
+    use crate::pipeline::STAGE_COUNT;
+
+    let pipeline_start = clock.now();
+    let last = RefCell::new(clock.now());
+    let durations = RefCell::new([Duration::ZERO; STAGE_COUNT]);
+    let on_stage = |index: usize, cached: bool| {
+        let elapsed = clock.elapsed(&last.replace(clock.now()));
+        if !cached && let Some(d) = durations.borrow_mut().get_mut(index) {
+            *d = elapsed;
+        }
+    };
+
+    let staged = PipelineCache::run_with_progress(
+        cache,
+        image_bytes,
+        config,
+        &on_stage,
+        &crate::Progress::NONE,
+    )?;
+    let total_duration = clock.elapsed(&pipeline_start);
+
+    let (metrics, invert) = cache
+        .as_ref()
+        .map(PipelineCache::stage_metrics)
+        .ok_or_else(|| {
+            crate::PipelineError::InvalidConfig("diagnostics bug: cache missing".to_owned())
+        })?;
+    let durations = durations.into_inner();
+    let stage_diags = std::array::from_fn(|i| {
+        metrics[i].clone().map(|metrics| StageDiagnostics {
+            duration: durations[i],
+            metrics,
+        })
+    });
+    let invert_diag = invert.map(|metrics| StageDiagnostics {
+        duration: Duration::ZERO,
+        metrics: metrics.clone(),
+    });
+    let pipeline_diagnostics = assemble(stage_diags, invert_diag, total_duration, &staged)?;
+    Ok((staged, pipeline_diagnostics))
+}
+
+/// Build [`PipelineDiagnostics`] from per-stage diagnostics indexed by
+/// stage.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::InvalidConfig`](crate::PipelineError::InvalidConfig)
+/// if any stage after `Pending` has no diagnostics, which indicates a bug.
+fn assemble(
+    mut stage_diags: [Option<StageDiagnostics>; crate::pipeline::STAGE_COUNT],
+    invert_diag: Option<StageDiagnostics>,
+    total_duration: Duration,
+    result: &crate::StagedResult,
+) -> Result<PipelineDiagnostics, crate::PipelineError> {
+    use crate::pipeline::{
+        Blurred, Canvas, ContoursTraced, Decoded, Downsampled, EdgesDetected, Joined, Output,
+        PipelineStage as _, Simplified, Toned,
+    };
+
+    let summary = PipelineSummary {
+        image_width: result.dimensions.width,
+        image_height: result.dimensions.height,
+        pixel_count: u64::from(result.dimensions.width) * u64::from(result.dimensions.height),
+        contour_count: result.contours.len(),
+        final_point_count: result.joined.len(),
+    };
+
+    let diag_missing = |name: &str| {
+        crate::PipelineError::InvalidConfig(format!("diagnostics bug: {name} diagnostics missing"))
+    };
+    Ok(PipelineDiagnostics {
+        decode: stage_diags[Decoded::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Decoded::NAME))?,
+        downsample: stage_diags[Downsampled::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Downsampled::NAME))?,
+        tone: stage_diags[Toned::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Toned::NAME))?,
+        blur: stage_diags[Blurred::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Blurred::NAME))?,
+        edge_detection: stage_diags[EdgesDetected::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(EdgesDetected::NAME))?,
+        invert: invert_diag,
+        contour_tracing: stage_diags[ContoursTraced::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(ContoursTraced::NAME))?,
+        simplification: stage_diags[Simplified::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Simplified::NAME))?,
+        canvas: stage_diags[Canvas::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Canvas::NAME))?,
+        join: stage_diags[Joined::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Joined::NAME))?,
+        output: stage_diags[Output::INDEX]
+            .take()
+            .ok_or_else(|| diag_missing(Output::NAME))?,
+        total_duration,
+        summary,
+        quality: None,
+    })
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -734,6 +925,7 @@ mod tests {
 
         assert_eq!(diag.decode.duration, ten_ms);
         assert_eq!(diag.downsample.duration, ten_ms);
+        assert_eq!(diag.tone.duration, ten_ms);
         assert_eq!(diag.blur.duration, ten_ms);
         assert_eq!(diag.edge_detection.duration, ten_ms);
         assert!(diag.invert.is_none());
@@ -743,7 +935,7 @@ mod tests {
         assert_eq!(diag.canvas.duration, ten_ms);
         assert_eq!(diag.join.duration, ten_ms);
         assert_eq!(diag.output.duration, ten_ms);
-        assert_eq!(diag.total_duration, Duration::from_millis(110));
+        assert_eq!(diag.total_duration, Duration::from_millis(120));
 
         // Summary should reflect the 40x40 image.
         assert_eq!(diag.summary.image_width, 40);
@@ -758,6 +950,48 @@ mod tests {
         assert!(report.contains("Edge Detection"));
     }
 
+    #[test]
+    fn cached_diagnostics_reuse_metrics_of_skipped_stages() {
+        let png = sharp_edge_png(40, 40);
+        let first = crate::PipelineConfig {
+            zoom: 0.5,
+            ..crate::PipelineConfig::default()
+        };
+        let second = crate::PipelineConfig {
+            simplify_tolerance: first.simplify_tolerance * 2.0,
+            ..first.clone()
+        };
+        let clock = FakeClock::new();
+        let mut cache = None;
+
+        let (_, diag) = run_cached_with_diagnostics(&mut cache, png.clone(), first, &clock)
+            .expect("first run should succeed");
+        assert!(diag.decode.duration > Duration::ZERO);
+
+        let (_, cached) =
+            run_cached_with_diagnostics(&mut cache, png.clone(), second.clone(), &clock)
+                .expect("second run should succeed");
+        let (_, fresh) = process_staged_with_diagnostics(&png, &second, &clock)
+            .expect("fresh run should succeed");
+
+        assert_eq!(cached.decode.duration, Duration::ZERO);
+        assert_eq!(cached.contour_tracing.duration, Duration::ZERO);
+        assert!(cached.simplification.duration > Duration::ZERO);
+        assert!(cached.join.duration > Duration::ZERO);
+
+        // Cached and recomputed stages both report the same metrics as
+        // a fresh run with the new config.
+        let json = |d: &StageDiagnostics| serde_json::to_string(&d.metrics).unwrap();
+        assert_eq!(json(&cached.decode), json(&fresh.decode));
+        assert_eq!(json(&cached.contour_tracing), json(&fresh.contour_tracing));
+        assert_eq!(json(&cached.simplification), json(&fresh.simplification));
+        assert_eq!(json(&cached.join), json(&fresh.join));
+        assert_eq!(
+            cached.summary.final_point_count,
+            fresh.summary.final_point_count
+        );
+    }
+
     #[test]
     fn fake_clock_diagnostics_with_invert_and_canvas() {
         let png = sharp_edge_png(40, 40);
@@ -776,6 +1010,7 @@ mod tests {
 
         assert_eq!(diag.decode.duration, ten_ms);
         assert_eq!(diag.downsample.duration, ten_ms);
+        assert_eq!(diag.tone.duration, ten_ms);
         assert_eq!(diag.blur.duration, ten_ms);
         assert_eq!(diag.edge_detection.duration, ten_ms);
 
@@ -798,7 +1033,7 @@ mod tests {
 
         assert_eq!(diag.join.duration, ten_ms);
         assert_eq!(diag.output.duration, ten_ms);
-        assert_eq!(diag.total_duration, Duration::from_millis(110));
+        assert_eq!(diag.total_duration, Duration::from_millis(120));
 
         // Summary should reflect the 40x40 image.
         assert_eq!(diag.summary.image_width, 40);
@@ -807,12 +1042,14 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::too_many_lines)]
     fn report_produces_nonempty_string() {
         let diag = PipelineDiagnostics {
             decode: StageDiagnostics {
                 duration: Duration::from_millis(10),
                 metrics: StageMetrics::Decode {
                     input_bytes: 1000,
+                    orientation: 6,
                     width: 100,
                     height: 100,
                     pixel_count: 10000,
@@ -830,9 +1067,20 @@ mod tests {
                     applied: false,
                 },
             },
+            tone: StageDiagnostics {
+                duration: Duration::from_millis(0),
+                metrics: StageMetrics::Tone {
+                    kind: "Off".to_owned(),
+                    mean_before: 128.0,
+                    mean_after: 128.0,
+                },
+            },
             blur: StageDiagnostics {
                 duration: Duration::from_millis(20),
-                metrics: StageMetrics::Blur { sigma: 1.4 },
+                metrics: StageMetrics::Blur {
+                    sigma: 1.4,
+                    smoothing: "Gaussian".to_owned(),
+                },
             },
             edge_detection: StageDiagnostics {
                 duration: Duration::from_millis(30),
@@ -860,6 +1108,7 @@ mod tests {
                 metrics: StageMetrics::Simplification {
                     tolerance: 2.0,
                     polyline_count: 10,
+                    dropped_count: 0,
                     points_before: 200,
                     points_after: 100,
                     reduction_ratio: 0.5,
@@ -883,15 +1132,19 @@ mod tests {
                     input_point_count: 100,
                     output_point_count: 150,
                     expansion_ratio: 1.5,
+                    perimeter_reroutes: 0,
+                    segment_lengths: SegmentLengths::default(),
                     quality: None,
                 },
             },
             output: StageDiagnostics {
                 duration: Duration::from_millis(2),
                 metrics: StageMetrics::Output {
+                    mode: "Cartesian".to_string(),
                     max_length: 2.0,
                     points_before: 150,
                     points_after: 200,
+                    center_reroutes: 0,
                 },
             },
             total_duration: Duration::from_millis(112),
@@ -902,6 +1155,7 @@ mod tests {
                 contour_count: 10,
                 final_point_count: 200,
             },
+            quality: None,
         };
 
         let report = diag.report();
@@ -909,6 +1163,7 @@ mod tests {
         assert!(report.contains("Pipeline Diagnostics Report"));
         assert!(report.contains("Edge Detection"));
         assert!(report.contains("Retrace"));
+        assert!(report.contains("EXIF orientation 6"));
     }
 
     #[test]
@@ -919,6 +1174,7 @@ mod tests {
                 duration: Duration::from_millis(10),
                 metrics: StageMetrics::Decode {
                     input_bytes: 5000,
+                    orientation: 1,
                     width: 800,
                     height: 600,
                     pixel_count: 480_000,
@@ -936,9 +1192,20 @@ mod tests {
                     applied: true,
                 },
             },
+            tone: StageDiagnostics {
+                duration: Duration::from_millis(0),
+                metrics: StageMetrics::Tone {
+                    kind: "Off".to_owned(),
+                    mean_before: 128.0,
+                    mean_after: 128.0,
+                },
+            },
             blur: StageDiagnostics {
                 duration: Duration::from_millis(10),
-                metrics: StageMetrics::Blur { sigma: 1.4 },
+                metrics: StageMetrics::Blur {
+                    sigma: 1.4,
+                    smoothing: "Gaussian".to_owned(),
+                },
             },
             edge_detection: StageDiagnostics {
                 duration: Duration::from_millis(20),
@@ -966,6 +1233,7 @@ mod tests {
                 metrics: StageMetrics::Simplification {
                     tolerance: 2.0,
                     polyline_count: 8,
+                    dropped_count: 2,
                     points_before: 150,
                     points_after: 80,
                     reduction_ratio: 0.467,
@@ -989,15 +1257,19 @@ mod tests {
                     input_point_count: 80,
                     output_point_count: 120,
                     expansion_ratio: 1.5,
+                    perimeter_reroutes: 0,
+                    segment_lengths: SegmentLengths::default(),
                     quality: None,
                 },
             },
             output: StageDiagnostics {
                 duration: Duration::from_millis(1),
                 metrics: StageMetrics::Output {
+                    mode: "Cartesian".to_string(),
                     max_length: 2.0,
                     points_before: 120,
                     points_after: 150,
+                    center_reroutes: 0,
                 },
             },
             total_duration: Duration::from_millis(81),
@@ -1008,6 +1280,7 @@ mod tests {
                 contour_count: 8,
                 final_point_count: 150,
             },
+            quality: None,
         };
 
         let report = diag.report();
@@ -1021,5 +1294,9 @@ mod tests {
             report.contains("Triangle"),
             "report should mention the filter, got:\n{report}",
         );
+        assert!(
+            report.contains("dropped=2"),
+            "report should contain the dropped contour count, got:\n{report}",
+        );
     }
 }
@@ -10,7 +10,9 @@
 
 use image::GrayImage;
 
-use crate::types::{EdgeChannels, RgbaImage};
+use crate::canny::Gradient;
+use crate::progress::Progress;
+use crate::types::{EdgeChannel, EdgeChannels, EdgeFusion, Luma32FImage, PipelineError, RgbaImage};
 
 /// Minimum allowed Canny threshold.
 ///
@@ -91,16 +93,16 @@ pub fn max_gradient_magnitude() -> f32 {
     max_mag
 }
 
-/// An RGB color channel that can be extracted from an RGBA pixel.
+/// A channel that can be extracted from an RGBA pixel.
 ///
 /// Used by [`extract_channel`] to select which byte of the RGBA pixel
-/// to extract. Alpha is intentionally excluded — it is not used for
-/// edge detection.
+/// to extract.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ColorChannel {
     Red = 0,
     Green = 1,
     Blue = 2,
+    Alpha = 3,
 }
 
 /// Extract a single color channel from an RGBA image as a grayscale image.
@@ -122,7 +124,7 @@ fn extract_channel(rgba: &RgbaImage, channel: ColorChannel) -> GrayImage {
 /// + `to_luma8()` would incur.
 #[must_use]
 #[allow(clippy::cast_possible_truncation)]
-fn rgba_to_luma(rgba: &RgbaImage) -> GrayImage {
+pub(crate) fn rgba_to_luma(rgba: &RgbaImage) -> GrayImage {
     GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
         let [r, g, b, _] = rgba.get_pixel(x, y).0;
         let luma = (2126 * u32::from(r) + 7152 * u32::from(g) + 722 * u32::from(b)) / 10_000;
@@ -152,6 +154,108 @@ fn extract_saturation(rgba: &RgbaImage) -> GrayImage {
     })
 }
 
+/// Compute the HSV value channel (`max(R, G, B)`) from an RGBA image.
+#[must_use]
+fn extract_value(rgba: &RgbaImage) -> GrayImage {
+    GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, _] = rgba.get_pixel(x, y).0;
+        image::Luma([r.max(g).max(b)])
+    })
+}
+
+/// Hue as a saturation-scaled unit vector `(cos h, sin h)`.
+///
+/// Returns the two component images.  Each is scaled by
+/// `127.5 × saturation`, so opposite fully saturated hues differ by 255
+/// — the same span as an 8-bit channel — and gray pixels, whose hue is
+/// arbitrary, sit at the origin.
+#[must_use]
+fn extract_hue_vector(rgba: &RgbaImage) -> (Luma32FImage, Luma32FImage) {
+    let vector = |[r, g, b, _]: [u8; 4]| -> (f32, f32) {
+        let max = r.max(g).max(b);
+        let chroma = f32::from(max - r.min(g).min(b));
+        if chroma == 0.0 {
+            return (0.0, 0.0);
+        }
+        let (rf, gf, bf) = (f32::from(r), f32::from(g), f32::from(b));
+        // Hue sextant, in units of 60°.
+        let sextant = if max == r {
+            (gf - bf) / chroma
+        } else if max == g {
+            (bf - rf) / chroma + 2.0
+        } else {
+            (rf - gf) / chroma + 4.0
+        };
+        let angle = sextant * std::f32::consts::FRAC_PI_3;
+        let radius = 127.5 * chroma / f32::from(max);
+        (radius * angle.cos(), radius * angle.sin())
+    };
+    let mut cos = Luma32FImage::new(rgba.width(), rgba.height());
+    let mut sin = Luma32FImage::new(rgba.width(), rgba.height());
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let (along, across) = vector(pixel.0);
+        cos.put_pixel(x, y, image::Luma([along]));
+        sin.put_pixel(x, y, image::Luma([across]));
+    }
+    (cos, sin)
+}
+
+/// A CIELAB component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LabComponent {
+    L,
+    A,
+    B,
+}
+
+/// Extract one CIELAB (D65) component from an sRGB image.
+///
+/// All components are multiplied by 2.55, mapping L* from 0–100 onto
+/// the 0–255 range of the other channels while keeping a unit step in
+/// a* or b* as strong as one in L* (equal ΔE*ab).
+#[must_use]
+fn extract_lab(rgba: &RgbaImage, component: LabComponent) -> Luma32FImage {
+    // sRGB transfer function, inverted once per 8-bit level.
+    let linear: Vec<f32> = (0..=255_u8)
+        .map(|v| {
+            let c = f32::from(v) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        })
+        .collect();
+    let f = |t: f32| {
+        const DELTA: f32 = 6.0 / 29.0;
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    };
+    Luma32FImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, _] = rgba.get_pixel(x, y).0;
+        let (r, g, b) = (
+            linear[usize::from(r)],
+            linear[usize::from(g)],
+            linear[usize::from(b)],
+        );
+        // Linear sRGB to XYZ, normalized by the D65 white point.
+        let fx =
+            f(0.180_437_5_f32.mul_add(b, 0.412_456_4_f32.mul_add(r, 0.357_576_1 * g)) / 0.950_47);
+        let fy = f(0.072_175_f32.mul_add(b, 0.212_672_9_f32.mul_add(r, 0.715_152_2 * g)));
+        let fz =
+            f(0.950_304_1_f32.mul_add(b, 0.019_333_9_f32.mul_add(r, 0.119_192 * g)) / 1.088_83);
+        let value = match component {
+            LabComponent::L => 116.0_f32.mul_add(fy, -16.0),
+            LabComponent::A => 500.0 * (fx - fy),
+            LabComponent::B => 200.0 * (fy - fz),
+        };
+        image::Luma([2.55 * value])
+    })
+}
+
 /// Combine two binary edge maps via pixel-wise maximum.
 ///
 /// Both images must have the same dimensions (caller must guarantee
@@ -167,17 +271,21 @@ fn combine_edge_maps(a: &GrayImage, b: &GrayImage) -> GrayImage {
     })
 }
 
-/// Run Canny edge detection on multiple image channels and combine
-/// the results via pixel-wise maximum.
+/// Run Canny edge detection on multiple image channels and fuse the
+/// results according to [`EdgeChannels::fusion`].
 ///
 /// `blurred_rgba` must already be Gaussian-blurred by the pipeline's
-/// blur stage. For each enabled channel in `channels`:
-/// 1. Extract the grayscale channel from the already-blurred RGBA.
-/// 2. Run Canny with the given thresholds.
+/// blur stage. Each enabled channel in `channels` is extracted from
+/// the already-blurred RGBA, then:
 ///
-/// All per-channel edge maps are then combined by taking the maximum
-/// value at each pixel, so edges detected in *any* channel appear in
-/// the final output.
+/// - [`EdgeFusion::Max`]: Canny runs on each channel with its own
+///   [thresholds](EdgeChannels::thresholds) (or the given ones) and
+///   the edge maps are combined by taking the maximum value at each
+///   pixel, so edges detected in *any* channel appear in the output.
+/// - [`EdgeFusion::WeightedSum`]: the channels' gradient magnitudes
+///   are averaged by weight (so only the ratio of weights matters) and
+///   a single non-maximum suppression and hysteresis pass runs with the
+///   given thresholds.
 ///
 /// # Panics
 ///
@@ -190,36 +298,176 @@ pub fn canny_combined(
     low_threshold: f32,
     high_threshold: f32,
 ) -> GrayImage {
-    assert!(
-        channels.any_enabled(),
-        "at least one edge channel must be enabled"
-    );
+    crate::progress::uncancellable(canny_combined_with_progress(
+        blurred_rgba,
+        channels,
+        low_threshold,
+        high_threshold,
+        &Progress::NONE,
+    ))
+}
 
-    let (low, high) = clamp_thresholds(low_threshold, high_threshold);
+/// [`canny_combined`] with cancellation and per-channel progress.
+///
+/// Reports progress after each channel's Canny pass and polls for
+/// cancellation before starting the next one.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+///
+/// # Panics
+///
+/// Panics if no channels are enabled (see [`canny_combined`]).
+pub fn canny_combined_with_progress(
+    blurred_rgba: &RgbaImage,
+    channels: &EdgeChannels,
+    low_threshold: f32,
+    high_threshold: f32,
+    progress: &Progress<'_>,
+) -> Result<GrayImage, PipelineError> {
+    canny_combined_with_luma(
+        blurred_rgba,
+        None,
+        channels,
+        low_threshold,
+        high_threshold,
+        progress,
+    )
+}
 
-    // Collect grayscale channel images extracted from the already-blurred RGBA.
-    let mut channel_images: Vec<GrayImage> = Vec::with_capacity(channels.count());
+/// A channel image handed to Canny.
+enum ChannelImage<'a> {
+    /// 8-bit channel.
+    Byte(GrayImage),
+    /// Borrowed full-precision channel (high bit-depth luminance).
+    Precise(&'a Luma32FImage),
+    /// Computed full-precision channel.
+    Float(Luma32FImage),
+    /// Two-component vector channel (hue); its gradient is the norm
+    /// over both components.
+    Vector(Luma32FImage, Luma32FImage),
+}
 
-    if channels.luminance {
-        channel_images.push(rgba_to_luma(blurred_rgba));
-    }
-    if channels.red {
-        channel_images.push(extract_channel(blurred_rgba, ColorChannel::Red));
+impl<'a> ChannelImage<'a> {
+    /// Extract `channel` from the blurred RGBA, using `blurred_luma`
+    /// for luminance when present.
+    fn extract(
+        channel: EdgeChannel,
+        blurred_rgba: &RgbaImage,
+        blurred_luma: Option<&'a Luma32FImage>,
+    ) -> Self {
+        match channel {
+            EdgeChannel::Luminance => {
+                blurred_luma.map_or_else(|| Self::Byte(rgba_to_luma(blurred_rgba)), Self::Precise)
+            }
+            EdgeChannel::Red => Self::Byte(extract_channel(blurred_rgba, ColorChannel::Red)),
+            EdgeChannel::Green => Self::Byte(extract_channel(blurred_rgba, ColorChannel::Green)),
+            EdgeChannel::Blue => Self::Byte(extract_channel(blurred_rgba, ColorChannel::Blue)),
+            EdgeChannel::Saturation => Self::Byte(extract_saturation(blurred_rgba)),
+            EdgeChannel::Alpha => Self::Byte(extract_channel(blurred_rgba, ColorChannel::Alpha)),
+            EdgeChannel::Hue => {
+                let (cos, sin) = extract_hue_vector(blurred_rgba);
+                Self::Vector(cos, sin)
+            }
+            EdgeChannel::Value => Self::Byte(extract_value(blurred_rgba)),
+            EdgeChannel::Lightness => Self::Float(extract_lab(blurred_rgba, LabComponent::L)),
+            EdgeChannel::LabA => Self::Float(extract_lab(blurred_rgba, LabComponent::A)),
+            EdgeChannel::LabB => Self::Float(extract_lab(blurred_rgba, LabComponent::B)),
+        }
     }
-    if channels.green {
-        channel_images.push(extract_channel(blurred_rgba, ColorChannel::Green));
+
+    /// Run Canny on this channel with already-clamped thresholds.
+    fn canny(&self, low: f32, high: f32) -> GrayImage {
+        match self {
+            Self::Byte(img) => crate::canny::canny(img, low, high),
+            Self::Precise(img) => crate::canny::canny_f32(img, low, high),
+            Self::Float(img) => crate::canny::canny_f32(img, low, high),
+            Self::Vector(..) => crate::canny::canny_from_gradient(&self.gradient(), low, high),
+        }
     }
-    if channels.blue {
-        channel_images.push(extract_channel(blurred_rgba, ColorChannel::Blue));
+
+    /// The blurred Sobel gradient of this channel, as Canny sees it.
+    fn gradient(&self) -> Gradient {
+        match self {
+            Self::Byte(img) => crate::canny::gradient_f32(&Luma32FImage::from_fn(
+                img.width(),
+                img.height(),
+                |x, y| image::Luma([f32::from(img.get_pixel(x, y).0[0])]),
+            )),
+            Self::Precise(img) => crate::canny::gradient_f32(img),
+            Self::Float(img) => crate::canny::gradient_f32(img),
+            Self::Vector(x, y) => crate::canny::vector_gradient(&[
+                crate::canny::gradient_f32(x),
+                crate::canny::gradient_f32(y),
+            ]),
+        }
     }
-    if channels.saturation {
-        channel_images.push(extract_saturation(blurred_rgba));
+}
+
+/// [`canny_combined_with_progress`] with an optional full-precision
+/// luminance.
+///
+/// When `blurred_luma` is `Some` (a high bit-depth source, already
+/// blurred like `blurred_rgba`), the luminance channel runs Canny on it
+/// instead of the 8-bit luminance of `blurred_rgba`, so gradients finer
+/// than one 8-bit level are still found.  The other channels always
+/// come from `blurred_rgba`.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+///
+/// # Panics
+///
+/// Panics if no channels are enabled (see [`canny_combined`]).
+pub fn canny_combined_with_luma(
+    blurred_rgba: &RgbaImage,
+    blurred_luma: Option<&Luma32FImage>,
+    channels: &EdgeChannels,
+    low_threshold: f32,
+    high_threshold: f32,
+    progress: &Progress<'_>,
+) -> Result<GrayImage, PipelineError> {
+    assert!(
+        channels.any_enabled(),
+        "at least one edge channel must be enabled"
+    );
+
+    let enabled: Vec<EdgeChannel> = channels.enabled().collect();
+
+    if channels.fusion == EdgeFusion::WeightedSum {
+        let (low, high) = clamp_thresholds(low_threshold, high_threshold);
+        let gradients = crate::par::map_with_progress(&enabled, progress, |_, &channel| {
+            let gradient = ChannelImage::extract(channel, blurred_rgba, blurred_luma).gradient();
+            (gradient, channels.weight(channel))
+        })?;
+        let edges = crate::canny::canny_from_gradient(
+            &crate::canny::weighted_gradient(&gradients),
+            low,
+            high,
+        );
+        progress.report(1.0);
+        return Ok(edges);
     }
 
-    let mut combined: Option<GrayImage> = None;
+    // Per-channel extraction and Canny are independent; only the
+    // combine is ordered.  All channels come from the already-blurred
+    // RGBA.  Weighting the gradient is the same as dividing the
+    // thresholds by the weight.
+    let edge_maps = crate::par::map_with_progress(&enabled, progress, |_, &channel| {
+        let (low, high) = channels.thresholds_for(channel, low_threshold, high_threshold);
+        let (low, high) = clamp_thresholds(low, high);
+        let weight = channels.weight(channel);
+        ChannelImage::extract(channel, blurred_rgba, blurred_luma)
+            .canny(low / weight, high / weight)
+    })?;
+    progress.report(1.0);
 
-    for img in &channel_images {
-        let edges = crate::canny::canny(img, low, high);
+    let mut combined: Option<GrayImage> = None;
+    for edges in edge_maps {
         combined = Some(match combined {
             Some(acc) => combine_edge_maps(&acc, &edges),
             None => edges,
@@ -228,7 +476,7 @@ pub fn canny_combined(
 
     // Safety: we asserted at least one channel is enabled, so combined is Some.
     #[allow(clippy::unwrap_used)]
-    combined.unwrap()
+    Ok(combined.unwrap())
 }
 
 /// Invert a binary edge map (bitwise NOT).
@@ -246,7 +494,10 @@ pub fn invert_edge_map(edges: &GrayImage) -> GrayImage {
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use super::*;
+    use crate::types::CannyThresholds;
 
     /// 20x20 image with a sharp vertical boundary at x = 10.
     fn sharp_edge_image() -> GrayImage {
@@ -459,6 +710,63 @@ mod tests {
         assert!((i16::from(s) - 127).abs() <= 1, "expected ~127, got {s}",);
     }
 
+    #[test]
+    fn extract_value_is_channel_max() {
+        assert_eq!(extract_value(&test_rgba()).get_pixel(0, 0).0[0], 30);
+    }
+
+    #[test]
+    fn extract_hue_vector_wraps_around_red() {
+        // Hues of about 359° and 1°: nearly the same vector, although
+        // the raw angles are at opposite ends of the range.
+        let rgba = RgbaImage::from_fn(2, 1, |x, _| {
+            image::Rgba(if x == 0 {
+                [255, 0, 4, 255]
+            } else {
+                [255, 4, 0, 255]
+            })
+        });
+        let (cos, sin) = extract_hue_vector(&rgba);
+        let dx = cos.get_pixel(0, 0).0[0] - cos.get_pixel(1, 0).0[0];
+        let dy = sin.get_pixel(0, 0).0[0] - sin.get_pixel(1, 0).0[0];
+        assert!(dx.hypot(dy) < 10.0, "got {}", dx.hypot(dy));
+        assert!((cos.get_pixel(0, 0).0[0] - 127.5).abs() < 1.0);
+    }
+
+    #[test]
+    fn extract_hue_vector_gray_is_origin() {
+        let rgba = RgbaImage::from_pixel(1, 1, image::Rgba([90, 90, 90, 255]));
+        let (cos, sin) = extract_hue_vector(&rgba);
+        assert_eq!(
+            (cos.get_pixel(0, 0).0[0], sin.get_pixel(0, 0).0[0]),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn extract_lab_reference_colors() {
+        let rgba = RgbaImage::from_fn(3, 1, |x, _| {
+            image::Rgba(match x {
+                0 => [255, 255, 255, 255],
+                1 => [0, 0, 0, 255],
+                _ => [255, 0, 0, 255],
+            })
+        });
+        let l = extract_lab(&rgba, LabComponent::L);
+        let a = extract_lab(&rgba, LabComponent::A);
+        let b = extract_lab(&rgba, LabComponent::B);
+        // White: L* = 100, a* = b* = 0.
+        assert!((l.get_pixel(0, 0).0[0] - 255.0).abs() < 0.5);
+        assert!(a.get_pixel(0, 0).0[0].abs() < 0.5);
+        assert!(b.get_pixel(0, 0).0[0].abs() < 0.5);
+        // Black: L* = 0.
+        assert!(l.get_pixel(1, 0).0[0].abs() < 0.5);
+        // sRGB red: L* ≈ 53.2, a* ≈ 80.1, b* ≈ 67.2.
+        assert!((l.get_pixel(2, 0).0[0] / 2.55 - 53.2).abs() < 0.2);
+        assert!((a.get_pixel(2, 0).0[0] / 2.55 - 80.1).abs() < 0.2);
+        assert!((b.get_pixel(2, 0).0[0] / 2.55 - 67.2).abs() < 0.2);
+    }
+
     #[test]
     fn combine_edge_maps_takes_maximum() {
         let a = GrayImage::from_fn(3, 1, |x, _| image::Luma([if x == 0 { 255 } else { 0 }]));
@@ -556,6 +864,251 @@ mod tests {
         );
     }
 
+    #[test]
+    fn alpha_channel_detects_transparency_boundary() {
+        // Same RGB everywhere; only opacity changes at x = 10.
+        let rgba = RgbaImage::from_fn(20, 20, |x, _y| {
+            image::Rgba([200, 30, 30, if x < 10 { 255 } else { 0 }])
+        });
+        let blurred_rgba = crate::blur::gaussian_blur_rgba(&rgba, 1.4);
+
+        let luma_only = EdgeChannels::default();
+        assert_eq!(
+            count_edges(&canny_combined(&blurred_rgba, &luma_only, 15.0, 40.0)),
+            0,
+        );
+
+        let with_alpha = EdgeChannels {
+            alpha: true,
+            ..EdgeChannels::default()
+        };
+        assert!(count_edges(&canny_combined(&blurred_rgba, &with_alpha, 15.0, 40.0)) > 0);
+    }
+
+    #[test]
+    fn hue_and_lab_channels_detect_isoluminant_boundary() {
+        let blurred_rgba = crate::blur::gaussian_blur_rgba(&isoluminant_hue_boundary_rgba(), 1.4);
+        for channel in [EdgeChannel::Hue, EdgeChannel::LabA] {
+            let mut channels = EdgeChannels {
+                luminance: false,
+                ..EdgeChannels::default()
+            };
+            channels.set_enabled(channel, true);
+            let edges = canny_combined(&blurred_rgba, &channels, 15.0, 40.0);
+            assert!(count_edges(&edges) > 0, "{channel} found no edges");
+        }
+    }
+
+    #[test]
+    fn hue_channel_ignores_wraparound() {
+        // Hue ~359° on the left, ~1° on the right: no visible boundary.
+        let rgba = RgbaImage::from_fn(20, 20, |x, _| {
+            image::Rgba(if x < 10 {
+                [255, 0, 4, 255]
+            } else {
+                [255, 4, 0, 255]
+            })
+        });
+        let channels = EdgeChannels {
+            luminance: false,
+            hue: true,
+            ..EdgeChannels::default()
+        };
+        assert_eq!(
+            count_edges(&canny_combined(&rgba, &channels, 15.0, 40.0)),
+            0
+        );
+    }
+
+    #[test]
+    fn channel_weight_scales_thresholds() {
+        let blurred_rgba = crate::blur::gaussian_blur_rgba(&isoluminant_hue_boundary_rgba(), 1.4);
+        let red = EdgeChannels {
+            luminance: false,
+            red: true,
+            ..EdgeChannels::default()
+        };
+        let weighted = EdgeChannels {
+            weights: BTreeMap::from([(EdgeChannel::Red, 2.0)]),
+            ..red.clone()
+        };
+        assert_eq!(
+            canny_combined(&blurred_rgba, &weighted, 30.0, 80.0),
+            canny_combined(&blurred_rgba, &red, 15.0, 40.0),
+        );
+    }
+
+    #[test]
+    fn channel_threshold_override_replaces_global_thresholds() {
+        let blurred_rgba = crate::blur::gaussian_blur_rgba(&isoluminant_hue_boundary_rgba(), 1.4);
+        let red = EdgeChannels {
+            luminance: false,
+            red: true,
+            ..EdgeChannels::default()
+        };
+        let overridden = EdgeChannels {
+            thresholds: BTreeMap::from([(
+                EdgeChannel::Red,
+                CannyThresholds {
+                    low: 15.0,
+                    high: 40.0,
+                },
+            )]),
+            ..red.clone()
+        };
+        assert_eq!(
+            canny_combined(&blurred_rgba, &overridden, 500.0, 900.0),
+            canny_combined(&blurred_rgba, &red, 15.0, 40.0),
+        );
+        assert_eq!(
+            count_edges(&canny_combined(&blurred_rgba, &red, 500.0, 900.0)),
+            0
+        );
+    }
+
+    #[test]
+    fn weighted_sum_of_one_channel_matches_max() {
+        let rgba = crate::blur::gaussian_blur_rgba(&sharp_edge_rgba(), 1.4);
+        let lightness = EdgeChannels {
+            luminance: false,
+            lightness: true,
+            ..EdgeChannels::default()
+        };
+        let fused = EdgeChannels {
+            fusion: EdgeFusion::WeightedSum,
+            ..lightness.clone()
+        };
+        assert_eq!(
+            canny_combined(&rgba, &fused, 15.0, 40.0),
+            canny_combined(&rgba, &lightness, 15.0, 40.0),
+        );
+    }
+
+    #[test]
+    fn weighted_sum_ignores_weight_of_lone_channel() {
+        // Under Max the weight scales the magnitude; under WeightedSum
+        // it is divided back out.
+        let rgba = crate::blur::gaussian_blur_rgba(&sharp_edge_rgba(), 1.4);
+        let unweighted = EdgeChannels {
+            fusion: EdgeFusion::WeightedSum,
+            ..EdgeChannels::default()
+        };
+        let weighted = EdgeChannels {
+            weights: BTreeMap::from([(EdgeChannel::Luminance, 3.0)]),
+            ..unweighted.clone()
+        };
+        assert_eq!(
+            canny_combined(&rgba, &weighted, 15.0, 40.0),
+            canny_combined(&rgba, &unweighted, 15.0, 40.0),
+        );
+
+        let max_weighted = EdgeChannels {
+            fusion: EdgeFusion::Max,
+            ..weighted
+        };
+        let max_unweighted = EdgeChannels {
+            fusion: EdgeFusion::Max,
+            ..unweighted
+        };
+        assert!(
+            count_edges(&canny_combined(&rgba, &max_weighted, 300.0, 600.0))
+                > count_edges(&canny_combined(&rgba, &max_unweighted, 300.0, 600.0))
+        );
+    }
+
+    #[test]
+    fn weighted_sum_dilutes_single_channel_edges() {
+        // The boundary is a step of 100 in red but only ~10 in luminance
+        // and 30 in blue.  Max keeps the red edge; averaging with the
+        // two weak channels pushes it below the thresholds.
+        let rgba = crate::blur::gaussian_blur_rgba(&isoluminant_hue_boundary_rgba(), 1.4);
+        let max = EdgeChannels {
+            luminance: true,
+            red: true,
+            blue: true,
+            ..EdgeChannels::default()
+        };
+        let fused = EdgeChannels {
+            fusion: EdgeFusion::WeightedSum,
+            ..max.clone()
+        };
+        let max_edges = count_edges(&canny_combined(&rgba, &max, 60.0, 150.0));
+        let fused_edges = count_edges(&canny_combined(&rgba, &fused, 60.0, 150.0));
+        assert!(max_edges > 0);
+        assert!(
+            fused_edges < max_edges,
+            "fused {fused_edges} should be fewer than max {max_edges}"
+        );
+    }
+
+    #[test]
+    fn canny_combined_with_progress_honours_cancellation() {
+        let img = sharp_edge_image();
+        let rgba = RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+            let v = img.get_pixel(x, y).0[0];
+            image::Rgba([v, v, v, 255])
+        });
+        let channels = EdgeChannels::default();
+        let cancelled = Progress::new(&|| true, &|_| {});
+        let result = canny_combined_with_progress(&rgba, &channels, 50.0, 150.0, &cancelled);
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+
+        let reported = std::cell::Cell::new(0.0);
+        let on_fraction = |f| reported.set(f);
+        let progress = Progress::new(&|| false, &on_fraction);
+        let edges = canny_combined_with_progress(&rgba, &channels, 50.0, 150.0, &progress).unwrap();
+        assert_eq!(edges, canny_combined(&rgba, &channels, 50.0, 150.0));
+        assert!((reported.get() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn canny_combined_matches_serial_channel_fold() {
+        let rgba = crate::blur::gaussian_blur_rgba(&isoluminant_hue_boundary_rgba(), 1.4);
+        let all = EdgeChannels {
+            luminance: true,
+            red: true,
+            green: true,
+            blue: true,
+            saturation: true,
+            alpha: true,
+            hue: true,
+            value: true,
+            lightness: true,
+            lab_a: true,
+            lab_b: true,
+            weights: BTreeMap::new(),
+            thresholds: BTreeMap::new(),
+            fusion: EdgeFusion::Max,
+        };
+        let (low, high) = clamp_thresholds(15.0, 40.0);
+        let expected = EdgeChannel::ALL
+            .iter()
+            .map(|&channel| ChannelImage::extract(channel, &rgba, None).canny(low, high))
+            .reduce(|acc, edges| combine_edge_maps(&acc, &edges))
+            .unwrap();
+        assert_eq!(canny_combined(&rgba, &all, 15.0, 40.0), expected);
+    }
+
+    #[test]
+    fn precise_luma_replaces_byte_luminance() {
+        // The RGBA is flat; only the precise luma carries a boundary.
+        let rgba = RgbaImage::from_pixel(20, 20, image::Rgba([128, 128, 128, 255]));
+        let luma = Luma32FImage::from_fn(20, 20, |x, _| {
+            image::Luma([if x < 10 { 0.0 } else { 255.0 }])
+        });
+        let channels = EdgeChannels::default();
+        let progress = Progress::new(&|| false, &|_| {});
+        let with_luma =
+            canny_combined_with_luma(&rgba, Some(&luma), &channels, 50.0, 150.0, &progress)
+                .unwrap();
+        assert!(with_luma.pixels().any(|p| p.0[0] > 0));
+        assert!(
+            canny_combined(&rgba, &channels, 50.0, 150.0)
+                .pixels()
+                .all(|p| p.0[0] == 0)
+        );
+    }
+
     #[test]
     #[should_panic(expected = "at least one edge channel must be enabled")]
     fn canny_combined_panics_with_no_channels() {
@@ -566,6 +1119,15 @@ mod tests {
             green: false,
             blue: false,
             saturation: false,
+            alpha: false,
+            hue: false,
+            value: false,
+            lightness: false,
+            lab_a: false,
+            lab_b: false,
+            weights: BTreeMap::new(),
+            thresholds: BTreeMap::new(),
+            fusion: EdgeFusion::Max,
         };
         let _ = canny_combined(&rgba, &none, 50.0, 150.0);
     }
@@ -590,8 +1152,17 @@ mod tests {
             green: true,
             blue: true,
             saturation: true,
+            alpha: true,
+            hue: true,
+            value: true,
+            lightness: true,
+            lab_a: true,
+            lab_b: true,
+            weights: BTreeMap::new(),
+            thresholds: BTreeMap::new(),
+            fusion: EdgeFusion::Max,
         };
-        assert_eq!(all.count(), 5);
+        assert_eq!(all.count(), 11);
         assert!(all.any_enabled());
 
         let none = EdgeChannels {
@@ -600,6 +1171,15 @@ mod tests {
             green: false,
             blue: false,
             saturation: false,
+            alpha: false,
+            hue: false,
+            value: false,
+            lightness: false,
+            lab_a: false,
+            lab_b: false,
+            weights: BTreeMap::new(),
+            thresholds: BTreeMap::new(),
+            fusion: EdgeFusion::Max,
         };
         assert_eq!(none.count(), 0);
         assert!(!none.any_enabled());
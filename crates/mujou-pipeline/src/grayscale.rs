@@ -1,18 +1,58 @@
 //! Image decoding and grayscale conversion.
 //!
-//! Accepts raw image bytes (PNG, JPEG, BMP, WebP) and produces a
+//! Accepts raw image bytes (PNG, JPEG, BMP, WebP, plus GIF, TIFF,
+//! PNM and ICO with the matching cargo features) and produces a
 //! single-channel grayscale image suitable for the processing pipeline.
+//! [`supported_extensions`] lists what the current build can decode.
 //!
 //! This is the first step in the pipeline: raw bytes in, `GrayImage` out.
+//!
+//! Decoding honours the EXIF orientation tag (JPEG, WebP, and the PNG
+//! `eXIf` chunk), so phone photos come out upright.
+
+use std::io::Cursor;
 
-use image::{DynamicImage, GrayImage, RgbaImage};
+use image::metadata::Orientation;
+use image::{
+    DynamicImage, GrayImage, ImageDecoder, ImageError, ImageFormat, ImageReader, RgbaImage,
+};
 
-use crate::types::PipelineError;
+use crate::types::{Luma32FImage, PipelineError};
 
-/// Decode raw image bytes into a [`DynamicImage`].
+/// File extensions (lowercase, without the dot) of every image format
+/// this build can decode.
 ///
-/// Supports PNG, JPEG, BMP, and WebP formats (whatever the `image` crate
-/// can decode).
+/// Derived from the `image` crate's enabled decoders, so upload filters
+/// and directory scans stay in sync with the cargo features.
+///
+/// # Examples
+///
+/// ```
+/// let extensions = mujou_pipeline::grayscale::supported_extensions();
+/// assert!(extensions.contains(&"png"));
+/// assert!(extensions.contains(&"jpg"));
+/// ```
+#[must_use]
+pub fn supported_extensions() -> Vec<&'static str> {
+    ImageFormat::all()
+        .filter(ImageFormat::reading_enabled)
+        .flat_map(ImageFormat::extensions_str)
+        .copied()
+        .collect()
+}
+
+/// Whether `extension` (without the dot, any case) names a format in
+/// [`supported_extensions`].
+#[must_use]
+pub fn is_supported_extension(extension: &str) -> bool {
+    ImageFormat::from_extension(extension).is_some_and(|format| format.reading_enabled())
+}
+
+/// Decode raw image bytes into an upright [`DynamicImage`].
+///
+/// Supports every format in [`supported_extensions`].  Animated inputs
+/// decode their first frame.  See [`decode_oriented`] for EXIF
+/// orientation handling and frame selection.
 ///
 /// # Errors
 ///
@@ -20,11 +60,138 @@ use crate::types::PipelineError;
 /// Returns [`PipelineError::ImageDecode`] if the image format is
 /// unrecognized or the data is corrupt.
 pub fn decode(bytes: &[u8]) -> Result<DynamicImage, PipelineError> {
+    decode_oriented(bytes, 0).map(|(image, _)| image)
+}
+
+/// Decode raw image bytes and rotate/flip the result upright according
+/// to the EXIF orientation tag.
+///
+/// `frame` selects the frame of an animated GIF (0 is the first); a
+/// frame past the end selects the last one, and other formats ignore
+/// it.  [`frame_count`] gives the number of frames.
+///
+/// Returns the upright image and the orientation that was applied
+/// ([`Orientation::NoTransforms`] when the file has no EXIF data or an
+/// unreadable orientation tag).  The bit depth of the source is kept,
+/// e.g. 16-bit PNG and TIFF decode to 16-bit buffers.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::EmptyInput`] if `bytes` is empty.
+/// Returns [`PipelineError::ImageDecode`] if the image format is
+/// unrecognized or the data is corrupt.
+pub fn decode_oriented(
+    bytes: &[u8],
+    frame: u32,
+) -> Result<(DynamicImage, Orientation), PipelineError> {
     if bytes.is_empty() {
         return Err(PipelineError::EmptyInput);
     }
 
-    Ok(image::load_from_memory(bytes)?)
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(ImageError::IoError)?;
+    #[cfg(feature = "gif")]
+    if reader.format() == Some(ImageFormat::Gif) {
+        return decode_gif_frame(bytes, frame).map(|image| (image, Orientation::NoTransforms));
+    }
+    let mut decoder = reader.into_decoder()?;
+    // Malformed EXIF is common in the wild; keep the pixels as stored.
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok((image, orientation))
+}
+
+/// Decode frame `frame` of a GIF, composited onto the full canvas, or
+/// the last frame if the GIF is shorter.
+#[cfg(feature = "gif")]
+fn decode_gif_frame(bytes: &[u8], frame: u32) -> Result<DynamicImage, PipelineError> {
+    use image::AnimationDecoder;
+
+    let decoder = image::codecs::gif::GifDecoder::new(Cursor::new(bytes))?;
+    let mut selected = None;
+    let count = usize::try_from(frame).map_or(usize::MAX, |f| f.saturating_add(1));
+    for decoded in decoder.into_frames().take(count) {
+        selected = Some(decoded?);
+    }
+    let selected = selected.ok_or_else(|| {
+        ImageError::Decoding(image::error::DecodingError::new(
+            ImageFormat::Gif.into(),
+            "GIF has no frames",
+        ))
+    })?;
+    Ok(DynamicImage::ImageRgba8(selected.into_buffer()))
+}
+
+/// Number of frames in encoded image bytes: the frame count of a GIF,
+/// and 1 for every other format.
+///
+/// Walks the GIF block structure without decompressing any frame, so
+/// it is cheap enough to call on the UI thread.  Truncated data counts
+/// the frames seen before the end.
+#[must_use]
+pub fn frame_count(bytes: &[u8]) -> u32 {
+    if !bytes.starts_with(b"GIF8") || bytes.len() < 13 {
+        return 1;
+    }
+    // Size of a colour table whose flag and size are packed into `flags`.
+    let table_len = |flags: u8| {
+        if flags & 0x80 == 0 {
+            0
+        } else {
+            3 << ((flags & 0x07) + 1)
+        }
+    };
+    // Skip a chain of data sub-blocks starting at `pos`.
+    let skip_sub_blocks = |mut pos: usize| {
+        while let Some(&len) = bytes.get(pos) {
+            pos += 1;
+            if len == 0 {
+                return Some(pos);
+            }
+            pos += usize::from(len);
+        }
+        None
+    };
+
+    let mut frames = 0;
+    let mut pos = 13 + table_len(bytes[10]);
+    loop {
+        let next = match bytes.get(pos) {
+            // Image descriptor, optional local colour table, LZW code size.
+            Some(0x2C) => bytes.get(pos + 9).and_then(|&flags| {
+                frames += 1;
+                skip_sub_blocks(pos + 11 + table_len(flags))
+            }),
+            // Extension: label, then sub-blocks.
+            Some(0x21) => skip_sub_blocks(pos + 2),
+            // Trailer, unknown block or end of data.
+            _ => None,
+        };
+        match next {
+            Some(next) => pos = next,
+            None => return frames.max(1),
+        }
+    }
+}
+
+/// Luminance of `img` at full precision when it has more than 8 bits
+/// per channel (16-bit PNG/TIFF/PNM, float images), or `None` for 8-bit
+/// images whose luminance the 8-bit stages already carry exactly.
+///
+/// Uses the same Rec.709 weights as [`to_grayscale`], scaled to 0–255.
+#[must_use]
+pub fn to_precise_luma(img: &DynamicImage) -> Option<Luma32FImage> {
+    let color = img.color();
+    if color.bits_per_pixel() <= 8 * u16::from(color.channel_count()) {
+        return None;
+    }
+    let mut luma = img.to_luma32f();
+    for value in luma.iter_mut() {
+        *value *= 255.0;
+    }
+    Some(luma)
 }
 
 /// Convert a decoded image to RGBA.
@@ -136,6 +303,209 @@ mod tests {
         );
     }
 
+    /// A 4x2 image whose left half is black and right half white.
+    fn half_black() -> image::RgbImage {
+        image::RgbImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        })
+    }
+
+    /// EXIF data (a TIFF block) holding only `orientation`.
+    fn exif_orientation(orientation: u16) -> Vec<u8> {
+        // Big-endian TIFF header, one IFD entry: Orientation (0x0112),
+        // type SHORT, count 1.
+        let mut tiff = b"MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+        tiff.extend_from_slice(&orientation.to_be_bytes());
+        tiff.extend_from_slice(&[0; 6]);
+        tiff
+    }
+
+    /// Encode [`half_black`] as a JPEG with an EXIF APP1 segment
+    /// holding `orientation`.
+    fn jpeg_with_orientation(orientation: u16) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 100)
+            .encode_image(&half_black())
+            .unwrap();
+
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&exif_orientation(orientation));
+        let len = u16::try_from(app1.len() + 2).unwrap();
+
+        let mut out = jpeg[..2].to_vec(); // SOI
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    /// Encode [`half_black`] with `encoder`, attaching EXIF data
+    /// holding `orientation`.
+    fn encode_with_orientation(mut encoder: impl image::ImageEncoder, orientation: u16) {
+        encoder
+            .set_exif_metadata(exif_orientation(orientation))
+            .unwrap();
+        encoder
+            .write_image(half_black().as_raw(), 4, 2, image::ExtendedColorType::Rgb8)
+            .unwrap();
+    }
+
+    /// Assert that `bytes` decode to [`half_black`] rotated 90° CW.
+    fn assert_rotated_90(bytes: &[u8]) {
+        let (image, orientation) = decode_oriented(bytes, 0).unwrap();
+        assert_eq!(orientation, Orientation::Rotate90);
+        let gray = to_grayscale(&image);
+        assert_eq!((gray.width(), gray.height()), (2, 4));
+        // The black left half ends up on top.
+        assert!(gray.get_pixel(0, 0).0[0] < 64);
+        assert!(gray.get_pixel(0, 3).0[0] > 192);
+    }
+
+    #[test]
+    fn exif_rotation_is_applied() {
+        // Orientation 6: stored rotated, display after rotating 90° CW.
+        assert_rotated_90(&jpeg_with_orientation(6));
+    }
+
+    #[test]
+    fn png_exif_rotation_is_applied() {
+        let mut png = Vec::new();
+        encode_with_orientation(image::codecs::png::PngEncoder::new(&mut png), 6);
+        assert_rotated_90(&png);
+    }
+
+    #[test]
+    fn webp_exif_rotation_is_applied() {
+        let mut webp = Vec::new();
+        encode_with_orientation(image::codecs::webp::WebPEncoder::new_lossless(&mut webp), 6);
+        assert_rotated_90(&webp);
+    }
+
+    #[test]
+    fn exif_flip_is_applied() {
+        let (image, orientation) = decode_oriented(&jpeg_with_orientation(2), 0).unwrap();
+        assert_eq!(orientation, Orientation::FlipHorizontal);
+        let gray = to_grayscale(&image);
+        assert_eq!((gray.width(), gray.height()), (4, 2));
+        assert!(gray.get_pixel(0, 0).0[0] > 192);
+        assert!(gray.get_pixel(3, 0).0[0] < 64);
+    }
+
+    #[test]
+    fn missing_exif_means_no_transform() {
+        let (image, orientation) = decode_oriented(&encode_rgba_pixel(1, 2, 3), 0).unwrap();
+        assert_eq!(orientation, Orientation::NoTransforms);
+        assert_eq!((image.width(), image.height()), (1, 1));
+    }
+
+    #[cfg(feature = "gif")]
+    fn two_frame_gif() -> Vec<u8> {
+        let frames = [[255, 0, 0, 255], [0, 0, 255, 255]]
+            .map(|color| image::Frame::new(image::RgbaImage::from_pixel(3, 2, image::Rgba(color))));
+        let mut buf = Vec::new();
+        image::codecs::gif::GifEncoder::new(&mut buf)
+            .encode_frames(frames)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    #[cfg(feature = "gif")]
+    fn gif_frame_is_selectable() {
+        let gif = two_frame_gif();
+        let first = decode_oriented(&gif, 0).unwrap().0.to_rgba8();
+        let second = decode_oriented(&gif, 1).unwrap().0.to_rgba8();
+        assert_eq!((first.width(), first.height()), (3, 2));
+        assert!(first.get_pixel(0, 0).0[0] > 200, "first frame is red");
+        assert!(second.get_pixel(0, 0).0[2] > 200, "second frame is blue");
+    }
+
+    #[test]
+    fn frame_past_the_end_selects_last_frame() {
+        let png = encode_rgba_pixel(0, 0, 255);
+        let still = decode_oriented(&png, 1).unwrap().0.to_rgba8();
+        assert_eq!(still.get_pixel(0, 0).0[2], 255);
+        #[cfg(feature = "gif")]
+        {
+            let last = decode_oriented(&two_frame_gif(), 5).unwrap().0.to_rgba8();
+            assert!(last.get_pixel(0, 0).0[2] > 200, "last frame is blue");
+        }
+    }
+
+    #[test]
+    fn frame_count_counts_gif_frames() {
+        assert_eq!(frame_count(&encode_rgba_pixel(0, 0, 0)), 1);
+        assert_eq!(frame_count(b"GIF89a"), 1);
+        #[cfg(feature = "gif")]
+        {
+            let gif = two_frame_gif();
+            assert_eq!(frame_count(&gif), 2);
+            // A GIF cut off inside its second frame still has one.
+            assert_eq!(frame_count(&gif[..gif.len() - 4]), 2);
+        }
+    }
+
+    /// A 16-bit grayscale ramp whose neighbouring values collapse to
+    /// the same 8-bit level.
+    fn gray16_ramp() -> image::ImageBuffer<image::Luma<u16>, Vec<u16>> {
+        image::ImageBuffer::from_fn(4, 1, |x, _| image::Luma([1000 + u16::try_from(x).unwrap()]))
+    }
+
+    #[test]
+    fn sixteen_bit_png_keeps_precise_luma() {
+        let img = DynamicImage::ImageLuma16(gray16_ramp());
+        let mut buf = Cursor::new(Vec::new());
+        img.write_to(&mut buf, ImageFormat::Png).unwrap();
+
+        let decoded = decode(buf.get_ref()).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::L16);
+        let luma = to_precise_luma(&decoded).unwrap();
+        // 8-bit luminance can't tell these pixels apart; f32 can.
+        assert_eq!(
+            to_grayscale(&decoded).get_pixel(0, 0),
+            to_grayscale(&decoded).get_pixel(1, 0)
+        );
+        assert!(luma.get_pixel(1, 0).0[0] > luma.get_pixel(0, 0).0[0]);
+        assert!((luma.get_pixel(0, 0).0[0] - 1000.0 / 257.0).abs() < 1e-3);
+    }
+
+    #[test]
+    #[cfg(feature = "tiff")]
+    fn sixteen_bit_tiff_decodes() {
+        let img = DynamicImage::ImageLuma16(gray16_ramp());
+        let mut buf = Cursor::new(Vec::new());
+        img.write_to(&mut buf, ImageFormat::Tiff).unwrap();
+
+        let decoded = decode(buf.get_ref()).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::L16);
+        assert!(to_precise_luma(&decoded).is_some());
+    }
+
+    #[test]
+    fn eight_bit_images_have_no_precise_luma() {
+        let decoded = decode(&encode_rgba_pixel(10, 20, 30)).unwrap();
+        assert!(to_precise_luma(&decoded).is_none());
+    }
+
+    #[test]
+    fn supported_extensions_follow_features() {
+        let extensions = supported_extensions();
+        for ext in ["png", "jpg", "jpeg", "bmp", "webp"] {
+            assert!(extensions.contains(&ext), "missing {ext}");
+        }
+        assert_eq!(extensions.contains(&"gif"), cfg!(feature = "gif"));
+        assert_eq!(extensions.contains(&"tiff"), cfg!(feature = "tiff"));
+        assert_eq!(extensions.contains(&"pgm"), cfg!(feature = "pnm"));
+        assert_eq!(extensions.contains(&"ico"), cfg!(feature = "ico"));
+        assert!(is_supported_extension("PNG"));
+        assert!(!is_supported_extension("svg"));
+    }
+
     /// Helper: encode a single 1x1 RGBA pixel as a PNG byte buffer.
     ///
     /// NOTE: The PNG encoding pattern here is duplicated in
@@ -13,8 +13,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::mst_join::{self, JoinQualityMetrics};
 use crate::optimize;
+use crate::progress::Progress;
 use crate::types::{
-    Dimensions, PipelineConfig, Point, Polyline, StartPointStrategy, polyline_bounding_box,
+    Dimensions, PipelineConfig, PipelineError, Point, Polyline, StartPointStrategy,
+    polyline_bounding_box,
 };
 
 /// Selects which path joining strategy to use.
@@ -71,14 +73,91 @@ pub enum PathJoinerKind {
     Mst,
 }
 
+/// Provenance of one segment of a joined path.
+///
+/// Joiners label every segment they emit, so downstream consumers can
+/// tell original geometry from the lines the join introduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SegmentKind {
+    /// Follows traced contour geometry, drawn for the first time.
+    Contour,
+    /// A new straight line between two contours — the only segments
+    /// that add visible lines not present in the image.
+    Connector,
+    /// Re-draws a segment the path has already covered (visually free
+    /// in sand).
+    Retrace,
+    /// Follows the canvas border polyline for the first time.
+    Border,
+}
+
+/// Total path length per [`SegmentKind`], in the path's units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SegmentLengths {
+    /// Length of [`SegmentKind::Contour`] segments.
+    pub contour: f64,
+    /// Length of [`SegmentKind::Connector`] segments: the visible new
+    /// line added by joining.
+    pub connector: f64,
+    /// Length of [`SegmentKind::Retrace`] segments.
+    pub retrace: f64,
+    /// Length of [`SegmentKind::Border`] segments.
+    pub border: f64,
+}
+
+impl SegmentLengths {
+    /// Sum segment lengths of `path` by kind (`segments[i]` labels the
+    /// segment from point `i` to point `i + 1`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mujou_pipeline::{Point, Polyline, SegmentKind, SegmentLengths};
+    ///
+    /// let path = Polyline::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(1.0, 0.0),
+    ///     Point::new(1.0, 2.0),
+    /// ]);
+    /// let lengths = SegmentLengths::measure(
+    ///     &path,
+    ///     &[SegmentKind::Contour, SegmentKind::Connector],
+    /// );
+    /// assert!((lengths.connector - 2.0).abs() < 1e-12);
+    /// ```
+    #[must_use]
+    pub fn measure(path: &Polyline, segments: &[SegmentKind]) -> Self {
+        let mut lengths = Self::default();
+        for (w, kind) in path.points().windows(2).zip(segments) {
+            let length = w[0].distance(w[1]);
+            match kind {
+                SegmentKind::Contour => lengths.contour += length,
+                SegmentKind::Connector => lengths.connector += length,
+                SegmentKind::Retrace => lengths.retrace += length,
+                SegmentKind::Border => lengths.border += length,
+            }
+        }
+        lengths
+    }
+}
+
 /// Result of a path joining operation.
 ///
-/// Contains the joined polyline and optional quality metrics.  Only the
-/// MST joiner currently produces metrics; other joiners return `None`.
+/// Contains the joined polyline, a provenance label for each of its
+/// segments, and optional quality metrics.  Only the MST joiner
+/// currently produces metrics; other joiners return `None`.
 #[derive(Debug)]
 pub struct JoinOutput {
     /// The single continuous output path.
     pub path: Polyline,
+    /// Provenance of each segment of `path`: `segments[i]` labels the
+    /// segment from point `i` to point `i + 1`, so the vector has one
+    /// entry fewer than the path has points.
+    ///
+    /// Joiners only label [`SegmentKind::Contour`], [`SegmentKind::Connector`]
+    /// and [`SegmentKind::Retrace`]; border segments are identified by
+    /// the pipeline, which knows which input is the border.
+    pub segments: Vec<SegmentKind>,
     /// Quality metrics for the join operation (MST only).
     pub quality_metrics: Option<JoinQualityMetrics>,
 }
@@ -93,6 +172,29 @@ pub struct JoinOutput {
 pub trait PathJoiner {
     /// Order and join the given contours into a single continuous path.
     fn join(&self, contours: &[Polyline], config: &PipelineConfig, dims: Dimensions) -> JoinOutput;
+
+    /// [`join`](Self::join) with cancellation and fractional progress.
+    ///
+    /// The default implementation only checks for cancellation before
+    /// joining and reports completion afterwards; joiners with
+    /// long-running phases override it with finer checkpoints.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if `progress` requests
+    /// cancellation.
+    fn join_with_progress(
+        &self,
+        contours: &[Polyline],
+        config: &PipelineConfig,
+        dims: Dimensions,
+        progress: &Progress<'_>,
+    ) -> Result<JoinOutput, PipelineError> {
+        progress.check()?;
+        let output = self.join(contours, config, dims);
+        progress.report(1.0);
+        Ok(output)
+    }
 }
 
 impl fmt::Display for PathJoinerKind {
@@ -107,33 +209,66 @@ impl fmt::Display for PathJoinerKind {
 
 impl PathJoiner for PathJoinerKind {
     fn join(&self, contours: &[Polyline], config: &PipelineConfig, dims: Dimensions) -> JoinOutput {
+        crate::progress::uncancellable(self.join_with_progress(
+            contours,
+            config,
+            dims,
+            &Progress::NONE,
+        ))
+    }
+
+    fn join_with_progress(
+        &self,
+        contours: &[Polyline],
+        config: &PipelineConfig,
+        dims: Dimensions,
+        progress: &Progress<'_>,
+    ) -> Result<JoinOutput, PipelineError> {
         match *self {
-            Self::StraightLine => JoinOutput {
-                path: join_straight_line(contours, config.start_point, dims),
-                quality_metrics: None,
-            },
-            Self::Retrace => JoinOutput {
-                path: join_retrace(contours, config.start_point, dims),
-                quality_metrics: None,
-            },
+            Self::StraightLine | Self::Retrace => {
+                progress.check()?;
+                let (path, segments) = if *self == Self::StraightLine {
+                    join_straight_line(contours, config.start_point, dims)
+                } else {
+                    join_retrace(contours, config.start_point, dims)
+                };
+                progress.report(1.0);
+                Ok(JoinOutput {
+                    path,
+                    segments,
+                    quality_metrics: None,
+                })
+            }
             Self::Mst => {
-                let (path, metrics) = mst_join::join_mst(
+                let (path, segments, metrics) = mst_join::join_mst_labeled(
                     contours,
                     config.mst_neighbours,
                     config.working_resolution,
                     config.parity_strategy,
                     config.start_point,
                     dims,
-                );
-                JoinOutput {
+                    progress,
+                )?;
+                Ok(JoinOutput {
                     path,
+                    segments,
                     quality_metrics: Some(metrics),
-                }
+                })
             }
         }
     }
 }
 
+/// Label the segments ending at the points pushed onto a path since the
+/// last call: grows `segments` to `path_len - 1` entries of `kind`.
+///
+/// Joiners call this after each emission phase (a connecting jump, a
+/// run of contour points, a retrace), so the labels stay parallel to
+/// the output path.
+fn label_new_segments(segments: &mut Vec<SegmentKind>, path_len: usize, kind: SegmentKind) {
+    segments.resize(path_len.saturating_sub(1), kind);
+}
+
 // ---------------------------------------------------------------------------
 // Start-point selection helper
 // ---------------------------------------------------------------------------
@@ -199,22 +334,28 @@ pub(crate) fn choose_start_contour(
 /// Delegates ordering to [`optimize::optimize_path_order()`], then
 /// concatenates contours end-to-start. The connecting segments are
 /// implicit -- the last point of contour N and the first point of
-/// contour N+1 form the straight-line jump.
+/// contour N+1 form the straight-line jump, labelled
+/// [`SegmentKind::Connector`].
 fn join_straight_line(
     contours: &[Polyline],
     strategy: StartPointStrategy,
     dims: Dimensions,
-) -> Polyline {
+) -> (Polyline, Vec<SegmentKind>) {
     let ordered = optimize::optimize_path_order(contours, strategy, dims);
 
     let total_points: usize = ordered.iter().map(Polyline::len).sum();
     let mut points = Vec::with_capacity(total_points);
-
-    for contour in &ordered {
-        points.extend_from_slice(contour.points());
+    let mut segments = Vec::with_capacity(total_points.saturating_sub(1));
+
+    for contour in ordered.iter().filter(|c| !c.is_empty()) {
+        let pts = contour.points();
+        points.push(pts[0]);
+        label_new_segments(&mut segments, points.len(), SegmentKind::Connector);
+        points.extend_from_slice(&pts[1..]);
+        label_new_segments(&mut segments, points.len(), SegmentKind::Contour);
     }
 
-    Polyline::new(points)
+    (Polyline::new(points), segments)
 }
 
 // ---------------------------------------------------------------------------
@@ -498,14 +639,20 @@ const GRID_CELLS_PER_AXIS: f64 = 50.0;
 ///    d. Emit the chosen contour starting from the entry index,
 ///    covering the full contour via a split traversal when the
 ///    entry is interior.
-/// 4. Return the output path.
+/// 4. Return the output path with its segment labels: the jump from
+///    the history point to the entry is the only
+///    [`SegmentKind::Connector`] per contour.
 #[allow(clippy::too_many_lines)]
-fn join_retrace(contours: &[Polyline], strategy: StartPointStrategy, dims: Dimensions) -> Polyline {
+fn join_retrace(
+    contours: &[Polyline],
+    strategy: StartPointStrategy,
+    dims: Dimensions,
+) -> (Polyline, Vec<SegmentKind>) {
     // Filter out empty contours.
     let candidates: Vec<&Polyline> = contours.iter().filter(|c| !c.is_empty()).collect();
 
     if candidates.is_empty() {
-        return Polyline::new(Vec::new());
+        return (Polyline::new(Vec::new()), Vec::new());
     }
 
     // Derive cell size from bounding box.
@@ -520,6 +667,7 @@ fn join_retrace(contours: &[Polyline], strategy: StartPointStrategy, dims: Dimen
     let n = candidates.len();
     let mut used = vec![false; n];
     let mut output: Vec<Point> = Vec::new();
+    let mut segments: Vec<SegmentKind> = Vec::new();
     let mut grid = SpatialGrid::new(min_x, min_y, max_x, max_y, cell_size);
 
     // Reserve a lower bound (all contour points).
@@ -561,6 +709,7 @@ fn join_retrace(contours: &[Polyline], strategy: StartPointStrategy, dims: Dimen
     } else {
         emit_and_index(&mut output, &mut grid, candidates[start_idx].points());
     }
+    label_new_segments(&mut segments, output.len(), SegmentKind::Contour);
 
     // Seed caches: query ALL candidates against initial grid.
     for (j, sample_pts) in samples.iter().enumerate() {
@@ -622,28 +771,35 @@ fn join_retrace(contours: &[Polyline], strategy: StartPointStrategy, dims: Dimen
                 grid.insert(output.len() - 1, pt);
             }
         }
+        label_new_segments(&mut segments, output.len(), SegmentKind::Retrace);
 
-        // Emit the chosen contour from the entry index.
+        // Emit the chosen contour from the entry index.  The jump from
+        // the history point to the entry is the connector.
         let pts = candidates[chosen_idx].points();
         let e = best_entry_idx;
         let last = pts.len() - 1;
 
+        emit_and_index(&mut output, &mut grid, &pts[e..=e]);
+        label_new_segments(&mut segments, output.len(), SegmentKind::Connector);
         if e == 0 {
-            emit_and_index(&mut output, &mut grid, pts);
+            emit_and_index(&mut output, &mut grid, &pts[1..]);
         } else if e == last {
-            emit_reversed_and_index(&mut output, &mut grid, pts);
+            emit_reversed_and_index(&mut output, &mut grid, &pts[..last]);
         } else {
             // Split traversal: forward → retrace → backward.
-            emit_and_index(&mut output, &mut grid, &pts[e..]);
+            emit_and_index(&mut output, &mut grid, &pts[e + 1..]);
+            label_new_segments(&mut segments, output.len(), SegmentKind::Contour);
             for i in (e..last).rev() {
                 output.push(pts[i]);
                 grid.insert(output.len() - 1, pts[i]);
             }
+            label_new_segments(&mut segments, output.len(), SegmentKind::Retrace);
             for i in (0..e).rev() {
                 output.push(pts[i]);
                 grid.insert(output.len() - 1, pts[i]);
             }
         }
+        label_new_segments(&mut segments, output.len(), SegmentKind::Contour);
 
         // Update caches for candidates near the newly emitted contour.
         // Only the contour's own points are passed because the retrace
@@ -660,7 +816,7 @@ fn join_retrace(contours: &[Polyline], strategy: StartPointStrategy, dims: Dimen
         );
     }
 
-    Polyline::new(output)
+    (Polyline::new(output), segments)
 }
 
 #[cfg(test)]
@@ -1103,4 +1259,45 @@ mod tests {
     fn start_point_strategy_default_is_outside() {
         assert_eq!(StartPointStrategy::default(), StartPointStrategy::Outside);
     }
+
+    #[test]
+    fn straight_line_labels_jumps_as_connectors() {
+        let contours = [
+            Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]),
+            Polyline::new(vec![Point::new(1.0, 3.0), Point::new(2.0, 3.0)]),
+        ];
+        let output = PathJoinerKind::StraightLine.join(&contours, &default_config(), TEST_DIMS);
+        assert_eq!(output.segments.len(), output.path.len() - 1);
+        assert_eq!(
+            output
+                .segments
+                .iter()
+                .filter(|&&k| k == SegmentKind::Connector)
+                .count(),
+            1,
+        );
+        let lengths = SegmentLengths::measure(&output.path, &output.segments);
+        assert!((lengths.contour - 2.0).abs() < 1e-12);
+        assert!(lengths.retrace.abs() < 1e-12);
+    }
+
+    #[test]
+    fn retrace_labels_split_traversal() {
+        // Same layout as `retrace_interior_entry_split_traversal`: the
+        // long contour is entered at its midpoint (0, 10) from (0, 0).
+        let c0 = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]);
+        let c1 = Polyline::new(
+            (0..101)
+                .map(|i| Point::new(f64::from(i) - 50.0, 10.0))
+                .collect(),
+        );
+        let output = PathJoinerKind::Retrace.join(&[c0, c1], &default_config(), TEST_DIMS);
+        assert_eq!(output.segments.len(), output.path.len() - 1);
+
+        let lengths = SegmentLengths::measure(&output.path, &output.segments);
+        assert!((lengths.connector - 10.0).abs() < 1e-9, "{lengths:?}");
+        assert!((lengths.contour - 101.0).abs() < 1e-9, "{lengths:?}");
+        // The split traversal runs back from one end of c1 to the entry.
+        assert!((lengths.retrace - 50.0).abs() < 1e-9, "{lengths:?}");
+    }
 }
@@ -1,16 +1,20 @@
 //! mujou-pipeline: Pure image processing pipeline (sans-IO).
 //!
 //! Converts raster images into vector polylines through:
-//! blur -> edge detection -> contour tracing ->
+//! tone -> blur -> edge detection -> contour tracing ->
 //! simplification -> optional mask -> ordering + joining.
 //!
 //! This crate has **no I/O dependencies** -- it operates on in-memory
 //! byte slices and returns structured data. All browser/filesystem
 //! interaction lives in `mujou-io`.
 
+pub mod alpha;
 pub mod blur;
 mod canny;
+pub mod center_avoid;
 pub mod contour;
+pub mod contour_filter;
+pub mod curve;
 pub mod diagnostics;
 pub mod downsample;
 pub mod edge;
@@ -20,30 +24,46 @@ pub mod mask;
 pub mod mst_join;
 pub mod normalize;
 pub mod optimize;
+mod par;
+pub mod perimeter;
 pub mod pipeline;
+pub mod progress;
+pub mod quality;
 pub mod segment_analysis;
 pub mod simplify;
 pub mod subsample;
+pub mod tone;
 pub mod types;
 
+pub use alpha::AlphaMode;
+pub use blur::{Smoother, SmoothingKind};
 pub use contour::{ContourTracer, ContourTracerKind};
+pub use contour_filter::ContourFilter;
+pub use curve::{CubicBezier, CurveKind, CurveSettings, fit_cubic_beziers};
 pub use diagnostics::PipelineDiagnostics;
 pub use downsample::DownsampleFilter;
 pub use edge::max_gradient_magnitude;
-pub use join::{JoinOutput, PathJoiner, PathJoinerKind};
+pub use join::{JoinOutput, PathJoiner, PathJoinerKind, SegmentKind, SegmentLengths};
 pub use mask::{BorderPathMode, CanvasShape, ClippedPolyline, MaskResult, MaskShape};
 pub use mst_join::{JoinQualityMetrics, MstEdgeInfo, ParityStrategy};
 pub use pipeline::{Pipeline, PipelineCache};
-pub use segment_analysis::{RankedSegment, SEGMENT_COLORS, find_top_segments};
+pub use progress::{CancelToken, Progress};
+pub use quality::QualityMetrics;
+pub use segment_analysis::{RankedSegment, SEGMENT_COLORS, SegmentRun, find_top_segments};
+pub use simplify::{Simplifier, SimplifierKind};
+pub use subsample::SubsampleMode;
+pub use tone::{ToneKind, ToneSettings};
 pub use types::{
-    Dimensions, EdgeChannels, GrayImage, PipelineConfig, PipelineError, Point, Polyline,
-    ProcessResult, RgbaImage, StagedResult, StartPointStrategy,
+    CannyThresholds, Dimensions, EdgeChannel, EdgeChannels, EdgeFusion, GrayImage, Luma32FImage,
+    PipelineConfig, PipelineError, Point, Polyline, ProcessResult, RgbaImage, StagedResult,
+    StartPointStrategy,
 };
 
 /// Run the full image processing pipeline, preserving all intermediate
 /// stage outputs.
 ///
-/// Takes raw image bytes (PNG, JPEG, BMP, WebP) and a configuration,
+/// Takes raw image bytes (PNG, JPEG, BMP, WebP, and with the default
+/// features GIF, TIFF, PNM, ICO) and a configuration,
 /// then produces a [`StagedResult`] containing every intermediate result
 /// along with the source image dimensions.
 ///
@@ -51,14 +71,15 @@ pub use types::{
 ///
 /// 1. Decode image
 /// 2. Downsample to working resolution
-/// 3. Gaussian blur (RGBA, preserves color for UI preview)
-/// 4. Canny edge detection
-/// 5. Optional edge map inversion
-/// 6. Contour tracing (pluggable strategy)
-/// 7. Path simplification (Ramer-Douglas-Peucker)
-/// 8. Optional mask (circle or rectangle)
-/// 9. Path ordering + joining into single continuous path (pluggable strategy;
-///    each joiner handles its own ordering internally)
+/// 3. Optional contrast normalization (histogram equalization, CLAHE, gamma)
+/// 4. Gaussian blur (RGBA, preserves color for UI preview)
+/// 5. Canny edge detection
+/// 6. Optional edge map inversion
+/// 7. Contour tracing (pluggable strategy)
+/// 8. Path simplification (Ramer-Douglas-Peucker)
+/// 9. Optional mask (circle or rectangle)
+/// 10. Path ordering + joining into single continuous path (pluggable strategy;
+///     each joiner handles its own ordering internally)
 ///
 /// # Errors
 ///
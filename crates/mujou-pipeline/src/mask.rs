@@ -7,12 +7,15 @@
 //!
 //! This is step 7 in the pipeline (optional), applied before path joining.
 
+use std::collections::HashSet;
 use std::f64::consts::PI;
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{Point, Polyline};
+use crate::join::SegmentKind;
+use crate::progress::Progress;
+use crate::types::{PipelineError, Point, Polyline};
 
 // ──────────────────────────── Public types ────────────────────────────
 
@@ -86,6 +89,20 @@ impl MaskShape {
             } => generate_rectangle_border(*center, *half_width, *half_height),
         }
     }
+
+    /// Whether `p` (in normalized coordinates) lies inside or on the
+    /// mask boundary.
+    #[must_use]
+    pub fn contains(&self, p: Point) -> bool {
+        match *self {
+            Self::Circle { center, radius } => is_inside(p, center, radius * radius),
+            Self::Rectangle {
+                center,
+                half_width,
+                half_height,
+            } => is_inside_rect(p, center, half_width, half_height),
+        }
+    }
 }
 
 /// A polyline produced by mask clipping, with explicit metadata about
@@ -145,6 +162,59 @@ impl MaskResult {
             .iter()
             .any(|c| c.start_clipped || c.end_clipped)
     }
+
+    /// Relabel the [`SegmentKind::Contour`] segments of a joined `path`
+    /// that follow the border polyline as [`SegmentKind::Border`].
+    ///
+    /// `segments[i]` labels the segment from point `i` to point `i + 1`.
+    /// A segment follows the border when both endpoints are border
+    /// vertices, or when one is and the other lies on the border within
+    /// [`BORDER_SPLIT_TOLERANCE`] (a joiner split the border segment to
+    /// attach a connector there).  Does nothing without a border.
+    pub fn mark_border_segments(&self, path: &Polyline, segments: &mut [SegmentKind]) {
+        let Some(border) = &self.border else {
+            return;
+        };
+        let key = |p: Point| (p.x.to_bits(), p.y.to_bits());
+        let vertices: HashSet<(u64, u64)> = border.points().iter().map(|&p| key(p)).collect();
+        let on_border = |p: Point| {
+            border
+                .points()
+                .windows(2)
+                .any(|w| point_segment_distance(p, w[0], w[1]) <= BORDER_SPLIT_TOLERANCE)
+        };
+
+        for (w, kind) in path.points().windows(2).zip(segments.iter_mut()) {
+            if *kind != SegmentKind::Contour {
+                continue;
+            }
+            let follows_border =
+                match (vertices.contains(&key(w[0])), vertices.contains(&key(w[1]))) {
+                    (true, true) => true,
+                    (true, false) => on_border(w[1]),
+                    (false, true) => on_border(w[0]),
+                    (false, false) => false,
+                };
+            if follows_border {
+                *kind = SegmentKind::Border;
+            }
+        }
+    }
+}
+
+/// Distance (normalized units) within which a point counts as lying on
+/// the border polyline in [`MaskResult::mark_border_segments`].
+pub const BORDER_SPLIT_TOLERANCE: f64 = 1e-9;
+
+/// Euclidean distance from `p` to the segment `a`–`b`.
+fn point_segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx.mul_add(dx, dy * dy);
+    if len_sq == 0.0 {
+        return p.distance(a);
+    }
+    let t = ((p.x - a.x).mul_add(dx, (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0);
+    p.distance(Point::new(t.mul_add(dx, a.x), t.mul_add(dy, a.y)))
 }
 
 /// Controls whether a border polyline matching the canvas shape is added
@@ -190,20 +260,33 @@ impl fmt::Display for BorderPathMode {
 /// which endpoints were created by intersection with the boundary.
 #[must_use = "returns the clipped polylines with clip metadata"]
 pub fn apply_mask(polylines: &[Polyline], shape: &MaskShape) -> Vec<ClippedPolyline> {
-    match shape {
-        MaskShape::Circle { center, radius } => polylines
-            .iter()
-            .flat_map(|pl| clip_polyline_to_circle(pl, *center, *radius))
-            .collect(),
+    crate::progress::uncancellable(apply_mask_with_progress(polylines, shape, &Progress::NONE))
+}
+
+/// [`apply_mask`] with cancellation and per-polyline progress.
+///
+/// Reports the fraction of input polylines clipped so far and polls
+/// for cancellation before clipping each one.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+pub fn apply_mask_with_progress(
+    polylines: &[Polyline],
+    shape: &MaskShape,
+    progress: &Progress<'_>,
+) -> Result<Vec<ClippedPolyline>, PipelineError> {
+    let per_polyline = crate::par::map_with_progress(polylines, progress, |_, pl| match shape {
+        MaskShape::Circle { center, radius } => clip_polyline_to_circle(pl, *center, *radius),
         MaskShape::Rectangle {
             center,
             half_width,
             half_height,
-        } => polylines
-            .iter()
-            .flat_map(|pl| clip_polyline_to_rectangle(pl, *center, *half_width, *half_height))
-            .collect(),
-    }
+        } => clip_polyline_to_rectangle(pl, *center, *half_width, *half_height),
+    })?;
+    progress.report(1.0);
+    Ok(per_polyline.into_iter().flatten().collect())
 }
 
 // ──────────────────── Circle clipping (internal) ─────────────────────
@@ -805,6 +888,45 @@ mod tests {
         assert!(!result[0].end_clipped);
     }
 
+    #[test]
+    fn apply_mask_matches_per_polyline_clipping() {
+        // Enough polylines to span several parallel chunks.
+        let polylines: Vec<Polyline> = (0..200)
+            .map(|i| {
+                let t = f64::from(i) * 0.1;
+                Polyline::new(vec![
+                    Point::new(t.cos().mul_add(14.0, 1.0), t.sin().mul_add(14.0, 1.0)),
+                    Point::new(t.sin(), t.cos()),
+                    Point::new(t.cos().mul_add(-14.0, 2.0), 0.5),
+                ])
+            })
+            .collect();
+        let shape = MaskShape::Circle {
+            center: CENTER,
+            radius: RADIUS,
+        };
+        let expected: Vec<ClippedPolyline> = polylines
+            .iter()
+            .flat_map(|pl| clip_polyline_to_circle(pl, CENTER, RADIUS))
+            .collect();
+        assert_eq!(apply_mask(&polylines, &shape), expected);
+    }
+
+    #[test]
+    fn apply_mask_with_progress_stops_when_cancelled() {
+        let polylines = vec![
+            Polyline::new(vec![Point::new(0.0, 0.0), Point::new(3.0, 0.0)]),
+            Polyline::new(vec![Point::new(1.0, 1.0), Point::new(2.0, 2.0)]),
+        ];
+        let shape = MaskShape::Circle {
+            center: CENTER,
+            radius: RADIUS,
+        };
+        let progress = Progress::new(&|| true, &|_| {});
+        let result = apply_mask_with_progress(&polylines, &shape, &progress);
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+    }
+
     // ── MaskResult ───────────────────────────────────────────────────
 
     #[test]
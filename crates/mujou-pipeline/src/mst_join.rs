@@ -20,6 +20,8 @@
 //!
 //! 4. **Phase 4 — Emit:** Convert the vertex sequence into a `Polyline`.
 
+use std::collections::HashSet;
+
 use geo::line_measures::Distance;
 use geo::{Closest, ClosestPoint, Euclidean, Line};
 use petgraph::algo::dijkstra;
@@ -30,8 +32,11 @@ use rstar::RTree;
 use rstar::primitives::GeomWithData;
 use serde::{Deserialize, Serialize};
 
-use crate::join::image_center;
-use crate::types::{Dimensions, Point, Polyline, StartPointStrategy, polyline_bounding_box};
+use crate::join::{SegmentKind, image_center};
+use crate::progress::Progress;
+use crate::types::{
+    Dimensions, PipelineError, Point, Polyline, StartPointStrategy, polyline_bounding_box,
+};
 
 // ---------------------------------------------------------------------------
 // Parity-fixing strategy
@@ -367,12 +372,19 @@ const SAMPLE_SPACING_PIXELS: f64 = 5.0;
 /// candidates are sorted by distance and processed via Kruskal's
 /// union-find merge.
 ///
-/// Returns a list of [`MstEdge`]s (one fewer than the number of polylines).
+/// Returns a list of [`MstEdge`]s (one fewer than the number of polylines),
+/// or [`PipelineError::Cancelled`] if `progress` requests cancellation.
+/// Progress is reported per polyline during candidate generation.
 #[allow(clippy::too_many_lines)]
-fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32) -> Vec<MstEdge> {
+fn build_mst(
+    polylines: &[&Polyline],
+    k_nearest: usize,
+    working_resolution: u32,
+    progress: &Progress<'_>,
+) -> Result<Vec<MstEdge>, PipelineError> {
     let n = polylines.len();
     if n <= 1 {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
     // Build R-tree of all segments.
@@ -413,10 +425,11 @@ fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32)
 
     // Generate candidate edges via R-tree k-nearest queries.
     // For each sample point, find the K nearest segments and record
-    // cross-polyline connections as candidate edges.
-    let mut candidates: Vec<(f64, MstEdge)> = Vec::new();
-
-    for samples in &all_samples {
+    // cross-polyline connections as candidate edges.  Polylines are
+    // queried independently; flattening in polyline order keeps the
+    // candidate list identical to a serial run.
+    let per_polyline = crate::par::map_with_progress(&all_samples, progress, |_, samples| {
+        let mut found: Vec<(f64, MstEdge)> = Vec::new();
         for &(query_pt, poly_idx, seg_idx) in samples {
             let my_pts = polylines[poly_idx].points();
             let my_seg_end = (seg_idx + 1).min(my_pts.len().saturating_sub(1));
@@ -453,7 +466,7 @@ fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32)
 
                 let use_dist = dist.min(exact_dist);
 
-                candidates.push((
+                found.push((
                     use_dist,
                     MstEdge {
                         poly_a: poly_idx,
@@ -471,7 +484,9 @@ fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32)
                 }
             }
         }
-    }
+        found
+    })?;
+    let mut candidates: Vec<(f64, MstEdge)> = per_polyline.into_iter().flatten().collect();
 
     // Sort candidates by distance (Kruskal's algorithm).
     candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -510,6 +525,7 @@ fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32)
         // Gather all cross-component endpoint-pair candidates.
         let mut fallback_candidates: Vec<(f64, MstEdge)> = Vec::new();
         for (idx_i, &root_a) in comp_roots.iter().enumerate() {
+            progress.check()?;
             for &root_b in comp_roots.iter().skip(idx_i + 1) {
                 let mut best_dist = f64::INFINITY;
                 let mut best_edge: Option<MstEdge> = None;
@@ -570,7 +586,8 @@ fn build_mst(polylines: &[&Polyline], k_nearest: usize, working_resolution: u32)
         }
     }
 
-    edges
+    progress.report(1.0);
+    Ok(edges)
 }
 
 // ---------------------------------------------------------------------------
@@ -599,13 +616,16 @@ impl CoordKey {
 
 /// Build the Eulerian graph from polyline segments and MST connecting edges.
 ///
-/// Returns `(graph, node_coords)` where `node_coords[node_index]` gives
-/// the `geo::Coord` for each node.
+/// Returns `(graph, node_coords, contour_edge_count)` where
+/// `node_coords[node_index]` gives the `geo::Coord` for each node.
+/// Polyline segments are added first, so edges with an index below
+/// `contour_edge_count` are contour geometry and the rest are MST
+/// connecting edges.
 #[allow(clippy::too_many_lines)]
 fn build_graph(
     polylines: &[&Polyline],
     mst_edges: &[MstEdge],
-) -> (UnGraph<(), f64>, Vec<geo::Coord<f64>>) {
+) -> (UnGraph<(), f64>, Vec<geo::Coord<f64>>, usize) {
     let mut graph = UnGraph::<(), f64>::new_undirected();
     let mut coord_to_node = std::collections::HashMap::<CoordKey, NodeIndex>::new();
     let mut node_coords: Vec<geo::Coord<f64>> = Vec::new();
@@ -714,6 +734,8 @@ fn build_graph(
         }
     }
 
+    let contour_edge_count = graph.edge_count();
+
     // Add MST connecting edges.
     //
     // Use the same snapped coordinates as the split-point insertion above
@@ -733,7 +755,7 @@ fn build_graph(
         // node — no edge is needed (the node is already connected).
     }
 
-    (graph, node_coords)
+    (graph, node_coords, contour_edge_count)
 }
 
 // ---------------------------------------------------------------------------
@@ -1489,6 +1511,31 @@ fn hierholzer(
 // Phase 4: Emit as Polyline
 // ---------------------------------------------------------------------------
 
+/// Label each step of an Eulerian path through the augmented graph.
+///
+/// The first traversal of a node pair is a [`SegmentKind::Connector`]
+/// when the pair is an MST connecting edge and a
+/// [`SegmentKind::Contour`] otherwise; any later traversal (a parity
+/// duplicate) is a [`SegmentKind::Retrace`].
+fn label_path(
+    path: &[NodeIndex],
+    connectors: &HashSet<(NodeIndex, NodeIndex)>,
+) -> Vec<SegmentKind> {
+    let mut drawn: HashSet<(NodeIndex, NodeIndex)> = HashSet::with_capacity(path.len());
+    path.windows(2)
+        .map(|w| {
+            let key = (w[0].min(w[1]), w[0].max(w[1]));
+            if !drawn.insert(key) {
+                SegmentKind::Retrace
+            } else if connectors.contains(&key) {
+                SegmentKind::Connector
+            } else {
+                SegmentKind::Contour
+            }
+        })
+        .collect()
+}
+
 /// Convert a sequence of node indices into a `Polyline` using the
 /// coordinate map.
 fn emit_polyline(path: &[NodeIndex], node_coords: &[geo::Coord<f64>]) -> Polyline {
@@ -1508,8 +1555,14 @@ fn emit_polyline(path: &[NodeIndex], node_coords: &[geo::Coord<f64>]) -> Polylin
 /// For [`Outside`](StartPointStrategy::Outside) the endpoint
 /// **farthest** from the image center should be first; for
 /// [`Inside`](StartPointStrategy::Inside) the **nearest**.
-/// If the wrong end currently leads, the path is reversed.
-fn orient_path(polyline: Polyline, strategy: StartPointStrategy, dims: Dimensions) -> Polyline {
+/// If the wrong end currently leads, the path (and its parallel
+/// `segments` labels) is reversed.
+fn orient_path(
+    polyline: Polyline,
+    segments: &mut [SegmentKind],
+    strategy: StartPointStrategy,
+    dims: Dimensions,
+) -> Polyline {
     let (Some(first), Some(last)) = (polyline.first(), polyline.last()) else {
         return polyline;
     };
@@ -1523,6 +1576,7 @@ fn orient_path(polyline: Polyline, strategy: StartPointStrategy, dims: Dimension
     if should_reverse {
         let mut pts = polyline.into_points();
         pts.reverse();
+        segments.reverse();
         Polyline::new(pts)
     } else {
         polyline
@@ -1559,7 +1613,6 @@ fn orient_path(polyline: Polyline, strategy: StartPointStrategy, dims: Dimension
 /// non-empty, parity-fixed graph — this would indicate a bug in graph
 /// construction or parity fixing.
 #[must_use]
-#[allow(clippy::expect_used)] // structural invariant: MST guarantees connectivity
 pub fn join_mst(
     contours: &[Polyline],
     k_nearest: usize,
@@ -1568,22 +1621,108 @@ pub fn join_mst(
     start_point: StartPointStrategy,
     dims: Dimensions,
 ) -> (Polyline, JoinQualityMetrics) {
+    crate::progress::uncancellable(join_mst_with_progress(
+        contours,
+        k_nearest,
+        working_resolution,
+        parity_strategy,
+        start_point,
+        dims,
+        &Progress::NONE,
+    ))
+}
+
+/// [`join_mst`] with cancellation and fractional progress.
+///
+/// MST candidate generation (usually the dominant cost) reports
+/// progress per polyline over the first 70% of the range; graph
+/// construction, parity fixing and the Eulerian walk are checkpointed
+/// between phases.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+///
+/// # Panics
+///
+/// Panics under the same structural-invariant violations as
+/// [`join_mst`].
+pub fn join_mst_with_progress(
+    contours: &[Polyline],
+    k_nearest: usize,
+    working_resolution: u32,
+    parity_strategy: ParityStrategy,
+    start_point: StartPointStrategy,
+    dims: Dimensions,
+    progress: &Progress<'_>,
+) -> Result<(Polyline, JoinQualityMetrics), PipelineError> {
+    let (path, _, metrics) = join_mst_labeled(
+        contours,
+        k_nearest,
+        working_resolution,
+        parity_strategy,
+        start_point,
+        dims,
+        progress,
+    )?;
+    Ok((path, metrics))
+}
+
+/// [`join_mst_with_progress`] that also labels each segment of the
+/// joined path (see [`JoinOutput::segments`]).
+///
+/// Segments follow the graph edge they traverse: MST connecting edges
+/// are [`SegmentKind::Connector`], polyline segments
+/// [`SegmentKind::Contour`], and the second traversal of any edge
+/// (parity duplication) [`SegmentKind::Retrace`].
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+///
+/// # Panics
+///
+/// Panics under the same structural-invariant violations as
+/// [`join_mst`].
+///
+/// [`JoinOutput::segments`]: crate::join::JoinOutput::segments
+#[allow(clippy::expect_used)] // structural invariant: MST guarantees connectivity
+#[allow(clippy::too_many_lines)]
+pub fn join_mst_labeled(
+    contours: &[Polyline],
+    k_nearest: usize,
+    working_resolution: u32,
+    parity_strategy: ParityStrategy,
+    start_point: StartPointStrategy,
+    dims: Dimensions,
+    progress: &Progress<'_>,
+) -> Result<(Polyline, Vec<SegmentKind>, JoinQualityMetrics), PipelineError> {
+    progress.check()?;
+
     // Filter out empty contours.
     let polylines: Vec<&Polyline> = contours.iter().filter(|c| !c.is_empty()).collect();
 
     if polylines.is_empty() {
-        return (Polyline::new(Vec::new()), JoinQualityMetrics::default());
+        return Ok((
+            Polyline::new(Vec::new()),
+            Vec::new(),
+            JoinQualityMetrics::default(),
+        ));
     }
 
     if polylines.len() == 1 {
-        let path = orient_path(polylines[0].clone(), start_point, dims);
+        let mut segments = vec![SegmentKind::Contour; polylines[0].len() - 1];
+        let path = orient_path(polylines[0].clone(), &mut segments, start_point, dims);
         let total_path_length = path_polyline_length(&path);
         // Single contour: no MST or Euler graph is constructed, so all
         // graph-related metrics are zero.  Only `total_path_length`
         // reflects the input — the remaining fields are legitimately
         // zero because the algorithm's graph phases were skipped.
-        return (
+        return Ok((
             path,
+            segments,
             JoinQualityMetrics {
                 mst_edge_count: 0,
                 total_mst_edge_weight: 0.0,
@@ -1597,11 +1736,16 @@ pub fn join_mst(
                 graph_edge_count_after_fix: 0,
                 mst_edge_details: Vec::new(),
             },
-        );
+        ));
     }
 
     // Phase 1: Build MST.
-    let mst_edges = build_mst(&polylines, k_nearest.max(1), working_resolution);
+    let mst_edges = build_mst(
+        &polylines,
+        k_nearest.max(1),
+        working_resolution,
+        &progress.span(0.0, 0.7),
+    )?;
 
     // MST edge metrics (criteria #1 and #4).
     let mst_edge_count = mst_edges.len();
@@ -1625,14 +1769,24 @@ pub fn join_mst(
         .collect();
 
     // Phase 2+3: Build graph, fix parity, find Eulerian path.
-    let (mut graph, node_coords) = build_graph(&polylines, &mst_edges);
+    let (mut graph, node_coords, contour_edge_count) = build_graph(&polylines, &mst_edges);
+    let connector_pairs: HashSet<(NodeIndex, NodeIndex)> = graph
+        .edge_indices()
+        .skip(contour_edge_count)
+        .filter_map(|e| graph.edge_endpoints(e))
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
     let graph_node_count = graph.node_count();
     let graph_edge_count_before_fix = graph.edge_count();
+    progress.report(0.75);
+    progress.check()?;
 
     let (total_retrace_distance, odd_vertices_before_fix, odd_vertices_after_fix) =
         fix_parity(&mut graph, &node_coords, parity_strategy, true)
             .expect("fix_parity: shortest-path reconstruction failed on MST-connected graph");
     let graph_edge_count_after_fix = graph.edge_count();
+    progress.report(0.9);
+    progress.check()?;
 
     let euler_path = hierholzer(&graph, &node_coords, start_point, dims);
 
@@ -1647,13 +1801,14 @@ pub fn join_mst(
 
     let total_path_length = compute_path_length(&euler_path, &node_coords);
     let polyline = emit_polyline(&euler_path, &node_coords);
+    let mut segments = label_path(&euler_path, &connector_pairs);
 
     // Orient the path so that the correct endpoint leads.  Hierholzer
     // picks a start vertex among odd-degree nodes but virtual-edge
     // removal can relocate the actual path endpoints.  Reversing an
     // Euler path is always valid — every edge traversed forward exists
     // in the undirected graph and can be traversed backward.
-    let polyline = orient_path(polyline, start_point, dims);
+    let polyline = orient_path(polyline, &mut segments, start_point, dims);
 
     let metrics = JoinQualityMetrics {
         mst_edge_count,
@@ -1669,7 +1824,8 @@ pub fn join_mst(
         mst_edge_details,
     };
 
-    (polyline, metrics)
+    progress.report(1.0);
+    Ok((polyline, segments, metrics))
 }
 
 /// Compute the total Euclidean length of a `Polyline`.
@@ -1710,6 +1866,69 @@ mod tests {
         assert_eq!(metrics.mst_edge_count, 0);
     }
 
+    fn three_separate_lines() -> Vec<Polyline> {
+        (0..3)
+            .map(|i| {
+                let y = f64::from(i) * 10.0;
+                Polyline::new(vec![Point::new(0.0, y), Point::new(5.0, y)])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mst_join_with_progress_matches_join_mst() {
+        let contours = three_separate_lines();
+        let fractions = std::cell::RefCell::new(Vec::new());
+        let on_fraction = |f| fractions.borrow_mut().push(f);
+        let progress = Progress::new(&|| false, &on_fraction);
+        let (path, _) = join_mst_with_progress(
+            &contours,
+            TEST_K,
+            TEST_RESOLUTION,
+            TEST_PARITY,
+            TEST_START,
+            TEST_DIMS,
+            &progress,
+        )
+        .unwrap();
+        let (expected, _) = join_mst(
+            &contours,
+            TEST_K,
+            TEST_RESOLUTION,
+            TEST_PARITY,
+            TEST_START,
+            TEST_DIMS,
+        );
+        assert_eq!(path, expected);
+
+        let fractions = fractions.into_inner();
+        assert!(fractions.windows(2).all(|w| w[0] <= w[1]), "{fractions:?}");
+        assert!((fractions.last().copied().unwrap() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn mst_join_with_progress_cancels_during_mst_build() {
+        let contours = three_separate_lines();
+        // Allow the entry check, then cancel at the first MST checkpoint.
+        let polls = std::cell::Cell::new(0);
+        let is_cancelled = || {
+            polls.set(polls.get() + 1);
+            polls.get() > 1
+        };
+        let progress = Progress::new(&is_cancelled, &|_| {});
+        let result = join_mst_with_progress(
+            &contours,
+            TEST_K,
+            TEST_RESOLUTION,
+            TEST_PARITY,
+            TEST_START,
+            TEST_DIMS,
+            &progress,
+        );
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+        assert_eq!(polls.get(), 2);
+    }
+
     #[test]
     fn mst_join_single_contour() {
         let contour = Polyline::new(vec![
@@ -2577,4 +2796,37 @@ mod tests {
         // Self-distance is 0.
         assert!((dist[1][1]).abs() < 1e-10);
     }
+
+    #[test]
+    fn labeled_join_marks_each_mst_edge_once_as_connector() {
+        let contours = vec![
+            Polyline::new(vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)]),
+            Polyline::new(vec![Point::new(0.0, 5.0), Point::new(10.0, 5.0)]),
+            Polyline::new(vec![Point::new(20.0, 0.0), Point::new(20.0, 10.0)]),
+        ];
+        let (path, segments, metrics) = join_mst_labeled(
+            &contours,
+            TEST_K,
+            TEST_RESOLUTION,
+            TEST_PARITY,
+            TEST_START,
+            TEST_DIMS,
+            &Progress::NONE,
+        )
+        .unwrap();
+        assert_eq!(segments.len(), path.len() - 1);
+
+        let lengths = crate::SegmentLengths::measure(&path, &segments);
+        assert!(
+            (lengths.connector - metrics.total_mst_edge_weight).abs() < 1e-9,
+            "{lengths:?} vs {}",
+            metrics.total_mst_edge_weight,
+        );
+        assert!((lengths.contour - 30.0).abs() < 1e-9, "{lengths:?}");
+        assert!(
+            (lengths.retrace - metrics.total_retrace_distance).abs() < 1e-9,
+            "{lengths:?} vs {}",
+            metrics.total_retrace_distance,
+        );
+    }
 }
@@ -61,6 +61,31 @@ pub fn normalize_contours(
         .collect()
 }
 
+/// Map a single pixel-space point into normalized coordinates.
+///
+/// Same transform as [`normalize_contours`].
+#[must_use]
+pub fn normalize_point(p: Point, dimensions: Dimensions, zoom: f64) -> Point {
+    let scale_factor = 2.0 * zoom / dimensions.shorter_dim();
+    Point::new(
+        (p.x - f64::from(dimensions.width) / 2.0) * scale_factor,
+        (f64::from(dimensions.height) / 2.0 - p.y) * scale_factor,
+    )
+}
+
+/// Map a normalized point back into pixel space at working resolution.
+///
+/// Inverse of [`normalize_point`]; used to rasterize output paths over
+/// the stage images.
+#[must_use]
+pub fn denormalize_point(p: Point, dimensions: Dimensions, zoom: f64) -> Point {
+    let scale_factor = 2.0 * zoom / dimensions.shorter_dim();
+    Point::new(
+        f64::from(dimensions.width) / 2.0 + p.x / scale_factor,
+        f64::from(dimensions.height) / 2.0 - p.y / scale_factor,
+    )
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -151,4 +176,21 @@ mod tests {
             p.x,
         );
     }
+
+    #[test]
+    fn denormalize_inverts_normalize() {
+        let d = dims(200, 100);
+        let pixel = Point::new(37.0, 81.5);
+        let norm = normalize_point(pixel, d, 1.25);
+        let expected = &normalize_contours(vec![Polyline::new(vec![pixel])], d, 1.25)[0];
+        assert!(norm.distance_squared(expected.points()[0]) < 1e-20);
+
+        let back = denormalize_point(norm, d, 1.25);
+        assert!(
+            back.distance_squared(pixel) < 1e-18,
+            "round trip should return the pixel, got ({}, {})",
+            back.x,
+            back.y,
+        );
+    }
 }
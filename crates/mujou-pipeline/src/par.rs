@@ -0,0 +1,114 @@
+//! Optional data parallelism behind the `parallel` cargo feature.
+//!
+//! With the feature enabled, the helpers here fan work out over the
+//! rayon global thread pool; without it they run serially.  Either way
+//! results are collected in input order, so a stage built on them
+//! produces bit-identical output in both configurations.
+//!
+//! The WASM build never enables `parallel` and is unaffected.
+
+use crate::progress::Progress;
+use crate::types::PipelineError;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Apply `f` to every item, returning the results in input order.
+pub fn map<T, U>(items: &[T], f: impl Fn(&T) -> U + Sync + Send) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Apply `f` to every `(index, item)` pair, returning the results in
+/// input order, with progress reporting and cancellation checks.
+///
+/// Serially, `progress` is stepped before every item.  In parallel
+/// the items are processed in chunks of a few items per worker thread,
+/// and `progress` — which is not thread-safe — is stepped between
+/// chunks on the calling thread.
+///
+/// # Errors
+///
+/// Returns [`PipelineError::Cancelled`] if `progress` requests
+/// cancellation.
+pub fn map_with_progress<T, U>(
+    items: &[T],
+    progress: &Progress<'_>,
+    f: impl Fn(usize, &T) -> U + Sync + Send,
+) -> Result<Vec<U>, PipelineError>
+where
+    T: Sync,
+    U: Send,
+{
+    let mut out = Vec::with_capacity(items.len());
+
+    #[cfg(feature = "parallel")]
+    {
+        let chunk_len = rayon::current_num_threads().max(1) * 4;
+        for (chunk_idx, chunk) in items.chunks(chunk_len).enumerate() {
+            let offset = chunk_idx * chunk_len;
+            progress.step(offset, items.len())?;
+            out.par_extend(
+                chunk
+                    .par_iter()
+                    .enumerate()
+                    .map(|(i, item)| f(offset + i, item)),
+            );
+        }
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        for (i, item) in items.iter().enumerate() {
+            progress.step(i, items.len())?;
+            out.push(f(i, item));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn map_preserves_order() {
+        let items: Vec<u32> = (0..1000).collect();
+        let squared = map(&items, |&x| x * x);
+        let expected: Vec<u32> = items.iter().map(|&x| x * x).collect();
+        assert_eq!(squared, expected);
+    }
+
+    #[test]
+    fn map_with_progress_preserves_order_and_indices() {
+        let items: Vec<u32> = (0..1000).rev().collect();
+        let result = map_with_progress(&items, &Progress::NONE, |i, &x| (i, x));
+        let expected: Vec<(usize, u32)> = items.iter().copied().enumerate().collect();
+        assert!(result.is_ok_and(|r| r == expected));
+    }
+
+    #[test]
+    fn map_with_progress_stops_when_cancelled() {
+        let items: Vec<u32> = (0..1000).collect();
+        let calls = Cell::new(0);
+        let is_cancelled = || {
+            calls.set(calls.get() + 1);
+            calls.get() > 1
+        };
+        let progress = Progress::new(&is_cancelled, &|_| {});
+        let result = map_with_progress(&items, &progress, |_, &x| x);
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+    }
+}
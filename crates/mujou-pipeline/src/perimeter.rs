@@ -0,0 +1,369 @@
+//! Perimeter routing: replace rim-to-rim connectors with travel along
+//! the circular canvas edge.
+//!
+//! Applied in the join stage (step 8), after the joiner has produced a
+//! single continuous path.
+//!
+//! On round sand tables the ball travelling along the rim (rho = 1) is
+//! nearly invisible — the outermost groove is hidden by the table frame
+//! and constantly re-traced.  A straight connector between two contours
+//! that both end near the edge, on the other hand, cuts a visible chord
+//! across the drawing.  Even with a [`MaskResult::border`] polyline in
+//! the join input, joiners still often pick the direct chord.
+//!
+//! [`route_along_perimeter`] finds connector segments — those the
+//! joiner labelled [`SegmentKind::Connector`] when it emitted them —
+//! whose endpoints both lie within ε of the mask edge and replaces them
+//! with the shorter arc of [`MaskShape::border_polyline`] between those
+//! endpoints.
+//!
+//! [`MaskResult::border`]: crate::mask::MaskResult::border
+
+use std::collections::HashSet;
+use std::f64::consts::PI;
+
+use crate::join::SegmentKind;
+use crate::mask::MaskShape;
+use crate::types::{Point, Polyline};
+
+/// Points closer than this are treated as duplicates when emitting
+/// rim travel (absorbs trig round-off at projected endpoints).
+const RIM_POINT_TOLERANCE: f64 = 1e-12;
+
+/// Replace rim-to-rim connectors in `path` with travel along the rim.
+///
+/// `segments[i]` labels the segment from point `i` to point `i + 1`
+/// (see [`JoinOutput::segments`]); missing labels are treated as
+/// [`SegmentKind::Contour`].  A [`SegmentKind::Connector`] segment is
+/// rerouted when both endpoints lie within `epsilon` of the circle
+/// boundary, and so is a later [`SegmentKind::Retrace`] of a rerouted
+/// connector, so the path never draws the chord it avoided.  The
+/// replacement runs radially from `a` onto the rim, follows the
+/// vertices of [`MaskShape::border_polyline`] along the shorter arc,
+/// and runs radially back in to `b` — so rerouted connectors retrace
+/// the border groove exactly when a border path is also drawn.
+///
+/// Every segment of a rerouted connector is labelled
+/// [`SegmentKind::Border`] (a rerouted retrace stays
+/// [`SegmentKind::Retrace`]), including the radial steps onto and off
+/// the rim (each at most `epsilon` long).  Other segments keep their
+/// label.
+///
+/// Only circular masks are supported; rectangular masks, empty paths
+/// and a negative `epsilon` return the path unchanged (by clone).
+///
+/// Returns the routed polyline, its segment labels, and the number of
+/// connectors that were rerouted.
+///
+/// [`JoinOutput::segments`]: crate::join::JoinOutput::segments
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{MaskShape, Point, Polyline, SegmentKind};
+/// use mujou_pipeline::perimeter::route_along_perimeter;
+///
+/// let shape = MaskShape::Circle { center: Point::new(0.0, 0.0), radius: 1.0 };
+/// // A joiner connected two contours with a chord (-1, 0) → (0, 1).
+/// let path = Polyline::new(vec![
+///     Point::new(-0.5, 0.0),
+///     Point::new(-1.0, 0.0),
+///     Point::new(0.0, 1.0),
+///     Point::new(0.0, 0.5),
+/// ]);
+/// let segments = [SegmentKind::Contour, SegmentKind::Connector, SegmentKind::Contour];
+/// let (routed, labels, count) = route_along_perimeter(&path, &segments, &shape, 0.01);
+/// assert_eq!(count, 1);
+/// assert!(routed.len() > path.len());
+/// assert_eq!(labels.len(), routed.len() - 1);
+/// ```
+#[must_use]
+pub fn route_along_perimeter(
+    path: &Polyline,
+    segments: &[SegmentKind],
+    shape: &MaskShape,
+    epsilon: f64,
+) -> (Polyline, Vec<SegmentKind>, usize) {
+    let points = path.points();
+    let label = |i: usize| segments.get(i).copied().unwrap_or(SegmentKind::Contour);
+    let unchanged = || {
+        let labels = (0..points.len().saturating_sub(1)).map(label).collect();
+        (path.clone(), labels, 0)
+    };
+    let MaskShape::Circle { center, radius } = *shape else {
+        return unchanged();
+    };
+    if points.len() < 2 || epsilon < 0.0 {
+        return unchanged();
+    }
+
+    let border = shape.border_polyline();
+    let near_rim = |p: Point| (p.distance(center) - radius).abs() <= epsilon;
+    // Both traversals of a connector come from the same joiner vertices,
+    // so its retrace has bit-identical endpoints.
+    let key = |a: Point, b: Point| {
+        let (a, b) = (
+            (a.x.to_bits(), a.y.to_bits()),
+            (b.x.to_bits(), b.y.to_bits()),
+        );
+        if a <= b { (a, b) } else { (b, a) }
+    };
+    let mut rim_chords: HashSet<_> = HashSet::new();
+
+    let mut result = Vec::with_capacity(points.len());
+    let mut labels = Vec::with_capacity(points.len());
+    result.push(points[0]);
+    let mut reroutes = 0;
+
+    for (i, window) in points.windows(2).enumerate() {
+        let a = window[0];
+        let b = window[1];
+        let kind = match label(i) {
+            SegmentKind::Connector if a != b && near_rim(a) && near_rim(b) => {
+                push_rim_arc(a, b, center, radius, &border, &mut result);
+                rim_chords.insert(key(a, b));
+                reroutes += 1;
+                SegmentKind::Border
+            }
+            SegmentKind::Retrace if rim_chords.contains(&key(a, b)) => {
+                push_rim_arc(a, b, center, radius, &border, &mut result);
+                SegmentKind::Retrace
+            }
+            kind => kind,
+        };
+        result.push(b);
+        labels.resize(result.len() - 1, kind);
+    }
+
+    (Polyline::new(result), labels, reroutes)
+}
+
+/// Push the rim travel between `a` and `b` (exclusive of both) onto
+/// `out`: the radial projection of `a`, the border vertices strictly
+/// inside the shorter arc, then the radial projection of `b`.
+fn push_rim_arc(
+    a: Point,
+    b: Point,
+    center: Point,
+    radius: f64,
+    border: &Polyline,
+    out: &mut Vec<Point>,
+) {
+    let start = (a.y - center.y).atan2(a.x - center.x);
+    let end = (b.y - center.y).atan2(b.x - center.x);
+    let mut sweep = (end - start) % (2.0 * PI);
+    if sweep > PI {
+        sweep -= 2.0 * PI;
+    } else if sweep <= -PI {
+        sweep += 2.0 * PI;
+    }
+
+    let project = |angle: f64| {
+        Point::new(
+            radius.mul_add(angle.cos(), center.x),
+            radius.mul_add(angle.sin(), center.y),
+        )
+    };
+    let push_distinct = |out: &mut Vec<Point>, p: Point| {
+        if out
+            .last()
+            .is_none_or(|last| last.distance(p) > RIM_POINT_TOLERANCE)
+        {
+            out.push(p);
+        }
+    };
+
+    push_distinct(out, project(start));
+
+    // The border polyline is closed (last point == first), with its
+    // `n` distinct vertices evenly spaced counter-clockwise from angle 0.
+    let vertices = &border.points()[..border.len().saturating_sub(1)];
+    if !vertices.is_empty() {
+        #[allow(clippy::cast_precision_loss)]
+        let step = 2.0 * PI / vertices.len() as f64;
+        let direction = sweep.signum();
+        // First border vertex strictly past `start` in the sweep direction.
+        let first = if direction > 0.0 {
+            (start / step).floor() + 1.0
+        } else {
+            (start / step).ceil() - 1.0
+        };
+        #[allow(clippy::cast_possible_wrap)]
+        let count = vertices.len() as i64;
+        let mut k = first;
+        loop {
+            if k.mul_add(step, -start) * direction >= sweep.abs() {
+                break;
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let idx = (k as i64).rem_euclid(count) as usize;
+            push_distinct(out, vertices[idx]);
+            k += direction;
+        }
+    }
+
+    let exit = project(end);
+    if exit.distance(b) > RIM_POINT_TOLERANCE {
+        push_distinct(out, exit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: build a simple polyline from (x, y) pairs.
+    fn poly(coords: &[(f64, f64)]) -> Polyline {
+        Polyline::new(coords.iter().map(|&(x, y)| Point::new(x, y)).collect())
+    }
+
+    fn unit_circle() -> MaskShape {
+        MaskShape::Circle {
+            center: Point::new(0.0, 0.0),
+            radius: 1.0,
+        }
+    }
+
+    /// Two contours touching the rim at (-1, 0) and (0, 1), joined by a
+    /// straight chord.
+    fn chord_fixture() -> (Polyline, Vec<SegmentKind>) {
+        let path = poly(&[(-0.5, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, 0.5)]);
+        let segments = vec![
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+            SegmentKind::Contour,
+        ];
+        (path, segments)
+    }
+
+    #[test]
+    fn rim_connector_follows_rim() {
+        let (path, segments) = chord_fixture();
+        let (routed, _, count) = route_along_perimeter(&path, &segments, &unit_circle(), 0.01);
+        assert_eq!(count, 1);
+        // Every point between the two contour endpoints lies on the rim.
+        let pts = routed.points();
+        for p in &pts[1..pts.len() - 1] {
+            assert!(
+                (p.x.hypot(p.y) - 1.0).abs() < 1e-9,
+                "point {p:?} is off the rim"
+            );
+        }
+        // The shorter arc from 180° to 90° passes through the upper-left
+        // quadrant only.
+        assert!(
+            pts[1..pts.len() - 1]
+                .iter()
+                .all(|p| p.x <= 1e-9 && p.y >= -1e-9)
+        );
+        assert_eq!(routed.first(), path.first());
+        assert_eq!(routed.last(), path.last());
+    }
+
+    #[test]
+    fn rim_arc_reuses_border_vertices() {
+        let (path, segments) = chord_fixture();
+        let shape = unit_circle();
+        let (routed, _, _) = route_along_perimeter(&path, &segments, &shape, 0.01);
+        let border = shape.border_polyline();
+        let interior = &routed.points()[2..routed.len() - 2];
+        assert!(!interior.is_empty());
+        for p in interior {
+            assert!(border.points().contains(p), "{p:?} is not a border vertex");
+        }
+    }
+
+    #[test]
+    fn contour_segments_are_not_rerouted() {
+        // A contour segment running rim-to-rim is real geometry.
+        let path = poly(&[(-1.0, 0.0), (0.0, 1.0)]);
+        let (routed, labels, count) =
+            route_along_perimeter(&path, &[SegmentKind::Contour], &unit_circle(), 0.01);
+        assert_eq!(count, 0);
+        assert_eq!(routed, path);
+        assert_eq!(labels, [SegmentKind::Contour]);
+    }
+
+    #[test]
+    fn unlabelled_segments_are_not_rerouted() {
+        let path = poly(&[(0.0, 1.0), (-1.0, 0.0)]);
+        let (routed, labels, count) = route_along_perimeter(&path, &[], &unit_circle(), 0.01);
+        assert_eq!(count, 0);
+        assert_eq!(routed, path);
+        assert_eq!(labels, [SegmentKind::Contour]);
+    }
+
+    #[test]
+    fn connector_away_from_rim_is_untouched() {
+        let path = poly(&[(0.0, 0.0), (-0.5, 0.0), (0.0, 0.5), (0.1, 0.1)]);
+        let segments = [
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+            SegmentKind::Contour,
+        ];
+        let (routed, _, count) = route_along_perimeter(&path, &segments, &unit_circle(), 0.01);
+        assert_eq!(count, 0);
+        assert_eq!(routed, path);
+    }
+
+    #[test]
+    fn endpoints_within_epsilon_are_projected_onto_rim() {
+        let path = poly(&[(-0.5, 0.0), (-0.98, 0.0), (0.0, 0.98), (0.0, 0.5)]);
+        let segments = [
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+            SegmentKind::Contour,
+        ];
+        let (routed, _, count) = route_along_perimeter(&path, &segments, &unit_circle(), 0.05);
+        assert_eq!(count, 1);
+        assert!(routed.points()[2].distance(Point::new(-1.0, 0.0)) < 1e-9);
+        assert!(routed.points()[routed.len() - 3].distance(Point::new(0.0, 1.0)) < 1e-9);
+    }
+
+    #[test]
+    fn rectangle_mask_is_noop() {
+        let (path, segments) = chord_fixture();
+        let shape = MaskShape::Rectangle {
+            center: Point::new(0.0, 0.0),
+            half_width: 1.0,
+            half_height: 1.0,
+        };
+        let (routed, labels, count) = route_along_perimeter(&path, &segments, &shape, 0.01);
+        assert_eq!(count, 0);
+        assert_eq!(routed, path);
+        assert_eq!(labels, segments);
+    }
+
+    #[test]
+    fn routing_keeps_labels_in_step() {
+        let (path, segments) = chord_fixture();
+        let (routed, labels, count) = route_along_perimeter(&path, &segments, &unit_circle(), 0.01);
+        assert_eq!(count, 1);
+        assert_eq!(labels.len(), routed.len() - 1);
+        assert_eq!(labels.first(), Some(&SegmentKind::Contour));
+        assert_eq!(labels.last(), Some(&SegmentKind::Contour));
+        let middle = &labels[1..labels.len() - 1];
+        assert!(!middle.is_empty());
+        assert!(middle.iter().all(|&k| k == SegmentKind::Border));
+    }
+
+    #[test]
+    fn retrace_of_rerouted_connector_follows_rim() {
+        // Out along the chord and back again (an MST parity duplicate).
+        let path = poly(&[(-0.5, 0.0), (-1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)]);
+        let segments = [
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+            SegmentKind::Retrace,
+        ];
+        let (routed, labels, count) = route_along_perimeter(&path, &segments, &unit_circle(), 0.01);
+        assert_eq!(count, 1);
+        for p in &routed.points()[1..] {
+            assert!(
+                (p.x.hypot(p.y) - 1.0).abs() < 1e-9,
+                "point {p:?} is off the rim"
+            );
+        }
+        assert_eq!(labels.last(), Some(&SegmentKind::Retrace));
+    }
+}
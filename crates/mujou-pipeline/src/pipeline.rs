@@ -11,10 +11,11 @@
 //! let pipeline = Pipeline::new(png, config)
 //!     .decode()?
 //!     .downsample()
+//!     .tone()
 //!     .blur()
 //!     .detect_edges()
 //!     .trace_contours()?
-//!     .simplify()
+//!     .simplify()?
 //!     .canvas()
 //!     .join()
 //!     .output();
@@ -32,7 +33,7 @@
 //! # Memory
 //!
 //! Every stage from [`ContoursTraced`] onward retains the full raster
-//! stack (original RGBA, blurred, and edge images) alongside
+//! stack (original, toned and blurred RGBA, and edge images) alongside
 //! the growing vector data. For a 1000×1000 source image this is roughly
 //! 7 MB of raster data pinned in memory until [`Joined::into_result`]
 //! consumes the final stage. This is intentional: [`StagedResult`] needs
@@ -45,14 +46,19 @@
 use std::sync::Arc;
 
 use image::DynamicImage;
+use image::metadata::Orientation;
 
+use crate::alpha::AlphaMode;
+use crate::blur::Smoother;
 use crate::contour::ContourTracer;
 use crate::diagnostics::StageMetrics;
-use crate::join::PathJoiner;
+use crate::join::{PathJoiner, SegmentKind, SegmentLengths};
 use crate::mask::{BorderPathMode, CanvasShape, MaskResult, MaskShape};
 use crate::mst_join::JoinQualityMetrics;
+use crate::progress::Progress;
 use crate::types::{
-    Dimensions, GrayImage, PipelineConfig, PipelineError, Point, Polyline, RgbaImage, StagedResult,
+    Dimensions, GrayImage, Luma32FImage, PipelineConfig, PipelineError, Point, Polyline, RgbaImage,
+    StagedResult,
 };
 
 // ───────────────────────── Stage 0: Pending ──────────────────────────
@@ -83,13 +89,15 @@ impl Pending {
     /// format is unrecognized or the data is corrupt.
     pub fn decode(self) -> Result<Decoded, PipelineError> {
         let source_len = self.source.len();
-        let image = crate::grayscale::decode(&self.source)?;
+        let (image, orientation) =
+            crate::grayscale::decode_oriented(&self.source, self.config.input_frame)?;
         let original = crate::grayscale::to_rgba(&image);
         Ok(Decoded {
             config: self.config,
             image,
             original,
             source_len,
+            orientation,
         })
     }
 }
@@ -107,27 +115,44 @@ pub struct Decoded {
     image: DynamicImage,
     original: RgbaImage,
     source_len: usize,
+    orientation: Orientation,
 }
 
 impl Decoded {
-    /// The original decoded RGBA image.
+    /// The original decoded RGBA image, rotated upright.
     #[must_use]
     pub const fn original(&self) -> &RgbaImage {
         &self.original
     }
 
+    /// The EXIF orientation applied to the decoded image.
+    #[must_use]
+    pub const fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
     /// Advance to the downsample stage.
+    ///
+    /// With [`AlphaMode::Composite`] the downsampled image is also
+    /// flattened over `config.alpha_background`.
     pub fn downsample(self) -> Downsampled {
         let (downsampled_dynamic, applied) = crate::downsample::downsample(
             &self.image,
             self.config.working_resolution,
             self.config.downsample_filter,
         );
-        let downsampled = crate::grayscale::to_rgba(&downsampled_dynamic);
+        let mut downsampled = crate::grayscale::to_rgba(&downsampled_dynamic);
+        let mut luma = crate::grayscale::to_precise_luma(&downsampled_dynamic);
+        if self.config.alpha_mode == AlphaMode::Composite {
+            let background = self.config.alpha_background;
+            luma = luma.map(|luma| crate::alpha::composite_luma(&luma, &downsampled, background));
+            downsampled = crate::alpha::composite(&downsampled, background);
+        }
         Downsampled {
             config: self.config,
             original: self.original,
             rgba: downsampled,
+            luma,
             applied,
         }
     }
@@ -138,14 +163,17 @@ impl Decoded {
 /// Pipeline state after downsampling to working resolution.
 ///
 /// The decoded image has been downsampled so the longest axis matches
-/// `config.working_resolution`. Call [`blur`](Self::blur) to
+/// `config.working_resolution`. Call [`tone`](Self::tone) to
 /// advance to the next stage.
-#[must_use = "pipeline stages are consumed by advancing — call .blur() to continue"]
+#[must_use = "pipeline stages are consumed by advancing — call .tone() to continue"]
 #[allow(clippy::struct_field_names)]
 pub struct Downsampled {
     config: PipelineConfig,
     original: RgbaImage,
     rgba: RgbaImage,
+    /// Full-precision luminance for sources with more than 8 bits per
+    /// channel.
+    luma: Option<Luma32FImage>,
     applied: bool,
 }
 
@@ -156,6 +184,14 @@ impl Downsampled {
         &self.rgba
     }
 
+    /// Full-precision luminance of the downsampled image, present only
+    /// for sources with more than 8 bits per channel (e.g. 16-bit PNG
+    /// or TIFF).
+    #[must_use]
+    pub const fn precise_luma(&self) -> Option<&Luma32FImage> {
+        self.luma.as_ref()
+    }
+
     /// Whether downsampling was actually applied (image was larger than
     /// `working_resolution`).
     #[must_use]
@@ -163,29 +199,87 @@ impl Downsampled {
         self.applied
     }
 
+    /// Advance to the tone stage.
+    ///
+    /// Applies the configured [`tone`](PipelineConfig::tone) mapping to
+    /// the RGBA image and, when present, the full-precision luminance.
+    /// With [`ToneKind::Off`](crate::ToneKind::Off) both pass through
+    /// unchanged.
+    pub fn tone(self) -> Toned {
+        let (toned, luma) = crate::tone::apply(&self.rgba, self.luma.as_ref(), &self.config.tone);
+        Toned {
+            config: self.config,
+            original: self.original,
+            downsampled: self.rgba,
+            toned,
+            luma,
+        }
+    }
+}
+
+// ───────────────────────── Stage 3: Toned ────────────────────────────
+
+/// Pipeline state after contrast normalization.
+///
+/// Call [`blur`](Self::blur) to advance to the next stage.
+#[must_use = "pipeline stages are consumed by advancing — call .blur() to continue"]
+#[allow(clippy::struct_field_names)]
+pub struct Toned {
+    config: PipelineConfig,
+    original: RgbaImage,
+    downsampled: RgbaImage,
+    toned: RgbaImage,
+    /// Tone-mapped full-precision luminance (see
+    /// [`Downsampled::precise_luma`]).
+    luma: Option<Luma32FImage>,
+}
+
+impl Toned {
+    /// The tone-mapped RGBA image.
+    #[must_use]
+    pub const fn toned(&self) -> &RgbaImage {
+        &self.toned
+    }
+
+    /// The tone-mapped full-precision luminance, if the source has more
+    /// than 8 bits per channel.
+    #[must_use]
+    pub const fn precise_luma(&self) -> Option<&Luma32FImage> {
+        self.luma.as_ref()
+    }
+
     /// Advance to the blur stage.
     ///
-    /// Applies Gaussian blur to the full RGBA image so the UI preview
+    /// Applies the configured [`smoothing`](PipelineConfig::smoothing)
+    /// (Gaussian by default) to the full RGBA image so the UI preview
     /// shows color (not grayscale). Downstream edge detection extracts
     /// channels from the already-blurred RGBA — no per-channel blur
-    /// needed.
+    /// needed.  The full-precision luminance, when present, is blurred
+    /// alongside it.
     pub fn blur(self) -> Blurred {
         let dimensions = Dimensions {
-            width: self.rgba.width(),
-            height: self.rgba.height(),
+            width: self.toned.width(),
+            height: self.toned.height(),
         };
-        let smooth = crate::blur::gaussian_blur_rgba(&self.rgba, self.config.blur_sigma);
+        let smoothing = self.config.smoothing;
+        let (sigma, range) = (self.config.blur_sigma, self.config.smoothing_range);
+        let smooth = smoothing.smooth_rgba(&self.toned, sigma, range);
+        let luma = self
+            .luma
+            .map(|luma| smoothing.smooth_luma32f(&luma, sigma, range));
         Blurred {
             config: self.config,
             original: self.original,
-            downsampled: self.rgba,
+            downsampled: self.downsampled,
+            toned: self.toned,
             smooth,
+            luma,
             dimensions,
         }
     }
 }
 
-// ───────────────────────── Stage 3: Blurred ──────────────────────────
+// ───────────────────────── Stage 4: Blurred ──────────────────────────
 
 /// Pipeline state after Gaussian blur.
 ///
@@ -199,7 +293,10 @@ pub struct Blurred {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     smooth: RgbaImage,
+    /// Blurred full-precision luminance (see [`Toned::precise_luma`]).
+    luma: Option<Luma32FImage>,
     dimensions: Dimensions,
 }
 
@@ -210,6 +307,13 @@ impl Blurred {
         &self.smooth
     }
 
+    /// The blurred full-precision luminance, if the source has more
+    /// than 8 bits per channel.
+    #[must_use]
+    pub const fn precise_luma(&self) -> Option<&Luma32FImage> {
+        self.luma.as_ref()
+    }
+
     /// Advance to the edge detection stage.
     ///
     /// Runs Canny edge detection on each enabled channel (see
@@ -218,33 +322,52 @@ impl Blurred {
     /// edge map when `config.invert` is `true`.
     ///
     /// All channels are extracted from the already-blurred RGBA image,
-    /// so no per-channel blurring is needed.
+    /// so no per-channel blurring is needed.  For high bit-depth
+    /// sources the luminance channel uses the full-precision blurred
+    /// luminance instead.
     pub fn detect_edges(self) -> EdgesDetected {
-        let edges_raw = crate::edge::canny_combined(
+        crate::progress::uncancellable(self.detect_edges_with_progress(&Progress::NONE))
+    }
+
+    /// [`detect_edges`](Self::detect_edges) with cancellation and
+    /// per-channel progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if `progress` requests
+    /// cancellation.
+    pub fn detect_edges_with_progress(
+        self,
+        progress: &Progress<'_>,
+    ) -> Result<EdgesDetected, PipelineError> {
+        let edges_raw = crate::edge::canny_combined_with_luma(
             &self.smooth,
+            self.luma.as_ref(),
             &self.config.edge_channels,
             self.config.canny_low,
             self.config.canny_high,
-        );
+            progress,
+        )?;
         let pre_invert_edge_pixels = crate::diagnostics::count_edge_pixels(&edges_raw);
         let edge_map = if self.config.invert {
             crate::edge::invert_edge_map(&edges_raw)
         } else {
             edges_raw
         };
-        EdgesDetected {
+        Ok(EdgesDetected {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.smooth,
             edge_map,
             pre_invert_edge_pixels,
             dimensions: self.dimensions,
-        }
+        })
     }
 }
 
-// ───────────────────────── Stage 4: EdgesDetected ────────────────────
+// ───────────────────────── Stage 5: EdgesDetected ────────────────────
 
 /// Pipeline state after Canny edge detection (and optional inversion).
 ///
@@ -256,6 +379,7 @@ pub struct EdgesDetected {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edge_map: GrayImage,
     /// Edge pixel count from Canny output, before optional inversion.
@@ -278,12 +402,19 @@ impl EdgesDetected {
     /// uses the mathematical convention (+Y up).  The normalization
     /// transform uses `config.zoom`.
     ///
+    /// With [`AlphaMode::Mask`], contours are first clipped to the
+    /// opaque region of the downsampled image.
+    ///
     /// # Errors
     ///
     /// Returns [`PipelineError::NoContours`] if the edge map produces no
     /// traceable contours.
     pub fn trace_contours(self) -> Result<ContoursTraced, PipelineError> {
-        let contours = self.config.contour_tracer.trace(&self.edge_map);
+        let mut contours = self.config.contour_tracer.trace(&self.edge_map);
+        if self.config.alpha_mode == AlphaMode::Mask {
+            let region = crate::alpha::opaque_region(&self.downsampled);
+            contours = crate::alpha::clip_to_region(&contours, &region);
+        }
         if contours.is_empty() {
             return Err(PipelineError::NoContours);
         }
@@ -294,6 +425,7 @@ impl EdgesDetected {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edge_map,
             contours,
@@ -302,7 +434,7 @@ impl EdgesDetected {
     }
 }
 
-// ───────────────────────── Stage 5: ContoursTraced ───────────────────
+// ───────────────────────── Stage 6: ContoursTraced ───────────────────
 
 /// Pipeline state after contour tracing.
 ///
@@ -315,6 +447,7 @@ pub struct ContoursTraced {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edges: GrayImage,
     contours: Vec<Polyline>,
@@ -329,25 +462,40 @@ impl ContoursTraced {
     }
 
     /// Advance to the simplification stage.
-    pub fn simplify(self) -> Simplified {
-        let reduced =
-            crate::simplify::simplify_paths(&self.contours, self.config.simplify_tolerance);
-        Simplified {
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::NoContours`] if the contour filter drops
+    /// every contour.
+    pub fn simplify(self) -> Result<Simplified, PipelineError> {
+        let reduced = crate::simplify::simplify_paths(
+            &self.contours,
+            self.config.simplifier,
+            self.config.simplify_tolerance,
+        );
+        let reduced = self.config.contour_filter.apply(reduced);
+        if reduced.is_empty() {
+            return Err(PipelineError::NoContours);
+        }
+        let reduced = self.config.curve.apply(reduced);
+        Ok(Simplified {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edges,
             contours: self.contours,
             reduced,
             dimensions: self.dimensions,
-        }
+        })
     }
 }
 
-// ───────────────────────── Stage 6: Simplified ───────────────────────
+// ───────────────────────── Stage 7: Simplified ───────────────────────
 
-/// Pipeline state after path simplification (RDP).
+/// Pipeline state after path simplification (RDP or Visvalingam-Whyatt,
+/// contour filtering and curve smoothing).
 ///
 /// Call [`mask`](Self::mask) to advance to the next stage.
 ///
@@ -358,6 +506,7 @@ pub struct Simplified {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edges: GrayImage,
     contours: Vec<Polyline>,
@@ -378,8 +527,9 @@ impl Simplified {
         polylines: &[Polyline],
         shape: MaskShape,
         border_mode: BorderPathMode,
-    ) -> MaskResult {
-        let clipped = crate::mask::apply_mask(polylines, &shape);
+        progress: &Progress<'_>,
+    ) -> Result<MaskResult, PipelineError> {
+        let clipped = crate::mask::apply_mask_with_progress(polylines, &shape, progress)?;
 
         let border = match border_mode {
             BorderPathMode::Off => None,
@@ -394,11 +544,11 @@ impl Simplified {
             }
         };
 
-        MaskResult {
+        Ok(MaskResult {
             clipped,
             border,
             shape,
-        }
+        })
     }
 
     /// Advance to the canvas stage.
@@ -411,6 +561,17 @@ impl Simplified {
     /// An optional border polyline is generated based on
     /// [`BorderPathMode`].
     pub fn canvas(self) -> Canvas {
+        crate::progress::uncancellable(self.canvas_with_progress(&Progress::NONE))
+    }
+
+    /// [`canvas`](Self::canvas) with cancellation and per-polyline
+    /// clipping progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if `progress` requests
+    /// cancellation.
+    pub fn canvas_with_progress(self, progress: &Progress<'_>) -> Result<Canvas, PipelineError> {
         let center = Point::new(0.0, 0.0);
 
         let margin_factor = 2.0f64.mul_add(-self.config.border_margin, 1.0);
@@ -419,7 +580,7 @@ impl Simplified {
             CanvasShape::Circle => {
                 let radius = 1.0 * margin_factor;
                 let shape = MaskShape::Circle { center, radius };
-                Self::clip_and_border(&self.reduced, shape, self.config.border_path)
+                Self::clip_and_border(&self.reduced, shape, self.config.border_path, progress)?
             }
             CanvasShape::Rectangle => {
                 let (half_width, half_height) = if self.config.landscape {
@@ -434,24 +595,25 @@ impl Simplified {
                     half_width,
                     half_height,
                 };
-                Self::clip_and_border(&self.reduced, shape, self.config.border_path)
+                Self::clip_and_border(&self.reduced, shape, self.config.border_path, progress)?
             }
         };
-        Canvas {
+        Ok(Canvas {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edges,
             contours: self.contours,
             simplified: self.reduced,
             canvas_result,
             dimensions: self.dimensions,
-        }
+        })
     }
 }
 
-// ───────────────────────── Stage 7: Canvas ───────────────────────────
+// ───────────────────────── Stage 8: Canvas ───────────────────────────
 
 /// Pipeline state after optional masking (circle or rectangle).
 ///
@@ -465,6 +627,7 @@ pub struct Canvas {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edges: GrayImage,
     contours: Vec<Polyline>,
@@ -481,29 +644,61 @@ impl Canvas {
     }
 
     /// Advance to the joining stage — the final pipeline step.
+    ///
+    /// When `config.perimeter_connectors` is set, rim-to-rim connectors
+    /// in the joined path are rerouted along the canvas border.
     pub fn join(self) -> Joined {
+        crate::progress::uncancellable(self.join_with_progress(&Progress::NONE))
+    }
+
+    /// [`join`](Self::join) with cancellation and fractional progress
+    /// from the path joiner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if `progress` requests
+    /// cancellation.
+    pub fn join_with_progress(self, progress: &Progress<'_>) -> Result<Joined, PipelineError> {
         let join_input: Vec<Polyline> = self.canvas_result.all_polylines().cloned().collect();
-        let output = self
-            .config
-            .path_joiner
-            .join(&join_input, &self.config, self.dimensions);
-        Joined {
+        let output = self.config.path_joiner.join_with_progress(
+            &join_input,
+            &self.config,
+            self.dimensions,
+            progress,
+        )?;
+        let mut segments = output.segments;
+        self.canvas_result
+            .mark_border_segments(&output.path, &mut segments);
+        let (path, segments, perimeter_reroutes) = if self.config.perimeter_connectors {
+            crate::perimeter::route_along_perimeter(
+                &output.path,
+                &segments,
+                &self.canvas_result.shape,
+                self.config.perimeter_epsilon,
+            )
+        } else {
+            (output.path, segments, 0)
+        };
+        Ok(Joined {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edges,
             contours: self.contours,
             simplified: self.simplified,
             canvas: self.canvas_result,
-            path: output.path,
+            path,
+            segments,
+            perimeter_reroutes,
             quality_metrics: output.quality_metrics,
             dimensions: self.dimensions,
-        }
+        })
     }
 }
 
-// ───────────────────────── Stage 8: Joined ───────────────────────────
+// ───────────────────────── Stage 9: Joined ───────────────────────────
 
 /// Pipeline state after path joining.
 ///
@@ -516,12 +711,17 @@ pub struct Joined {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edges: GrayImage,
     contours: Vec<Polyline>,
     simplified: Vec<Polyline>,
     canvas: MaskResult,
     path: Polyline,
+    /// Provenance of each segment of `path`.
+    segments: Vec<SegmentKind>,
+    /// Number of connectors rerouted along the canvas border.
+    perimeter_reroutes: usize,
     quality_metrics: Option<JoinQualityMetrics>,
     dimensions: Dimensions,
 }
@@ -533,6 +733,13 @@ impl Joined {
         &self.path
     }
 
+    /// Provenance of each segment of the joined path (see
+    /// [`StagedResult::joined_segments`]).
+    #[must_use]
+    pub fn segments(&self) -> &[SegmentKind] {
+        &self.segments
+    }
+
     /// Image dimensions.
     #[must_use]
     pub const fn dimensions(&self) -> Dimensions {
@@ -540,33 +747,49 @@ impl Joined {
     }
 
     /// Advance to the output stage — the final pipeline step.
+    ///
+    /// Segments passing near the polar center are first rerouted around
+    /// it (when `config.center_avoid_radius > 0`), then long segments
+    /// are subsampled.
     pub fn output(self) -> Output {
-        let subsampled = crate::subsample::subsample(&self.path, self.config.subsample_max_length);
+        let (rerouted, center_reroutes) =
+            crate::center_avoid::avoid_center(&self.path, self.config.center_avoid_radius);
+        let subsampled = self
+            .config
+            .subsample_mode
+            .apply(&rerouted, self.config.subsample_max_length);
         Output {
             config: self.config,
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edges,
             contours: self.contours,
             simplified: self.simplified,
             canvas: self.canvas,
             joined: self.path,
+            joined_segments: self.segments,
+            perimeter_reroutes: self.perimeter_reroutes,
             subsampled,
+            center_reroutes,
             quality_metrics: self.quality_metrics,
             dimensions: self.dimensions,
         }
     }
 }
 
-// ───────────────────────── Stage 9: Output ──────────────────────────
+// ───────────────────────── Stage 10: Output ─────────────────────────
 
 /// Pipeline state after segment subsampling — the final stage.
 ///
 /// Long segments in the joined path have been subdivided so no
-/// segment exceeds `config.subsample_max_length` pixels. This
-/// prevents angular artifacts when converting to polar coordinates
-/// for THR export.
+/// segment exceeds `config.subsample_max_length` normalized units
+/// (measured in XY or, with [`SubsampleMode::Polar`], as Δrho and
+/// rho·Δtheta). This prevents angular artifacts when converting to
+/// polar coordinates for THR export.
+///
+/// [`SubsampleMode::Polar`]: crate::subsample::SubsampleMode::Polar
 ///
 /// Call [`into_result`](Self::into_result) to extract the
 /// [`StagedResult`] containing all intermediates.
@@ -579,13 +802,19 @@ pub struct Output {
     config: PipelineConfig,
     original: RgbaImage,
     downsampled: RgbaImage,
+    toned: RgbaImage,
     blurred: RgbaImage,
     edges: GrayImage,
     contours: Vec<Polyline>,
     simplified: Vec<Polyline>,
     canvas: MaskResult,
     joined: Polyline,
+    joined_segments: Vec<SegmentKind>,
+    /// Number of connectors rerouted along the canvas border.
+    perimeter_reroutes: usize,
     subsampled: Polyline,
+    /// Number of segments rerouted around the polar center.
+    center_reroutes: usize,
     quality_metrics: Option<JoinQualityMetrics>,
     dimensions: Dimensions,
 }
@@ -612,12 +841,15 @@ impl Output {
         StagedResult {
             original: self.original,
             downsampled: self.downsampled,
+            toned: self.toned,
             blurred: self.blurred,
             edges: self.edges,
             contours: self.contours,
             simplified: self.simplified,
             canvas: self.canvas,
             joined: self.joined,
+            joined_segments: self.joined_segments,
+            perimeter_reroutes: self.perimeter_reroutes,
             output: self.subsampled,
             mst_edge_details,
             dimensions: self.dimensions,
@@ -628,7 +860,23 @@ impl Output {
 // ──────────────────── PipelineStage trait + Stage enum ────────────────
 
 /// Total number of stages in the pipeline.
-pub const STAGE_COUNT: usize = 10;
+pub const STAGE_COUNT: usize = 11;
+
+/// Stage names indexed by [`PipelineStage::INDEX`] — the index passed
+/// to [`PipelineCache::run`]'s `on_stage` callback.
+pub const STAGE_NAMES: [&str; STAGE_COUNT] = [
+    Pending::NAME,
+    Decoded::NAME,
+    Downsampled::NAME,
+    Toned::NAME,
+    Blurred::NAME,
+    EdgesDetected::NAME,
+    ContoursTraced::NAME,
+    Simplified::NAME,
+    Canvas::NAME,
+    Joined::NAME,
+    Output::NAME,
+];
 
 /// The output produced by a single pipeline stage.
 ///
@@ -652,6 +900,11 @@ pub enum StageOutput<'a> {
         /// The downsampled image.
         downsampled: &'a RgbaImage,
     },
+    /// Contrast normalization result.
+    Toned {
+        /// The tone-mapped RGBA image.
+        toned: &'a RgbaImage,
+    },
     /// Gaussian blur result.
     Blurred {
         /// The blurred RGBA image.
@@ -721,8 +974,8 @@ pub trait PipelineStage: Sized {
     /// Human-readable name of this stage (e.g. `"source"`, `"blur"`).
     const NAME: &str;
 
-    /// Zero-based index of this stage (`0` for Pending through `8` for
-    /// Joined).
+    /// Zero-based index of this stage (`0` for Pending through `10` for
+    /// Output).
     const INDEX: usize;
 
     /// The output this stage produced.
@@ -756,6 +1009,23 @@ pub trait PipelineStage: Sized {
     /// contours.
     fn next(self) -> Result<Option<Stage>, PipelineError>;
 
+    /// Advance to the next stage with cancellation and fractional
+    /// progress.
+    ///
+    /// The default implementation polls for cancellation and then
+    /// calls [`next`](Self::next); stages with long-running work
+    /// (edge detection, canvas clipping, joining) override it to
+    /// checkpoint inside the stage.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`next`](Self::next), plus [`PipelineError::Cancelled`]
+    /// if `progress` requests cancellation.
+    fn next_with_progress(self, progress: &Progress<'_>) -> Result<Option<Stage>, PipelineError> {
+        progress.check()?;
+        self.next()
+    }
+
     /// Run all remaining stages to completion and return the final
     /// [`StagedResult`].
     ///
@@ -801,6 +1071,7 @@ impl PipelineStage for Decoded {
     fn metrics(&self) -> Option<StageMetrics> {
         Some(StageMetrics::Decode {
             input_bytes: self.source_len,
+            orientation: self.orientation.to_exif(),
             width: self.original.width(),
             height: self.original.height(),
             pixel_count: u64::from(self.original.width()) * u64::from(self.original.height()),
@@ -838,6 +1109,31 @@ impl PipelineStage for Downsampled {
         })
     }
 
+    fn next(self) -> Result<Option<Stage>, PipelineError> {
+        Ok(Some(Stage::Toned(self.tone())))
+    }
+
+    fn complete(self) -> Result<StagedResult, PipelineError> {
+        self.tone().complete()
+    }
+}
+
+impl PipelineStage for Toned {
+    const NAME: &str = "tone";
+    const INDEX: usize = 3;
+
+    fn output(&self) -> StageOutput<'_> {
+        StageOutput::Toned { toned: &self.toned }
+    }
+
+    fn metrics(&self) -> Option<StageMetrics> {
+        Some(StageMetrics::Tone {
+            kind: self.config.tone.kind.to_string(),
+            mean_before: crate::diagnostics::mean_luma(&self.downsampled),
+            mean_after: crate::diagnostics::mean_luma(&self.toned),
+        })
+    }
+
     fn next(self) -> Result<Option<Stage>, PipelineError> {
         Ok(Some(Stage::Blurred(self.blur())))
     }
@@ -849,7 +1145,7 @@ impl PipelineStage for Downsampled {
 
 impl PipelineStage for Blurred {
     const NAME: &str = "blur";
-    const INDEX: usize = 3;
+    const INDEX: usize = 4;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::Blurred {
@@ -860,6 +1156,7 @@ impl PipelineStage for Blurred {
     fn metrics(&self) -> Option<StageMetrics> {
         Some(StageMetrics::Blur {
             sigma: self.config.blur_sigma,
+            smoothing: self.config.smoothing.to_string(),
         })
     }
 
@@ -867,6 +1164,12 @@ impl PipelineStage for Blurred {
         Ok(Some(Stage::EdgesDetected(self.detect_edges())))
     }
 
+    fn next_with_progress(self, progress: &Progress<'_>) -> Result<Option<Stage>, PipelineError> {
+        Ok(Some(Stage::EdgesDetected(
+            self.detect_edges_with_progress(progress)?,
+        )))
+    }
+
     fn complete(self) -> Result<StagedResult, PipelineError> {
         self.detect_edges().complete()
     }
@@ -874,7 +1177,7 @@ impl PipelineStage for Blurred {
 
 impl PipelineStage for EdgesDetected {
     const NAME: &str = "edges";
-    const INDEX: usize = 4;
+    const INDEX: usize = 5;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::EdgesDetected {
@@ -917,7 +1220,7 @@ impl PipelineStage for EdgesDetected {
 
 impl PipelineStage for ContoursTraced {
     const NAME: &str = "contours";
-    const INDEX: usize = 5;
+    const INDEX: usize = 6;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::ContoursTraced {
@@ -937,17 +1240,17 @@ impl PipelineStage for ContoursTraced {
     }
 
     fn next(self) -> Result<Option<Stage>, PipelineError> {
-        Ok(Some(Stage::Simplified(self.simplify())))
+        Ok(Some(Stage::Simplified(self.simplify()?)))
     }
 
     fn complete(self) -> Result<StagedResult, PipelineError> {
-        self.simplify().complete()
+        self.simplify()?.complete()
     }
 }
 
 impl PipelineStage for Simplified {
     const NAME: &str = "simplify";
-    const INDEX: usize = 6;
+    const INDEX: usize = 7;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::Simplified {
@@ -967,6 +1270,7 @@ impl PipelineStage for Simplified {
         Some(StageMetrics::Simplification {
             tolerance: self.config.simplify_tolerance,
             polyline_count: self.reduced.len(),
+            dropped_count: self.contours.len() - self.reduced.len(),
             points_before,
             points_after,
             reduction_ratio,
@@ -977,6 +1281,10 @@ impl PipelineStage for Simplified {
         Ok(Some(Stage::Canvas(self.canvas())))
     }
 
+    fn next_with_progress(self, progress: &Progress<'_>) -> Result<Option<Stage>, PipelineError> {
+        Ok(Some(Stage::Canvas(self.canvas_with_progress(progress)?)))
+    }
+
     fn complete(self) -> Result<StagedResult, PipelineError> {
         self.canvas().complete()
     }
@@ -984,7 +1292,7 @@ impl PipelineStage for Simplified {
 
 impl PipelineStage for Canvas {
     const NAME: &str = "canvas";
-    const INDEX: usize = 7;
+    const INDEX: usize = 8;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::Canvas {
@@ -1039,6 +1347,10 @@ impl PipelineStage for Canvas {
         Ok(Some(Stage::Joined(self.join())))
     }
 
+    fn next_with_progress(self, progress: &Progress<'_>) -> Result<Option<Stage>, PipelineError> {
+        Ok(Some(Stage::Joined(self.join_with_progress(progress)?)))
+    }
+
     fn complete(self) -> Result<StagedResult, PipelineError> {
         Ok(self.join().output().into_result())
     }
@@ -1046,7 +1358,7 @@ impl PipelineStage for Canvas {
 
 impl PipelineStage for Joined {
     const NAME: &str = "join";
-    const INDEX: usize = 8;
+    const INDEX: usize = 9;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::Joined {
@@ -1072,6 +1384,8 @@ impl PipelineStage for Joined {
             input_point_count,
             output_point_count,
             expansion_ratio,
+            perimeter_reroutes: self.perimeter_reroutes,
+            segment_lengths: SegmentLengths::measure(&self.path, &self.segments),
             quality: self.quality_metrics.clone(),
         })
     }
@@ -1087,7 +1401,7 @@ impl PipelineStage for Joined {
 
 impl PipelineStage for Output {
     const NAME: &str = "output";
-    const INDEX: usize = 9;
+    const INDEX: usize = 10;
 
     fn output(&self) -> StageOutput<'_> {
         StageOutput::Output {
@@ -1101,9 +1415,11 @@ impl PipelineStage for Output {
         let points_before = self.joined.len();
         let points_after = self.subsampled.len();
         Some(StageMetrics::Output {
+            mode: self.config.subsample_mode.to_string(),
             max_length: self.config.subsample_max_length,
             points_before,
             points_after,
+            center_reroutes: self.center_reroutes,
         })
     }
 
@@ -1144,6 +1460,8 @@ pub enum Stage {
     Decoded(Decoded),
     /// See [`Downsampled`].
     Downsampled(Downsampled),
+    /// See [`Toned`].
+    Toned(Toned),
     /// See [`Blurred`].
     Blurred(Blurred),
     /// See [`EdgesDetected`].
@@ -1168,6 +1486,7 @@ const fn _stage_count_guard(s: &Stage) {
         Stage::Pending(_)
         | Stage::Decoded(_)
         | Stage::Downsampled(_)
+        | Stage::Toned(_)
         | Stage::Blurred(_)
         | Stage::EdgesDetected(_)
         | Stage::ContoursTraced(_)
@@ -1195,6 +1514,7 @@ macro_rules! delegate {
              Self::Pending(s) => s.$method($($arg),*),
              Self::Decoded(s) => s.$method($($arg),*),
              Self::Downsampled(s) => s.$method($($arg),*),
+             Self::Toned(s) => s.$method($($arg),*),
              Self::Blurred(s) => s.$method($($arg),*),
             Self::EdgesDetected(s) => s.$method($($arg),*),
             Self::ContoursTraced(s) => s.$method($($arg),*),
@@ -1258,6 +1578,20 @@ impl Stage {
         delegate!(self, next)
     }
 
+    /// Advance to the next stage with cancellation and fractional
+    /// progress (see [`PipelineStage::next_with_progress`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError`] if a fallible stage transition fails,
+    /// including [`PipelineError::Cancelled`].
+    pub fn next_with_progress(
+        self,
+        progress: &Progress<'_>,
+    ) -> Result<Option<Self>, PipelineError> {
+        delegate!(self, next_with_progress, progress)
+    }
+
     /// Advance to the next stage, returning `self` unchanged if
     /// already complete.
     ///
@@ -1287,6 +1621,17 @@ impl Stage {
     /// # }
     /// ```
     pub fn advance(self) -> Result<Advance, PipelineError> {
+        self.advance_with_progress(&Progress::NONE)
+    }
+
+    /// [`advance`](Self::advance) with cancellation and fractional
+    /// progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError`] if a fallible stage transition fails,
+    /// including [`PipelineError::Cancelled`].
+    pub fn advance_with_progress(self, progress: &Progress<'_>) -> Result<Advance, PipelineError> {
         if self.is_complete() {
             return Ok(Advance::Complete(self));
         }
@@ -1294,7 +1639,7 @@ impl Stage {
         // The is_complete() guard above ensures we never reach None here.
         #[allow(clippy::unreachable)]
         let next = self
-            .next()?
+            .next_with_progress(progress)?
             .unwrap_or_else(|| unreachable!("non-complete stage returned None from next()"));
         Ok(Advance::Next(next))
     }
@@ -1345,6 +1690,12 @@ impl From<Downsampled> for Stage {
     }
 }
 
+impl From<Toned> for Stage {
+    fn from(s: Toned) -> Self {
+        Self::Toned(s)
+    }
+}
+
 impl From<Blurred> for Stage {
     fn from(s: Blurred) -> Self {
         Self::Blurred(s)
@@ -1401,10 +1752,11 @@ impl From<Output> for Stage {
 /// let result = Pipeline::new(png, PipelineConfig::default())
 ///     .decode()?
 ///     .downsample()
+///     .tone()
 ///     .blur()
 ///     .detect_edges()
 ///     .trace_contours()?
-///     .simplify()
+///     .simplify()?
 ///     .canvas()
 ///     .join()
 ///     .output()
@@ -1435,6 +1787,15 @@ impl Pipeline {
 
 // ─────────────────────── Pipeline cache ──────────────────────────────
 
+/// Full-precision luminance from the downsample, tone and blur stages
+/// (all `None` for 8-bit sources).
+#[derive(Clone, Default)]
+struct CachedLuma {
+    downsampled: Option<Luma32FImage>,
+    toned: Option<Luma32FImage>,
+    blurred: Option<Luma32FImage>,
+}
+
 /// Cached state from a previous pipeline run.
 ///
 /// Holds every intermediate result so that a subsequent run with the
@@ -1465,11 +1826,22 @@ pub struct PipelineCache {
     decoded_image: DynamicImage,
     /// Byte length of the source image (for decode-stage metrics).
     source_len: usize,
+    /// EXIF orientation applied on decode (for decode-stage metrics).
+    orientation: Orientation,
     /// Whether downsampling was actually applied (image was larger
     /// than `working_resolution`).  Diagnostic-only.
     downsampled_applied: bool,
+    /// Full-precision luminance of high bit-depth sources, which
+    /// `StagedResult` does not carry.
+    luma: CachedLuma,
     /// Edge pixel count before optional inversion.  Diagnostic-only.
     pre_invert_edge_pixels: u64,
+    /// Metrics of every stage as of the run that produced this cache,
+    /// indexed by stage.  Cached stages keep the metrics from the run
+    /// that computed them.  Diagnostic-only.
+    stage_metrics: [Option<StageMetrics>; STAGE_COUNT],
+    /// Edge inversion metrics recorded alongside `stage_metrics`.
+    invert_metrics: Option<StageMetrics>,
     /// All intermediate raster and vector outputs.
     ///
     /// Wrapped in [`Arc`] so the caller and the cache can share a
@@ -1506,25 +1878,84 @@ impl PipelineCache {
         config: PipelineConfig,
         on_stage: &dyn Fn(usize, bool),
     ) -> Result<(Arc<StagedResult>, Self), PipelineError> {
-        let image_hash = Self::hash_bytes(&image_bytes);
+        let mut cache = cache;
+        let staged =
+            Self::run_with_progress(&mut cache, image_bytes, config, on_stage, &Progress::NONE)?;
+        let Some(cache) = cache else {
+            unreachable!("a successful run always leaves a cache behind")
+        };
+        Ok((staged, cache))
+    }
 
-        match cache {
-            Some(c) if c.image_hash == image_hash => {
-                let earliest = c.config.earliest_changed_stage(&config);
-                if earliest >= STAGE_COUNT {
-                    // Nothing changed — fire progress for all stages
-                    // (all cached) and return the cached result.
-                    for i in 0..STAGE_COUNT {
-                        on_stage(i, true);
-                    }
-                    return Ok((c.staged.clone(), Self { config, ..c }));
+    /// Run the pipeline like [`run`](Self::run), with cooperative
+    /// cancellation and intra-stage progress.
+    ///
+    /// `progress` receives the fraction complete of the stage being
+    /// computed — the one following the index most recently passed to
+    /// `on_stage` — at checkpoints inside edge detection, canvas
+    /// clipping and joining.  It is polled for cancellation at every
+    /// stage boundary and at those checkpoints.
+    ///
+    /// Unlike `run`, the cache is borrowed.  On success it is updated
+    /// in place.  On any error, including cancellation, it is left
+    /// untouched so the next run can still resume from it; the
+    /// intermediates a resumed run starts from are therefore copied
+    /// out of the cache rather than moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if `progress` requests
+    /// cancellation, or any error [`run`](Self::run) can return.
+    pub fn run_with_progress(
+        cache: &mut Option<Self>,
+        image_bytes: Vec<u8>,
+        config: PipelineConfig,
+        on_stage: &dyn Fn(usize, bool),
+        progress: &Progress<'_>,
+    ) -> Result<Arc<StagedResult>, PipelineError> {
+        let image_hash = Self::hash_bytes(&image_bytes);
+        let earliest = cache
+            .as_ref()
+            .filter(|c| c.image_hash == image_hash)
+            .map(|c| c.config.earliest_changed_stage(&config));
+
+        match (earliest, cache.as_mut()) {
+            (Some(earliest), Some(c)) if earliest >= STAGE_COUNT => {
+                // Nothing changed — fire progress for all stages
+                // (all cached) and return the cached result.
+                for i in 0..STAGE_COUNT {
+                    on_stage(i, true);
                 }
-                c.resume(config, earliest, on_stage)
+                c.config = config;
+                Ok(Arc::clone(&c.staged))
+            }
+            (Some(earliest), Some(c)) if earliest > Decoded::INDEX => {
+                c.resume(config, earliest, on_stage, progress)
+            }
+            // No cache, a different image, or a different input frame
+            // (which needs a fresh decode).
+            _ => {
+                let (staged, updated) =
+                    Self::full_run(image_bytes, config, image_hash, on_stage, progress)?;
+                *cache = Some(updated);
+                Ok(staged)
             }
-            _ => Self::full_run(image_bytes, config, image_hash, on_stage),
         }
     }
 
+    /// Metrics of every stage of the cached result, indexed by stage,
+    /// together with the edge inversion metrics.
+    ///
+    /// Used by [`run_cached_with_diagnostics`] to report metrics for
+    /// stages that were served from the cache.
+    ///
+    /// [`run_cached_with_diagnostics`]: crate::diagnostics::run_cached_with_diagnostics
+    pub(crate) const fn stage_metrics(
+        &self,
+    ) -> (&[Option<StageMetrics>; STAGE_COUNT], Option<&StageMetrics>) {
+        (&self.stage_metrics, self.invert_metrics.as_ref())
+    }
+
     /// Hash image bytes using SipHash-2-4 with fixed keys.
     ///
     /// Uses `siphasher::sip::SipHasher` instead of `DefaultHasher` so the
@@ -1545,43 +1976,75 @@ impl PipelineCache {
         config: PipelineConfig,
         image_hash: u64,
         on_stage: &dyn Fn(usize, bool),
+        progress: &Progress<'_>,
     ) -> Result<(Arc<StagedResult>, Self), PipelineError> {
         let cache_config = config.clone();
 
+        let mut stage_metrics: [Option<StageMetrics>; STAGE_COUNT] = Default::default();
+
         let pending = Pipeline::new(image_bytes, config);
         on_stage(Pending::INDEX, false);
 
+        progress.check()?;
         let decoded = pending.decode()?;
+        stage_metrics[Decoded::INDEX] = decoded.metrics();
         on_stage(Decoded::INDEX, false);
 
         // Capture DynamicImage before downsample consumes it.
         let decoded_image = decoded.image.clone();
         let source_len = decoded.source_len;
+        let orientation = decoded.orientation;
 
+        progress.check()?;
         let downsampled = decoded.downsample();
         let downsampled_applied = downsampled.applied;
+        let mut luma = CachedLuma {
+            downsampled: downsampled.luma.clone(),
+            toned: None,
+            blurred: None,
+        };
+        stage_metrics[Downsampled::INDEX] = downsampled.metrics();
         on_stage(Downsampled::INDEX, false);
 
-        let blurred = downsampled.blur();
+        progress.check()?;
+        let toned = downsampled.tone();
+        luma.toned.clone_from(&toned.luma);
+        stage_metrics[Toned::INDEX] = toned.metrics();
+        on_stage(Toned::INDEX, false);
+
+        progress.check()?;
+        let blurred = toned.blur();
+        luma.blurred.clone_from(&blurred.luma);
+        stage_metrics[Blurred::INDEX] = blurred.metrics();
         on_stage(Blurred::INDEX, false);
 
-        let edges = blurred.detect_edges();
+        let edges = blurred.detect_edges_with_progress(progress)?;
         let pre_invert_edge_pixels = edges.pre_invert_edge_pixels;
+        stage_metrics[EdgesDetected::INDEX] = edges.metrics();
+        let invert_metrics = edges.invert_metrics();
         on_stage(EdgesDetected::INDEX, false);
 
+        progress.check()?;
         let contours = edges.trace_contours()?;
+        stage_metrics[ContoursTraced::INDEX] = contours.metrics();
         on_stage(ContoursTraced::INDEX, false);
 
-        let simplified = contours.simplify();
+        progress.check()?;
+        let simplified = contours.simplify()?;
+        stage_metrics[Simplified::INDEX] = simplified.metrics();
         on_stage(Simplified::INDEX, false);
 
-        let canvas = simplified.canvas();
+        let canvas = simplified.canvas_with_progress(progress)?;
+        stage_metrics[Canvas::INDEX] = canvas.metrics();
         on_stage(Canvas::INDEX, false);
 
-        let joined = canvas.join();
+        let joined = canvas.join_with_progress(progress)?;
+        stage_metrics[Joined::INDEX] = joined.metrics();
         on_stage(Joined::INDEX, false);
 
+        progress.check()?;
         let subsampled = joined.output();
+        stage_metrics[Output::INDEX] = subsampled.metrics();
         on_stage(Output::INDEX, false);
 
         let staged = Arc::new(subsampled.into_result());
@@ -1590,8 +2053,12 @@ impl PipelineCache {
             config: cache_config,
             decoded_image,
             source_len,
+            orientation,
             downsampled_applied,
+            luma,
             pre_invert_edge_pixels,
+            stage_metrics,
+            invert_metrics,
             staged: Arc::clone(&staged),
         };
 
@@ -1600,42 +2067,25 @@ impl PipelineCache {
 
     /// Re-run from the earliest changed stage, reusing cached
     /// intermediates for all upstream stages.
+    ///
+    /// The cache is only updated once the run succeeds, so an error
+    /// or cancellation part-way through leaves it as it was.
     fn resume(
-        self,
+        &mut self,
         new_config: PipelineConfig,
         earliest_changed: usize,
         on_stage: &dyn Fn(usize, bool),
-    ) -> Result<(Arc<StagedResult>, Self), PipelineError> {
-        // Destructure self so we can move `staged` fields into the
-        // resume stage instead of cloning them.
-        let Self {
-            image_hash,
-            config: _,
-            decoded_image,
-            source_len,
-            mut downsampled_applied,
-            mut pre_invert_edge_pixels,
-            staged: old_staged_arc,
-        } = self;
-
-        // Unwrap the Arc to get owned StagedResult.  Succeeds without
-        // cloning when the caller has dropped their Arc from the
-        // previous run (the normal case); falls back to clone if the
-        // caller still holds a reference.
-        let old_staged = Arc::try_unwrap(old_staged_arc).unwrap_or_else(|arc| (*arc).clone());
+        progress: &Progress<'_>,
+    ) -> Result<Arc<StagedResult>, PipelineError> {
+        let mut downsampled_applied = self.downsampled_applied;
+        let mut luma = self.luma.clone();
+        let mut pre_invert_edge_pixels = self.pre_invert_edge_pixels;
+        let mut stage_metrics = self.stage_metrics.clone();
+        let mut invert_metrics = self.invert_metrics.clone();
 
         // Build a Stage at the predecessor of the earliest changed
         // stage, populated with cached data and the new config.
-        // Consumes `old_staged` by move — no image buffer clones.
-        let mut stage = Self::build_resume_stage(
-            old_staged,
-            &decoded_image,
-            source_len,
-            downsampled_applied,
-            pre_invert_edge_pixels,
-            &new_config,
-            earliest_changed,
-        );
+        let mut stage = self.build_resume_stage(&new_config, earliest_changed);
 
         // Report progress for all cached (skipped) stages up to and
         // including the resume point.
@@ -1653,13 +2103,24 @@ impl PipelineCache {
             // advance past (these are lost after `advance()`).
             if let Stage::Downsampled(ref ds) = stage {
                 downsampled_applied = ds.applied;
+                luma.downsampled.clone_from(&ds.luma);
+            }
+            if let Stage::Toned(ref tn) = stage {
+                luma.toned.clone_from(&tn.luma);
+            }
+            if let Stage::Blurred(ref bl) = stage {
+                luma.blurred.clone_from(&bl.luma);
             }
             if let Stage::EdgesDetected(ref ed) = stage {
                 pre_invert_edge_pixels = ed.pre_invert_edge_pixels;
             }
 
-            match stage.advance()? {
+            match stage.advance_with_progress(progress)? {
                 Advance::Next(next) => {
+                    stage_metrics[next.index()] = next.metrics();
+                    if let Stage::EdgesDetected(ref ed) = next {
+                        invert_metrics = ed.invert_metrics();
+                    }
                     on_stage(next.index(), false);
                     stage = next;
                 }
@@ -1671,146 +2132,145 @@ impl PipelineCache {
         }
 
         let staged = Arc::new(stage.complete()?);
-        let cache = Self {
-            image_hash,
-            config: new_config,
-            decoded_image,
-            source_len,
-            downsampled_applied,
-            pre_invert_edge_pixels,
-            staged: Arc::clone(&staged),
-        };
+        self.config = new_config;
+        self.downsampled_applied = downsampled_applied;
+        self.luma = luma;
+        self.pre_invert_edge_pixels = pre_invert_edge_pixels;
+        self.stage_metrics = stage_metrics;
+        self.invert_metrics = invert_metrics;
+        self.staged = Arc::clone(&staged);
 
-        Ok((staged, cache))
+        Ok(staged)
     }
 
     /// Reconstruct a [`Stage`] at the predecessor of `earliest_changed`
     /// using cached data and the new config.
     ///
-    /// Consumes `staged` by value so image buffers are moved rather
-    /// than cloned — avoiding ~13 MB of allocations at working
-    /// resolution.
+    /// Only the intermediates the resumed stage carries are copied out
+    /// of the cache; everything downstream of it is recomputed.
     ///
-    /// For example, if `earliest_changed == 4` (edges), this builds a
-    /// `Stage::Blurred` (index 3) so that `advance()` will re-run
+    /// For example, if `earliest_changed == 5` (edges), this builds a
+    /// `Stage::Blurred` (index 4) so that `advance()` will re-run
     /// edge detection with the new config.
     #[allow(clippy::too_many_lines)]
-    fn build_resume_stage(
-        staged: StagedResult,
-        decoded_image: &DynamicImage,
-        source_len: usize,
-        downsampled_applied: bool,
-        pre_invert_edge_pixels: u64,
-        new_config: &PipelineConfig,
-        earliest_changed: usize,
-    ) -> Stage {
-        let StagedResult {
-            original,
-            downsampled,
-            blurred,
-            edges,
-            contours,
-            simplified,
-            canvas,
-            joined,
-            output: _,
-            mst_edge_details,
-            dimensions,
-        } = staged;
+    fn build_resume_stage(&self, new_config: &PipelineConfig, earliest_changed: usize) -> Stage {
+        let staged = &*self.staged;
+        let original = staged.original.clone();
+        let dimensions = staged.dimensions;
 
         match earliest_changed {
             // Stage 2 changed (downsample) — resume from Decoded (index 1).
             2 => Stage::Decoded(Decoded {
                 config: new_config.clone(),
-                image: decoded_image.clone(),
+                image: self.decoded_image.clone(),
                 original,
-                source_len,
+                source_len: self.source_len,
+                orientation: self.orientation,
             }),
 
-            // Stage 3 changed (blur) — resume from Downsampled (index 2).
+            // Stage 3 changed (tone) — resume from Downsampled (index 2).
             3 => Stage::Downsampled(Downsampled {
                 config: new_config.clone(),
                 original,
-                rgba: downsampled,
-                applied: downsampled_applied,
+                rgba: staged.downsampled.clone(),
+                luma: self.luma.downsampled.clone(),
+                applied: self.downsampled_applied,
             }),
 
-            // Stage 4 changed (edges) — resume from Blurred (index 3).
-            4 => Stage::Blurred(Blurred {
+            // Stage 4 changed (blur) — resume from Toned (index 3).
+            4 => Stage::Toned(Toned {
                 config: new_config.clone(),
                 original,
-                downsampled,
-                smooth: blurred,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                luma: self.luma.toned.clone(),
+            }),
+
+            // Stage 5 changed (edges) — resume from Blurred (index 4).
+            5 => Stage::Blurred(Blurred {
+                config: new_config.clone(),
+                original,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                smooth: staged.blurred.clone(),
+                luma: self.luma.blurred.clone(),
                 dimensions,
             }),
 
-            // Stage 5 changed (contours) — resume from EdgesDetected (index 4).
-            5 => Stage::EdgesDetected(EdgesDetected {
+            // Stage 6 changed (contours) — resume from EdgesDetected (index 5).
+            6 => Stage::EdgesDetected(EdgesDetected {
                 config: new_config.clone(),
                 original,
-                downsampled,
-                blurred,
-                edge_map: edges,
-                pre_invert_edge_pixels,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                blurred: staged.blurred.clone(),
+                edge_map: staged.edges.clone(),
+                pre_invert_edge_pixels: self.pre_invert_edge_pixels,
                 dimensions,
             }),
 
-            // Stage 6 changed (simplify) — resume from ContoursTraced (index 5).
-            6 => Stage::ContoursTraced(ContoursTraced {
+            // Stage 7 changed (simplify) — resume from ContoursTraced (index 6).
+            7 => Stage::ContoursTraced(ContoursTraced {
                 config: new_config.clone(),
                 original,
-                downsampled,
-                blurred,
-                edges,
-                contours,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                blurred: staged.blurred.clone(),
+                edges: staged.edges.clone(),
+                contours: staged.contours.clone(),
                 dimensions,
             }),
 
-            // Stage 7 changed (canvas) — resume from Simplified (index 6).
-            7 => Stage::Simplified(Simplified {
+            // Stage 8 changed (canvas) — resume from Simplified (index 7).
+            8 => Stage::Simplified(Simplified {
                 config: new_config.clone(),
                 original,
-                downsampled,
-                blurred,
-                edges,
-                contours,
-                reduced: simplified,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                blurred: staged.blurred.clone(),
+                edges: staged.edges.clone(),
+                contours: staged.contours.clone(),
+                reduced: staged.simplified.clone(),
                 dimensions,
             }),
 
-            // Stage 8 changed (join) — resume from Canvas (index 7).
-            8 => Stage::Canvas(Canvas {
+            // Stage 9 changed (join) — resume from Canvas (index 8).
+            9 => Stage::Canvas(Canvas {
                 config: new_config.clone(),
                 original,
-                downsampled,
-                blurred,
-                edges,
-                contours,
-                simplified,
-                canvas_result: canvas,
+                downsampled: staged.downsampled.clone(),
+                toned: staged.toned.clone(),
+                blurred: staged.blurred.clone(),
+                edges: staged.edges.clone(),
+                contours: staged.contours.clone(),
+                simplified: staged.simplified.clone(),
+                canvas_result: staged.canvas.clone(),
                 dimensions,
             }),
 
-            // Stage 9 changed (subsample) — resume from Joined (index 8).
-            9 => {
-                let quality_metrics = if mst_edge_details.is_empty() {
+            // Stage 10 changed (subsample) — resume from Joined (index 9).
+            10 => {
+                let quality_metrics = if staged.mst_edge_details.is_empty() {
                     None
                 } else {
                     Some(JoinQualityMetrics {
-                        mst_edge_details,
+                        mst_edge_details: staged.mst_edge_details.clone(),
                         ..JoinQualityMetrics::default()
                     })
                 };
                 Stage::Joined(Joined {
                     config: new_config.clone(),
                     original,
-                    downsampled,
-                    blurred,
-                    edges,
-                    contours,
-                    simplified,
-                    canvas,
-                    path: joined,
+                    downsampled: staged.downsampled.clone(),
+                    toned: staged.toned.clone(),
+                    blurred: staged.blurred.clone(),
+                    edges: staged.edges.clone(),
+                    contours: staged.contours.clone(),
+                    simplified: staged.simplified.clone(),
+                    canvas: staged.canvas.clone(),
+                    path: staged.joined.clone(),
+                    segments: staged.joined_segments.clone(),
+                    perimeter_reroutes: staged.perimeter_reroutes,
                     quality_metrics,
                     dimensions,
                 })
@@ -1818,7 +2278,7 @@ impl PipelineCache {
 
             _ => unreachable!(
                 "earliest_changed_stage returned {earliest_changed}, \
-                 expected 2..=9"
+                 expected 2..=10"
             ),
         }
     }
@@ -1915,6 +2375,7 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur();
         assert_eq!(blurred.blurred().width(), 20);
         assert_eq!(blurred.blurred().height(), 20);
@@ -1927,6 +2388,7 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges();
         assert_eq!(edges.edges().width(), 20);
@@ -1940,6 +2402,7 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
@@ -1947,6 +2410,96 @@ mod tests {
         assert!(!contours.contours().is_empty());
     }
 
+    /// 40×40 PNG with dark stripes at x = 8..12 and x = 28..32 on a white
+    /// background; the right half (x >= 20) is fully transparent.
+    fn transparent_right_half_png() -> Vec<u8> {
+        let img = image::RgbaImage::from_fn(40, 40, |x, _y| {
+            let v = if (8..12).contains(&x) || (28..32).contains(&x) {
+                0
+            } else {
+                255
+            };
+            image::Rgba([v, v, v, if x < 20 { 255 } else { 0 }])
+        });
+        let mut buf = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn alpha_composite_flattens_downsampled() {
+        let config = PipelineConfig {
+            alpha_mode: AlphaMode::Composite,
+            alpha_background: [0, 200, 0],
+            ..PipelineConfig::default()
+        };
+        let downsampled = Pipeline::new(transparent_right_half_png(), config)
+            .decode()
+            .unwrap()
+            .downsample();
+        assert_eq!(
+            downsampled.downsampled().get_pixel(0, 0).0,
+            [255, 255, 255, 255]
+        );
+        assert_eq!(
+            downsampled.downsampled().get_pixel(39, 0).0,
+            [0, 200, 0, 255]
+        );
+    }
+
+    #[test]
+    fn median_smoothing_keeps_alpha_boundary_sharp() {
+        let blurred = |smoothing| {
+            let config = PipelineConfig {
+                smoothing,
+                blur_sigma: 2.0,
+                ..PipelineConfig::default()
+            };
+            Pipeline::new(transparent_right_half_png(), config)
+                .decode()
+                .unwrap()
+                .downsample()
+                .tone()
+                .blur()
+                .blurred()
+                .get_pixel(19, 0)
+                .0
+        };
+        // The last opaque pixel bleeds into the transparent half under
+        // Gaussian blur but not under the median filter.
+        assert_eq!(blurred(crate::SmoothingKind::Median)[3], 255);
+        assert!(blurred(crate::SmoothingKind::Gaussian)[3] < 255);
+    }
+
+    #[test]
+    fn alpha_mask_drops_contours_in_transparent_region() {
+        let max_x = |alpha_mode| {
+            let config = PipelineConfig {
+                alpha_mode,
+                ..PipelineConfig::default()
+            };
+            Pipeline::new(transparent_right_half_png(), config)
+                .decode()
+                .unwrap()
+                .downsample()
+                .tone()
+                .blur()
+                .detect_edges()
+                .trace_contours()
+                .unwrap()
+                .contours()
+                .iter()
+                .flat_map(Polyline::points)
+                .map(|p| p.x)
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        // Normalized x of the transparent stripe is around +0.6; the
+        // mask margin reaches only a couple of pixels past x = 20 (0).
+        assert!(max_x(AlphaMode::Ignore) > 0.4);
+        assert!(max_x(AlphaMode::Mask) < 0.2);
+    }
+
     #[test]
     fn trace_contours_returns_no_contours_for_uniform_image() {
         let img = image::RgbaImage::from_fn(20, 20, |_, _| image::Rgba([128, 128, 128, 255]));
@@ -1965,6 +2518,7 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours();
@@ -1978,14 +2532,97 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
-            .simplify();
+            .simplify()
+            .unwrap();
         assert!(!simplified.simplified().is_empty());
     }
 
+    #[test]
+    fn simplify_with_every_simplifier_reduces_points() {
+        for simplifier in [
+            crate::SimplifierKind::Rdp,
+            crate::SimplifierKind::VisvalingamWhyatt,
+            crate::SimplifierKind::TopologyPreserving,
+        ] {
+            let config = PipelineConfig {
+                simplifier,
+                ..PipelineConfig::default()
+            };
+            let simplified = Pipeline::new(ring_png(64), config)
+                .decode()
+                .unwrap()
+                .downsample()
+                .tone()
+                .blur()
+                .detect_edges()
+                .trace_contours()
+                .unwrap()
+                .simplify()
+                .unwrap();
+            let before: usize = simplified.contours.iter().map(Polyline::len).sum();
+            let after: usize = simplified.simplified().iter().map(Polyline::len).sum();
+            assert_eq!(simplified.simplified().len(), simplified.contours.len());
+            assert!(after < before, "{simplifier}: {after} >= {before}");
+        }
+    }
+
+    #[test]
+    fn simplify_applies_curve_smoothing() {
+        let simplify = |curve| {
+            let config = PipelineConfig {
+                curve,
+                ..PipelineConfig::default()
+            };
+            Pipeline::new(ring_png(64), config)
+                .decode()
+                .unwrap()
+                .downsample()
+                .tone()
+                .blur()
+                .detect_edges()
+                .trace_contours()
+                .unwrap()
+                .simplify()
+                .unwrap()
+        };
+        let plain = simplify(crate::CurveSettings::default());
+        let smoothed = simplify(crate::CurveSettings {
+            kind: crate::CurveKind::Chaikin,
+            ..crate::CurveSettings::default()
+        });
+        let points = |s: &Simplified| s.simplified().iter().map(Polyline::len).sum::<usize>();
+        assert_eq!(plain.simplified().len(), smoothed.simplified().len());
+        assert!(points(&smoothed) > points(&plain));
+    }
+
+    #[test]
+    fn simplify_fails_when_filter_drops_every_contour() {
+        let png = sharp_edge_png(40, 40);
+        let config = PipelineConfig {
+            contour_filter: crate::ContourFilter {
+                min_length: 100.0,
+                ..crate::ContourFilter::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let traced = Pipeline::new(png, config)
+            .decode()
+            .unwrap()
+            .downsample()
+            .tone()
+            .blur()
+            .detect_edges()
+            .trace_contours()
+            .unwrap();
+        assert!(!traced.contours().is_empty());
+        assert!(matches!(traced.simplify(), Err(PipelineError::NoContours)));
+    }
+
     #[test]
     fn canvas_with_circular_shape() {
         let png = sharp_edge_png(40, 40);
@@ -1998,11 +2635,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas();
         // Canvas always produces a result; verify it's accessible.
         let _result = canvas_stage.canvas();
@@ -2015,11 +2654,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join();
         assert!(!joined.joined().is_empty());
@@ -2035,11 +2676,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join()
             .output()
@@ -2070,11 +2713,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join()
             .output()
@@ -2098,11 +2743,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join()
             .output()
@@ -2112,6 +2759,21 @@ mod tests {
         assert_eq!(staged.joined, pipeline_result.joined);
     }
 
+    #[test]
+    fn joined_segments_label_every_segment_including_border() {
+        let png = sharp_edge_png(40, 40);
+        let config = PipelineConfig {
+            shape: crate::mask::CanvasShape::Circle,
+            border_path: crate::mask::BorderPathMode::On,
+            ..wide_canvas_config()
+        };
+
+        let staged = crate::process_staged(&png, &config).unwrap();
+        assert_eq!(staged.joined_segments.len(), staged.joined.len() - 1);
+        assert!(staged.joined_segments.contains(&SegmentKind::Border));
+        assert!(staged.joined_segments.contains(&SegmentKind::Contour));
+    }
+
     #[test]
     fn joined_dimensions_accessor() {
         let png = sharp_edge_png(40, 40);
@@ -2119,11 +2781,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join();
         assert_eq!(
@@ -2166,13 +2830,14 @@ mod tests {
             (0, "source"),
             (1, "decode"),
             (2, "downsample"),
-            (3, "blur"),
-            (4, "edges"),
-            (5, "contours"),
-            (6, "simplify"),
-            (7, "canvas"),
-            (8, "join"),
-            (9, "output"),
+            (3, "tone"),
+            (4, "blur"),
+            (5, "edges"),
+            (6, "contours"),
+            (7, "simplify"),
+            (8, "canvas"),
+            (9, "join"),
+            (10, "output"),
         ];
         assert_eq!(log.as_slice(), &expected);
     }
@@ -2187,11 +2852,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join()
             .output()
@@ -2237,6 +2904,7 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur();
         let result = blurred.complete().unwrap();
         assert!(!result.joined.is_empty());
@@ -2249,11 +2917,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join();
         let result = joined.complete().unwrap();
@@ -2267,11 +2937,13 @@ mod tests {
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur()
             .detect_edges()
             .trace_contours()
             .unwrap()
             .simplify()
+            .unwrap()
             .canvas()
             .join()
             .output();
@@ -2301,15 +2973,21 @@ mod tests {
                 StageOutput::Source { .. } => 0,
                 StageOutput::Decoded { .. } => 1,
                 StageOutput::Downsampled { .. } => 2,
-                StageOutput::Blurred { .. } => 3,
-                StageOutput::EdgesDetected { .. } => 4,
-                StageOutput::ContoursTraced { .. } => 5,
-                StageOutput::Simplified { .. } => 6,
-                StageOutput::Canvas { .. } => 7,
-                StageOutput::Joined { .. } => 8,
-                StageOutput::Output { .. } => 9,
+                StageOutput::Toned { .. } => 3,
+                StageOutput::Blurred { .. } => 4,
+                StageOutput::EdgesDetected { .. } => 5,
+                StageOutput::ContoursTraced { .. } => 6,
+                StageOutput::Simplified { .. } => 7,
+                StageOutput::Canvas { .. } => 8,
+                StageOutput::Joined { .. } => 9,
+                StageOutput::Output { .. } => 10,
             };
             assert_eq!(idx, variant_idx, "output variant mismatch at index {idx}");
+            assert_eq!(
+                STAGE_NAMES[idx],
+                stage.name(),
+                "name mismatch at index {idx}"
+            );
             visited += 1;
             match stage.advance().unwrap() {
                 Advance::Next(next) => stage = next,
@@ -2339,13 +3017,22 @@ mod tests {
         let stage: Stage = downsampled.into();
         assert_eq!(stage.index(), 2);
 
+        let toned = Pipeline::new(png.clone(), PipelineConfig::default())
+            .decode()
+            .unwrap()
+            .downsample()
+            .tone();
+        let stage: Stage = toned.into();
+        assert_eq!(stage.index(), 3);
+
         let blurred = Pipeline::new(png, PipelineConfig::default())
             .decode()
             .unwrap()
             .downsample()
+            .tone()
             .blur();
         let stage: Stage = blurred.into();
-        assert_eq!(stage.index(), 3);
+        assert_eq!(stage.index(), 4);
     }
 
     #[test]
@@ -2363,7 +3050,7 @@ mod tests {
 
         let (_, log) = drive_to_end(start).unwrap();
         let indices: Vec<usize> = log.iter().map(|(idx, _)| *idx).collect();
-        assert_eq!(indices, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(indices, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
     }
 
     #[test]
@@ -2382,12 +3069,18 @@ mod tests {
     fn assert_staged_eq(a: &StagedResult, b: &StagedResult) {
         assert_eq!(a.original, b.original, "original mismatch");
         assert_eq!(a.downsampled, b.downsampled, "downsampled mismatch");
+        assert_eq!(a.toned, b.toned, "toned mismatch");
         assert_eq!(a.blurred, b.blurred, "blurred mismatch");
         assert_eq!(a.edges, b.edges, "edges mismatch");
         assert_eq!(a.contours, b.contours, "contours mismatch");
         assert_eq!(a.simplified, b.simplified, "simplified mismatch");
         assert_eq!(a.canvas, b.canvas, "canvas mismatch");
         assert_eq!(a.joined, b.joined, "joined mismatch");
+        assert_eq!(a.joined_segments, b.joined_segments, "segments mismatch");
+        assert_eq!(
+            a.perimeter_reroutes, b.perimeter_reroutes,
+            "reroutes mismatch"
+        );
         assert_eq!(a.output, b.output, "output mismatch");
         assert_eq!(a.dimensions, b.dimensions, "dimensions mismatch");
     }
@@ -2416,8 +3109,8 @@ mod tests {
 
     #[test]
     fn cache_changed_late_stage_produces_correct_result() {
-        // Change scale (stage 7) — stages 0-6 should be cached,
-        // only stages 7-8 re-run.
+        // Change scale (stage 8) — stages 0-7 should be cached,
+        // only stages 8-10 re-run.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2436,7 +3129,7 @@ mod tests {
 
     #[test]
     fn cache_changed_mid_stage_produces_correct_result() {
-        // Change canny_high (stage 4) — stages 0-3 cached, 4-8 re-run.
+        // Change canny_high (stage 5) — stages 0-4 cached, 5-10 re-run.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2456,7 +3149,7 @@ mod tests {
     #[test]
     fn cache_changed_working_resolution_produces_correct_result() {
         // Change working_resolution (stage 2) — only stages 0-1 cached,
-        // stages 2-8 re-run using the cached decoded_image.
+        // stages 2-10 re-run using the cached decoded_image.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2474,7 +3167,7 @@ mod tests {
 
     #[test]
     fn cache_changed_early_stage_produces_correct_result() {
-        // Change blur_sigma (stage 3) — stages 0-2 cached, 3-8 re-run.
+        // Change blur_sigma (stage 4) — stages 0-3 cached, 4-10 re-run.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2490,9 +3183,41 @@ mod tests {
         assert_staged_eq(&expected, &cached_result);
     }
 
+    #[test]
+    fn cache_changed_tone_produces_correct_result() {
+        // Change tone (stage 3) — stages 0-2 cached, 3-10 re-run.  The
+        // blur-only change afterwards resumes from the cached tone stage.
+        let png = sharp_edge_png(40, 40);
+        let config1 = PipelineConfig::default();
+        let config2 = PipelineConfig {
+            tone: crate::ToneSettings {
+                kind: crate::ToneKind::Clahe,
+                tile_size: 16,
+                ..crate::ToneSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let config3 = PipelineConfig {
+            blur_sigma: 2.5,
+            ..config2.clone()
+        };
+
+        let (_first, cache) = PipelineCache::run(None, png.clone(), config1, &noop).unwrap();
+        let (cached_result, cache) =
+            PipelineCache::run(Some(cache), png.clone(), config2.clone(), &noop).unwrap();
+        let expected = crate::process_staged(&png, &config2).unwrap();
+        assert_staged_eq(&expected, &cached_result);
+        assert_ne!(expected.toned, expected.downsampled);
+
+        let (cached_result, _cache) =
+            PipelineCache::run(Some(cache), png.clone(), config3.clone(), &noop).unwrap();
+        let expected = crate::process_staged(&png, &config3).unwrap();
+        assert_staged_eq(&expected, &cached_result);
+    }
+
     #[test]
     fn cache_changed_join_produces_correct_result() {
-        // Change mst_neighbours (stage 8 only) — stages 0-7 cached.
+        // Change mst_neighbours (stage 9) — stages 0-8 cached.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2508,6 +3233,42 @@ mod tests {
         assert_staged_eq(&expected, &cached_result);
     }
 
+    #[test]
+    fn cache_changed_output_keeps_perimeter_reroutes() {
+        // Change subsample_max_length (stage 10) — the join stage,
+        // including its perimeter routing, is served from the cache.
+        // Vertical stripes: every edge is a chord of the circular
+        // canvas, so the joiner connects rim to rim.
+        let img = image::RgbaImage::from_fn(80, 80, |x, _y| {
+            if (x / 10) % 2 == 0 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        });
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let config1 = PipelineConfig {
+            shape: crate::mask::CanvasShape::Circle,
+            zoom: 1.0,
+            perimeter_connectors: true,
+            ..PipelineConfig::default()
+        };
+        let config2 = PipelineConfig {
+            subsample_max_length: config1.subsample_max_length / 2.0,
+            ..config1.clone()
+        };
+
+        let (first, cache) = PipelineCache::run(None, png.clone(), config1, &noop).unwrap();
+        assert!(first.perimeter_reroutes > 0);
+        let (cached_result, _cache2) =
+            PipelineCache::run(Some(cache), png.clone(), config2.clone(), &noop).unwrap();
+
+        let expected = crate::process_staged(&png, &config2).unwrap();
+        assert_staged_eq(&expected, &cached_result);
+    }
+
     #[test]
     fn cache_different_image_does_full_rerun() {
         let png1 = sharp_edge_png(40, 40);
@@ -2536,7 +3297,7 @@ mod tests {
 
     #[test]
     fn cache_changed_simplify_produces_correct_result() {
-        // Change simplify_tolerance (stage 6) — stages 0-5 cached.
+        // Change simplify_tolerance (stage 7) — stages 0-6 cached.
         let png = sharp_edge_png(40, 40);
         let config1 = PipelineConfig::default();
         let config2 = PipelineConfig {
@@ -2554,9 +3315,9 @@ mod tests {
 
     #[test]
     fn cache_changed_contour_tracer_produces_correct_result() {
-        // Change contour_tracer (stage 5) — stages 0-4 cached.
+        // Change contour_tracer (stage 6) — stages 0-5 cached.
         // Only one variant currently, so we just verify the code path
-        // doesn't panic when earliest_changed == 5.
+        // doesn't panic when earliest_changed == 6.
         let png = sharp_edge_png(40, 40);
         let config = PipelineConfig::default();
 
@@ -2606,4 +3367,177 @@ mod tests {
         let expected = crate::process_staged(&png, &config3).unwrap();
         assert_staged_eq(&expected, &r3);
     }
+
+    #[test]
+    fn cache_run_with_progress_matches_run() {
+        let png = sharp_edge_png(40, 40);
+        let config = PipelineConfig::default();
+
+        let fractions = std::cell::RefCell::new(Vec::new());
+        let on_fraction = |f| fractions.borrow_mut().push(f);
+        let progress = Progress::new(&|| false, &on_fraction);
+        let mut cache = None;
+        let actual = PipelineCache::run_with_progress(
+            &mut cache,
+            png.clone(),
+            config.clone(),
+            &noop,
+            &progress,
+        )
+        .unwrap();
+
+        let (expected, _cache) = PipelineCache::run(None, png, config, &noop).unwrap();
+        assert_staged_eq(&expected, &actual);
+        assert!(cache.is_some(), "successful run should store a cache");
+        assert!(
+            fractions.borrow().iter().all(|f| (0.0..=1.0).contains(f)),
+            "fractions out of range: {:?}",
+            fractions.borrow(),
+        );
+    }
+
+    #[test]
+    fn cache_cancelled_run_keeps_previous_cache() {
+        let png = sharp_edge_png(40, 40);
+        let config1 = PipelineConfig::default();
+        let config2 = PipelineConfig {
+            path_joiner: crate::join::PathJoinerKind::StraightLine,
+            ..PipelineConfig::default()
+        };
+
+        let mut cache = None;
+        let first = PipelineCache::run_with_progress(
+            &mut cache,
+            png.clone(),
+            config1,
+            &noop,
+            &Progress::NONE,
+        )
+        .unwrap();
+
+        let token = crate::progress::CancelToken::new();
+        token.cancel();
+        let is_cancelled = || token.is_cancelled();
+        let cancelled = Progress::new(&is_cancelled, &|_| {});
+        let result =
+            PipelineCache::run_with_progress(&mut cache, png.clone(), config2, &noop, &cancelled);
+        assert!(matches!(result, Err(PipelineError::Cancelled)));
+
+        // The previous cache survives: rerunning the original config is
+        // served entirely from it.
+        let executed = std::cell::Cell::new(0);
+        let on_stage = |_: usize, cached: bool| {
+            if !cached {
+                executed.set(executed.get() + 1);
+            }
+        };
+        let (second, _cache) =
+            PipelineCache::run(cache, png, PipelineConfig::default(), &on_stage).unwrap();
+        assert_eq!(executed.get(), 0, "no stage should be recomputed");
+        assert_staged_eq(&first, &second);
+    }
+
+    /// Concentric colored rings: many contours on every edge channel.
+    fn ring_png(size: u32) -> Vec<u8> {
+        let c = f64::from(size) / 2.0;
+        let img = image::RgbaImage::from_fn(size, size, |x, y| {
+            let r = (f64::from(x) - c).hypot(f64::from(y) - c);
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let band = (r / 6.0) as u8;
+            match band % 3 {
+                0 => image::Rgba([230, 40, 40, 255]),
+                1 => image::Rgba([40, 200, 60, 255]),
+                _ => image::Rgba([30, 30, 220, 255]),
+            }
+        });
+        let mut buf = Vec::new();
+        let encoder = image::codecs::png::PngEncoder::new(&mut buf);
+        image::ImageEncoder::write_image(
+            encoder,
+            img.as_raw(),
+            img.width(),
+            img.height(),
+            image::ExtendedColorType::Rgba8,
+        )
+        .unwrap();
+        buf
+    }
+
+    /// Many contours on every edge channel, so every parallel code path
+    /// has work to split.
+    fn all_channels_ring_input() -> (Vec<u8>, PipelineConfig) {
+        let config = PipelineConfig {
+            edge_channels: crate::types::EdgeChannels {
+                luminance: true,
+                red: true,
+                green: true,
+                blue: true,
+                saturation: true,
+                alpha: true,
+                hue: true,
+                value: true,
+                lightness: true,
+                lab_a: true,
+                lab_b: true,
+                weights: std::collections::BTreeMap::new(),
+                thresholds: std::collections::BTreeMap::new(),
+                fusion: crate::types::EdgeFusion::Max,
+            },
+            ..wide_canvas_config()
+        };
+        (ring_png(120), config)
+    }
+
+    /// FNV-1a hash of the exact bits of every point of `polyline`.
+    fn polyline_hash(polyline: &Polyline) -> u64 {
+        polyline
+            .points()
+            .iter()
+            .flat_map(|p| [p.x.to_bits(), p.y.to_bits()])
+            .flat_map(u64::to_le_bytes)
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// Hash of the output of [`all_channels_ring_input`] from a build
+    /// without the `parallel` feature.  Asserted in both builds, so the
+    /// parallel build must stay bit-identical to the serial one.
+    const SERIAL_OUTPUT_HASH: u64 = 0x0f8f_2171_ceb4_8a7a;
+
+    #[test]
+    fn output_matches_serial_build() {
+        let (png, config) = all_channels_ring_input();
+        let staged = crate::process_staged(&png, &config).unwrap();
+        assert!(
+            staged.contours.len() > 1,
+            "fixture should yield many contours"
+        );
+        assert_eq!(polyline_hash(&staged.output), SERIAL_OUTPUT_HASH);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_output_matches_single_threaded() {
+        let (png, config) = all_channels_ring_input();
+
+        let serial = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(|| crate::process_staged(&png, &config))
+            .unwrap();
+        let parallel = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap()
+            .install(|| crate::process_staged(&png, &config))
+            .unwrap();
+
+        assert!(
+            serial.contours.len() > 1,
+            "fixture should yield many contours"
+        );
+        assert_staged_eq(&serial, &parallel);
+    }
 }
@@ -0,0 +1,233 @@
+//! Cooperative cancellation and intra-stage progress reporting.
+//!
+//! Most pipeline stages finish in milliseconds, but edge detection on
+//! many channels, canvas clipping of dense contour sets, and MST joining
+//! can each take seconds on large images.  [`PipelineCache::run`] only
+//! reports progress at stage boundaries, and a caller that wants to
+//! abort has no way to interrupt a stage except by killing the thread.
+//!
+//! A [`Progress`] handle is threaded through the long-running stages
+//! (`canny_combined`, `apply_mask` and `join_mst`, via their
+//! `*_with_progress` variants).  At regular checkpoints they
+//!
+//! - report the fraction of the current stage completed, and
+//! - poll for cancellation, returning [`PipelineError::Cancelled`] if
+//!   the caller asked to stop.
+//!
+//! Cancellation is cooperative: work between two checkpoints always
+//! runs to completion.
+//!
+//! [`PipelineCache::run`]: crate::pipeline::PipelineCache::run
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::PipelineError;
+
+/// A cloneable flag used to request cancellation of a pipeline run.
+///
+/// All clones share the same flag: calling [`cancel`](Self::cancel) on
+/// any clone is observed by every other clone.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::progress::{CancelToken, Progress};
+///
+/// let token = CancelToken::new();
+/// let is_cancelled = || token.is_cancelled();
+/// let progress = Progress::new(&is_cancelled, &|_| {});
+/// assert!(progress.check().is_ok());
+///
+/// token.cancel();
+/// assert!(progress.check().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Create a new, not-yet-cancelled token.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation.  Idempotent.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress and cancellation handle passed into long-running stages.
+///
+/// Wraps two caller-supplied callbacks:
+///
+/// - `is_cancelled`, polled at each checkpoint (a [`CancelToken`] or
+///   any other flag, e.g. one backed by shared memory in a web worker);
+/// - `on_fraction`, called with the fraction of the current stage
+///   completed, in `[0.0, 1.0]`.
+///
+/// A `Progress` can be narrowed with [`span`](Self::span) so that a
+/// sub-step reports fractions within a slice of the parent's range.
+#[derive(Clone, Copy)]
+pub struct Progress<'a> {
+    is_cancelled: &'a dyn Fn() -> bool,
+    on_fraction: &'a dyn Fn(f64),
+    start: f64,
+    end: f64,
+}
+
+const fn never_cancelled() -> bool {
+    false
+}
+
+const fn ignore_fraction(_: f64) {}
+
+impl Progress<'static> {
+    /// A handle that never cancels and discards progress reports.
+    pub const NONE: Self = Self::new(&never_cancelled, &ignore_fraction);
+}
+
+impl<'a> Progress<'a> {
+    /// Create a handle from a cancellation poll and a progress callback.
+    #[must_use]
+    pub const fn new(is_cancelled: &'a dyn Fn() -> bool, on_fraction: &'a dyn Fn(f64)) -> Self {
+        Self {
+            is_cancelled,
+            on_fraction,
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+
+    /// Return a handle whose `[0.0, 1.0]` maps onto `[start, end]` of
+    /// this handle's range.
+    #[must_use]
+    pub fn span(&self, start: f64, end: f64) -> Self {
+        let width = self.end - self.start;
+        Self {
+            start: width.mul_add(start, self.start),
+            end: width.mul_add(end, self.start),
+            ..*self
+        }
+    }
+
+    /// Report that `fraction` (clamped to `[0.0, 1.0]`) of this
+    /// handle's range is complete.
+    pub fn report(&self, fraction: f64) {
+        let f = fraction.clamp(0.0, 1.0);
+        (self.on_fraction)((self.end - self.start).mul_add(f, self.start));
+    }
+
+    /// Poll for cancellation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if cancellation was
+    /// requested.
+    pub fn check(&self) -> Result<(), PipelineError> {
+        if (self.is_cancelled)() {
+            Err(PipelineError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Report `done` of `total` items complete, then poll for
+    /// cancellation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::Cancelled`] if cancellation was
+    /// requested.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn step(&self, done: usize, total: usize) -> Result<(), PipelineError> {
+        if total > 0 {
+            self.report(done as f64 / total as f64);
+        }
+        self.check()
+    }
+}
+
+impl fmt::Debug for Progress<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Unwrap the result of a computation run with [`Progress::NONE`].
+///
+/// `Progress::NONE` never cancels and the wrapped computations have no
+/// other failure mode, so an `Err` here is a logic error.
+pub(crate) fn uncancellable<T>(result: Result<T, PipelineError>) -> T {
+    match result {
+        Ok(value) => value,
+        #[allow(clippy::unreachable)]
+        Err(e) => unreachable!("computation without a cancel source failed: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn none_never_cancels() {
+        assert!(Progress::NONE.check().is_ok());
+        assert!(Progress::NONE.step(1, 2).is_ok());
+    }
+
+    #[test]
+    fn token_clones_share_flag() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+
+    #[test]
+    fn cancelled_check_returns_cancelled_error() {
+        let token = CancelToken::new();
+        token.cancel();
+        let is_cancelled = || token.is_cancelled();
+        let progress = Progress::new(&is_cancelled, &|_| {});
+        assert!(matches!(progress.check(), Err(PipelineError::Cancelled)));
+    }
+
+    #[test]
+    fn span_maps_into_parent_range() {
+        let seen = RefCell::new(Vec::new());
+        let on_fraction = |f: f64| seen.borrow_mut().push(f);
+        let progress = Progress::new(&|| false, &on_fraction);
+        let inner = progress.span(0.5, 1.0).span(0.0, 0.5);
+        inner.report(0.0);
+        inner.report(1.0);
+        inner.report(2.0); // clamped
+        assert_eq!(*seen.borrow(), vec![0.5, 0.75, 0.75]);
+    }
+
+    #[test]
+    fn step_reports_fraction_of_total() {
+        let seen = RefCell::new(Vec::new());
+        let on_fraction = |f: f64| seen.borrow_mut().push(f);
+        let progress = Progress::new(&|| false, &on_fraction);
+        assert!(progress.step(1, 4).is_ok());
+        assert!(progress.step(0, 0).is_ok()); // no report for empty work
+        assert_eq!(*seen.borrow(), vec![0.25]);
+    }
+}
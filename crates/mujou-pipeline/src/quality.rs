@@ -0,0 +1,402 @@
+//! Render-similarity quality scores.
+//!
+//! The join metrics in [`crate::mst_join::JoinQualityMetrics`] are purely
+//! geometric: they say how short a path is, not how faithful it looks.
+//! This module rasterizes the final path back onto the working-resolution
+//! pixel grid and compares it against the Canny edge map the contours
+//! were traced from (restricted to the canvas mask) and against the
+//! blurred input image:
+//!
+//! - **Edge recall / precision** — the fraction of edge pixels with a
+//!   drawn pixel within the tolerance band, and vice versa.
+//! - **Chamfer distance** — the symmetric mean distance in pixels
+//!   between the two pixel sets.
+//! - **SSIM-like score** — structural similarity between the luminance
+//!   of the blurred image and a Gaussian-blurred render of the path as
+//!   dark strokes on white, averaged over windows with any structure.
+//!
+//! Connectors, border segments and retrace overdraw that do not follow
+//! an edge lower the precision; edges dropped by simplification,
+//! clipping or the contour filters lower the recall.
+
+use image::{GrayImage, Luma};
+use serde::{Deserialize, Serialize};
+
+use crate::mask::MaskShape;
+use crate::normalize::{denormalize_point, normalize_point};
+use crate::types::{Dimensions, Point, Polyline, StagedResult};
+
+/// Default tolerance band, in working-resolution pixels.
+pub const DEFAULT_TOLERANCE_PX: f64 = 2.0;
+
+/// SSIM window side length in pixels.
+const SSIM_WINDOW: u32 = 8;
+
+/// Step between SSIM windows (half-overlapping).
+const SSIM_STEP: u32 = SSIM_WINDOW / 2;
+
+/// SSIM stabilizing constants for 8-bit data, `(0.01·255)²` and `(0.03·255)²`.
+const SSIM_C1: f64 = 6.5025;
+const SSIM_C2: f64 = 58.5225;
+
+/// Render-similarity scores between the output path and its source.
+///
+/// Ratios are in `[0, 1]` (higher is better); the chamfer distance is in
+/// working-resolution pixels (lower is better).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QualityMetrics {
+    /// Tolerance band used for recall and precision, in pixels.
+    pub tolerance_px: f64,
+    /// Fraction of edge pixels within the tolerance of a drawn pixel.
+    pub edge_recall: f64,
+    /// Fraction of drawn pixels within the tolerance of an edge pixel.
+    pub edge_precision: f64,
+    /// Harmonic mean of recall and precision.
+    pub edge_f1: f64,
+    /// Symmetric mean nearest-pixel distance, in pixels.
+    pub chamfer_distance: f64,
+    /// Mean structural similarity between the blurred image and the
+    /// blurred render, over windows with any structure.
+    pub ssim: f64,
+}
+
+/// Score the final path of `result` against its edge map and its
+/// blurred image.
+///
+/// `zoom` must be the [`PipelineConfig::zoom`](crate::PipelineConfig::zoom)
+/// the result was produced with, so normalized coordinates map back onto
+/// the working-resolution pixel grid.
+///
+/// The SSIM reference is [`StagedResult::blurred`], which edge inversion
+/// never touches, so the score does not flip with
+/// [`PipelineConfig::invert`](crate::PipelineConfig::invert).  Pixels
+/// outside the canvas mask are painted white in both images.
+#[must_use]
+pub fn measure(result: &StagedResult, zoom: f64, tolerance_px: f64) -> QualityMetrics {
+    let drawn = rasterize(result.final_polyline(), result.dimensions, zoom);
+    let target = mask_edges(&result.edges, &result.canvas.shape, zoom);
+    let mut reference = image::imageops::grayscale(&result.blurred);
+    mask_to_white(&mut reference, &result.canvas.shape, zoom);
+    compare(&target, &drawn, &reference, tolerance_px)
+}
+
+/// Rasterize `path` (normalized coordinates) as 1-px white strokes on a
+/// black image at working resolution.
+#[must_use]
+pub fn rasterize(path: &Polyline, dimensions: Dimensions, zoom: f64) -> GrayImage {
+    let mut image = GrayImage::new(dimensions.width, dimensions.height);
+    #[allow(clippy::cast_possible_truncation)]
+    let to_pixel = |p: Point| {
+        let px = denormalize_point(p, dimensions, zoom);
+        (px.x as f32, px.y as f32)
+    };
+    match path.points() {
+        [] => {}
+        [only] => {
+            imageproc::drawing::draw_line_segment_mut(
+                &mut image,
+                to_pixel(*only),
+                to_pixel(*only),
+                Luma([255]),
+            );
+        }
+        points => {
+            for w in points.windows(2) {
+                imageproc::drawing::draw_line_segment_mut(
+                    &mut image,
+                    to_pixel(w[0]),
+                    to_pixel(w[1]),
+                    Luma([255]),
+                );
+            }
+        }
+    }
+    image
+}
+
+/// Compare a target edge map against a drawn map (non-zero pixels are
+/// set in both), and the drawn map against a reference luminance image,
+/// all of the same size.
+///
+/// An empty target counts as fully recalled and an empty drawing as
+/// fully precise; a one-sided chamfer term against an empty set is the
+/// image diagonal.
+#[must_use]
+pub fn compare(
+    target: &GrayImage,
+    drawn: &GrayImage,
+    reference: &GrayImage,
+    tolerance_px: f64,
+) -> QualityMetrics {
+    let diagonal = f64::from(target.width()).hypot(f64::from(target.height()));
+    let (recall, target_to_drawn) = match_pixels(target, drawn, tolerance_px, diagonal);
+    let (precision, drawn_to_target) = match_pixels(drawn, target, tolerance_px, diagonal);
+    let edge_f1 = if recall + precision > 0.0 {
+        2.0 * recall * precision / (recall + precision)
+    } else {
+        0.0
+    };
+
+    #[allow(clippy::cast_possible_truncation)]
+    let sigma = tolerance_px.max(0.5) as f32;
+    let mut render = crate::blur::gaussian_blur(drawn, sigma);
+    image::imageops::invert(&mut render);
+    let ssim = windowed_ssim(reference, &render);
+
+    QualityMetrics {
+        tolerance_px,
+        edge_recall: recall,
+        edge_precision: precision,
+        edge_f1,
+        chamfer_distance: f64::midpoint(target_to_drawn, drawn_to_target),
+        ssim,
+    }
+}
+
+/// Keep only the edge pixels whose centres fall inside the canvas mask.
+fn mask_edges(edges: &GrayImage, shape: &MaskShape, zoom: f64) -> GrayImage {
+    let dimensions = Dimensions {
+        width: edges.width(),
+        height: edges.height(),
+    };
+    let mut masked = edges.clone();
+    for (x, y, px) in masked.enumerate_pixels_mut() {
+        let p = normalize_point(Point::new(f64::from(x), f64::from(y)), dimensions, zoom);
+        if px[0] != 0 && !shape.contains(p) {
+            px[0] = 0;
+        }
+    }
+    masked
+}
+
+/// Paint the pixels whose centres fall outside the canvas mask white.
+fn mask_to_white(image: &mut GrayImage, shape: &MaskShape, zoom: f64) {
+    let dimensions = Dimensions {
+        width: image.width(),
+        height: image.height(),
+    };
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        let p = normalize_point(Point::new(f64::from(x), f64::from(y)), dimensions, zoom);
+        if !shape.contains(p) {
+            px[0] = 255;
+        }
+    }
+}
+
+/// For the set pixels of `from`, return the fraction within `tolerance`
+/// of a set pixel of `to` and their mean distance to it (capped at
+/// `cap`).
+#[allow(clippy::cast_precision_loss)]
+fn match_pixels(from: &GrayImage, to: &GrayImage, tolerance: f64, cap: f64) -> (f64, f64) {
+    let count = from.pixels().filter(|p| p[0] != 0).count();
+    if count == 0 {
+        return (1.0, 0.0);
+    }
+    if to.pixels().all(|p| p[0] == 0) {
+        return (0.0, cap);
+    }
+
+    let distances = imageproc::distance_transform::euclidean_squared_distance_transform(to);
+    let mut matched = 0_usize;
+    let mut total = 0.0;
+    for (src, dist_sq) in from.pixels().zip(distances.pixels()) {
+        if src[0] == 0 {
+            continue;
+        }
+        let d = dist_sq[0].sqrt().min(cap);
+        if d <= tolerance {
+            matched += 1;
+        }
+        total += d;
+    }
+    (matched as f64 / count as f64, total / count as f64)
+}
+
+/// Mean SSIM over half-overlapping windows where either image varies.
+///
+/// Images smaller than one window are scored as a single window.
+/// Returns 1.0 when both images are flat everywhere.
+#[allow(clippy::cast_precision_loss)]
+fn windowed_ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    let (width, height) = a.dimensions();
+    let win_w = SSIM_WINDOW.min(width);
+    let win_h = SSIM_WINDOW.min(height);
+
+    let mut sum = 0.0;
+    let mut windows = 0_usize;
+    for y0 in (0..=height.saturating_sub(win_h)).step_by(SSIM_STEP as usize) {
+        for x0 in (0..=width.saturating_sub(win_w)).step_by(SSIM_STEP as usize) {
+            let mut stats = [0.0_f64; 5]; // Σa, Σb, Σa², Σb², Σab
+            let (first_a, first_b) = (a.get_pixel(x0, y0)[0], b.get_pixel(x0, y0)[0]);
+            let mut varies = false;
+            for y in y0..y0 + win_h {
+                for x in x0..x0 + win_w {
+                    let (pa, pb) = (a.get_pixel(x, y)[0], b.get_pixel(x, y)[0]);
+                    varies |= pa != first_a || pb != first_b;
+                    let (va, vb) = (f64::from(pa), f64::from(pb));
+                    stats[0] += va;
+                    stats[1] += vb;
+                    stats[2] += va * va;
+                    stats[3] += vb * vb;
+                    stats[4] += va * vb;
+                }
+            }
+            if !varies {
+                continue;
+            }
+            let n = f64::from(win_w * win_h);
+            let (mean_a, mean_b) = (stats[0] / n, stats[1] / n);
+            let var_a = mean_a.mul_add(-mean_a, stats[2] / n);
+            let var_b = mean_b.mul_add(-mean_b, stats[3] / n);
+            let cov = mean_a.mul_add(-mean_b, stats[4] / n);
+            sum += (2.0 * mean_a).mul_add(mean_b, SSIM_C1) * 2.0_f64.mul_add(cov, SSIM_C2)
+                / (mean_a.mul_add(mean_a, mean_b.mul_add(mean_b, SSIM_C1))
+                    * (var_a + var_b + SSIM_C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        1.0
+    } else {
+        sum / windows as f64
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn map(width: u32, height: u32, set: &[(u32, u32)]) -> GrayImage {
+        let mut image = GrayImage::new(width, height);
+        for &(x, y) in set {
+            image.put_pixel(x, y, Luma([255]));
+        }
+        image
+    }
+
+    fn vertical_line(x: u32) -> Vec<(u32, u32)> {
+        (4..28).map(|y| (x, y)).collect()
+    }
+
+    /// A reference image that looks exactly like `drawn` rendered as
+    /// dark strokes on white at `tolerance_px`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn ink(drawn: &GrayImage, tolerance_px: f64) -> GrayImage {
+        let mut image = crate::blur::gaussian_blur(drawn, tolerance_px.max(0.5) as f32);
+        image::imageops::invert(&mut image);
+        image
+    }
+
+    fn white(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_pixel(width, height, Luma([255]))
+    }
+
+    #[test]
+    fn identical_maps_score_perfectly() {
+        let edges = map(32, 32, &vertical_line(10));
+        let reference = ink(&edges, DEFAULT_TOLERANCE_PX);
+        let q = compare(&edges, &edges, &reference, DEFAULT_TOLERANCE_PX);
+        assert!((q.edge_recall - 1.0).abs() < 1e-12);
+        assert!((q.edge_precision - 1.0).abs() < 1e-12);
+        assert!((q.edge_f1 - 1.0).abs() < 1e-12);
+        assert!(q.chamfer_distance.abs() < 1e-12);
+        assert!((q.ssim - 1.0).abs() < 1e-9, "ssim = {}", q.ssim);
+    }
+
+    #[test]
+    fn offset_within_tolerance_matches_but_costs_chamfer() {
+        let target = map(32, 32, &vertical_line(10));
+        let drawn = map(32, 32, &vertical_line(12));
+        let q = compare(&target, &drawn, &ink(&target, 2.0), 2.0);
+        assert!((q.edge_recall - 1.0).abs() < 1e-12);
+        assert!((q.edge_precision - 1.0).abs() < 1e-12);
+        assert!((q.chamfer_distance - 2.0).abs() < 1e-12);
+        assert!(q.ssim < 1.0);
+
+        let strict = compare(&target, &drawn, &ink(&target, 1.0), 1.0);
+        assert!(strict.edge_recall.abs() < 1e-12);
+        assert!(strict.edge_precision.abs() < 1e-12);
+    }
+
+    #[test]
+    fn spurious_stroke_lowers_precision_only() {
+        let target = map(32, 32, &vertical_line(5));
+        let mut set = vertical_line(5);
+        set.extend(vertical_line(25));
+        let drawn = map(32, 32, &set);
+        let q = compare(&target, &drawn, &ink(&target, 1.0), 1.0);
+        assert!((q.edge_recall - 1.0).abs() < 1e-12);
+        assert!((q.edge_precision - 0.5).abs() < 1e-12);
+        assert!(q.ssim < 1.0);
+    }
+
+    #[test]
+    fn empty_drawing_has_no_recall() {
+        let target = map(16, 16, &[(3, 3), (4, 4)]);
+        let q = compare(&target, &GrayImage::new(16, 16), &white(16, 16), 1.0);
+        assert!(q.edge_recall.abs() < 1e-12);
+        assert!((q.edge_precision - 1.0).abs() < 1e-12);
+        assert!(q.edge_f1.abs() < 1e-12);
+        assert!(q.chamfer_distance > 10.0);
+    }
+
+    #[test]
+    fn ssim_scores_against_reference_image() {
+        // A dark bar in the image: drawing along it resembles the image
+        // more than drawing through blank paper, whatever the edge map.
+        let mut reference = white(32, 32);
+        for y in 4..28 {
+            for x in 9..12 {
+                reference.put_pixel(x, y, Luma([0]));
+            }
+        }
+        let edges = GrayImage::new(32, 32);
+        let on_bar = compare(&edges, &map(32, 32, &vertical_line(10)), &reference, 1.0);
+        let off_bar = compare(&edges, &map(32, 32, &vertical_line(24)), &reference, 1.0);
+        assert!(
+            on_bar.ssim > off_bar.ssim,
+            "on = {}, off = {}",
+            on_bar.ssim,
+            off_bar.ssim
+        );
+    }
+
+    #[test]
+    fn rasterize_maps_normalized_path_onto_pixels() {
+        let dims = Dimensions {
+            width: 40,
+            height: 20,
+        };
+        // Horizontal line through the centre spanning the mask (zoom 1).
+        let path = Polyline::new(vec![Point::new(-1.0, 0.0), Point::new(1.0, 0.0)]);
+        let image = rasterize(&path, dims, 1.0);
+        assert_eq!(image.get_pixel(10, 10)[0], 255);
+        assert_eq!(image.get_pixel(20, 10)[0], 255);
+        assert_eq!(image.get_pixel(30, 10)[0], 255);
+        assert_eq!(image.get_pixel(5, 10)[0], 0);
+        assert_eq!(image.get_pixel(20, 5)[0], 0);
+    }
+
+    #[test]
+    fn measure_scores_pipeline_output() {
+        let mut image = image::RgbaImage::from_pixel(64, 64, image::Rgba([255, 255, 255, 255]));
+        for y in 16..48 {
+            for x in 16..48 {
+                image.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+            }
+        }
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let config = crate::PipelineConfig::default();
+        let result = crate::process_staged(&png, &config).unwrap();
+        let q = measure(&result, config.zoom, DEFAULT_TOLERANCE_PX);
+        assert!(q.edge_recall > 0.9, "recall = {}", q.edge_recall);
+        assert!(q.edge_precision > 0.0 && q.edge_precision <= 1.0);
+        assert!(q.chamfer_distance.is_finite());
+    }
+}
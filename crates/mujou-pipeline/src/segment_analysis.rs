@@ -2,9 +2,10 @@
 //!
 //! Identifies the longest individual segments across a set of polylines,
 //! returning ranked results suitable for both SVG export diagnostics
-//! and live UI overlays.
+//! and live UI overlays, and splits a labelled joined path into
+//! same-kind runs for layered rendering.
 
-use crate::Polyline;
+use crate::{Polyline, SegmentKind};
 
 /// Distinct colors for the top-N highlighted segments.
 ///
@@ -93,6 +94,66 @@ pub fn find_top_segments(polylines: &[Polyline], top_n: usize) -> Vec<RankedSegm
     all_segments
 }
 
+/// A run of consecutive path segments sharing one [`SegmentKind`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentRun {
+    /// Kind of every segment in the run.
+    pub kind: SegmentKind,
+    /// The run's points; consecutive runs share their boundary point.
+    pub polyline: Polyline,
+}
+
+/// Split `path` into runs of consecutive segments of the same kind.
+///
+/// `kinds[i]` is the kind of segment `i` (e.g.
+/// [`StagedResult::joined_segments`]).  `path` may be a
+/// [`Polyline::prefix`] of the labelled path: its segments line up
+/// with the original ones (the last possibly shortened), so a timeline
+/// view can split each prefix without relabelling.  Segments without a
+/// kind are treated as [`SegmentKind::Contour`].
+///
+/// [`StagedResult::joined_segments`]: crate::StagedResult::joined_segments
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{Point, Polyline, SegmentKind};
+/// use mujou_pipeline::segment_analysis::segment_runs;
+///
+/// let path = Polyline::new(vec![
+///     Point::new(0.0, 0.0),
+///     Point::new(1.0, 0.0),
+///     Point::new(0.0, 0.0),
+/// ]);
+/// let kinds = [SegmentKind::Contour, SegmentKind::Retrace];
+/// let runs = segment_runs(&path.prefix(0.75), &kinds);
+/// assert_eq!(runs.len(), 2);
+/// assert_eq!(runs[1].kind, SegmentKind::Retrace);
+/// assert_eq!(runs[1].polyline.last(), Some(&Point::new(0.5, 0.0)));
+/// ```
+#[must_use]
+pub fn segment_runs(path: &Polyline, kinds: &[SegmentKind]) -> Vec<SegmentRun> {
+    let points = path.points();
+    let mut runs: Vec<SegmentRun> = Vec::new();
+    let mut start = 0;
+    for i in 1..points.len() {
+        let kind = kind_at(kinds, i - 1);
+        let run_ends = i + 1 == points.len() || kind_at(kinds, i) != kind;
+        if run_ends {
+            runs.push(SegmentRun {
+                kind,
+                polyline: Polyline::new(points[start..=i].to_vec()),
+            });
+            start = i;
+        }
+    }
+    runs
+}
+
+fn kind_at(kinds: &[SegmentKind], segment: usize) -> SegmentKind {
+    kinds.get(segment).copied().unwrap_or(SegmentKind::Contour)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -160,4 +221,45 @@ mod tests {
         assert!(result[0].length >= result[1].length);
         assert!(result[1].length >= result[2].length);
     }
+
+    #[test]
+    fn runs_share_boundary_points() {
+        let path = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 1.0),
+        ]);
+        let kinds = [
+            SegmentKind::Contour,
+            SegmentKind::Contour,
+            SegmentKind::Connector,
+        ];
+        let runs = segment_runs(&path, &kinds);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].kind, SegmentKind::Contour);
+        assert_eq!(runs[0].polyline.len(), 3);
+        assert_eq!(runs[1].kind, SegmentKind::Connector);
+        assert_eq!(
+            runs[1].polyline.points(),
+            [Point::new(2.0, 0.0), Point::new(2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn runs_of_prefix_end_at_interpolated_point() {
+        let path = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(4.0, 0.0),
+        ]);
+        let kinds = [SegmentKind::Contour, SegmentKind::Connector];
+        let runs = segment_runs(&path.prefix(0.25), &kinds);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].kind, SegmentKind::Contour);
+        assert_eq!(runs[0].polyline.last(), Some(&Point::new(1.0, 0.0)));
+
+        assert!(segment_runs(&path.prefix(0.0), &kinds).is_empty());
+        assert!(segment_runs(&Polyline::new(Vec::new()), &kinds).is_empty());
+    }
 }
@@ -1,15 +1,84 @@
-//! Path simplification using the Ramer-Douglas-Peucker algorithm.
+//! Path simplification: reduce the point count of traced polylines.
 //!
-//! Reduces point count in polylines by removing points that are within
-//! a given tolerance of the line between their neighbors. This is
-//! implemented from scratch (~30 lines) to avoid pulling in the `geo`
-//! crate dependency tree.
+//! This module defines the [`Simplifier`] trait for pluggable
+//! simplification algorithms and the [`SimplifierKind`] enum for
+//! selecting which algorithm to use at runtime:
 //!
-//! This is step 6 in the pipeline, between contour tracing and path
-//! optimization.
+//! - [`Rdp`](SimplifierKind::Rdp) — Ramer-Douglas-Peucker.  Removes
+//!   points within a given tolerance of the line between their
+//!   neighbors.  Implemented from scratch (~30 lines) to avoid pulling
+//!   in the `geo` crate dependency tree.
+//! - [`VisvalingamWhyatt`](SimplifierKind::VisvalingamWhyatt) —
+//!   repeatedly removes the point forming the smallest triangle with
+//!   its neighbors.
+//! - [`TopologyPreserving`](SimplifierKind::TopologyPreserving) —
+//!   Visvalingam-Whyatt that never lets a path cross itself.
+//!
+//! This is step 7 in the pipeline, between contour tracing and the
+//! canvas mask.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+use rstar::primitives::{GeomWithData, Line};
+use rstar::{AABB, RTree};
+use serde::{Deserialize, Serialize};
 
 use crate::types::{Point, Polyline};
 
+/// Selects which simplification algorithm to use.
+///
+/// Every algorithm reads the same
+/// [`simplify_tolerance`](crate::PipelineConfig::simplify_tolerance)
+/// in normalized units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SimplifierKind {
+    /// Ramer-Douglas-Peucker: keeps every point farther than the
+    /// tolerance from the simplified path.  Preserves spiky outliers
+    /// but flattens shallow curvature.
+    #[default]
+    Rdp,
+    /// Visvalingam-Whyatt: removes points whose effective triangle area
+    /// with their neighbors is at most `tolerance²`.  Drops narrow
+    /// spikes and keeps broad, shallow curves.
+    VisvalingamWhyatt,
+    /// Visvalingam-Whyatt that keeps a point whenever removing it would
+    /// make the shortcut cross another segment of the same polyline,
+    /// so simplified paths never self-intersect.
+    TopologyPreserving,
+}
+
+impl fmt::Display for SimplifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rdp => f.write_str("RDP"),
+            Self::VisvalingamWhyatt => f.write_str("Visvalingam-Whyatt"),
+            Self::TopologyPreserving => f.write_str("Topology-preserving"),
+        }
+    }
+}
+
+/// Trait for path simplification strategies.
+///
+/// Input: a polyline and a tolerance in normalized units.
+/// Output: a polyline through a subset of the input points, always
+/// including both end points.
+pub trait Simplifier {
+    /// Simplify a single polyline.
+    fn simplify(&self, polyline: &Polyline, tolerance: f64) -> Polyline;
+}
+
+impl Simplifier for SimplifierKind {
+    fn simplify(&self, polyline: &Polyline, tolerance: f64) -> Polyline {
+        match *self {
+            Self::Rdp => simplify(polyline, tolerance),
+            Self::VisvalingamWhyatt => visvalingam_whyatt(polyline, tolerance, false),
+            Self::TopologyPreserving => visvalingam_whyatt(polyline, tolerance, true),
+        }
+    }
+}
+
 /// Simplify a single polyline using the Ramer-Douglas-Peucker algorithm.
 ///
 /// Points within `tolerance` (in normalized units) of the line between
@@ -42,10 +111,187 @@ pub fn simplify(polyline: &Polyline, tolerance: f64) -> Polyline {
     Polyline::new(simplified)
 }
 
-/// Simplify multiple polylines, applying RDP to each independently.
+/// Simplify multiple polylines, applying `simplifier` to each
+/// independently.
 #[must_use = "returns the simplified polylines"]
-pub fn simplify_paths(polylines: &[Polyline], tolerance: f64) -> Vec<Polyline> {
-    polylines.iter().map(|pl| simplify(pl, tolerance)).collect()
+pub fn simplify_paths(
+    polylines: &[Polyline],
+    simplifier: SimplifierKind,
+    tolerance: f64,
+) -> Vec<Polyline> {
+    crate::par::map(polylines, |pl| simplifier.simplify(pl, tolerance))
+}
+
+/// Simplify a single polyline using the Visvalingam-Whyatt algorithm.
+///
+/// Repeatedly removes the interior point with the smallest effective
+/// area — the area of the triangle it forms with its current neighbors,
+/// raised to at least the area of the previously removed point — while
+/// that area is at most `tolerance²`.  A tolerance of 0.0 removes only
+/// collinear points.
+///
+/// With `preserve_topology`, a point is kept while removing it would
+/// make the new segment between its neighbors cross any other segment
+/// of the polyline.
+///
+/// Polylines with fewer than 3 points are returned unchanged.
+#[must_use = "returns the simplified polyline"]
+pub fn visvalingam_whyatt(
+    polyline: &Polyline,
+    tolerance: f64,
+    preserve_topology: bool,
+) -> Polyline {
+    let points = polyline.points();
+    let n = points.len();
+    if n < 3 {
+        return polyline.clone();
+    }
+    let threshold = tolerance * tolerance;
+
+    // Doubly linked list over the surviving points.
+    let mut prev: Vec<usize> = (0..n).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=n).collect();
+    let mut area = vec![f64::INFINITY; n];
+    let mut kept = vec![true; n];
+    let mut heap = BinaryHeap::with_capacity(n);
+    for i in 1..n - 1 {
+        area[i] = triangle_area(points[i - 1], points[i], points[i + 1]);
+        heap.push(Candidate {
+            area: area[i],
+            index: i,
+        });
+    }
+    let mut segments = preserve_topology
+        .then(|| RTree::bulk_load((0..n - 1).map(|i| segment(points, i, i + 1)).collect()));
+
+    while let Some(Candidate { area: min, index }) = heap.pop() {
+        // Skip entries superseded by a later push for the same point.
+        if !kept[index] || min.to_bits() != area[index].to_bits() {
+            continue;
+        }
+        if min > threshold {
+            break;
+        }
+        let before = prev[index];
+        let after = next[index];
+        if let Some(tree) = segments.as_mut() {
+            if crosses_any(tree, points, before, after) {
+                // Retried once a neighbor's removal changes its area.
+                continue;
+            }
+            tree.remove(&segment(points, before, index));
+            tree.remove(&segment(points, index, after));
+            tree.insert(segment(points, before, after));
+        }
+        kept[index] = false;
+        next[before] = after;
+        prev[after] = before;
+        for j in [before, after] {
+            if j != 0 && j != n - 1 {
+                area[j] = triangle_area(points[prev[j]], points[j], points[next[j]]).max(min);
+                heap.push(Candidate {
+                    area: area[j],
+                    index: j,
+                });
+            }
+        }
+    }
+
+    Polyline::new(
+        points
+            .iter()
+            .zip(&kept)
+            .filter(|&(_, k)| *k)
+            .map(|(&p, _)| p)
+            .collect(),
+    )
+}
+
+/// A point awaiting removal, ordered so [`BinaryHeap`] pops the
+/// smallest area first.
+struct Candidate {
+    area: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Segment from `points[a]` to `points[b]`, tagged with both indices.
+type Segment = GeomWithData<Line<[f64; 2]>, (usize, usize)>;
+
+fn segment(points: &[Point], a: usize, b: usize) -> Segment {
+    GeomWithData::new(
+        Line::new([points[a].x, points[a].y], [points[b].x, points[b].y]),
+        (a, b),
+    )
+}
+
+/// Whether the segment from `points[a]` to `points[b]` would cross any
+/// segment in `tree` other than those ending at `a` or `b`.
+fn crosses_any(tree: &RTree<Segment>, points: &[Point], a: usize, b: usize) -> bool {
+    let (p, q) = (points[a], points[b]);
+    let envelope = AABB::from_corners([p.x.min(q.x), p.y.min(q.y)], [p.x.max(q.x), p.y.max(q.y)]);
+    tree.locate_in_envelope_intersecting(&envelope)
+        .filter(|s| ![a, b].contains(&s.data.0) && ![a, b].contains(&s.data.1))
+        .any(|s| segments_intersect(p, q, points[s.data.0], points[s.data.1]))
+}
+
+/// Whether segments `p1–p2` and `q1–q2` intersect.  Segments meeting
+/// only at a shared end point (e.g. where a closed loop meets itself)
+/// do not count.
+fn segments_intersect(p1: Point, p2: Point, q1: Point, q2: Point) -> bool {
+    if p1 == q1 || p1 == q2 || p2 == q1 || p2 == q2 {
+        return false;
+    }
+    let d1 = orientation(q1, q2, p1);
+    let d2 = orientation(q1, q2, p2);
+    let d3 = orientation(p1, p2, q1);
+    let d4 = orientation(p1, p2, q2);
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+    // Collinear touching cases.
+    (d1 == 0.0 && within_box(q1, q2, p1))
+        || (d2 == 0.0 && within_box(q1, q2, p2))
+        || (d3 == 0.0 && within_box(p1, p2, q1))
+        || (d4 == 0.0 && within_box(p1, p2, q2))
+}
+
+/// Twice the signed area of triangle `a`, `b`, `c`: positive for a
+/// counter-clockwise turn.
+fn orientation(a: Point, b: Point, c: Point) -> f64 {
+    (b.x - a.x).mul_add(c.y - a.y, -((b.y - a.y) * (c.x - a.x)))
+}
+
+/// Whether `p` lies in the bounding box of `a` and `b`.
+fn within_box(a: Point, b: Point, p: Point) -> bool {
+    (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x) && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y)
+}
+
+/// Area of the triangle `a`, `b`, `c`.
+fn triangle_area(a: Point, b: Point, c: Point) -> f64 {
+    orientation(a, b, c).abs() / 2.0
 }
 
 /// Iterative core of the Ramer-Douglas-Peucker algorithm.
@@ -198,7 +444,7 @@ mod tests {
                 Point::new(2.0, 0.0),
             ]),
         ];
-        let results = simplify_paths(&polylines, 0.5);
+        let results = simplify_paths(&polylines, SimplifierKind::Rdp, 0.5);
         assert_eq!(results.len(), 2);
         // First polyline: collinear, should collapse.
         assert_eq!(results[0].len(), 2);
@@ -206,6 +452,143 @@ mod tests {
         assert_eq!(results[1].len(), 3);
     }
 
+    #[test]
+    fn default_is_rdp() {
+        assert_eq!(SimplifierKind::default(), SimplifierKind::Rdp);
+    }
+
+    #[test]
+    fn every_simplifier_collapses_collinear_points() {
+        let pl = Polyline::new((0..5).map(|i| Point::new(f64::from(i), 0.0)).collect());
+        for kind in [
+            SimplifierKind::Rdp,
+            SimplifierKind::VisvalingamWhyatt,
+            SimplifierKind::TopologyPreserving,
+        ] {
+            let result = kind.simplify(&pl, 0.0);
+            assert_eq!(
+                result.points(),
+                [Point::new(0.0, 0.0), Point::new(4.0, 0.0)],
+                "{kind}",
+            );
+        }
+    }
+
+    #[test]
+    fn visvalingam_zero_tolerance_preserves_non_collinear_points() {
+        let pl = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.1),
+            Point::new(2.0, 0.0),
+            Point::new(3.0, 0.05),
+            Point::new(4.0, 0.0),
+        ]);
+        assert_eq!(visvalingam_whyatt(&pl, 0.0, false).len(), 5);
+    }
+
+    #[test]
+    fn visvalingam_drops_narrow_spike_that_rdp_keeps() {
+        // A tall, narrow spike: far from the baseline but tiny in area.
+        let pl = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.01, 1.0),
+            Point::new(1.02, 0.0),
+            Point::new(2.0, 0.0),
+        ]);
+        let spike = Point::new(1.01, 1.0);
+        assert!(simplify(&pl, 0.2).points().contains(&spike));
+        assert!(
+            !SimplifierKind::VisvalingamWhyatt
+                .simplify(&pl, 0.2)
+                .points()
+                .contains(&spike)
+        );
+    }
+
+    #[test]
+    fn visvalingam_keeps_broad_shallow_curve_that_rdp_flattens() {
+        // A wide, low arch: close to the chord but large in area.
+        let pl = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(5.0, 0.15),
+            Point::new(10.0, 0.0),
+        ]);
+        assert_eq!(simplify(&pl, 0.2).len(), 2);
+        assert_eq!(visvalingam_whyatt(&pl, 0.2, false).len(), 3);
+    }
+
+    /// Whether any two non-adjacent segments of `pl` intersect.
+    fn self_intersects(pl: &Polyline) -> bool {
+        let s: Vec<_> = pl.points().windows(2).collect();
+        (0..s.len()).any(|i| {
+            (i + 2..s.len()).any(|j| segments_intersect(s[i][0], s[i][1], s[j][0], s[j][1]))
+        })
+    }
+
+    #[test]
+    fn topology_preserving_prevents_self_intersection() {
+        // Removing (4, 7) would leave the segment (8, 5)–(3, 9) crossing
+        // (4, 8)–(7, 9).
+        let pl = Polyline::new(vec![
+            Point::new(4.0, 8.0),
+            Point::new(7.0, 9.0),
+            Point::new(8.0, 5.0),
+            Point::new(4.0, 7.0),
+            Point::new(3.0, 9.0),
+        ]);
+        assert!(!self_intersects(&pl));
+
+        let plain = visvalingam_whyatt(&pl, 2.1, false);
+        assert_eq!(plain.len(), 4);
+        assert!(self_intersects(&plain));
+
+        let preserved = SimplifierKind::TopologyPreserving.simplify(&pl, 2.1);
+        assert_eq!(preserved, pl);
+        assert!(!self_intersects(&preserved));
+    }
+
+    #[test]
+    fn topology_preserving_simplifies_closed_loops() {
+        // A finely sampled square: every edge point is collinear.
+        let mut points = Vec::new();
+        for (from, to) in [
+            ((0.0_f64, 0.0_f64), (1.0, 0.0)),
+            ((1.0, 0.0), (1.0, 1.0)),
+            ((1.0, 1.0), (0.0, 1.0)),
+            ((0.0, 1.0), (0.0, 0.0)),
+        ] {
+            for i in 0..10 {
+                let t = f64::from(i) / 10.0;
+                points.push(Point::new(
+                    (to.0 - from.0).mul_add(t, from.0),
+                    (to.1 - from.1).mul_add(t, from.1),
+                ));
+            }
+        }
+        points.push(Point::new(0.0, 0.0));
+        let result = SimplifierKind::TopologyPreserving.simplify(&Polyline::new(points), 0.01);
+        assert_eq!(result.len(), 5, "{result:?}");
+        assert!(!self_intersects(&result));
+    }
+
+    #[test]
+    fn segments_sharing_an_end_point_do_not_intersect() {
+        let a = Point::new(0.0, 0.0);
+        assert!(!segments_intersect(
+            a,
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+            a
+        ));
+        assert!(segments_intersect(
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 2.0),
+            Point::new(0.0, 2.0),
+            Point::new(2.0, 0.0),
+        ));
+    }
+
     #[test]
     fn perpendicular_distance_on_axis() {
         // Point (1, 3) is 3 units from the line y=0 (from (0,0) to (2,0)).
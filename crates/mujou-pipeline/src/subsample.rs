@@ -12,9 +12,57 @@
 //! operation (with a 2 mm threshold in machine coordinates) before
 //! its Cartesian-to-polar conversion.  The threshold is expressed in
 //! normalized units (mask edge = 1.0).
+//!
+//! A Cartesian length bound still under-samples near the polar center,
+//! where a short XY move can sweep a large theta, and over-samples at
+//! the rim.  [`SubsampleMode::Polar`] instead bounds both the radial
+//! step (Δrho) and the arc length (rho·Δtheta) of every step, then
+//! drops points that are redundant in theta-rho space.
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
 
 use crate::types::{Point, Polyline};
 
+/// Selects how long segments are resampled before polar conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SubsampleMode {
+    /// Split segments by Cartesian length only (see [`subsample`]).
+    #[default]
+    Cartesian,
+
+    /// Bound both Δrho and rho·Δtheta per step, then drop points that
+    /// are collinear in theta-rho space (see [`subsample_polar`]).
+    ///
+    /// Produces denser sampling near the center, where small XY moves
+    /// become large theta swings, and sparser sampling along straight
+    /// radial or circular runs.  Best suited to polar (THR) tables.
+    Polar,
+}
+
+impl SubsampleMode {
+    /// Resample `polyline` using this mode with the given maximum step
+    /// length (in normalized units).
+    #[must_use]
+    pub fn apply(self, polyline: &Polyline, max_length: f64) -> Polyline {
+        match self {
+            Self::Cartesian => subsample(polyline, max_length),
+            Self::Polar => subsample_polar(polyline, max_length),
+        }
+    }
+}
+
+impl fmt::Display for SubsampleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cartesian => f.write_str("Cartesian"),
+            Self::Polar => f.write_str("Polar"),
+        }
+    }
+}
+
 /// Subdivide long segments in a polyline so no segment exceeds
 /// `max_length` (in normalized units).
 ///
@@ -81,6 +129,210 @@ pub fn subsample(polyline: &Polyline, max_length: f64) -> Polyline {
     Polyline::new(result)
 }
 
+/// Maximum bisection depth when splitting a single segment in
+/// [`subsample_polar`].
+///
+/// 2^24 sub-steps per segment is far beyond anything a real path needs;
+/// the limit only guards against pathological inputs (e.g. NaN).
+const MAX_POLAR_SPLIT_DEPTH: u32 = 24;
+
+/// Tolerance (normalized units) below which a point is considered
+/// collinear with its neighbours in theta-rho space and dropped by
+/// [`remove_polar_redundant`].
+///
+/// On a 34" table (radius ≈ 430 mm) this is ≈ 0.04 mm — well below the
+/// ball's track width.
+pub const POLAR_COLLINEAR_TOLERANCE: f64 = 1e-4;
+
+/// Subdivide segments so every step is short **in polar terms**, then
+/// drop redundant points.
+///
+/// Each segment is bisected until, for every resulting step, both the
+/// radial change `|Δrho|` and the arc length `max(rho)·|Δtheta|` are at
+/// most `max_length` (normalized units).  Inserted points lie on the
+/// original Cartesian segment, so the drawn geometry is unchanged.
+///
+/// A cleanup pass ([`remove_polar_redundant`]) then removes points that
+/// lie on the straight theta-rho interpolation between their
+/// neighbours, as long as the merged step still satisfies the bounds.
+///
+/// Returns the polyline unchanged (by clone) when `max_length` is
+/// non-positive or the polyline has fewer than 2 points.
+///
+/// # Examples
+///
+/// ```
+/// use mujou_pipeline::{Point, Polyline};
+/// use mujou_pipeline::subsample::subsample_polar;
+///
+/// // A chord passing close to the center sweeps nearly π radians.
+/// let polyline = Polyline::new(vec![
+///     Point::new(-0.5, 0.01),
+///     Point::new(0.5, 0.01),
+/// ]);
+/// let result = subsample_polar(&polyline, 0.05);
+/// assert!(result.len() > 2);
+/// ```
+#[must_use]
+pub fn subsample_polar(polyline: &Polyline, max_length: f64) -> Polyline {
+    let points = polyline.points();
+    if points.len() < 2 || max_length <= 0.0 {
+        return polyline.clone();
+    }
+
+    let mut result = Vec::with_capacity(points.len());
+    result.push(points[0]);
+
+    for window in points.windows(2) {
+        split_polar(window[0], window[1], max_length, 0, &mut result);
+    }
+
+    remove_polar_redundant(
+        &Polyline::new(result),
+        max_length,
+        POLAR_COLLINEAR_TOLERANCE,
+    )
+}
+
+/// Recursively bisect `a → b` until the step satisfies
+/// [`polar_step_ok`], pushing every point after `a` (including `b`).
+fn split_polar(a: Point, b: Point, max_length: f64, depth: u32, out: &mut Vec<Point>) {
+    if depth >= MAX_POLAR_SPLIT_DEPTH || polar_step_ok(a, b, max_length) {
+        out.push(b);
+        return;
+    }
+    let mid = Point::new(f64::midpoint(a.x, b.x), f64::midpoint(a.y, b.y));
+    split_polar(a, mid, max_length, depth + 1, out);
+    split_polar(mid, b, max_length, depth + 1, out);
+}
+
+/// Whether a single step `a → b` stays within `max_length` both
+/// radially and along the arc.
+fn polar_step_ok(a: Point, b: Point, max_length: f64) -> bool {
+    let rho_a = a.x.hypot(a.y);
+    let rho_b = b.x.hypot(b.y);
+    let d_theta = theta_delta(a, b).abs();
+    (rho_b - rho_a).abs() <= max_length && rho_a.max(rho_b) * d_theta <= max_length
+}
+
+/// Like [`polar_step_ok`], but for `(theta, rho)` pairs whose theta
+/// has already been unwound (so `|Δtheta|` may exceed π).
+fn polar_pair_ok(a: (f64, f64), b: (f64, f64), max_length: f64) -> bool {
+    (b.1 - a.1).abs() <= max_length && a.1.max(b.1) * (b.0 - a.0).abs() <= max_length
+}
+
+/// Signed angular change from `a` to `b` in `(-π, π]`, using the THR
+/// `atan2(x, y)` convention.
+///
+/// Returns 0 when either point sits exactly on the polar origin, where
+/// theta is undefined (the THR exporter reuses the previous theta there).
+fn theta_delta(a: Point, b: Point) -> f64 {
+    if (a.x == 0.0 && a.y == 0.0) || (b.x == 0.0 && b.y == 0.0) {
+        return 0.0;
+    }
+    wrap_angle(b.x.atan2(b.y) - a.x.atan2(a.y))
+}
+
+/// Wrap an angle difference into `(-π, π]`.
+fn wrap_angle(delta: f64) -> f64 {
+    let two_pi = 2.0 * PI;
+    let mut d = delta % two_pi;
+    if d > PI {
+        d -= two_pi;
+    } else if d <= -PI {
+        d += two_pi;
+    }
+    d
+}
+
+/// Drop points that are collinear with their neighbours in theta-rho
+/// space.
+///
+/// Walks the polyline greedily: from the last kept point (the anchor),
+/// the step is extended one point at a time for as long as
+///
+/// - every skipped point lies within `tolerance` of the straight
+///   theta-rho interpolation between the anchor and the candidate, and
+/// - the merged step still satisfies the `max_length` bound on both
+///   `|Δrho|` and `max(rho)·|Δtheta|`.
+///
+/// Deviations are measured in a locally-flattened polar plane with
+/// coordinates `(mean_rho·theta, rho)`, so `tolerance` is approximately
+/// a distance in normalized units.  The first and last points are
+/// always kept.
+#[must_use]
+pub fn remove_polar_redundant(polyline: &Polyline, max_length: f64, tolerance: f64) -> Polyline {
+    let points = polyline.points();
+    if points.len() < 3 {
+        return polyline.clone();
+    }
+
+    let polar = unwound_polar(points);
+    let mut kept = Vec::with_capacity(points.len());
+    kept.push(points[0]);
+
+    let mut anchor = 0;
+    let mut candidate = anchor + 2;
+    while candidate < points.len() {
+        let mergeable = polar_pair_ok(polar[anchor], polar[candidate], max_length)
+            && (anchor + 1..candidate)
+                .all(|m| polar_deviation(polar[anchor], polar[candidate], polar[m]) <= tolerance);
+        if mergeable {
+            candidate += 1;
+        } else {
+            // The previous candidate is the furthest mergeable point.
+            anchor = candidate - 1;
+            kept.push(points[anchor]);
+            candidate = anchor + 2;
+        }
+    }
+    if let Some(&last) = points.last() {
+        kept.push(last);
+    }
+
+    Polyline::new(kept)
+}
+
+/// Convert points to `(theta, rho)` pairs with continuously unwound
+/// theta, matching the THR exporter's convention.
+fn unwound_polar(points: &[Point]) -> Vec<(f64, f64)> {
+    let mut out = Vec::with_capacity(points.len());
+    let mut prev: Option<(Point, f64)> = None;
+    for &p in points {
+        let rho = p.x.hypot(p.y);
+        let theta = match prev {
+            None if rho == 0.0 => 0.0,
+            None => p.x.atan2(p.y),
+            Some((prev_point, prev_theta)) => prev_theta + theta_delta(prev_point, p),
+        };
+        out.push((theta, rho));
+        prev = Some((p, theta));
+    }
+    out
+}
+
+/// Distance of `m` from the straight line `a → c` in the flattened
+/// polar plane `(mean_rho·theta, rho)`.
+fn polar_deviation(a: (f64, f64), c: (f64, f64), m: (f64, f64)) -> f64 {
+    let scale = f64::midpoint(a.1, c.1);
+    let (ax, ay) = (a.0 * scale, a.1);
+    let (cx, cy) = (c.0 * scale, c.1);
+    let (mx, my) = (m.0 * scale, m.1);
+    let dx = cx - ax;
+    let dy = cy - ay;
+    let len_sq = dx.mul_add(dx, dy * dy);
+    if len_sq == 0.0 {
+        return (mx - ax).hypot(my - ay);
+    }
+    let t = (mx - ax).mul_add(dx, (my - ay) * dy) / len_sq;
+    if !(0.0..=1.0).contains(&t) {
+        // Outside the step — the point would be skipped over, not
+        // interpolated, so it is never redundant.
+        return f64::INFINITY;
+    }
+    dx.mul_add(my - ay, -(dy * (mx - ax))).abs() / len_sq.sqrt()
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -261,4 +513,136 @@ mod tests {
             );
         }
     }
+
+    // --- Polar mode ---
+
+    /// Assert every step of `polyline` satisfies the polar bounds.
+    fn assert_polar_steps_within(polyline: &Polyline, max_len: f64) {
+        let polar = unwound_polar(polyline.points());
+        for (i, w) in polar.windows(2).enumerate() {
+            let d_rho = (w[1].1 - w[0].1).abs();
+            let arc = w[0].1.max(w[1].1) * (w[1].0 - w[0].0).abs();
+            assert!(
+                d_rho <= max_len + 1e-9,
+                "step {i} has Δrho {d_rho}, exceeds {max_len}"
+            );
+            assert!(
+                arc <= max_len + 1e-9,
+                "step {i} has arc length {arc}, exceeds {max_len}"
+            );
+        }
+    }
+
+    #[test]
+    fn polar_short_polyline_returns_unchanged() {
+        let p = poly(&[(0.5, 0.0)]);
+        assert_eq!(subsample_polar(&p, 0.01), p);
+        let p = poly(&[(0.0, 0.0), (0.5, 0.0)]);
+        assert_eq!(subsample_polar(&p, 0.0), p);
+    }
+
+    #[test]
+    fn polar_preserves_endpoints() {
+        let p = poly(&[(-0.8, 0.1), (0.3, -0.2), (0.9, 0.0)]);
+        let result = subsample_polar(&p, 0.01);
+        assert_eq!(result.first(), p.first());
+        assert_eq!(result.last(), p.last());
+    }
+
+    #[test]
+    fn polar_steps_respect_bounds() {
+        let p = poly(&[
+            (-0.9, 0.02),
+            (0.9, 0.02),
+            (0.0, -0.7),
+            (0.0, 0.7),
+            (0.7, 0.7),
+        ]);
+        let max_len = 0.01;
+        let result = subsample_polar(&p, max_len);
+        assert_polar_steps_within(&result, max_len);
+    }
+
+    #[test]
+    fn polar_samples_near_center_more_densely_than_cartesian() {
+        // A chord passing just above the center: Cartesian sampling
+        // spaces points evenly, but theta swings rapidly near rho = 0.
+        let p = poly(&[(-0.5, 0.005), (0.5, 0.005)]);
+        let max_len = 0.05;
+        let cartesian = subsample(&p, max_len);
+        let polar = subsample_polar(&p, max_len);
+
+        let near_center = |pl: &Polyline| {
+            pl.points()
+                .iter()
+                .filter(|pt| pt.x.hypot(pt.y) < 0.05)
+                .count()
+        };
+        assert!(
+            near_center(&polar) > near_center(&cartesian),
+            "polar ({}) should place more points near the center than cartesian ({})",
+            near_center(&polar),
+            near_center(&cartesian),
+        );
+    }
+
+    #[test]
+    fn polar_radial_line_collapses_redundant_points() {
+        // A purely radial line is straight in theta-rho space, so only
+        // the Δrho bound forces intermediate points.
+        let p = poly(&[(0.0, 0.1), (0.0, 0.2), (0.0, 0.3), (0.0, 0.4), (0.0, 0.5)]);
+        let result = subsample_polar(&p, 0.25);
+        // 0.4 total Δrho with 0.25 max → one interior point is needed.
+        assert_eq!(result.len(), 3);
+        assert_polar_steps_within(&result, 0.25);
+    }
+
+    #[test]
+    fn polar_produces_fewer_points_than_cartesian_on_rim_arc() {
+        // A densely-sampled arc along the rim is a straight line in
+        // theta-rho space, so the cleanup pass collapses it to steps
+        // of max_len arc length.
+        let n = 200;
+        let coords: Vec<(f64, f64)> = (0..=n)
+            .map(|i| {
+                let theta = PI * f64::from(i) / f64::from(n);
+                (0.95 * theta.sin(), 0.95 * theta.cos())
+            })
+            .collect();
+        let p = poly(&coords);
+        let max_len = 0.05;
+        let cartesian = subsample(&p, max_len);
+        let polar = subsample_polar(&p, max_len);
+        assert!(
+            polar.len() < cartesian.len(),
+            "polar ({}) should be smaller than cartesian ({})",
+            polar.len(),
+            cartesian.len(),
+        );
+        assert_polar_steps_within(&polar, max_len);
+    }
+
+    #[test]
+    fn remove_polar_redundant_keeps_corners() {
+        // Radial out, then along an arc: the corner is not collinear in
+        // theta-rho space and must survive.
+        let p = poly(&[(0.0, 0.2), (0.0, 0.5), (0.5, 0.0)]);
+        let result = remove_polar_redundant(&p, 10.0, POLAR_COLLINEAR_TOLERANCE);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn subsample_mode_apply_dispatches() {
+        let p = poly(&[(0.0, 0.0), (0.0, 0.5)]);
+        assert_eq!(SubsampleMode::Cartesian.apply(&p, 0.1), subsample(&p, 0.1));
+        assert_eq!(
+            SubsampleMode::Polar.apply(&p, 0.1),
+            subsample_polar(&p, 0.1)
+        );
+    }
+
+    #[test]
+    fn subsample_mode_default_is_cartesian() {
+        assert_eq!(SubsampleMode::default(), SubsampleMode::Cartesian);
+    }
 }
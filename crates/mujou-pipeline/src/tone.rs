@@ -0,0 +1,475 @@
+//! Contrast normalization between downsampling and blur.
+//!
+//! Underexposed or low-contrast photos have small gradients everywhere,
+//! so Canny at the default thresholds finds almost no edges.  The tone
+//! stage stretches the intensity range first.  [`ToneKind`] selects the
+//! mapping:
+//!
+//! - [`Equalize`](ToneKind::Equalize) — global histogram equalization.
+//! - [`Clahe`](ToneKind::Clahe) — contrast-limited adaptive histogram
+//!   equalization: one clipped equalization per tile, bilinearly
+//!   interpolated between tile centers.
+//! - [`Gamma`](ToneKind::Gamma) — a fixed power curve.
+//!
+//! Every mapping is a monotone tone curve computed from the image's
+//! luminance and applied to each of R, G and B (alpha is untouched), so
+//! hues shift little and all edge channels see the same stretch.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Luma32FImage, PipelineError, RgbaImage};
+
+/// Selects the tone mapping applied before blur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ToneKind {
+    /// Pass the image through unchanged.
+    #[default]
+    Off,
+    /// Global histogram equalization.  Strongest stretch, but amplifies
+    /// noise in large flat areas.
+    Equalize,
+    /// Contrast-limited adaptive histogram equalization.  Brings out
+    /// local detail in both shadows and highlights; the clip limit caps
+    /// noise amplification.
+    Clahe,
+    /// Power curve `255 · (v / 255)^gamma`.  Values below 1 lift
+    /// shadows, above 1 deepen them.
+    Gamma,
+}
+
+impl fmt::Display for ToneKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => f.write_str("Off"),
+            Self::Equalize => f.write_str("Equalize"),
+            Self::Clahe => f.write_str("CLAHE"),
+            Self::Gamma => f.write_str("Gamma"),
+        }
+    }
+}
+
+/// Tone stage settings.
+///
+/// Only the parameters of the selected [`kind`](Self::kind) are used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToneSettings {
+    /// Which tone mapping to apply.
+    pub kind: ToneKind,
+    /// CLAHE tile edge length in pixels.  Smaller tiles adapt to finer
+    /// regions.  Must be at least [`MIN_TILE_SIZE`](Self::MIN_TILE_SIZE).
+    pub tile_size: u32,
+    /// CLAHE clip limit as a multiple of the mean histogram bin count.
+    /// 1.0 leaves contrast nearly unchanged; larger values allow more.
+    /// Must be at least 1.0.
+    pub clip_limit: f32,
+    /// Exponent for [`ToneKind::Gamma`].  Must be positive and finite.
+    pub gamma: f32,
+}
+
+impl ToneSettings {
+    /// Smallest allowed [`tile_size`](Self::tile_size).
+    pub const MIN_TILE_SIZE: u32 = 8;
+    /// Default [`tile_size`](Self::tile_size).
+    pub const DEFAULT_TILE_SIZE: u32 = 128;
+    /// Default [`clip_limit`](Self::clip_limit).
+    pub const DEFAULT_CLIP_LIMIT: f32 = 2.0;
+    /// Default [`gamma`](Self::gamma).
+    pub const DEFAULT_GAMMA: f32 = 0.6;
+
+    /// Check the parameter bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PipelineError::InvalidConfig`] if `tile_size` is below
+    /// [`MIN_TILE_SIZE`](Self::MIN_TILE_SIZE), `clip_limit` is below
+    /// 1.0, or `gamma` is not positive and finite.
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        if self.tile_size < Self::MIN_TILE_SIZE {
+            return Err(PipelineError::InvalidConfig(format!(
+                "tone tile_size must be at least {}, got {}",
+                Self::MIN_TILE_SIZE,
+                self.tile_size,
+            )));
+        }
+        if self.clip_limit.is_nan() || self.clip_limit < 1.0 {
+            return Err(PipelineError::InvalidConfig(format!(
+                "tone clip_limit must be at least 1.0, got {}",
+                self.clip_limit,
+            )));
+        }
+        if !self.gamma.is_finite() || self.gamma <= 0.0 {
+            return Err(PipelineError::InvalidConfig(format!(
+                "tone gamma must be positive and finite, got {}",
+                self.gamma,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether `self` and `other` produce the same tone mapping, ignoring
+    /// parameters the selected [`kind`](Self::kind) does not read.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn output_eq(&self, other: &Self) -> bool {
+        if self.kind != other.kind {
+            return false;
+        }
+        match self.kind {
+            ToneKind::Off | ToneKind::Equalize => true,
+            ToneKind::Clahe => {
+                self.tile_size == other.tile_size && self.clip_limit == other.clip_limit
+            }
+            ToneKind::Gamma => self.gamma == other.gamma,
+        }
+    }
+}
+
+impl Default for ToneSettings {
+    fn default() -> Self {
+        Self {
+            kind: ToneKind::Off,
+            tile_size: Self::DEFAULT_TILE_SIZE,
+            clip_limit: Self::DEFAULT_CLIP_LIMIT,
+            gamma: Self::DEFAULT_GAMMA,
+        }
+    }
+}
+
+/// A 256-entry tone curve: output intensity (0–255) per input level.
+type Lut = [f32; 256];
+
+/// The tone curve for one image, possibly varying by position.
+enum ToneCurve {
+    /// One curve for every pixel.
+    Global(Box<Lut>),
+    /// One curve per `tile_size` square tile, row-major, interpolated
+    /// between tile centers.
+    Tiled {
+        luts: Vec<Lut>,
+        tile_size: u32,
+        cols: u32,
+        rows: u32,
+    },
+}
+
+impl ToneCurve {
+    /// Build the curve for `settings` from the luminance of `rgba`.
+    /// Returns `None` for [`ToneKind::Off`].
+    fn new(rgba: &RgbaImage, settings: &ToneSettings) -> Option<Self> {
+        match settings.kind {
+            ToneKind::Off => None,
+            ToneKind::Gamma => Some(Self::Global(Box::new(gamma_lut(settings.gamma)))),
+            ToneKind::Equalize => {
+                let luma = crate::edge::rgba_to_luma(rgba);
+                let hist = histogram(luma.pixels().map(|p| p.0[0]));
+                Some(Self::Global(Box::new(equalize_lut(&hist))))
+            }
+            ToneKind::Clahe => {
+                let luma = crate::edge::rgba_to_luma(rgba);
+                let tile_size = settings.tile_size;
+                let cols = luma.width().div_ceil(tile_size);
+                let rows = luma.height().div_ceil(tile_size);
+                let luts = (0..rows)
+                    .flat_map(|row| (0..cols).map(move |col| (col, row)))
+                    .map(|(col, row)| {
+                        let x0 = col * tile_size;
+                        let y0 = row * tile_size;
+                        let x1 = (x0 + tile_size).min(luma.width());
+                        let y1 = (y0 + tile_size).min(luma.height());
+                        let hist = histogram(
+                            (y0..y1)
+                                .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+                                .map(|(x, y)| luma.get_pixel(x, y).0[0]),
+                        );
+                        clahe_lut(&hist, settings.clip_limit)
+                    })
+                    .collect();
+                Some(Self::Tiled {
+                    luts,
+                    tile_size,
+                    cols,
+                    rows,
+                })
+            }
+        }
+    }
+
+    /// Map intensity `value` (0–255, fractional allowed) at pixel
+    /// `(x, y)`.
+    fn map(&self, x: u32, y: u32, value: f32) -> f32 {
+        match self {
+            Self::Global(lut) => sample(lut, value),
+            Self::Tiled {
+                luts,
+                tile_size,
+                cols,
+                rows,
+            } => {
+                // Position in tile-center coordinates: tile i's center
+                // sits at i.  Outside the outermost centers the nearest
+                // curve is used unblended.
+                #[allow(clippy::cast_precision_loss)]
+                let center = |p: u32, count: u32| {
+                    let t = (p as f32 + 0.5) / *tile_size as f32 - 0.5;
+                    t.clamp(0.0, (count - 1) as f32)
+                };
+                let (tx, ty) = (center(x, *cols), center(y, *rows));
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let (c0, r0) = (tx.floor() as u32, ty.floor() as u32);
+                let (c1, r1) = ((c0 + 1).min(cols - 1), (r0 + 1).min(rows - 1));
+                let (fx, fy) = (tx.fract(), ty.fract());
+                let at = |c: u32, r: u32| sample(&luts[(r * cols + c) as usize], value);
+                let top = (at(c1, r0) - at(c0, r0)).mul_add(fx, at(c0, r0));
+                let bottom = (at(c1, r1) - at(c0, r1)).mul_add(fx, at(c0, r1));
+                (bottom - top).mul_add(fy, top)
+            }
+        }
+    }
+}
+
+/// Apply the tone mapping in `settings` to the R, G and B channels of
+/// `rgba` and, when present, to the full-precision luminance `luma`
+/// (same dimensions).
+///
+/// The curve is always computed from the 8-bit luminance of `rgba`, so
+/// both outputs get the same stretch.  [`ToneKind::Off`] returns clones
+/// of the inputs.
+#[must_use]
+pub fn apply(
+    rgba: &RgbaImage,
+    luma: Option<&Luma32FImage>,
+    settings: &ToneSettings,
+) -> (RgbaImage, Option<Luma32FImage>) {
+    let Some(curve) = ToneCurve::new(rgba, settings) else {
+        return (rgba.clone(), luma.cloned());
+    };
+
+    let toned = RgbaImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let map = |v: u8| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let mapped = curve.map(x, y, f32::from(v)).round().clamp(0.0, 255.0) as u8;
+            mapped
+        };
+        image::Rgba([map(r), map(g), map(b), a])
+    });
+    let luma = luma.map(|luma| {
+        Luma32FImage::from_fn(luma.width(), luma.height(), |x, y| {
+            image::Luma([curve.map(x, y, luma.get_pixel(x, y).0[0])])
+        })
+    });
+    (toned, luma)
+}
+
+/// Histogram of 8-bit intensities.
+fn histogram(values: impl Iterator<Item = u8>) -> [u32; 256] {
+    let mut hist = [0_u32; 256];
+    for v in values {
+        hist[usize::from(v)] += 1;
+    }
+    hist
+}
+
+/// Global histogram equalization curve.
+///
+/// Maps the darkest occupied level to 0 and the brightest to 255; a
+/// histogram with a single occupied level maps to the identity.
+#[allow(clippy::cast_precision_loss)]
+fn equalize_lut(hist: &[u32; 256]) -> Lut {
+    let cdf = cumulative(&hist.map(|h| h as f32));
+    let first = hist.iter().position(|&h| h > 0).map_or(0.0, |i| cdf[i]);
+    let span = cdf[255] - first;
+    if span <= 0.0 {
+        return std::array::from_fn(|i| i as f32);
+    }
+    cdf.map(|c| (c - first) / span * 255.0)
+}
+
+/// Contrast-limited equalization curve for one CLAHE tile.
+///
+/// Bins are capped at `clip_limit` times the mean bin count and the
+/// excess is spread evenly over all bins, which bounds the curve's
+/// slope at `clip_limit`.  Unlike [`equalize_lut`] the curve is not
+/// stretched to the occupied range, so a limit of 1 gives the identity.
+#[allow(clippy::cast_precision_loss)]
+fn clahe_lut(hist: &[u32; 256], clip_limit: f32) -> Lut {
+    let total: u32 = hist.iter().sum();
+    if total == 0 {
+        return std::array::from_fn(|i| i as f32);
+    }
+    let cap = clip_limit * total as f32 / 256.0;
+    let excess: f32 = hist.iter().map(|&h| (h as f32 - cap).max(0.0)).sum();
+    let cdf = cumulative(&hist.map(|h| (h as f32).min(cap) + excess / 256.0));
+    cdf.map(|c| c / total as f32 * 255.0)
+}
+
+/// Running sum of histogram bins.
+fn cumulative(bins: &[f32; 256]) -> [f32; 256] {
+    let mut acc = 0.0;
+    bins.map(|b| {
+        acc += b;
+        acc
+    })
+}
+
+/// Power curve `255 · (v / 255)^gamma`.
+#[allow(clippy::cast_precision_loss)]
+fn gamma_lut(gamma: f32) -> Lut {
+    std::array::from_fn(|i| 255.0 * (i as f32 / 255.0).powf(gamma))
+}
+
+/// Look up a fractional intensity, interpolating between entries.
+fn sample(lut: &Lut, value: f32) -> f32 {
+    let value = value.clamp(0.0, 255.0);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let lo = value.floor() as usize;
+    let hi = (lo + 1).min(255);
+    (lut[hi] - lut[lo]).mul_add(value.fract(), lut[lo])
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// 64x64 underexposed gradient: intensities 10..=41 along x.
+    fn dark_gradient() -> RgbaImage {
+        RgbaImage::from_fn(64, 64, |x, _| {
+            #[allow(clippy::cast_possible_truncation)]
+            let v = 10 + (x / 2) as u8;
+            image::Rgba([v, v, v, 255])
+        })
+    }
+
+    fn settings(kind: ToneKind) -> ToneSettings {
+        ToneSettings {
+            kind,
+            tile_size: 16,
+            ..ToneSettings::default()
+        }
+    }
+
+    #[test]
+    fn off_is_identity() {
+        let img = dark_gradient();
+        let (toned, luma) = apply(&img, None, &ToneSettings::default());
+        assert_eq!(toned, img);
+        assert!(luma.is_none());
+    }
+
+    #[test]
+    fn equalize_stretches_to_full_range() {
+        let (toned, _) = apply(&dark_gradient(), None, &settings(ToneKind::Equalize));
+        assert_eq!(toned.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(toned.get_pixel(63, 0).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn equalize_of_flat_image_is_identity() {
+        let img = RgbaImage::from_pixel(8, 8, image::Rgba([90, 90, 90, 200]));
+        let (toned, _) = apply(&img, None, &settings(ToneKind::Equalize));
+        assert_eq!(toned, img);
+    }
+
+    #[test]
+    fn gamma_below_one_brightens_and_keeps_endpoints() {
+        let lut = gamma_lut(0.5);
+        assert!(lut[0].abs() < f32::EPSILON);
+        assert!((lut[255] - 255.0).abs() < 1e-3);
+        assert!(lut[64] > 64.0);
+    }
+
+    #[test]
+    fn clahe_contrast_grows_with_clip_limit() {
+        // Low-contrast dark checkerboard: levels 20 and 30.
+        let img = RgbaImage::from_fn(64, 64, |x, y| {
+            let v = if (x + y) % 2 == 0 { 20 } else { 30 };
+            image::Rgba([v, v, v, 255])
+        });
+        let spread = |clip_limit| {
+            let (toned, _) = apply(
+                &img,
+                None,
+                &ToneSettings {
+                    clip_limit,
+                    ..settings(ToneKind::Clahe)
+                },
+            );
+            toned.get_pixel(33, 32).0[0] - toned.get_pixel(32, 32).0[0]
+        };
+        assert!(spread(2.0) > 10);
+        assert!(spread(8.0) > spread(2.0));
+    }
+
+    #[test]
+    fn clahe_clip_limit_bounds_curve_slope() {
+        // A single spike would map to a step of 255 without clipping.
+        let mut hist = [0; 256];
+        hist[100] = 1000;
+        for clip_limit in [1.0, 2.0, 4.0] {
+            let lut = clahe_lut(&hist, clip_limit);
+            for pair in lut.windows(2) {
+                let step = pair[1] - pair[0];
+                assert!(step >= 0.0, "curve must be monotone");
+                assert!(step <= clip_limit + 1.0, "step {step} at clip {clip_limit}");
+            }
+        }
+    }
+
+    #[test]
+    fn precise_luma_gets_same_curve() {
+        let img = dark_gradient();
+        let luma = Luma32FImage::from_fn(64, 64, |x, y| {
+            image::Luma([f32::from(img.get_pixel(x, y).0[0])])
+        });
+        let (toned, luma) = apply(&img, Some(&luma), &settings(ToneKind::Equalize));
+        let luma = luma.unwrap();
+        for (x, y, p) in toned.enumerate_pixels() {
+            assert!((luma.get_pixel(x, y).0[0] - f32::from(p.0[0])).abs() <= 0.5);
+        }
+    }
+
+    #[test]
+    fn alpha_is_untouched() {
+        let img = RgbaImage::from_fn(16, 16, |x, _| {
+            #[allow(clippy::cast_possible_truncation)]
+            let v = (x * 4) as u8;
+            image::Rgba([v, v, v, 77])
+        });
+        let (toned, _) = apply(&img, None, &settings(ToneKind::Equalize));
+        assert!(toned.pixels().all(|p| p.0[3] == 77));
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_settings() {
+        let bad = [
+            ToneSettings {
+                tile_size: 4,
+                ..ToneSettings::default()
+            },
+            ToneSettings {
+                clip_limit: 0.5,
+                ..ToneSettings::default()
+            },
+            ToneSettings {
+                gamma: 0.0,
+                ..ToneSettings::default()
+            },
+            ToneSettings {
+                gamma: f32::INFINITY,
+                ..ToneSettings::default()
+            },
+            ToneSettings {
+                gamma: f32::NAN,
+                ..ToneSettings::default()
+            },
+        ];
+        for settings in bad {
+            assert!(settings.validate().is_err(), "{settings:?}");
+        }
+        ToneSettings::default().validate().unwrap();
+    }
+}
@@ -1,13 +1,21 @@
 //! Shared types for the mujou image processing pipeline.
 
+use std::collections::BTreeMap;
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::alpha::AlphaMode;
+use crate::blur::SmoothingKind;
 use crate::contour::ContourTracerKind;
+use crate::contour_filter::ContourFilter;
+use crate::curve::CurveSettings;
 use crate::downsample::DownsampleFilter;
 use crate::join::PathJoinerKind;
 use crate::mask::{BorderPathMode, CanvasShape, MaskResult};
+use crate::simplify::SimplifierKind;
+use crate::subsample::SubsampleMode;
+use crate::tone::ToneSettings;
 
 /// Re-export `GrayImage` so downstream crates can reference
 /// intermediate raster data without depending on `image` directly.
@@ -17,10 +25,18 @@ pub use image::GrayImage;
 /// original decoded image without depending on `image` directly.
 pub use image::RgbaImage;
 
+/// Single-channel `f32` luminance image.
+///
+/// Carries the luminance of a source with more than 8 bits per channel
+/// at full precision.  Values use the
+/// 0–255 scale of the 8-bit stages so Canny thresholds keep their
+/// meaning.
+pub type Luma32FImage = image::ImageBuffer<image::Luma<f32>, Vec<f32>>;
+
 /// A 2D point.
 ///
-/// Before normalization (stages 0–4): pixel coordinates (origin at
-/// top-left, +Y down).  After normalization (stages 5–9): center-origin
+/// Before normalization (stages 0–5): pixel coordinates (origin at
+/// top-left, +Y down).  After normalization (stages 6–10): center-origin
 /// normalized coordinates (+Y up, mask edge = 1.0).
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point {
@@ -100,6 +116,44 @@ impl Polyline {
     pub fn into_points(self) -> Vec<Point> {
         self.0
     }
+
+    /// Total Euclidean length of the path.
+    #[must_use]
+    pub fn length(&self) -> f64 {
+        self.0.windows(2).map(|w| w[0].distance(w[1])).sum()
+    }
+
+    /// The part of the path covering the first `fraction` of its length.
+    ///
+    /// `fraction` is clamped to `[0, 1]`.  The last point is
+    /// interpolated along its segment, so it is exactly where a ball
+    /// drawing the path would be at that point.  A zero-length path (or
+    /// `fraction = 0`) yields just its first point; an empty path yields
+    /// an empty polyline.
+    #[must_use]
+    pub fn prefix(&self, fraction: f64) -> Self {
+        let Some(&first) = self.0.first() else {
+            return Self::new(Vec::new());
+        };
+        let mut remaining = self.length() * fraction.clamp(0.0, 1.0);
+        let mut points = vec![first];
+        for w in self.0.windows(2) {
+            let step = w[0].distance(w[1]);
+            if step >= remaining {
+                if remaining > 0.0 {
+                    let t = remaining / step;
+                    points.push(Point::new(
+                        (w[1].x - w[0].x).mul_add(t, w[0].x),
+                        (w[1].y - w[0].y).mul_add(t, w[0].y),
+                    ));
+                }
+                return Self::new(points);
+            }
+            remaining -= step;
+            points.push(w[1]);
+        }
+        Self::new(points)
+    }
 }
 
 /// Compute the axis-aligned bounding box of all points across polylines.
@@ -144,11 +198,116 @@ impl Dimensions {
     }
 }
 
+/// A single image channel that edge detection can run on.
+///
+/// Names the fields of [`EdgeChannels`] so per-channel settings (like
+/// [`EdgeChannels::weights`]) can be keyed by channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum EdgeChannel {
+    /// See [`EdgeChannels::luminance`].
+    Luminance,
+    /// See [`EdgeChannels::red`].
+    Red,
+    /// See [`EdgeChannels::green`].
+    Green,
+    /// See [`EdgeChannels::blue`].
+    Blue,
+    /// See [`EdgeChannels::saturation`].
+    Saturation,
+    /// See [`EdgeChannels::alpha`].
+    Alpha,
+    /// See [`EdgeChannels::hue`].
+    Hue,
+    /// See [`EdgeChannels::value`].
+    Value,
+    /// See [`EdgeChannels::lightness`].
+    Lightness,
+    /// See [`EdgeChannels::lab_a`].
+    LabA,
+    /// See [`EdgeChannels::lab_b`].
+    LabB,
+}
+
+impl EdgeChannel {
+    /// Every channel, in the order edge detection visits them.
+    pub const ALL: [Self; 11] = [
+        Self::Luminance,
+        Self::Red,
+        Self::Green,
+        Self::Blue,
+        Self::Saturation,
+        Self::Alpha,
+        Self::Hue,
+        Self::Value,
+        Self::Lightness,
+        Self::LabA,
+        Self::LabB,
+    ];
+}
+
+impl fmt::Display for EdgeChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Luminance => f.write_str("Luminance"),
+            Self::Red => f.write_str("Red"),
+            Self::Green => f.write_str("Green"),
+            Self::Blue => f.write_str("Blue"),
+            Self::Saturation => f.write_str("Saturation"),
+            Self::Alpha => f.write_str("Alpha"),
+            Self::Hue => f.write_str("Hue"),
+            Self::Value => f.write_str("Value"),
+            Self::Lightness => f.write_str("Lightness"),
+            Self::LabA => f.write_str("a*"),
+            Self::LabB => f.write_str("b*"),
+        }
+    }
+}
+
+/// Low/high Canny hysteresis thresholds for one edge channel.
+///
+/// See [`EdgeChannels::thresholds`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CannyThresholds {
+    /// Gradient magnitude below which a pixel is never an edge.
+    pub low: f32,
+    /// Gradient magnitude above which a pixel is always an edge.
+    pub high: f32,
+}
+
+/// How the enabled [`EdgeChannels`] are fused into one edge map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EdgeFusion {
+    /// Run Canny on each channel and keep a pixel if *any* channel
+    /// marked it as an edge.
+    #[default]
+    Max,
+    /// Average the channels' gradient magnitudes by
+    /// [weight](EdgeChannels::weights) and run a single non-maximum
+    /// suppression and hysteresis pass.  Noise in one channel is
+    /// diluted by the others, giving cleaner multi-channel edges.
+    WeightedSum,
+}
+
+impl fmt::Display for EdgeFusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Max => f.write_str("Max"),
+            Self::WeightedSum => f.write_str("Weighted Sum"),
+        }
+    }
+}
+
 /// Channels to use for edge detection.
 ///
-/// Canny edge detection is run independently on each enabled channel.
-/// The resulting edge maps are combined via pixel-wise maximum, so edges
-/// detected in *any* enabled channel appear in the final edge map.
+/// By default ([`EdgeFusion::Max`]) Canny edge detection is run
+/// independently on each enabled channel and the resulting edge maps
+/// are combined via pixel-wise maximum, so edges detected in *any*
+/// enabled channel appear in the final edge map.  A channel's
+/// [weight](Self::weights) scales its gradient magnitude before
+/// thresholding, making it more or less sensitive than the rest, and
+/// its [thresholds](Self::thresholds) can replace the global ones.
+/// [`EdgeFusion::WeightedSum`] instead fuses the gradients and
+/// thresholds once.
 ///
 /// By default only [`luminance`](Self::luminance) is enabled, reproducing
 /// the standard single-channel grayscale pipeline. Enabling additional
@@ -157,7 +316,7 @@ impl Dimensions {
 ///
 /// At least one channel must be enabled. See
 /// [`PipelineConfig::validate`] for the enforcement rule.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct EdgeChannels {
     /// sRGB/Rec.709 weighted grayscale (`0.2126R + 0.7152G + 0.0722B`).
@@ -192,13 +351,94 @@ pub struct EdgeChannels {
     /// Computed as `(max(R,G,B) - min(R,G,B)) / max(R,G,B)`, scaled
     /// to 0–255.
     pub saturation: bool,
+
+    /// Alpha (opacity) channel.
+    ///
+    /// Traces the silhouette of the opaque region — e.g. the outline of
+    /// a logo on a transparent background.  Fully opaque images have no
+    /// alpha edges.
+    #[serde(default)]
+    pub alpha: bool,
+
+    /// Hue channel from HSV, handled as an angle.
+    ///
+    /// Finds boundaries between different colors of similar brightness
+    /// and saturation.  Hue wraps around (red sits at both 0° and
+    /// 360°), so its gradient is taken on the unit vector
+    /// `(cos h, sin h)` rather than on the raw angle.  The vector is
+    /// scaled by saturation so the meaningless hue of near-gray pixels
+    /// does not produce noise.
+    #[serde(default)]
+    pub hue: bool,
+
+    /// Value channel from HSV: `max(R, G, B)`.
+    ///
+    /// Like luminance, but a saturated color is as bright as white, so
+    /// it ignores boundaries between equally vivid colors.
+    #[serde(default)]
+    pub value: bool,
+
+    /// CIELAB lightness L* (D65), scaled from 0–100 to 0–255.
+    ///
+    /// Perceptually uniform brightness: equal steps look equally
+    /// strong, unlike sRGB luminance which compresses dark tones.
+    #[serde(default)]
+    pub lightness: bool,
+
+    /// CIELAB a* (green–red opponent axis).
+    ///
+    /// Separates reds and pinks from greens — e.g. petals against
+    /// foliage, lips against skin.
+    #[serde(default)]
+    pub lab_a: bool,
+
+    /// CIELAB b* (blue–yellow opponent axis).
+    ///
+    /// Separates yellows from blues — e.g. flower centers, sky
+    /// against warm subjects.
+    #[serde(default)]
+    pub lab_b: bool,
+
+    /// Optional per-channel gradient weight (default 1.0).
+    ///
+    /// Must be positive and at most [`MAX_WEIGHT`](Self::MAX_WEIGHT).
+    /// What a weight means depends on [`fusion`](Self::fusion):
+    ///
+    /// - [`EdgeFusion::Max`]: absolute.  A channel's gradient magnitude
+    ///   is multiplied by its weight before the Canny thresholds apply,
+    ///   so a weight of 2.0 finds edges half as strong and 0.5 only
+    ///   edges twice as strong.
+    /// - [`EdgeFusion::WeightedSum`]: relative.  The fused magnitude is
+    ///   the weighted mean `Σ wᵢ·|gᵢ| / Σ wᵢ`, so weights only shift the
+    ///   balance between channels; scaling every weight, or weighting
+    ///   a lone channel, changes nothing.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub weights: BTreeMap<EdgeChannel, f32>,
+
+    /// Optional per-channel Canny thresholds replacing
+    /// [`canny_low`](PipelineConfig::canny_low) and
+    /// [`canny_high`](PipelineConfig::canny_high) for that channel.
+    ///
+    /// Noisy channels such as saturation and blue usually need higher
+    /// thresholds than luminance.  Only used by [`EdgeFusion::Max`];
+    /// [`EdgeFusion::WeightedSum`] thresholds the fused gradient with
+    /// the global values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub thresholds: BTreeMap<EdgeChannel, CannyThresholds>,
+
+    /// How the per-channel results are fused.
+    #[serde(default)]
+    pub fusion: EdgeFusion,
 }
 
 impl EdgeChannels {
+    /// Largest allowed per-channel [weight](Self::weights).
+    pub const MAX_WEIGHT: f32 = 4.0;
+
     /// Returns `true` if at least one channel is enabled.
     #[must_use]
     pub const fn any_enabled(&self) -> bool {
-        self.luminance || self.red || self.green || self.blue || self.saturation
+        self.count() > 0
     }
 
     /// Returns the number of enabled channels.
@@ -209,6 +449,112 @@ impl EdgeChannels {
             + self.green as usize
             + self.blue as usize
             + self.saturation as usize
+            + self.alpha as usize
+            + self.hue as usize
+            + self.value as usize
+            + self.lightness as usize
+            + self.lab_a as usize
+            + self.lab_b as usize
+    }
+
+    /// Whether `channel` is enabled.
+    #[must_use]
+    pub const fn is_enabled(&self, channel: EdgeChannel) -> bool {
+        match channel {
+            EdgeChannel::Luminance => self.luminance,
+            EdgeChannel::Red => self.red,
+            EdgeChannel::Green => self.green,
+            EdgeChannel::Blue => self.blue,
+            EdgeChannel::Saturation => self.saturation,
+            EdgeChannel::Alpha => self.alpha,
+            EdgeChannel::Hue => self.hue,
+            EdgeChannel::Value => self.value,
+            EdgeChannel::Lightness => self.lightness,
+            EdgeChannel::LabA => self.lab_a,
+            EdgeChannel::LabB => self.lab_b,
+        }
+    }
+
+    /// Enable or disable `channel`.
+    pub const fn set_enabled(&mut self, channel: EdgeChannel, enabled: bool) {
+        let field = match channel {
+            EdgeChannel::Luminance => &mut self.luminance,
+            EdgeChannel::Red => &mut self.red,
+            EdgeChannel::Green => &mut self.green,
+            EdgeChannel::Blue => &mut self.blue,
+            EdgeChannel::Saturation => &mut self.saturation,
+            EdgeChannel::Alpha => &mut self.alpha,
+            EdgeChannel::Hue => &mut self.hue,
+            EdgeChannel::Value => &mut self.value,
+            EdgeChannel::Lightness => &mut self.lightness,
+            EdgeChannel::LabA => &mut self.lab_a,
+            EdgeChannel::LabB => &mut self.lab_b,
+        };
+        *field = enabled;
+    }
+
+    /// The enabled channels, in [`EdgeChannel::ALL`] order.
+    pub fn enabled(&self) -> impl Iterator<Item = EdgeChannel> + '_ {
+        EdgeChannel::ALL
+            .into_iter()
+            .filter(|&channel| self.is_enabled(channel))
+    }
+
+    /// The [weight](Self::weights) of `channel`, 1.0 when unset.
+    #[must_use]
+    pub fn weight(&self, channel: EdgeChannel) -> f32 {
+        self.weights.get(&channel).copied().unwrap_or(1.0)
+    }
+
+    /// Check the channel invariants listed in
+    /// [`PipelineConfig::validate`]; threshold overrides may not exceed
+    /// `canny_max`.
+    fn validate(&self, canny_max: f32) -> Result<(), PipelineError> {
+        if !self.any_enabled() {
+            return Err(PipelineError::InvalidConfig(
+                "at least one edge channel must be enabled".to_owned(),
+            ));
+        }
+        for (channel, weight) in &self.weights {
+            if !(*weight > 0.0 && *weight <= Self::MAX_WEIGHT) {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "{channel} edge channel weight must be in (0, {}], got {weight}",
+                    Self::MAX_WEIGHT,
+                )));
+            }
+        }
+        for (channel, thresholds) in &self.thresholds {
+            if thresholds.low < crate::edge::MIN_THRESHOLD {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "{channel} edge channel low threshold must be at least {}, got {}",
+                    crate::edge::MIN_THRESHOLD,
+                    thresholds.low,
+                )));
+            }
+            if thresholds.low > thresholds.high {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "{channel} edge channel low threshold ({}) must not exceed high threshold ({})",
+                    thresholds.low, thresholds.high,
+                )));
+            }
+            if thresholds.high > canny_max {
+                return Err(PipelineError::InvalidConfig(format!(
+                    "{channel} edge channel high threshold ({}) must not exceed canny_max ({})",
+                    thresholds.high, canny_max,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `(low, high)` thresholds for `channel`: its
+    /// [override](Self::thresholds) if set, otherwise the given
+    /// global values.
+    #[must_use]
+    pub fn thresholds_for(&self, channel: EdgeChannel, low: f32, high: f32) -> (f32, f32) {
+        self.thresholds
+            .get(&channel)
+            .map_or((low, high), |t| (t.low, t.high))
     }
 }
 
@@ -220,6 +566,15 @@ impl Default for EdgeChannels {
             green: false,
             blue: false,
             saturation: false,
+            alpha: false,
+            hue: false,
+            value: false,
+            lightness: false,
+            lab_a: false,
+            lab_b: false,
+            weights: BTreeMap::new(),
+            thresholds: BTreeMap::new(),
+            fusion: EdgeFusion::Max,
         }
     }
 }
@@ -280,10 +635,32 @@ impl fmt::Display for StartPointStrategy {
 /// See [open-questions: PipelineConfig validation](https://github.com/altendky/mujou/pull/2#discussion_r2778003093).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PipelineConfig {
+    /// Contrast normalization applied between downsampling and blur
+    /// (see [`crate::tone`]).  Off by default.
+    #[serde(default)]
+    pub tone: ToneSettings,
+
     /// Gaussian blur kernel sigma. Higher values produce more smoothing
     /// before edge detection.
+    ///
+    /// Also sets the spatial scale of the other
+    /// [`smoothing`](Self::smoothing) strategies.  Must be positive and
+    /// at most the strategy's [`max_sigma`](SmoothingKind::max_sigma).
     pub blur_sigma: f32,
 
+    /// Smoothing filter applied before edge detection (see
+    /// [`crate::blur`]).  Gaussian by default; the alternatives keep
+    /// edges sharper.
+    #[serde(default)]
+    pub smoothing: SmoothingKind,
+
+    /// Intensity difference (0–255 levels) that the bilateral and
+    /// Perona-Malik [`smoothing`](Self::smoothing) strategies treat as
+    /// an edge.  Smaller values preserve fainter edges.  Must be
+    /// positive.
+    #[serde(default = "PipelineConfig::default_smoothing_range")]
+    pub smoothing_range: f32,
+
     /// Canny edge detector low threshold. Pixels with gradient magnitude
     /// between `canny_low` and `canny_high` are edges only if connected
     /// to a strong edge.
@@ -308,10 +685,25 @@ pub struct PipelineConfig {
     /// Which contour tracing algorithm to use.
     pub contour_tracer: ContourTracerKind,
 
-    /// Ramer-Douglas-Peucker simplification tolerance in normalized units.
-    /// Higher values remove more points, producing simpler paths.
+    /// Which path simplification algorithm to use.
+    #[serde(default)]
+    pub simplifier: SimplifierKind,
+
+    /// Simplification tolerance in normalized units (see
+    /// [`simplifier`](Self::simplifier)).  Higher values remove more
+    /// points, producing simpler paths.
     pub simplify_tolerance: f64,
 
+    /// Minimum contour size applied after simplification (see
+    /// [`crate::contour_filter`]).  Keeps every contour by default.
+    #[serde(default)]
+    pub contour_filter: ContourFilter,
+
+    /// Curve smoothing applied after simplification and the contour
+    /// filter (see [`crate::curve`]).  Off by default.
+    #[serde(default)]
+    pub curve: CurveSettings,
+
     /// Which path joining strategy to use for connecting disconnected
     /// contours into a single continuous path.
     pub path_joiner: PathJoinerKind,
@@ -418,6 +810,22 @@ pub struct PipelineConfig {
     #[serde(default)]
     pub start_point: StartPointStrategy,
 
+    /// Route rim-to-rim connectors along the canvas edge.
+    ///
+    /// When enabled on a circular canvas, any connector whose endpoints
+    /// both lie within [`perimeter_epsilon`](Self::perimeter_epsilon) of
+    /// the mask edge is replaced with travel along the border arc, which
+    /// is nearly invisible on real tables (see [`crate::perimeter`]).
+    /// Has no effect on rectangular canvases.
+    #[serde(default)]
+    pub perimeter_connectors: bool,
+
+    /// Distance from the mask edge (normalized units) within which a
+    /// connector endpoint counts as "on the rim" for
+    /// [`perimeter_connectors`](Self::perimeter_connectors).
+    #[serde(default = "PipelineConfig::default_perimeter_epsilon")]
+    pub perimeter_epsilon: f64,
+
     /// Maximum segment length in normalized units for subsampling.
     ///
     /// After path joining, any segment longer than this value is
@@ -429,11 +837,55 @@ pub struct PipelineConfig {
     /// to effectively disable subsampling.
     #[serde(default = "PipelineConfig::default_subsample_max_length")]
     pub subsample_max_length: f64,
+
+    /// How long segments are resampled after path joining.
+    ///
+    /// `Cartesian` bounds only the XY length of each step.  `Polar`
+    /// bounds both Δrho and rho·Δtheta by
+    /// [`subsample_max_length`](Self::subsample_max_length) and drops
+    /// points that are redundant in theta-rho space, producing smaller
+    /// and more faithful THR files for polar tables.
+    #[serde(default)]
+    pub subsample_mode: SubsampleMode,
+
+    /// Radius (normalized units) around the polar center that the output
+    /// path should route around.
+    ///
+    /// Straight chords passing within this radius of rho = 0 make polar
+    /// tables spin theta violently.  Such segments are replaced by a
+    /// short arc at this radius (see [`crate::center_avoid`]).  `0.0`
+    /// (the default) disables rerouting.
+    #[serde(default)]
+    pub center_avoid_radius: f64,
+
+    /// Frame of an animated input (GIF) to trace; 0 is the first frame.
+    ///
+    /// A frame past the end selects the last one, so any value is valid
+    /// and still images ignore it.  Changing it re-decodes the source.
+    #[serde(default)]
+    pub input_frame: u32,
+
+    /// How the source's alpha channel is used (see [`crate::alpha`]).
+    ///
+    /// `Ignore` (the default) leaves transparent pixels as stored,
+    /// `Composite` flattens the image over
+    /// [`alpha_background`](Self::alpha_background), and `Mask`
+    /// discards contours outside the opaque region.
+    #[serde(default)]
+    pub alpha_mode: AlphaMode,
+
+    /// Background RGB color for [`AlphaMode::Composite`].
+    #[serde(default = "PipelineConfig::default_alpha_background")]
+    pub alpha_background: [u8; 3],
 }
 
 impl PipelineConfig {
     /// Default Gaussian blur sigma.
     pub const DEFAULT_BLUR_SIGMA: f32 = 1.4;
+    /// Default smoothing strategy.
+    pub const DEFAULT_SMOOTHING: SmoothingKind = SmoothingKind::Gaussian;
+    /// Default edge-preserving smoothing range.
+    pub const DEFAULT_SMOOTHING_RANGE: f32 = 25.0;
     /// Default Canny low threshold.
     pub const DEFAULT_CANNY_LOW: f32 = 15.0;
     /// Default Canny high threshold.
@@ -478,14 +930,37 @@ impl PipelineConfig {
         green: false,
         blue: false,
         saturation: false,
+        alpha: false,
+        hue: false,
+        value: false,
+        lightness: false,
+        lab_a: false,
+        lab_b: false,
+        weights: BTreeMap::new(),
+        thresholds: BTreeMap::new(),
+        fusion: EdgeFusion::Max,
     };
     /// Default start point strategy (outside / perimeter).
     pub const DEFAULT_START_POINT: StartPointStrategy = StartPointStrategy::Outside;
+    /// Default perimeter connector routing state (disabled).
+    pub const DEFAULT_PERIMETER_CONNECTORS: bool = false;
+    /// Default rim distance for perimeter connector routing.
+    pub const DEFAULT_PERIMETER_EPSILON: f64 = 0.02;
     /// Default subsample max segment length in normalized units.
     ///
     /// Equivalent to the old 2.0 px default at 1000 px working
     /// resolution: `2.0 / 500.0 = 0.004`.
     pub const DEFAULT_SUBSAMPLE_MAX_LENGTH: f64 = 0.004;
+    /// Default subsample mode (Cartesian length only).
+    pub const DEFAULT_SUBSAMPLE_MODE: SubsampleMode = SubsampleMode::Cartesian;
+    /// Default polar center avoidance radius (disabled).
+    pub const DEFAULT_CENTER_AVOID_RADIUS: f64 = 0.0;
+    /// Default input frame (the first).
+    pub const DEFAULT_INPUT_FRAME: u32 = 0;
+    /// Default alpha handling (ignore alpha).
+    pub const DEFAULT_ALPHA_MODE: AlphaMode = AlphaMode::Ignore;
+    /// Default composite background color (white).
+    pub const DEFAULT_ALPHA_BACKGROUND: [u8; 3] = [255, 255, 255];
 
     // Serde default helpers — serde's per-field `#[serde(default)]` uses
     // the *type's* `Default`, which is wrong for `f64` (0.0) and `bool`
@@ -502,6 +977,15 @@ impl PipelineConfig {
     const fn default_subsample_max_length() -> f64 {
         Self::DEFAULT_SUBSAMPLE_MAX_LENGTH
     }
+    const fn default_perimeter_epsilon() -> f64 {
+        Self::DEFAULT_PERIMETER_EPSILON
+    }
+    const fn default_alpha_background() -> [u8; 3] {
+        Self::DEFAULT_ALPHA_BACKGROUND
+    }
+    const fn default_smoothing_range() -> f32 {
+        Self::DEFAULT_SMOOTHING_RANGE
+    }
 
     /// Validate that all fields satisfy the documented invariants.
     ///
@@ -511,26 +995,46 @@ impl PipelineConfig {
     ///
     /// # Checked invariants
     ///
-    /// - `blur_sigma > 0`
+    /// - `tone.tile_size >= ToneSettings::MIN_TILE_SIZE` (8)
+    /// - `tone.clip_limit >= 1`
+    /// - `tone.gamma > 0`
+    /// - `blur_sigma` in `(0, max_sigma]` for the smoothing strategy's
+    ///   [`max_sigma`](SmoothingKind::max_sigma)
+    /// - `smoothing_range > 0`
     /// - `canny_low >= edge::MIN_THRESHOLD` (1.0)
     /// - `canny_low <= canny_high`
     /// - `canny_high <= canny_max`
     /// - `canny_max <= edge::max_gradient_magnitude()`
     /// - `simplify_tolerance >= 0`
+    /// - `contour_filter` thresholds finite and `>= 0`
+    /// - `curve.iterations` in `1..=CurveSettings::MAX_ITERATIONS`
+    /// - `curve.spacing > 0` and `curve.tolerance > 0`
     /// - `zoom` in `[0.4, 3.0]`
     /// - `aspect_ratio` in `[1.0, 4.0]`
     /// - `working_resolution > 0`
     /// - `mst_neighbours > 0`
+    /// - `center_avoid_radius` in `[0.0, 0.5]`
+    /// - `perimeter_epsilon` in `[0.0, 0.2]`
+    /// - edge channel weights in `(0, EdgeChannels::MAX_WEIGHT]`
+    /// - edge channel threshold overrides satisfy the same `canny_low`
+    ///   and `canny_high` bounds as the global thresholds
     ///
     /// # Errors
     ///
     /// Returns [`PipelineError::InvalidConfig`] with a human-readable
     /// message if any invariant is violated.
     pub fn validate(&self) -> Result<(), PipelineError> {
-        if self.blur_sigma <= 0.0 {
+        let max_sigma = self.smoothing.max_sigma();
+        if !(self.blur_sigma > 0.0 && self.blur_sigma <= max_sigma) {
+            return Err(PipelineError::InvalidConfig(format!(
+                "blur_sigma must be in (0, {max_sigma}] for {} smoothing, got {}",
+                self.smoothing, self.blur_sigma,
+            )));
+        }
+        if self.smoothing_range.is_nan() || self.smoothing_range <= 0.0 {
             return Err(PipelineError::InvalidConfig(format!(
-                "blur_sigma must be positive, got {}",
-                self.blur_sigma,
+                "smoothing_range must be positive, got {}",
+                self.smoothing_range,
             )));
         }
         if self.canny_low < crate::edge::MIN_THRESHOLD {
@@ -593,17 +1097,28 @@ impl PipelineConfig {
                 "mst_neighbours must be positive".to_owned(),
             ));
         }
-        if !self.edge_channels.any_enabled() {
-            return Err(PipelineError::InvalidConfig(
-                "at least one edge channel must be enabled".to_owned(),
-            ));
-        }
+        self.tone.validate()?;
+        self.contour_filter.validate()?;
+        self.curve.validate()?;
+        self.edge_channels.validate(self.canny_max)?;
         if self.subsample_max_length <= 0.0 || self.subsample_max_length.is_nan() {
             return Err(PipelineError::InvalidConfig(format!(
                 "subsample_max_length must be positive, got {}",
                 self.subsample_max_length,
             )));
         }
+        if !(0.0..=0.5).contains(&self.center_avoid_radius) {
+            return Err(PipelineError::InvalidConfig(format!(
+                "center_avoid_radius must be in [0.0, 0.5], got {}",
+                self.center_avoid_radius,
+            )));
+        }
+        if !(0.0..=0.2).contains(&self.perimeter_epsilon) {
+            return Err(PipelineError::InvalidConfig(format!(
+                "perimeter_epsilon must be in [0.0, 0.2], got {}",
+                self.perimeter_epsilon,
+            )));
+        }
         Ok(())
     }
 }
@@ -611,12 +1126,18 @@ impl PipelineConfig {
 impl Default for PipelineConfig {
     fn default() -> Self {
         Self {
+            tone: ToneSettings::default(),
             blur_sigma: Self::DEFAULT_BLUR_SIGMA,
+            smoothing: Self::DEFAULT_SMOOTHING,
+            smoothing_range: Self::DEFAULT_SMOOTHING_RANGE,
             canny_low: Self::DEFAULT_CANNY_LOW,
             canny_high: Self::DEFAULT_CANNY_HIGH,
             canny_max: Self::DEFAULT_CANNY_MAX,
             contour_tracer: ContourTracerKind::default(),
+            simplifier: SimplifierKind::default(),
             simplify_tolerance: Self::DEFAULT_SIMPLIFY_TOLERANCE,
+            contour_filter: ContourFilter::default(),
+            curve: CurveSettings::default(),
             path_joiner: PathJoinerKind::default(),
             shape: Self::DEFAULT_SHAPE,
             zoom: Self::DEFAULT_ZOOM,
@@ -631,7 +1152,14 @@ impl Default for PipelineConfig {
             parity_strategy: Self::DEFAULT_PARITY_STRATEGY,
             edge_channels: Self::DEFAULT_EDGE_CHANNELS,
             start_point: Self::DEFAULT_START_POINT,
+            perimeter_connectors: Self::DEFAULT_PERIMETER_CONNECTORS,
+            perimeter_epsilon: Self::DEFAULT_PERIMETER_EPSILON,
             subsample_max_length: Self::DEFAULT_SUBSAMPLE_MAX_LENGTH,
+            subsample_mode: Self::DEFAULT_SUBSAMPLE_MODE,
+            center_avoid_radius: Self::DEFAULT_CENTER_AVOID_RADIUS,
+            input_frame: Self::DEFAULT_INPUT_FRAME,
+            alpha_mode: Self::DEFAULT_ALPHA_MODE,
+            alpha_background: Self::DEFAULT_ALPHA_BACKGROUND,
         }
     }
 }
@@ -646,12 +1174,18 @@ impl PipelineConfig {
         // Destructure so adding a field to PipelineConfig without updating
         // this match causes a compile error.
         let Self {
+            tone,
             blur_sigma,
+            smoothing,
+            smoothing_range,
             canny_low,
             canny_high,
             canny_max: _,
             contour_tracer,
+            simplifier,
             simplify_tolerance,
+            contour_filter,
+            curve,
             path_joiner,
             shape,
             zoom,
@@ -666,14 +1200,28 @@ impl PipelineConfig {
             parity_strategy,
             edge_channels,
             start_point,
+            perimeter_connectors,
+            perimeter_epsilon,
             subsample_max_length,
+            subsample_mode,
+            center_avoid_radius,
+            input_frame,
+            alpha_mode,
+            alpha_background,
         } = self;
 
-        *blur_sigma == other.blur_sigma
+        tone.output_eq(&other.tone)
+            && *blur_sigma == other.blur_sigma
+            && *smoothing == other.smoothing
+            && (!(smoothing.uses_range() || other.smoothing.uses_range())
+                || *smoothing_range == other.smoothing_range)
             && *canny_low == other.canny_low
             && *canny_high == other.canny_high
             && *contour_tracer == other.contour_tracer
+            && *simplifier == other.simplifier
             && *simplify_tolerance == other.simplify_tolerance
+            && *contour_filter == other.contour_filter
+            && curve.output_eq(&other.curve)
             && *path_joiner == other.path_joiner
             && *shape == other.shape
             && *zoom == other.zoom
@@ -688,17 +1236,23 @@ impl PipelineConfig {
             && *parity_strategy == other.parity_strategy
             && *edge_channels == other.edge_channels
             && *start_point == other.start_point
+            && *perimeter_connectors == other.perimeter_connectors
+            && *perimeter_epsilon == other.perimeter_epsilon
             && *subsample_max_length == other.subsample_max_length
+            && *subsample_mode == other.subsample_mode
+            && *center_avoid_radius == other.center_avoid_radius
+            && *input_frame == other.input_frame
+            && *alpha_mode == other.alpha_mode
+            && (*alpha_mode != AlphaMode::Composite || *alpha_background == other.alpha_background)
     }
 
     /// Return the zero-based index of the earliest pipeline stage whose
     /// output would differ between `self` and `other`, given the same
     /// input image.
     ///
-    /// Stages 0 (pending) and 1 (decode) have no config dependencies —
-    /// they only depend on the source image bytes.  The earliest stage
-    /// that can be invalidated by a config change is stage 2
-    /// (downsample).
+    /// Stage 0 (pending) has no config dependencies.  Stage 1 (decode)
+    /// depends only on `input_frame`; every other field invalidates
+    /// stage 2 (downsample) or later.
     ///
     /// Returns [`pipeline::STAGE_COUNT`](crate::pipeline::STAGE_COUNT)
     /// when the configs are pipeline-equivalent (identical modulo
@@ -708,16 +1262,22 @@ impl PipelineConfig {
     /// [`PipelineConfig`] without updating this method causes a compile
     /// error.
     #[must_use]
-    #[allow(clippy::float_cmp)]
+    #[allow(clippy::float_cmp, clippy::too_many_lines)]
     pub fn earliest_changed_stage(&self, other: &Self) -> usize {
         // Destructure to enforce compile-time coverage of all fields.
         let Self {
+            tone,
             blur_sigma,
+            smoothing,
+            smoothing_range,
             canny_low,
             canny_high,
             canny_max: _,
             contour_tracer,
+            simplifier,
             simplify_tolerance,
+            contour_filter,
+            curve,
             path_joiner,
             shape,
             zoom,
@@ -732,42 +1292,79 @@ impl PipelineConfig {
             parity_strategy,
             edge_channels,
             start_point,
+            perimeter_connectors,
+            perimeter_epsilon,
             subsample_max_length,
+            subsample_mode,
+            center_avoid_radius,
+            input_frame,
+            alpha_mode,
+            alpha_background,
         } = self;
 
-        // Stage 2 — downsample: working_resolution, downsample_filter
+        // Stage 1 — decode: input_frame
+        if *input_frame != other.input_frame {
+            return 1;
+        }
+
+        // Stage 2 — downsample: working_resolution, downsample_filter,
+        // alpha compositing.  alpha_background only matters when compositing.
+        let composite_relevant =
+            *alpha_mode == AlphaMode::Composite || other.alpha_mode == AlphaMode::Composite;
         if *working_resolution != other.working_resolution
             || *downsample_filter != other.downsample_filter
+            || (composite_relevant
+                && (*alpha_mode != other.alpha_mode || *alpha_background != other.alpha_background))
         {
             return 2;
         }
 
-        // Stage 3 — blur: blur_sigma
-        if *blur_sigma != other.blur_sigma {
+        // Stage 3 — tone: tone.  Each parameter only matters for the
+        // mappings that read it.
+        if !tone.output_eq(&other.tone) {
             return 3;
         }
 
-        // Stage 4 — edge detection: edge_channels, canny_low, canny_high, invert
+        // Stage 4 — blur: blur_sigma, smoothing.  smoothing_range only
+        // matters for strategies that read it.
+        let range_relevant = smoothing.uses_range() || other.smoothing.uses_range();
+        if *blur_sigma != other.blur_sigma
+            || *smoothing != other.smoothing
+            || (range_relevant && *smoothing_range != other.smoothing_range)
+        {
+            return 4;
+        }
+
+        // Stage 5 — edge detection: edge_channels, canny_low, canny_high, invert
         if *edge_channels != other.edge_channels
             || *canny_low != other.canny_low
             || *canny_high != other.canny_high
             || *invert != other.invert
         {
-            return 4;
-        }
-
-        // Stage 5 — contour tracing + normalization: contour_tracer, zoom
-        // Zoom is part of the normalization transform folded into this stage.
-        if *contour_tracer != other.contour_tracer || *zoom != other.zoom {
             return 5;
         }
 
-        // Stage 6 — simplification: simplify_tolerance
-        if *simplify_tolerance != other.simplify_tolerance {
+        // Stage 6 — contour tracing + normalization: contour_tracer, zoom,
+        // alpha masking.  Zoom is part of the normalization transform
+        // folded into this stage.
+        if *contour_tracer != other.contour_tracer
+            || *zoom != other.zoom
+            || *alpha_mode != other.alpha_mode
+        {
             return 6;
         }
 
-        // Stage 7 — canvas: shape, aspect_ratio, landscape, border_path, border_margin
+        // Stage 7 — simplification: simplifier, simplify_tolerance,
+        // contour_filter, curve
+        if *simplifier != other.simplifier
+            || *simplify_tolerance != other.simplify_tolerance
+            || *contour_filter != other.contour_filter
+            || !curve.output_eq(&other.curve)
+        {
+            return 7;
+        }
+
+        // Stage 8 — canvas: shape, aspect_ratio, landscape, border_path, border_margin
         // aspect_ratio and landscape only affect output in Rectangle mode.
         let rect_relevant =
             *shape == CanvasShape::Rectangle || other.shape == CanvasShape::Rectangle;
@@ -777,21 +1374,28 @@ impl PipelineConfig {
             || *border_path != other.border_path
             || *border_margin != other.border_margin
         {
-            return 7;
+            return 8;
         }
 
-        // Stage 8 — joining: path_joiner, mst_neighbours, parity_strategy, start_point
+        // Stage 9 — joining: path_joiner, mst_neighbours, parity_strategy,
+        // start_point, perimeter_connectors, perimeter_epsilon
         if *path_joiner != other.path_joiner
             || *mst_neighbours != other.mst_neighbours
             || *parity_strategy != other.parity_strategy
             || *start_point != other.start_point
+            || *perimeter_connectors != other.perimeter_connectors
+            || *perimeter_epsilon != other.perimeter_epsilon
         {
-            return 8;
+            return 9;
         }
 
-        // Stage 9 — subsampling: subsample_max_length
-        if *subsample_max_length != other.subsample_max_length {
-            return 9;
+        // Stage 10 — output: subsample_max_length, subsample_mode,
+        // center_avoid_radius
+        if *subsample_max_length != other.subsample_max_length
+            || *subsample_mode != other.subsample_mode
+            || *center_avoid_radius != other.center_avoid_radius
+        {
+            return 10;
         }
 
         // All pipeline-relevant fields match.
@@ -835,26 +1439,38 @@ pub struct StagedResult {
     pub original: RgbaImage,
     /// Stage 1: downsampled RGBA image (working resolution).
     pub downsampled: RgbaImage,
-    /// Stage 2: Gaussian-blurred RGBA image.
+    /// Stage 2: tone-mapped RGBA image (equal to `downsampled` when
+    /// [`ToneKind::Off`]).
+    pub toned: RgbaImage,
+    /// Stage 3: Gaussian-blurred RGBA image.
     pub blurred: RgbaImage,
-    /// Stages 3+4: Canny edge map (post-inversion when `invert=true`).
+    /// Stages 4+5: Canny edge map (post-inversion when `invert=true`).
     pub edges: GrayImage,
-    /// Stage 5: traced contour polylines.
+    /// Stage 6: traced contour polylines.
     pub contours: Vec<Polyline>,
-    /// Stage 6: RDP-simplified polylines.
+    /// Stage 7: RDP-simplified polylines.
     pub simplified: Vec<Polyline>,
-    /// Stage 7: canvas result.
+    /// Stage 8: canvas result.
     ///
     /// Contains the simplified polylines after clipping to the canvas
     /// boundary (with explicit per-endpoint clip metadata) and an
     /// optional border polyline matching the canvas shape.
     pub canvas: MaskResult,
-    /// Stage 8: joined single continuous path.
+    /// Stage 9: joined single continuous path.
     ///
     /// When masking is enabled, this is the join of the canvas polylines.
     /// When disabled, this is the join of the simplified polylines.
     pub joined: Polyline,
-    /// Stage 9: output path (the final output).
+    /// Provenance of each segment of [`joined`](Self::joined):
+    /// `joined_segments[i]` labels the segment from point `i` to point
+    /// `i + 1` as contour, connector, retrace, or border.  Empty for
+    /// results deserialized from data predating segment labels.
+    pub joined_segments: Vec<crate::SegmentKind>,
+    /// Number of connectors in [`joined`](Self::joined) rerouted along
+    /// the canvas border (zero when `perimeter_connectors` is
+    /// disabled).
+    pub perimeter_reroutes: usize,
+    /// Stage 10: output path (the final output).
     ///
     /// Long segments in the joined path are subdivided so no segment
     /// exceeds `config.subsample_max_length` normalized units. This
@@ -893,6 +1509,8 @@ impl StagedResult {
 struct StagedResultProxy {
     original: (u32, u32, Vec<u8>),
     downsampled: (u32, u32, Vec<u8>),
+    #[serde(default)]
+    toned: Option<(u32, u32, Vec<u8>)>,
     blurred: (u32, u32, Vec<u8>),
     edges: (u32, u32, Vec<u8>),
     contours: Vec<Polyline>,
@@ -900,6 +1518,10 @@ struct StagedResultProxy {
     canvas: MaskResult,
     joined: Polyline,
     #[serde(default)]
+    joined_segments: Vec<crate::SegmentKind>,
+    #[serde(default)]
+    perimeter_reroutes: usize,
+    #[serde(default)]
     output: Option<Polyline>,
     #[serde(default)]
     mst_edge_details: Vec<crate::MstEdgeInfo>,
@@ -919,6 +1541,11 @@ impl Serialize for StagedResult {
                 self.downsampled.height(),
                 self.downsampled.as_raw().clone(),
             ),
+            toned: Some((
+                self.toned.width(),
+                self.toned.height(),
+                self.toned.as_raw().clone(),
+            )),
             blurred: (
                 self.blurred.width(),
                 self.blurred.height(),
@@ -933,6 +1560,8 @@ impl Serialize for StagedResult {
             simplified: self.simplified.clone(),
             canvas: self.canvas.clone(),
             joined: self.joined.clone(),
+            joined_segments: self.joined_segments.clone(),
+            perimeter_reroutes: self.perimeter_reroutes,
             output: Some(self.output.clone()),
 
             mst_edge_details: self.mst_edge_details.clone(),
@@ -956,6 +1585,13 @@ impl<'de> Deserialize<'de> for StagedResult {
             proxy.downsampled.2,
         )
         .ok_or_else(|| serde::de::Error::custom("invalid downsampled image dimensions"))?;
+        // Backward-compatible: data predating the tone stage has no
+        // `toned` image; the stage was then an identity.
+        let toned = match proxy.toned {
+            Some((w, h, raw)) => RgbaImage::from_raw(w, h, raw)
+                .ok_or_else(|| serde::de::Error::custom("invalid toned image dimensions"))?,
+            None => downsampled.clone(),
+        };
         let blurred = RgbaImage::from_raw(proxy.blurred.0, proxy.blurred.1, proxy.blurred.2)
             .ok_or_else(|| serde::de::Error::custom("invalid blurred image dimensions"))?;
         let edges = GrayImage::from_raw(proxy.edges.0, proxy.edges.1, proxy.edges.2)
@@ -967,12 +1603,15 @@ impl<'de> Deserialize<'de> for StagedResult {
         Ok(Self {
             original,
             downsampled,
+            toned,
             blurred,
             edges,
             contours: proxy.contours,
             simplified: proxy.simplified,
             canvas: proxy.canvas,
             joined: proxy.joined,
+            joined_segments: proxy.joined_segments,
+            perimeter_reroutes: proxy.perimeter_reroutes,
             output,
             mst_edge_details: proxy.mst_edge_details,
             dimensions: proxy.dimensions,
@@ -999,9 +1638,15 @@ pub enum PipelineError {
     #[error("invalid pipeline configuration: {0}")]
     InvalidConfig(String),
 
-    /// Edge detection produced no contours.
+    /// Edge detection produced no contours, or the contour filter
+    /// dropped all of them.
     #[error("no contours found in the image")]
     NoContours,
+
+    /// The run was cancelled via its [`Progress`](crate::progress::Progress)
+    /// handle before it completed.
+    #[error("pipeline run was cancelled")]
+    Cancelled,
 }
 
 /// Serde-compatible proxy for `PipelineError`.
@@ -1016,6 +1661,7 @@ enum PipelineErrorProxy {
     EmptyInput,
     InvalidConfig(String),
     NoContours,
+    Cancelled,
 }
 
 impl Serialize for PipelineError {
@@ -1025,6 +1671,7 @@ impl Serialize for PipelineError {
             Self::EmptyInput => PipelineErrorProxy::EmptyInput,
             Self::InvalidConfig(s) => PipelineErrorProxy::InvalidConfig(s.clone()),
             Self::NoContours => PipelineErrorProxy::NoContours,
+            Self::Cancelled => PipelineErrorProxy::Cancelled,
         };
         proxy.serialize(serializer)
     }
@@ -1043,6 +1690,7 @@ impl<'de> Deserialize<'de> for PipelineError {
             PipelineErrorProxy::EmptyInput => Self::EmptyInput,
             PipelineErrorProxy::InvalidConfig(s) => Self::InvalidConfig(s),
             PipelineErrorProxy::NoContours => Self::NoContours,
+            PipelineErrorProxy::Cancelled => Self::Cancelled,
         })
     }
 }
@@ -1051,7 +1699,10 @@ impl<'de> Deserialize<'de> for PipelineError {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::curve::CurveKind;
     use crate::mask::MaskShape;
+    use crate::simplify::SimplifierKind;
+    use crate::tone::ToneKind;
 
     // --- Point tests ---
 
@@ -1131,6 +1782,49 @@ mod tests {
         assert_eq!(pl.points(), &points);
     }
 
+    #[test]
+    fn polyline_length_sums_segments() {
+        let pl = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(3.0, 4.0),
+            Point::new(3.0, 6.0),
+        ]);
+        assert!((pl.length() - 7.0).abs() < 1e-12);
+        assert!(Polyline::new(vec![Point::new(1.0, 1.0)]).length().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn polyline_prefix_interpolates_by_arc_length() {
+        let pl = Polyline::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 4.0),
+        ]);
+
+        let half = pl.prefix(0.5);
+        assert_eq!(half.points(), [Point::new(0.0, 0.0), Point::new(4.0, 0.0)]);
+
+        let three_quarters = pl.prefix(0.75);
+        assert_eq!(three_quarters.len(), 3);
+        let end = *three_quarters.last().unwrap();
+        assert!(end.distance(Point::new(4.0, 2.0)) < 1e-12);
+
+        let quarter = pl.prefix(0.25);
+        assert_eq!(quarter.len(), 2);
+        assert!(quarter.points()[1].distance(Point::new(2.0, 0.0)) < 1e-12);
+    }
+
+    #[test]
+    fn polyline_prefix_clamps_and_handles_degenerate_paths() {
+        let pl = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]);
+        assert_eq!(pl.prefix(-1.0).points(), [Point::new(0.0, 0.0)]);
+        assert_eq!(pl.prefix(2.0), pl);
+        assert!(Polyline::new(Vec::new()).prefix(0.5).is_empty());
+
+        let still = Polyline::new(vec![Point::new(1.0, 1.0), Point::new(1.0, 1.0)]);
+        assert_eq!(still.prefix(0.5).points(), [Point::new(1.0, 1.0)]);
+    }
+
     #[test]
     fn polyline_into_points_returns_owned_vec() {
         let points = vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)];
@@ -1201,6 +1895,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pipeline_config_default_subsample_mode_is_cartesian() {
+        let config = PipelineConfig::default();
+        assert_eq!(config.subsample_mode, SubsampleMode::Cartesian);
+        assert!(config.center_avoid_radius.abs() < f64::EPSILON);
+        assert!(!config.perimeter_connectors);
+        assert!((config.perimeter_epsilon - 0.02).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn pipeline_eq_detects_perimeter_change() {
+        let a = PipelineConfig::default();
+
+        let mut b = a.clone();
+        b.perimeter_connectors = true;
+        assert!(
+            !a.pipeline_eq(&b),
+            "perimeter_connectors change should be detected"
+        );
+
+        let mut b = a.clone();
+        b.perimeter_epsilon = 0.05;
+        assert!(
+            !a.pipeline_eq(&b),
+            "perimeter_epsilon change should be detected"
+        );
+    }
+
     #[test]
     fn pipeline_eq_ignores_canny_max() {
         let a = PipelineConfig::default();
@@ -1213,6 +1935,127 @@ mod tests {
         assert!(a.pipeline_eq(&b));
     }
 
+    #[test]
+    fn pipeline_config_default_simplifier_is_rdp() {
+        assert_eq!(PipelineConfig::default().simplifier, SimplifierKind::Rdp);
+    }
+
+    #[test]
+    fn pipeline_config_default_smoothing_is_gaussian() {
+        let config = PipelineConfig::default();
+        assert_eq!(config.smoothing, SmoothingKind::Gaussian);
+        assert!((config.smoothing_range - 25.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn pipeline_config_default_tone_is_off() {
+        let config = PipelineConfig::default();
+        assert_eq!(config.tone.kind, ToneKind::Off);
+        assert_eq!(config.tone, ToneSettings::default());
+    }
+
+    #[test]
+    fn pipeline_eq_detects_tone_change() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.tone.kind = ToneKind::Equalize;
+        assert!(!a.pipeline_eq(&b), "tone change should be detected");
+    }
+
+    #[test]
+    fn pipeline_eq_ignores_tone_parameters_the_kind_does_not_read() {
+        let a = PipelineConfig {
+            tone: ToneSettings {
+                kind: ToneKind::Gamma,
+                ..ToneSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let mut b = a.clone();
+        b.tone.clip_limit += 1.0;
+        b.tone.tile_size *= 2;
+        assert!(a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), crate::pipeline::STAGE_COUNT);
+
+        b.tone.gamma += 0.1;
+        assert!(!a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), 3);
+    }
+
+    #[test]
+    fn pipeline_eq_detects_contour_filter_change() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.contour_filter.min_length = 0.01;
+        assert!(
+            !a.pipeline_eq(&b),
+            "contour_filter change should be detected"
+        );
+    }
+
+    #[test]
+    fn pipeline_eq_detects_simplifier_change() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.simplifier = SimplifierKind::VisvalingamWhyatt;
+        assert!(!a.pipeline_eq(&b), "simplifier change should be detected");
+    }
+
+    #[test]
+    fn pipeline_eq_detects_curve_change() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.curve.kind = CurveKind::Chaikin;
+        assert!(!a.pipeline_eq(&b), "curve change should be detected");
+    }
+
+    #[test]
+    fn pipeline_eq_ignores_curve_parameters_the_kind_does_not_read() {
+        let a = PipelineConfig {
+            curve: CurveSettings {
+                kind: CurveKind::CatmullRom,
+                ..CurveSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let mut b = a.clone();
+        b.curve.tolerance *= 2.0;
+        b.curve.iterations += 1;
+        assert!(a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), crate::pipeline::STAGE_COUNT);
+
+        b.curve.spacing *= 2.0;
+        assert!(!a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), 7);
+    }
+
+    #[test]
+    fn pipeline_eq_detects_smoothing_change() {
+        let a = PipelineConfig::default();
+
+        let mut b = a.clone();
+        b.smoothing = SmoothingKind::Median;
+        assert!(!a.pipeline_eq(&b), "smoothing change should be detected");
+    }
+
+    #[test]
+    fn pipeline_eq_ignores_smoothing_range_unless_used() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.smoothing_range += 1.0;
+        assert!(a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), crate::pipeline::STAGE_COUNT);
+
+        let a = PipelineConfig {
+            smoothing: SmoothingKind::Bilateral,
+            ..PipelineConfig::default()
+        };
+        let mut b = a.clone();
+        b.smoothing_range += 1.0;
+        assert!(!a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), 4);
+    }
+
     #[test]
     fn pipeline_eq_detects_processing_field_change() {
         let a = PipelineConfig::default();
@@ -1319,6 +2162,46 @@ mod tests {
         let mut b = a.clone();
         b.start_point = StartPointStrategy::Inside;
         assert!(!a.pipeline_eq(&b), "start_point change should be detected");
+
+        let mut b = a.clone();
+        b.subsample_mode = SubsampleMode::Polar;
+        assert!(
+            !a.pipeline_eq(&b),
+            "subsample_mode change should be detected"
+        );
+
+        let mut b = a.clone();
+        b.center_avoid_radius = 0.05;
+        assert!(
+            !a.pipeline_eq(&b),
+            "center_avoid_radius change should be detected"
+        );
+
+        let mut b = a.clone();
+        b.input_frame = 1;
+        assert!(!a.pipeline_eq(&b), "input_frame change should be detected");
+
+        let mut b = a.clone();
+        b.alpha_mode = AlphaMode::Mask;
+        assert!(!a.pipeline_eq(&b), "alpha_mode change should be detected");
+    }
+
+    #[test]
+    fn pipeline_eq_ignores_alpha_background_unless_compositing() {
+        let a = PipelineConfig::default();
+        let mut b = a.clone();
+        b.alpha_background = [1, 2, 3];
+        assert!(a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), crate::pipeline::STAGE_COUNT);
+
+        let a = PipelineConfig {
+            alpha_mode: AlphaMode::Composite,
+            ..PipelineConfig::default()
+        };
+        let mut b = a.clone();
+        b.alpha_background = [1, 2, 3];
+        assert!(!a.pipeline_eq(&b));
+        assert_eq!(a.earliest_changed_stage(&b), 2);
     }
 
     #[test]
@@ -1326,6 +2209,49 @@ mod tests {
         PipelineConfig::default().validate().unwrap();
     }
 
+    #[test]
+    fn validate_rejects_out_of_range_channel_weight() {
+        for weight in [0.0, -1.0, EdgeChannels::MAX_WEIGHT + 0.5, f32::NAN] {
+            let config = PipelineConfig {
+                edge_channels: EdgeChannels {
+                    weights: BTreeMap::from([(EdgeChannel::LabA, weight)]),
+                    ..EdgeChannels::default()
+                },
+                ..PipelineConfig::default()
+            };
+            let err = config.validate().unwrap_err();
+            assert!(
+                matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("a* edge channel weight")),
+                "expected InvalidConfig about the a* weight, got {err:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_channel_thresholds() {
+        for (low, high, expected) in [
+            (0.5, 40.0, "low threshold must be at least"),
+            (50.0, 40.0, "must not exceed high threshold"),
+            (15.0, 1000.0, "must not exceed canny_max"),
+        ] {
+            let config = PipelineConfig {
+                edge_channels: EdgeChannels {
+                    thresholds: BTreeMap::from([(
+                        EdgeChannel::Blue,
+                        CannyThresholds { low, high },
+                    )]),
+                    ..EdgeChannels::default()
+                },
+                ..PipelineConfig::default()
+            };
+            let err = config.validate().unwrap_err();
+            assert!(
+                matches!(err, PipelineError::InvalidConfig(ref s) if s.starts_with("Blue edge channel") && s.contains(expected)),
+                "expected InvalidConfig containing {expected:?}, got {err:?}",
+            );
+        }
+    }
+
     #[test]
     fn validate_rejects_no_edge_channels() {
         let config = PipelineConfig {
@@ -1335,6 +2261,15 @@ mod tests {
                 green: false,
                 blue: false,
                 saturation: false,
+                alpha: false,
+                hue: false,
+                value: false,
+                lightness: false,
+                lab_a: false,
+                lab_b: false,
+                weights: BTreeMap::new(),
+                thresholds: BTreeMap::new(),
+                fusion: EdgeFusion::Max,
             },
             ..PipelineConfig::default()
         };
@@ -1443,6 +2378,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_rejects_center_avoid_radius_out_of_range() {
+        for radius in [-0.01, 0.51] {
+            let config = PipelineConfig {
+                center_avoid_radius: radius,
+                ..PipelineConfig::default()
+            };
+            let err = config.validate().unwrap_err();
+            assert!(
+                matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("center_avoid_radius")),
+                "expected InvalidConfig about center_avoid_radius, got {err:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn validate_limits_blur_sigma_per_smoothing_kind() {
+        for smoothing in [
+            SmoothingKind::Gaussian,
+            SmoothingKind::Bilateral,
+            SmoothingKind::Median,
+            SmoothingKind::PeronaMalik,
+        ] {
+            let at_max = PipelineConfig {
+                smoothing,
+                blur_sigma: smoothing.max_sigma(),
+                ..PipelineConfig::default()
+            };
+            at_max.validate().unwrap();
+            let err = PipelineConfig {
+                blur_sigma: smoothing.max_sigma() + 0.5,
+                ..at_max
+            }
+            .validate()
+            .unwrap_err();
+            assert!(
+                matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("blur_sigma")),
+                "expected InvalidConfig about blur_sigma, got {err:?}",
+            );
+        }
+        assert!(SmoothingKind::Bilateral.max_sigma() < SmoothingKind::Gaussian.max_sigma());
+    }
+
     // --- PipelineError tests ---
 
     #[test]
@@ -1466,6 +2444,12 @@ mod tests {
         assert_eq!(err.to_string(), "no contours found in the image");
     }
 
+    #[test]
+    fn error_cancelled_display() {
+        let err = PipelineError::Cancelled;
+        assert_eq!(err.to_string(), "pipeline run was cancelled");
+    }
+
     // --- Serde round-trip tests ---
 
     #[test]
@@ -1502,12 +2486,32 @@ mod tests {
     #[test]
     fn pipeline_config_serde_round_trip() {
         let config = PipelineConfig {
+            tone: ToneSettings {
+                kind: ToneKind::Clahe,
+                tile_size: 64,
+                clip_limit: 3.5,
+                gamma: 0.8,
+            },
             blur_sigma: 2.0,
+            smoothing: SmoothingKind::PeronaMalik,
+            smoothing_range: 12.5,
             canny_low: 30.0,
             canny_high: 120.0,
             canny_max: 200.0,
             contour_tracer: ContourTracerKind::BorderFollowing,
+            simplifier: SimplifierKind::TopologyPreserving,
             simplify_tolerance: 1.5,
+            contour_filter: ContourFilter {
+                min_length: 0.02,
+                min_area: 0.0001,
+                keep_near: 0.01,
+            },
+            curve: CurveSettings {
+                kind: CurveKind::Bezier,
+                iterations: 3,
+                spacing: 0.01,
+                tolerance: 0.004,
+            },
             path_joiner: PathJoinerKind::Retrace,
             shape: CanvasShape::Rectangle,
             zoom: 0.85,
@@ -1526,9 +2530,31 @@ mod tests {
                 green: false,
                 blue: false,
                 saturation: true,
+                alpha: true,
+                hue: true,
+                value: true,
+                lightness: true,
+                lab_a: true,
+                lab_b: true,
+                weights: BTreeMap::from([(EdgeChannel::Hue, 2.0)]),
+                thresholds: BTreeMap::from([(
+                    EdgeChannel::Saturation,
+                    CannyThresholds {
+                        low: 30.0,
+                        high: 90.0,
+                    },
+                )]),
+                fusion: EdgeFusion::WeightedSum,
             },
             start_point: StartPointStrategy::Inside,
+            perimeter_connectors: true,
+            perimeter_epsilon: 0.05,
             subsample_max_length: 3.5,
+            subsample_mode: SubsampleMode::Polar,
+            center_avoid_radius: 0.05,
+            input_frame: 3,
+            alpha_mode: AlphaMode::Composite,
+            alpha_background: [12, 34, 56],
         };
         let json = serde_json::to_string(&config).unwrap();
         let deserialized: PipelineConfig = serde_json::from_str(&json).unwrap();
@@ -1558,6 +2584,7 @@ mod tests {
         }"#;
         let config: PipelineConfig = serde_json::from_str(json).unwrap();
         assert_eq!(config.edge_channels, EdgeChannels::default());
+        assert_eq!(config.simplifier, SimplifierKind::Rdp);
         assert!(config.edge_channels.luminance);
         assert!(!config.edge_channels.red);
         // Also verifies parity_strategy defaults when absent.
@@ -1567,6 +2594,14 @@ mod tests {
         );
         // Also verifies start_point defaults when absent.
         assert_eq!(config.start_point, StartPointStrategy::Outside);
+        // Also verifies subsample_mode defaults when absent.
+        assert_eq!(config.subsample_mode, SubsampleMode::Cartesian);
+        // Also verifies perimeter routing defaults when absent.
+        assert!(!config.perimeter_connectors);
+        assert!(
+            (config.perimeter_epsilon - PipelineConfig::DEFAULT_PERIMETER_EPSILON).abs()
+                < f64::EPSILON
+        );
     }
 
     #[test]
@@ -1614,6 +2649,7 @@ mod tests {
         let staged = StagedResult {
             original: RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 255])),
             downsampled: RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 255])),
+            toned: RgbaImage::from_pixel(2, 2, image::Rgba([20, 40, 60, 255])),
             blurred: RgbaImage::from_pixel(2, 2, image::Rgba([40, 45, 50, 255])),
             edges: GrayImage::from_pixel(2, 2, image::Luma([255])),
             contours: vec![Polyline::new(vec![
@@ -1633,6 +2669,8 @@ mod tests {
                 },
             },
             joined: Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]),
+            joined_segments: vec![crate::SegmentKind::Contour],
+            perimeter_reroutes: 1,
             output: Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]),
             mst_edge_details: vec![],
             dimensions: Dimensions {
@@ -1654,6 +2692,7 @@ mod tests {
             staged.downsampled.as_raw(),
             deserialized.downsampled.as_raw()
         );
+        assert_eq!(staged.toned.as_raw(), deserialized.toned.as_raw());
         assert_eq!(staged.blurred.as_raw(), deserialized.blurred.as_raw());
         assert_eq!(staged.edges.as_raw(), deserialized.edges.as_raw());
 
@@ -1663,6 +2702,8 @@ mod tests {
         assert_eq!(staged.canvas, deserialized.canvas);
         assert_eq!(staged.joined, deserialized.joined);
         assert_eq!(staged.output, deserialized.output);
+        assert_eq!(staged.joined_segments, deserialized.joined_segments);
+        assert_eq!(staged.perimeter_reroutes, deserialized.perimeter_reroutes);
         assert_eq!(staged.mst_edge_details, deserialized.mst_edge_details);
         assert_eq!(staged.dimensions, deserialized.dimensions);
     }
@@ -1683,6 +2724,14 @@ mod tests {
         assert!(matches!(deserialized, PipelineError::NoContours));
     }
 
+    #[test]
+    fn pipeline_error_serde_round_trip_cancelled() {
+        let err = PipelineError::Cancelled;
+        let json = serde_json::to_string(&err).unwrap();
+        let deserialized: PipelineError = serde_json::from_str(&json).unwrap();
+        assert!(matches!(deserialized, PipelineError::Cancelled));
+    }
+
     #[test]
     fn pipeline_error_serde_round_trip_invalid_config() {
         let err = PipelineError::InvalidConfig("bad value".to_string());
@@ -1698,6 +2747,7 @@ mod tests {
         let staged = StagedResult {
             original: RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
             downsampled: RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
+            toned: RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
             blurred: RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
             edges: GrayImage::from_pixel(1, 1, image::Luma([0])),
             contours: vec![],
@@ -1711,6 +2761,8 @@ mod tests {
                 },
             },
             joined: Polyline::new(vec![]),
+            joined_segments: vec![],
+            perimeter_reroutes: 0,
             output: Polyline::new(vec![]),
             mst_edge_details: vec![],
             dimensions: Dimensions {
@@ -1780,7 +2832,7 @@ mod tests {
             blur_sigma: 3.0,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 3);
+        assert_eq!(a.earliest_changed_stage(&b), 4);
     }
 
     #[test]
@@ -1790,7 +2842,7 @@ mod tests {
             canny_low: 20.0,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 4);
+        assert_eq!(a.earliest_changed_stage(&b), 5);
     }
 
     #[test]
@@ -1800,7 +2852,7 @@ mod tests {
             canny_high: 50.0,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 4);
+        assert_eq!(a.earliest_changed_stage(&b), 5);
     }
 
     #[test]
@@ -1810,7 +2862,7 @@ mod tests {
             invert: true,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 4);
+        assert_eq!(a.earliest_changed_stage(&b), 5);
     }
 
     #[test]
@@ -1824,7 +2876,7 @@ mod tests {
             },
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 4);
+        assert_eq!(a.earliest_changed_stage(&b), 5);
     }
 
     #[test]
@@ -1834,7 +2886,49 @@ mod tests {
             simplify_tolerance: 5.0,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 6);
+        assert_eq!(a.earliest_changed_stage(&b), 7);
+    }
+
+    #[test]
+    fn earliest_changed_stage_simplifier() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            simplifier: SimplifierKind::TopologyPreserving,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 7);
+    }
+
+    #[test]
+    fn earliest_changed_stage_contour_filter() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            contour_filter: ContourFilter {
+                min_area: 0.001,
+                ..ContourFilter::default()
+            },
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 7);
+    }
+
+    #[test]
+    fn earliest_changed_stage_curve() {
+        let a = PipelineConfig {
+            curve: CurveSettings {
+                kind: CurveKind::CatmullRom,
+                ..CurveSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+
+        // Parameters of other curve kinds are ignored.
+        let mut b = a.clone();
+        b.curve.tolerance *= 2.0;
+        assert_eq!(a.earliest_changed_stage(&b), crate::pipeline::STAGE_COUNT);
+
+        b.curve.spacing *= 2.0;
+        assert_eq!(a.earliest_changed_stage(&b), 7);
     }
 
     #[test]
@@ -1844,7 +2938,7 @@ mod tests {
             shape: CanvasShape::Rectangle,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 7);
+        assert_eq!(a.earliest_changed_stage(&b), 8);
     }
 
     #[test]
@@ -1855,7 +2949,7 @@ mod tests {
             ..PipelineConfig::default()
         };
         // Zoom is folded into stage 5 (contour tracing + normalization).
-        assert_eq!(a.earliest_changed_stage(&b), 5);
+        assert_eq!(a.earliest_changed_stage(&b), 6);
     }
 
     #[test]
@@ -1865,7 +2959,7 @@ mod tests {
             path_joiner: crate::PathJoinerKind::StraightLine,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 8);
+        assert_eq!(a.earliest_changed_stage(&b), 9);
     }
 
     #[test]
@@ -1875,7 +2969,7 @@ mod tests {
             mst_neighbours: 50,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 8);
+        assert_eq!(a.earliest_changed_stage(&b), 9);
     }
 
     #[test]
@@ -1885,7 +2979,7 @@ mod tests {
             parity_strategy: crate::mst_join::ParityStrategy::Optimal,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 8);
+        assert_eq!(a.earliest_changed_stage(&b), 9);
     }
 
     #[test]
@@ -1895,20 +2989,226 @@ mod tests {
             start_point: StartPointStrategy::Inside,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 8);
+        assert_eq!(a.earliest_changed_stage(&b), 9);
+    }
+
+    #[test]
+    fn earliest_changed_stage_perimeter_connectors() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            perimeter_connectors: true,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 9);
+    }
+
+    #[test]
+    fn earliest_changed_stage_subsample_mode() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            subsample_mode: SubsampleMode::Polar,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 10);
+    }
+
+    #[test]
+    fn earliest_changed_stage_center_avoid_radius() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            center_avoid_radius: 0.05,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 10);
+    }
+
+    #[test]
+    fn earliest_changed_stage_input_frame() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            input_frame: 2,
+            blur_sigma: 3.0,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 1);
+    }
+
+    #[test]
+    fn earliest_changed_stage_alpha_composite() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            alpha_mode: AlphaMode::Composite,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 2);
+
+        let c = PipelineConfig {
+            alpha_background: [0, 0, 0],
+            ..b.clone()
+        };
+        assert_eq!(b.earliest_changed_stage(&c), 2);
+    }
+
+    #[test]
+    fn earliest_changed_stage_alpha_mask() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            alpha_mode: AlphaMode::Mask,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 6);
+
+        // The background color is unused outside Composite mode.
+        let c = PipelineConfig {
+            alpha_background: [0, 0, 0],
+            ..b.clone()
+        };
+        assert_eq!(b.earliest_changed_stage(&c), crate::pipeline::STAGE_COUNT);
+    }
+
+    #[test]
+    fn earliest_changed_stage_smoothing() {
+        let a = PipelineConfig::default();
+        let b = PipelineConfig {
+            smoothing: SmoothingKind::Bilateral,
+            ..PipelineConfig::default()
+        };
+        assert_eq!(a.earliest_changed_stage(&b), 4);
+
+        let c = PipelineConfig {
+            smoothing_range: 10.0,
+            ..b.clone()
+        };
+        assert_eq!(b.earliest_changed_stage(&c), 4);
+
+        // The range is unused by Gaussian blur.
+        let d = PipelineConfig {
+            smoothing_range: 10.0,
+            ..a.clone()
+        };
+        assert_eq!(a.earliest_changed_stage(&d), crate::pipeline::STAGE_COUNT);
+    }
+
+    #[test]
+    fn earliest_changed_stage_tone() {
+        let off = PipelineConfig::default();
+        let clahe = PipelineConfig {
+            tone: ToneSettings {
+                kind: ToneKind::Clahe,
+                ..ToneSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        assert_eq!(off.earliest_changed_stage(&clahe), 3);
+
+        let clipped = PipelineConfig {
+            tone: ToneSettings {
+                clip_limit: 4.0,
+                ..clahe.tone.clone()
+            },
+            ..clahe.clone()
+        };
+        assert_eq!(clahe.earliest_changed_stage(&clipped), 3);
+
+        // Gamma is unused by CLAHE, and nothing is read when off.
+        let gamma = PipelineConfig {
+            tone: ToneSettings {
+                gamma: 2.0,
+                ..clahe.tone.clone()
+            },
+            ..clahe.clone()
+        };
+        assert_eq!(
+            clahe.earliest_changed_stage(&gamma),
+            crate::pipeline::STAGE_COUNT
+        );
+        let tiled = PipelineConfig {
+            tone: ToneSettings {
+                tile_size: 32,
+                ..ToneSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        assert_eq!(
+            off.earliest_changed_stage(&tiled),
+            crate::pipeline::STAGE_COUNT
+        );
+    }
+
+    #[test]
+    fn validate_rejects_invalid_tone_settings() {
+        let config = PipelineConfig {
+            tone: ToneSettings {
+                clip_limit: 0.5,
+                ..ToneSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(
+            matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("clip_limit")),
+            "expected InvalidConfig about clip_limit, got {err:?}",
+        );
+    }
+
+    #[test]
+    fn validate_rejects_negative_contour_filter_threshold() {
+        let config = PipelineConfig {
+            contour_filter: ContourFilter {
+                keep_near: -1.0,
+                ..ContourFilter::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(
+            matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("contour_filter.keep_near")),
+            "expected InvalidConfig about contour_filter.keep_near, got {err:?}",
+        );
+    }
+
+    #[test]
+    fn validate_rejects_invalid_curve_settings() {
+        let config = PipelineConfig {
+            curve: CurveSettings {
+                iterations: 0,
+                ..CurveSettings::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(
+            matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("curve iterations")),
+            "expected InvalidConfig about curve iterations, got {err:?}",
+        );
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_smoothing_range() {
+        for range in [0.0, -1.0, f32::NAN] {
+            let config = PipelineConfig {
+                smoothing_range: range,
+                ..PipelineConfig::default()
+            };
+            let err = config.validate().unwrap_err();
+            assert!(
+                matches!(err, PipelineError::InvalidConfig(ref s) if s.contains("smoothing_range")),
+                "expected InvalidConfig about smoothing_range, got {err:?}",
+            );
+        }
     }
 
     #[test]
     fn earliest_changed_stage_returns_earliest() {
-        // When both blur_sigma (stage 3) and zoom (stage 5)
-        // change, the earliest stage should be 3.
+        // When both blur_sigma (stage 4) and zoom (stage 6)
+        // change, the earliest stage should be 4.
         let a = PipelineConfig::default();
         let b = PipelineConfig {
             blur_sigma: 5.0,
             zoom: 1.2,
             ..PipelineConfig::default()
         };
-        assert_eq!(a.earliest_changed_stage(&b), 3);
+        assert_eq!(a.earliest_changed_stage(&b), 4);
     }
 
     #[test]
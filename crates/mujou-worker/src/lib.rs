@@ -17,15 +17,23 @@
 //! The worker retains a [`PipelineCache`] from the last successful run.
 //! On subsequent runs with the same image, only stages affected by
 //! config changes (and their downstream dependents) are re-executed.
-//! When the worker is terminated and recreated (cancel), the cache is
-//! lost and the next run is a full pipeline execution.
+//!
+//! # Cancellation
+//!
+//! When the main thread shares a `cancelFlag` (an `Int32Array` over a
+//! `SharedArrayBuffer`), cancellation is cooperative: the pipeline
+//! polls the flag at checkpoints inside long stages and returns
+//! `PipelineError::Cancelled`, leaving the cache intact.  Without a
+//! shared flag the main thread terminates the worker and spawns a
+//! fresh one, losing the cache.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use image::ImageEncoder;
 use mujou_pipeline::pipeline::STAGE_COUNT;
 use mujou_pipeline::{
-    Dimensions, GrayImage, MaskResult, MstEdgeInfo, PipelineCache, Polyline, StagedResult,
+    Dimensions, GrayImage, MaskResult, MstEdgeInfo, PipelineCache, PipelineError, Polyline,
+    Progress, SegmentKind, StagedResult, ToneKind,
 };
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsCast;
@@ -34,9 +42,8 @@ use wasm_bindgen::prelude::*;
 thread_local! {
     /// Cached pipeline state from the last successful run.
     ///
-    /// Populated after each successful pipeline execution and consumed
-    /// (taken) at the start of the next run.  Lost when the worker is
-    /// terminated.
+    /// Updated after each successful pipeline execution and kept when
+    /// a run is cancelled.
     static PIPELINE_CACHE: RefCell<Option<PipelineCache>> = const { RefCell::new(None) };
 }
 
@@ -49,6 +56,7 @@ pub struct VectorResult {
     pub canvas: MaskResult,
     pub joined: Polyline,
     pub output: Polyline,
+    pub joined_segments: Vec<SegmentKind>,
     pub mst_edge_details: Vec<MstEdgeInfo>,
     pub dimensions: Dimensions,
 }
@@ -59,6 +67,9 @@ pub struct VectorResult {
 /// - `generation`: `f64` generation counter (passed through to response)
 /// - `lightBg`, `lightFg`: `String` — hex RGB for light theme (e.g. "f5f5f5,1a1a1a")
 /// - `darkBg`, `darkFg`: `String` — hex RGB for dark theme
+/// - `cancelFlag` (optional): `Int32Array` over a `SharedArrayBuffer`
+///   holding the highest cancelled generation; the run aborts once it
+///   is at least `generation`
 ///
 /// The worker responds with three types of messages, distinguished by a
 /// `type` field:
@@ -69,12 +80,19 @@ pub struct VectorResult {
 /// - `stageIndex`: `f64` — the 0-based index of the stage just reached
 /// - `stageCount`: `f64` — total number of pipeline stages
 ///
+/// **Stage fraction** (sent from checkpoints inside long stages):
+/// - `type`: `"fraction"`
+/// - `generation`: `f64` matching the request generation
+/// - `fraction`: `f64` — fraction of the running stage completed
+///
 /// **Success** (sent once when the pipeline completes):
 /// - `generation`: `f64` matching the request generation
 /// - `ok`: `true`
 /// - `vectorJson`: `String` — JSON-serialized `VectorResult`
 /// - `originalPng`: `Uint8Array` — pre-encoded RGBA PNG
 /// - `downsampledPng`: `Uint8Array` — pre-encoded RGBA PNG (working resolution)
+/// - `tonedPng` (absent when tone mapping is off): `Uint8Array` —
+///   pre-encoded RGBA PNG (tone-mapped)
 /// - `blurredPng`: `Uint8Array` — pre-encoded RGBA PNG (blurred)
 /// - `edgesLightPng`: `Uint8Array` — themed edge PNG (light mode)
 /// - `edgesDarkPng`: `Uint8Array` — themed edge PNG (dark mode)
@@ -83,6 +101,7 @@ pub struct VectorResult {
 /// - `generation`: `f64`
 /// - `ok`: `false`
 /// - `errorJson`: `String` — JSON-serialized `PipelineError`
+///   (`Cancelled` when the run was aborted via `cancelFlag`)
 ///
 /// # Worker entry point
 ///
@@ -160,34 +179,62 @@ fn handle_message(event: web_sys::MessageEvent) {
         }
     };
     log("worker: config parsed, running pipeline");
+    // With tone mapping off the toned image is the downsampled one.
+    let tone_off = config.tone.kind == ToneKind::Off;
 
-    // Take the cache from the previous run (if any).
-    let prev_cache = PIPELINE_CACHE.with(|c| c.borrow_mut().take());
+    let cancel_flag = js_sys::Reflect::get(&data, &JsValue::from_str("cancelFlag"))
+        .ok()
+        .and_then(|v| v.dyn_into::<js_sys::Int32Array>().ok());
+    let is_cancelled = || {
+        cancel_flag.as_ref().is_some_and(|flag| {
+            js_sys::Atomics::load(flag, 0).is_ok_and(|v| f64::from(v) >= generation)
+        })
+    };
+    // Throttle fraction messages to whole-percent steps.
+    let last_fraction = Cell::new(f64::NEG_INFINITY);
+    let on_fraction = |fraction: f64| {
+        if (fraction - last_fraction.get()).abs() >= 0.01 {
+            last_fraction.set(fraction);
+            post_fraction(generation, fraction);
+        }
+    };
+    let progress = Progress::new(&is_cancelled, &on_fraction);
 
     // Run the pipeline with caching — only changed stages are re-executed
     // when the image is the same as the previous run.  The on_stage
     // callback posts per-stage progress so the main thread can update
     // its timing display.
     let on_stage = |index: usize, cached: bool| {
+        last_fraction.set(f64::NEG_INFINITY);
         post_progress(generation, index, STAGE_COUNT, cached);
     };
-    let outcome = PipelineCache::run(prev_cache, image_bytes, config, &on_stage);
+    let outcome = PIPELINE_CACHE.with(|c| {
+        PipelineCache::run_with_progress(
+            &mut c.borrow_mut(),
+            image_bytes,
+            config,
+            &on_stage,
+            &progress,
+        )
+    });
 
     match outcome {
-        Ok((staged, new_cache)) => {
-            // Store the updated cache for the next run.
-            PIPELINE_CACHE.with(|c| *c.borrow_mut() = Some(new_cache));
-
+        Ok(staged) => {
             log(&format!(
                 "worker: pipeline ok, {}x{}, encoding PNGs",
                 staged.dimensions.width, staged.dimensions.height,
             ));
-            post_success_response(generation, &staged, light_bg, light_fg, dark_bg, dark_fg);
+            post_success_response(
+                generation, &staged, tone_off, light_bg, light_fg, dark_bg, dark_fg,
+            );
             log("worker: response posted");
         }
         Err(e) => {
-            // Clear cache on error to avoid stale state.
-            PIPELINE_CACHE.with(|c| *c.borrow_mut() = None);
+            // A cancelled run leaves the previous cache usable; any
+            // other error clears it to avoid stale state.
+            if !matches!(e, PipelineError::Cancelled) {
+                PIPELINE_CACHE.with(|c| *c.borrow_mut() = None);
+            }
 
             log(&format!("worker: pipeline error: {e}"));
             let error_json = serde_json::to_string(&e).unwrap_or_else(|ser_err| {
@@ -226,12 +273,15 @@ fn get_rgb(data: &JsValue, key: &str) -> [u8; 3] {
 /// Post a successful pipeline result back to the main thread.
 ///
 /// All raster images are pre-encoded as PNG bytes so the main thread
-/// only needs to create Blob URLs (near-instant).
+/// only needs to create Blob URLs (near-instant).  When `tone_off`,
+/// the toned image is skipped; the main thread reuses the downsampled
+/// one.
 #[allow(clippy::expect_used)]
 #[allow(clippy::similar_names)]
 fn post_success_response(
     generation: f64,
     staged: &StagedResult,
+    tone_off: bool,
     light_bg: [u8; 3],
     light_fg: [u8; 3],
     dark_bg: [u8; 3],
@@ -243,6 +293,7 @@ fn post_success_response(
         canvas: staged.canvas.clone(),
         joined: staged.joined.clone(),
         output: staged.output.clone(),
+        joined_segments: staged.joined_segments.clone(),
         mst_edge_details: staged.mst_edge_details.clone(),
         dimensions: staged.dimensions,
     };
@@ -269,6 +320,11 @@ fn post_success_response(
     }
     let original_png = encode_or_error!(encode_rgba_png(&staged.original));
     let downsampled_png = encode_or_error!(encode_rgba_png(&staged.downsampled));
+    let toned_png = if tone_off {
+        None
+    } else {
+        Some(encode_or_error!(encode_rgba_png(&staged.toned)))
+    };
     let blurred_png = encode_or_error!(encode_rgba_png(&staged.blurred));
     // Dilate once — both themes use the same dilated edge image.
     let dilated_edges = dilate_soft(&staged.edges);
@@ -296,6 +352,9 @@ fn post_success_response(
         "downsampledPng",
         &js_sys::Uint8Array::from(downsampled_png.as_slice()),
     );
+    if let Some(toned_png) = toned_png {
+        set("tonedPng", &js_sys::Uint8Array::from(toned_png.as_slice()));
+    }
     set(
         "blurredPng",
         &js_sys::Uint8Array::from(blurred_png.as_slice()),
@@ -435,6 +494,30 @@ fn post_progress(generation: f64, stage_index: usize, stage_count: usize, cached
     }
 }
 
+/// Post an intra-stage fraction message back to the main thread.
+fn post_fraction(generation: f64, fraction: f64) {
+    let msg = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(
+        &msg,
+        &JsValue::from_str("type"),
+        &JsValue::from_str("fraction"),
+    );
+    let _ = js_sys::Reflect::set(
+        &msg,
+        &JsValue::from_str("generation"),
+        &JsValue::from_f64(generation),
+    );
+    let _ = js_sys::Reflect::set(
+        &msg,
+        &JsValue::from_str("fraction"),
+        &JsValue::from_f64(fraction),
+    );
+
+    if let Ok(global) = js_sys::global().dyn_into::<web_sys::DedicatedWorkerGlobalScope>() {
+        let _ = global.post_message(&msg);
+    }
+}
+
 /// Post an error response back to the main thread.
 fn post_error_response(generation: f64, error_msg: &str) {
     let error = mujou_pipeline::PipelineError::InvalidConfig(error_msg.to_string());